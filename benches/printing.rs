@@ -0,0 +1,59 @@
+//! Compares the per-line `println!()` printing, which was used for the
+//! commit list before, with the locked and buffered standard output used
+//! by the printer now. The lines mimic the table output of commrate.
+//!
+//! The output itself should be discarded, while the timings are printed
+//! to the standard error:
+//!
+//! ```text
+//! cargo bench --bench printing > /dev/null
+//! ```
+
+use std::{
+    io::{self, BufWriter, Write},
+    time::{Duration, Instant},
+};
+
+const LINES: usize = 200_000;
+
+fn main() {
+    let unbuffered = measure(|| {
+        for index in 0..LINES {
+            println!(
+                "{:012x} {:<5} {:19.19} {}",
+                index, "B", "Leeroy Jenkins", SUBJECT
+            );
+        }
+    });
+
+    let buffered = measure(|| {
+        let stdout = io::stdout();
+        let mut out = BufWriter::new(stdout.lock());
+
+        for index in 0..LINES {
+            writeln!(
+                out,
+                "{:012x} {:<5} {:19.19} {}",
+                index, "B", "Leeroy Jenkins", SUBJECT
+            )
+            .unwrap();
+        }
+
+        out.flush().unwrap();
+    });
+
+    eprintln!("println!():          {:?}", unbuffered);
+    eprintln!("buffered and locked: {:?}", buffered);
+    eprintln!(
+        "speedup:             {:.1}x",
+        unbuffered.as_secs_f64() / buffered.as_secs_f64()
+    );
+}
+
+const SUBJECT: &str = "Describe the frobnicator configuration";
+
+fn measure<F: FnOnce()>(action: F) -> Duration {
+    let start = Instant::now();
+    action();
+    start.elapsed()
+}
@@ -0,0 +1,112 @@
+//! Measures the message parsing (`MessageInfo::new()`) and the scoring
+//! by the whole rule set, which are the CPU-bound stages of the run (see
+//! `--timing` for the breakdown of the real runs).
+//!
+//! The commrate sources are included directly, as it is the binary crate:
+//!
+//! ```text
+//! cargo bench --bench scoring
+//! ```
+
+// Not everything of the included modules is used here.
+#![allow(dead_code, unused_imports)]
+
+#[macro_use]
+extern crate lazy_static;
+
+#[path = "../src/changelog.rs"]
+mod changelog;
+
+#[path = "../src/commit/mod.rs"]
+mod commit;
+
+#[path = "../src/patterns.rs"]
+mod patterns;
+
+#[path = "../src/scoring/mod.rs"]
+mod scoring;
+
+use commit::{CommitId, DiffInfo, MessageInfo, Metadata, ParseOptions};
+use scoring::{
+    BodyLenRule, BodyPresenceRule, BodyWrappingRule, BranchSubjectRule, BreakingChangeRule,
+    DuplicateMessageRule, IssueSubjectRule, MessageHygieneRule, MetadataLinesRule,
+    MultiPurposeRule, ParagraphLenRule, ReferenceRule, Scorer, ScorerBuilder, StructuredBodyRule,
+    SubjectBodyBreakRule, SubjectRule, SubjectStyleRule, TrailerFormatRule, VagueSubjectRule,
+};
+
+use std::time::{Duration, Instant};
+
+const ITERATIONS: usize = 100_000;
+
+const MESSAGES: &[&str] = &[
+    "Fix typo",
+    "Add the frobnicator\n\nThe frobnicator is required for the upcoming\nbaz feature, so it is added in advance.\n",
+    "Rework the parser and fix the CLI\n\n* split the parser into modules;\n* fix the option parsing.\n\nSigned-off-by: Leeroy Jenkins <leeroy@example.com>\n",
+    "Update dependencies (#1234)\n\n* Bump foo to 1.2.3\n\n* Bump bar to 4.5.6\n\nCo-authored-by: Leeroy Jenkins <leeroy@example.com>\n",
+];
+
+fn main() {
+    let parsing = measure(|index| {
+        MessageInfo::new(MESSAGES[index % MESSAGES.len()]);
+    });
+
+    let options = ParseOptions::default();
+    let scorer = init_scorer();
+    let scoring = measure(|index| {
+        let metadata = Metadata::new(CommitId::Placeholder("bench"), "Leeroy Jenkins".into(), 1);
+        let msg_info = MessageInfo::new(MESSAGES[index % MESSAGES.len()]);
+        let diff_info = DiffInfo::new(index % 500, index % 100).with_top_dirs(1 + index % 3);
+        let commit = commit::Commit::new(metadata, diff_info, msg_info, &options);
+
+        scorer.score(commit);
+    });
+
+    report("MessageInfo::new()", parsing);
+    report("parsing and scoring", scoring);
+}
+
+/// The rule set of the default profile (see `main::init_scorer()`).
+fn init_scorer() -> Scorer {
+    ScorerBuilder::new()
+        .with_rule(SubjectRule::default(), 0.25)
+        .with_rule(VagueSubjectRule, 0.05)
+        .with_rule(IssueSubjectRule::default(), 0.15)
+        .with_rule(BranchSubjectRule, 0.1)
+        .with_rule(SubjectStyleRule, 0.05)
+        .with_rule(BodyPresenceRule, 0.1)
+        .with_rule(SubjectBodyBreakRule, 0.1)
+        .with_rule(BodyLenRule::default(), 0.2)
+        .with_rule(BodyWrappingRule, 0.2)
+        .with_rule(ParagraphLenRule, 0.05)
+        .with_rule(DuplicateMessageRule::bounded(1000), 0.05)
+        .with_rule(MultiPurposeRule, 0.05)
+        .with_rule(MetadataLinesRule, 0.05)
+        .with_rule(ReferenceRule, 0.05)
+        .with_rule(TrailerFormatRule, 0.05)
+        .with_rule(MessageHygieneRule, 0.05)
+        .with_rule(StructuredBodyRule, 0.05)
+        .with_rule(BreakingChangeRule, 0.05)
+        .build()
+}
+
+fn measure<F: FnMut(usize)>(mut action: F) -> Duration {
+    // Warm up the lazily compiled regexes and the caches.
+    for index in 0..ITERATIONS / 10 {
+        action(index);
+    }
+
+    let start = Instant::now();
+    for index in 0..ITERATIONS {
+        action(index);
+    }
+
+    start.elapsed()
+}
+
+fn report(name: &str, elapsed: Duration) {
+    println!(
+        "{:24} {:>10.0} ns/iter",
+        name,
+        elapsed.as_nanos() as f64 / ITERATIONS as f64
+    );
+}
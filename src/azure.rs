@@ -0,0 +1,244 @@
+use crate::{
+    http::{self, encode_component, Auth},
+    json::{quote, Json},
+    pull_request::{is_own_comment, PullRequestApi, PullRequestCommit},
+};
+
+/// The version of the REST API, which is requested explicitly,
+/// as Azure DevOps requires.
+const API_VERSION: &str = "7.0";
+
+/// The number of the commits of the pull request requested per page.
+const PAGE_SIZE: usize = 1000;
+
+/// The response header with the token of the next page, if any.
+const CONTINUATION_HEADER: &str = "x-ms-continuationtoken";
+
+/// The thread status "closed": the comment is informational, so it must
+/// not block the completion of the pull request by the comment policy.
+const CLOSED_THREAD_STATUS: u32 = 4;
+
+/// The pull request of the Azure DevOps repository.
+pub struct PullRequest {
+    /// The URL of the repository API, e.g.
+    /// `https://dev.azure.com/ORG/PROJECT/_apis/git/repositories/REPO`.
+    repository_url: String,
+    id: u32,
+
+    /// The personal access token or the `System.AccessToken`
+    /// of the pipeline.
+    token: Option<String>,
+}
+
+impl PullRequest {
+    /// Creates the pull request of the `PROJECT/REPO` repository of the
+    /// organization (or the collection of Azure DevOps Server), e.g.
+    /// `https://dev.azure.com/ORG`.
+    pub fn new(
+        organization_url: &str,
+        repository: &str,
+        id: u32,
+        token: Option<&str>,
+    ) -> Result<Self, String> {
+        let (project, name) = match repository.find('/') {
+            Some(pos) => (&repository[..pos], &repository[pos + 1..]),
+            None => return Err(format!("'{}' must be in the PROJECT/REPO form", repository)),
+        };
+
+        let repository_url = format!(
+            "{}/{}/_apis/git/repositories/{}",
+            organization_url.trim_end_matches('/'),
+            encode_component(project),
+            encode_component(name)
+        );
+
+        Ok(Self {
+            repository_url,
+            id,
+            token: token.map(str::to_string),
+        })
+    }
+
+    fn auth(&self) -> Auth<'_> {
+        self.token.as_deref().map_or(Auth::None, Auth::Token)
+    }
+
+    fn threads_url(&self) -> String {
+        format!("{}/pullRequests/{}/threads", self.repository_url, self.id)
+    }
+
+    /// Finds the thread and the comment posted before, if any.
+    fn find_own_comment(&self) -> Result<Option<(u64, u64)>, String> {
+        let url = format!("{}?api-version={}", self.threads_url(), API_VERSION);
+        let json = Json::parse(&http::get(&url, self.auth())?)?;
+        let threads = json.get("value").and_then(Json::as_array).unwrap_or(&[]);
+
+        for thread in threads {
+            let comments = thread
+                .get("comments")
+                .and_then(Json::as_array)
+                .unwrap_or(&[]);
+            for comment in comments {
+                let content = comment.get("content").and_then(Json::as_str).unwrap_or("");
+                if !is_own_comment(content) {
+                    continue;
+                }
+
+                let thread_id = thread.get("id").and_then(Json::as_u64);
+                let comment_id = comment.get("id").and_then(Json::as_u64);
+                if let (Some(thread_id), Some(comment_id)) = (thread_id, comment_id) {
+                    return Ok(Some((thread_id, comment_id)));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl PullRequestApi for PullRequest {
+    fn fetch_commits(&self) -> Result<Vec<PullRequestCommit>, String> {
+        let mut commits = Vec::new();
+        let mut continuation: Option<String> = None;
+
+        loop {
+            let mut url = format!(
+                "{}/pullRequests/{}/commits?$top={}&api-version={}",
+                self.repository_url, self.id, PAGE_SIZE, API_VERSION
+            );
+            if let Some(token) = &continuation {
+                url.push_str(&format!("&continuationToken={}", encode_component(token)));
+            }
+
+            let (content, next) = http::get_with_header(&url, self.auth(), CONTINUATION_HEADER)?;
+            let json = Json::parse(&content)?;
+            let values = json
+                .get("value")
+                .and_then(Json::as_array)
+                .ok_or("the pull request without commits")?;
+
+            for value in values {
+                let mut commit = parse_commit(value)?;
+
+                // The long messages are truncated in the list,
+                // so these are fetched along with their commits.
+                if value.get("commentTruncated") == Some(&Json::Bool(true)) {
+                    let url = format!(
+                        "{}/commits/{}?api-version={}",
+                        self.repository_url, commit.id, API_VERSION
+                    );
+                    commit = parse_commit(&Json::parse(&http::get(&url, self.auth())?)?)?;
+                }
+
+                commits.push(commit);
+            }
+
+            continuation = next.filter(|token| !token.is_empty());
+            if continuation.is_none() {
+                break;
+            }
+        }
+
+        // The newest commits are listed first.
+        commits.reverse();
+
+        Ok(commits)
+    }
+
+    /// The new comment is posted as the closed thread.
+    fn post_comment(&self, text: &str) -> Result<(), String> {
+        if self.token.is_none() {
+            return Err("the token is required to post the comment".to_string());
+        }
+
+        let (method, url, body) = match self.find_own_comment()? {
+            Some((thread_id, comment_id)) => (
+                "PATCH",
+                format!(
+                    "{}/{}/comments/{}?api-version={}",
+                    self.threads_url(),
+                    thread_id,
+                    comment_id,
+                    API_VERSION
+                ),
+                format!("{{\"content\": {}}}", quote(text)),
+            ),
+            None => (
+                "POST",
+                format!("{}?api-version={}", self.threads_url(), API_VERSION),
+                format!(
+                    "{{\"comments\": [{{\"parentCommentId\": 0, \"content\": {}, \"commentType\": 1}}], \"status\": {}}}",
+                    quote(text),
+                    CLOSED_THREAD_STATUS
+                ),
+            ),
+        };
+
+        http::send_json(method, &url, self.auth(), &body).map(|_| ())
+    }
+}
+
+/// Parses the `GitCommitRef` entity.
+fn parse_commit(value: &Json) -> Result<PullRequestCommit, String> {
+    let id = value
+        .get("commitId")
+        .and_then(Json::as_str)
+        .ok_or("commit without ID")?;
+    let message = value
+        .get("comment")
+        .and_then(Json::as_str)
+        .ok_or_else(|| format!("the commit {} without the message", id))?;
+    let author = value
+        .get("author")
+        .and_then(|author| author.get("name"))
+        .and_then(Json::as_str)
+        .unwrap_or("");
+    let parents = value
+        .get("parents")
+        .and_then(Json::as_array)
+        .map_or(1, <[Json]>::len);
+
+    Ok(PullRequestCommit {
+        id: id.to_string(),
+        author: author.to_string(),
+        message: message.to_string(),
+        parents,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commits_are_parsed() {
+        let commit = parse_commit(
+            &Json::parse(
+                r#"{"commitId": "aaa", "comment": "Fix the parser\n\nIt crashed.",
+                    "author": {"name": "Foo", "email": "foo@example.com"},
+                    "parents": ["x", "y"]}"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(commit.id, "aaa");
+        assert_eq!(commit.author, "Foo");
+        assert_eq!(commit.message, "Fix the parser\n\nIt crashed.");
+        assert_eq!(commit.parents, 2);
+
+        assert!(parse_commit(&Json::parse(r#"{"commitId": "aaa"}"#).unwrap()).is_err());
+    }
+
+    #[test]
+    fn repository_url_is_built() {
+        let pull_request =
+            PullRequest::new("https://dev.azure.com/org/", "My Project/app", 7, None);
+
+        assert_eq!(
+            pull_request.unwrap().repository_url,
+            "https://dev.azure.com/org/My%20Project/_apis/git/repositories/app"
+        );
+        assert!(PullRequest::new("https://dev.azure.com/org", "app", 7, None).is_err());
+    }
+}
@@ -0,0 +1,333 @@
+use crate::{
+    http::{self, encode_component, Auth},
+    json::{quote, Json},
+    pull_request::{is_own_comment, PullRequestApi, PullRequestCommit},
+};
+
+/// The API of Bitbucket Cloud, which is used unless the URL
+/// of the Bitbucket Server (Data Center) is configured.
+pub const CLOUD_API_URL: &str = "https://api.bitbucket.org/2.0";
+
+/// The number of items requested per page.
+const PAGE_SIZE: u32 = 100;
+
+/// The APIs of Bitbucket Cloud and Bitbucket Server are different,
+/// though these have much in common.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Flavor {
+    Cloud,
+    Server,
+}
+
+/// The page of the paged API resource: the items and the reference
+/// to the next page, if any.
+struct Page {
+    values: Vec<Json>,
+    next: Option<PageRef>,
+}
+
+enum PageRef {
+    /// The URL of the next page (Cloud).
+    Url(String),
+
+    /// The start of the next page (Server).
+    Start(u64),
+}
+
+/// The pull request of the Bitbucket repository.
+pub struct PullRequest {
+    /// The URL of the pull request API resource.
+    url: String,
+    flavor: Flavor,
+    token: Option<String>,
+}
+
+impl PullRequest {
+    /// Creates the pull request of the repository, which is `WORKSPACE/REPO`
+    /// for Bitbucket Cloud and `PROJECT/REPO` for Bitbucket Server. The
+    /// API is told by the URL: the Cloud one is `CLOUD_API_URL`, while the
+    /// Server one is the base URL of the server.
+    pub fn new(
+        api_url: &str,
+        repository: &str,
+        id: u32,
+        token: Option<&str>,
+    ) -> Result<Self, String> {
+        let api_url = api_url.trim_end_matches('/');
+        let (owner, name) = match repository.find('/') {
+            Some(pos) => (&repository[..pos], &repository[pos + 1..]),
+            None => return Err(format!("'{}' must be in the OWNER/REPO form", repository)),
+        };
+
+        let (flavor, url) = if api_url.contains("api.bitbucket.org") {
+            (
+                Flavor::Cloud,
+                format!(
+                    "{}/repositories/{}/{}/pullrequests/{}",
+                    api_url,
+                    encode_component(owner),
+                    encode_component(name),
+                    id
+                ),
+            )
+        } else {
+            (
+                Flavor::Server,
+                format!(
+                    "{}/rest/api/1.0/projects/{}/repos/{}/pull-requests/{}",
+                    api_url,
+                    encode_component(owner),
+                    encode_component(name),
+                    id
+                ),
+            )
+        };
+
+        Ok(Self {
+            url,
+            flavor,
+            token: token.map(str::to_string),
+        })
+    }
+
+    fn auth(&self) -> Auth<'_> {
+        self.token.as_deref().map_or(Auth::None, Auth::Bearer)
+    }
+
+    /// Fetches all the items of the paged resource of the pull request.
+    fn fetch_all(&self, resource: &str) -> Result<Vec<Json>, String> {
+        let mut values = Vec::new();
+        let mut url = match self.flavor {
+            Flavor::Cloud => format!("{}/{}?pagelen={}", self.url, resource, PAGE_SIZE),
+            Flavor::Server => format!("{}/{}?start=0&limit={}", self.url, resource, PAGE_SIZE),
+        };
+
+        loop {
+            let page = parse_page(&http::get(&url, self.auth())?)?;
+            values.extend(page.values);
+
+            url = match page.next {
+                Some(PageRef::Url(next)) => next,
+                Some(PageRef::Start(start)) => format!(
+                    "{}/{}?start={}&limit={}",
+                    self.url, resource, start, PAGE_SIZE
+                ),
+                None => return Ok(values),
+            };
+        }
+    }
+
+    /// Finds the comment posted before, if any: its ID and, for Bitbucket
+    /// Server, its version, which is required for the update.
+    fn find_own_comment(&self) -> Result<Option<(u64, Option<u64>)>, String> {
+        let comments = match self.flavor {
+            Flavor::Cloud => self.fetch_all("comments")?,
+
+            // The comments of Bitbucket Server are listed as the activities.
+            Flavor::Server => self
+                .fetch_all("activities")?
+                .into_iter()
+                .filter(|activity| {
+                    activity.get("action").and_then(Json::as_str) == Some("COMMENTED")
+                })
+                .filter_map(|mut activity| take_field(&mut activity, "comment"))
+                .collect(),
+        };
+
+        for comment in &comments {
+            let text = match self.flavor {
+                Flavor::Cloud => comment
+                    .get("content")
+                    .and_then(|content| content.get("raw")),
+                Flavor::Server => comment.get("text"),
+            };
+
+            if is_own_comment(text.and_then(Json::as_str).unwrap_or("")) {
+                if let Some(id) = comment.get("id").and_then(Json::as_u64) {
+                    return Ok(Some((id, comment.get("version").and_then(Json::as_u64))));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl PullRequestApi for PullRequest {
+    fn fetch_commits(&self) -> Result<Vec<PullRequestCommit>, String> {
+        let mut commits = self
+            .fetch_all("commits")?
+            .iter()
+            .map(|value| parse_commit(value, self.flavor))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The newest commits are listed first.
+        commits.reverse();
+
+        Ok(commits)
+    }
+
+    fn post_comment(&self, text: &str) -> Result<(), String> {
+        if self.token.is_none() {
+            return Err("the token is required to post the comment".to_string());
+        }
+
+        let content = match self.flavor {
+            Flavor::Cloud => format!("\"content\": {{\"raw\": {}}}", quote(text)),
+            Flavor::Server => format!("\"text\": {}", quote(text)),
+        };
+
+        let (method, url, body) = match self.find_own_comment()? {
+            Some((id, Some(version))) => (
+                "PUT",
+                format!("{}/comments/{}", self.url, id),
+                format!("{{{}, \"version\": {}}}", content, version),
+            ),
+            Some((id, None)) => (
+                "PUT",
+                format!("{}/comments/{}", self.url, id),
+                format!("{{{}}}", content),
+            ),
+            None => (
+                "POST",
+                format!("{}/comments", self.url),
+                format!("{{{}}}", content),
+            ),
+        };
+
+        http::send_json(method, &url, self.auth(), &body).map(|_| ())
+    }
+}
+
+/// Parses the page of the paged resource of either API.
+fn parse_page(content: &str) -> Result<Page, String> {
+    let mut json = Json::parse(content)?;
+
+    let next = match json.get("next").and_then(Json::as_str) {
+        Some(url) => Some(PageRef::Url(url.to_string())),
+        None if json.get("isLastPage") == Some(&Json::Bool(false)) => json
+            .get("nextPageStart")
+            .and_then(Json::as_u64)
+            .map(PageRef::Start),
+        None => None,
+    };
+
+    let values = match take_field(&mut json, "values") {
+        Some(Json::Array(values)) => values,
+        _ => return Err("the page without values".to_string()),
+    };
+
+    Ok(Page { values, next })
+}
+
+/// Moves the field out of the object.
+fn take_field(json: &mut Json, name: &str) -> Option<Json> {
+    match json {
+        Json::Object(fields) => {
+            let pos = fields.iter().position(|(field, _)| field == name)?;
+            Some(fields.swap_remove(pos).1)
+        }
+        _ => None,
+    }
+}
+
+fn parse_commit(value: &Json, flavor: Flavor) -> Result<PullRequestCommit, String> {
+    let author = value.get("author");
+    let (id_field, author) = match flavor {
+        Flavor::Cloud => (
+            "hash",
+            author
+                .and_then(|author| author.get("user"))
+                .and_then(|user| user.get("display_name"))
+                .or_else(|| author.and_then(|author| author.get("raw"))),
+        ),
+        Flavor::Server => (
+            "id",
+            author
+                .and_then(|author| author.get("displayName"))
+                .or_else(|| author.and_then(|author| author.get("name"))),
+        ),
+    };
+
+    let id = value
+        .get(id_field)
+        .and_then(Json::as_str)
+        .ok_or("commit without ID")?;
+    let message = value
+        .get("message")
+        .and_then(Json::as_str)
+        .ok_or_else(|| format!("the commit {} without the message", id))?;
+    let parents = value
+        .get("parents")
+        .and_then(Json::as_array)
+        .map_or(1, <[Json]>::len);
+
+    Ok(PullRequestCommit {
+        id: id.to_string(),
+        author: author.and_then(Json::as_str).unwrap_or("").to_string(),
+        message: message.to_string(),
+        parents,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cloud_commits_are_parsed() {
+        let page = parse_page(
+            r#"{
+  "values": [
+    {"hash": "bbb", "message": "Fix the parser\n", "parents": [{"hash": "aaa"}],
+     "author": {"raw": "Foo <foo@example.com>", "user": {"display_name": "Foo Bar"}}},
+    {"hash": "aaa", "message": "Merge master", "parents": [{"hash": "x"}, {"hash": "y"}],
+     "author": {"raw": "Foo <foo@example.com>"}}
+  ],
+  "next": "https://api.bitbucket.org/2.0/next"
+}"#,
+        )
+        .unwrap();
+
+        let commits: Vec<_> = page
+            .values
+            .iter()
+            .map(|value| parse_commit(value, Flavor::Cloud).unwrap())
+            .collect();
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].id, "bbb");
+        assert_eq!(commits[0].author, "Foo Bar");
+        assert_eq!(commits[1].author, "Foo <foo@example.com>");
+        assert_eq!(commits[1].parents, 2);
+        assert!(
+            matches!(page.next, Some(PageRef::Url(url)) if url == "https://api.bitbucket.org/2.0/next")
+        );
+
+        let invalid = Json::parse(r#"{"hash": "aaa"}"#).unwrap();
+        assert!(parse_commit(&invalid, Flavor::Cloud).is_err());
+    }
+
+    #[test]
+    fn server_commits_are_parsed() {
+        let page = |last| {
+            parse_page(&format!(
+                r#"{{"values": [{{"id": "aaa", "message": "Fix", "author": {{"name": "foo", "displayName": "Foo"}}}}],
+                    "isLastPage": {}, "nextPageStart": 25}}"#,
+                last
+            ))
+            .unwrap()
+        };
+
+        let first = page(false);
+        assert_eq!(
+            parse_commit(&first.values[0], Flavor::Server)
+                .unwrap()
+                .author,
+            "Foo"
+        );
+        assert!(matches!(first.next, Some(PageRef::Start(25))));
+        assert!(page(true).next.is_none());
+
+        assert!(parse_page(r#"{"isLastPage": true}"#).is_err());
+    }
+}
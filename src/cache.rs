@@ -0,0 +1,297 @@
+use crate::{commit::DiffInfo, error::warning};
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    env,
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+/// The version of the cache file format, which must be bumped
+/// on every change of the format or of the cached data.
+const CACHE_FORMAT_VERSION: u32 = 2;
+
+/// The name of the cache file in the repository cache directory.
+const CACHE_FILE_NAME: &str = "diffs";
+
+/// The on-disk cache of the diff statistics of commits, as calculating
+/// the diffs is by far the most expensive part of scoring. The cache
+/// is stored in `$XDG_CACHE_HOME/commrate/<repo-id>`, where the ID is
+/// derived from the location of the repository.
+///
+/// The cache file starts with the header, which contains the versions
+/// of the cache format and of commrate (the diff calculation may change
+/// along with the bundled libgit2), followed by a line per commit:
+///
+/// ```text
+/// commrate-diff-cache 2 commrate 0.1.0
+/// 9335a4dc0e098830dec14fe3997c6a654695b935 12 3 1 2 0
+/// ```
+///
+/// The cache with a different header is discarded. The lists of changed
+/// paths are not cached, so the cache is not used when these are needed.
+///
+/// The cache is an optimization only, so it is disabled (with a warning)
+/// on any I/O error instead of failing the whole run.
+pub struct DiffCache {
+    path: PathBuf,
+    entries: RefCell<HashMap<String, CachedDiff>>,
+    writer: RefCell<Option<BufWriter<File>>>,
+
+    /// Set on the first write failure, so the cache is not used (and the
+    /// failure is not reported again) for the rest of the run.
+    disabled: Cell<bool>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct CachedDiff {
+    insertions: usize,
+    deletions: usize,
+    top_dirs: usize,
+    files: usize,
+    binary_files: usize,
+}
+
+impl DiffCache {
+    /// Opens the cache of the repository with the specified Git
+    /// directory, `None` if there is no place for the cache.
+    pub fn open(git_dir: &Path) -> Option<Self> {
+        let path = cache_dir()?.join(repository_id(git_dir));
+
+        let entries = match fs::read_to_string(path.join(CACHE_FILE_NAME)) {
+            Ok(content) => parse_cache(&content, &cache_header()).unwrap_or_default(),
+            Err(ref err) if err.kind() == ErrorKind::NotFound => HashMap::new(),
+            Err(err) => {
+                warning(format!("cannot read the diff cache: {}", err));
+                return None;
+            }
+        };
+
+        Some(Self {
+            path,
+            entries: RefCell::new(entries),
+            writer: RefCell::new(None),
+            disabled: Cell::new(false),
+        })
+    }
+
+    pub fn get(&self, commit_id: &str) -> Option<DiffInfo> {
+        self.entries.borrow().get(commit_id).map(|cached| {
+            DiffInfo::new(cached.insertions, cached.deletions)
+                .with_top_dirs(cached.top_dirs)
+                .with_files(cached.files, cached.binary_files)
+        })
+    }
+
+    /// Stores the diff statistics of the commit, appending
+    /// them to the cache file immediately.
+    pub fn put(&self, commit_id: &str, diff_info: &DiffInfo) {
+        if self.disabled.get() {
+            return;
+        }
+
+        let cached = CachedDiff {
+            insertions: diff_info.insertions(),
+            deletions: diff_info.deletions(),
+            top_dirs: diff_info.top_dirs(),
+            files: diff_info.files(),
+            binary_files: diff_info.binary_files(),
+        };
+
+        if self
+            .entries
+            .borrow_mut()
+            .insert(commit_id.to_string(), cached)
+            .is_some()
+        {
+            return;
+        }
+
+        if let Err(err) = self.append(commit_id, cached) {
+            warning(format!("cannot update the diff cache: {}", err));
+
+            // Disable the cache for the rest of the run,
+            // so the warning is not repeated for each commit.
+            self.disabled.set(true);
+            self.entries.borrow_mut().clear();
+            self.writer.replace(None);
+        }
+    }
+
+    fn append(&self, commit_id: &str, cached: CachedDiff) -> std::io::Result<()> {
+        let mut writer = self.writer.borrow_mut();
+
+        if writer.is_none() {
+            *writer = Some(self.open_writer()?);
+        }
+
+        let writer = writer.as_mut().unwrap();
+        writeln!(
+            writer,
+            "{} {} {} {} {} {}",
+            commit_id,
+            cached.insertions,
+            cached.deletions,
+            cached.top_dirs,
+            cached.files,
+            cached.binary_files
+        )
+    }
+
+    /// Opens the cache file for appending. The file is rewritten
+    /// from scratch if its header does not match the current one.
+    fn open_writer(&self) -> std::io::Result<BufWriter<File>> {
+        fs::create_dir_all(&self.path)?;
+
+        let file_path = self.path.join(CACHE_FILE_NAME);
+        let header = cache_header();
+
+        let valid = match fs::read_to_string(&file_path) {
+            Ok(content) => content.lines().next() == Some(header.as_str()),
+            Err(ref err) if err.kind() == ErrorKind::NotFound => false,
+            Err(err) => return Err(err),
+        };
+
+        if valid {
+            let file = OpenOptions::new().append(true).open(&file_path)?;
+            return Ok(BufWriter::new(file));
+        }
+
+        let mut writer = BufWriter::new(File::create(&file_path)?);
+        writeln!(writer, "{}", header)?;
+
+        Ok(writer)
+    }
+}
+
+/// Parses the cache file content, `None` if the file is written
+/// with another header. Malformed lines (e.g. the last one of the
+/// interrupted run) are skipped.
+fn parse_cache(content: &str, header: &str) -> Option<HashMap<String, CachedDiff>> {
+    let mut lines = content.lines();
+
+    if lines.next() != Some(header) {
+        return None;
+    }
+
+    let entries = lines
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(' ').collect();
+            if fields.len() != 6 {
+                return None;
+            }
+
+            let cached = CachedDiff {
+                insertions: fields[1].parse().ok()?,
+                deletions: fields[2].parse().ok()?,
+                top_dirs: fields[3].parse().ok()?,
+                files: fields[4].parse().ok()?,
+                binary_files: fields[5].parse().ok()?,
+            };
+
+            Some((fields[0].to_string(), cached))
+        })
+        .collect();
+
+    Some(entries)
+}
+
+fn cache_header() -> String {
+    // git2 of this version does not expose the version of the bundled
+    // libgit2, which changes only along with the commrate build anyway.
+    format!(
+        "commrate-diff-cache {} commrate {}",
+        CACHE_FORMAT_VERSION,
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// The base directory for the caches of all the repositories:
+/// `$XDG_CACHE_HOME/commrate` or `~/.cache/commrate`.
+fn cache_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".cache"),
+    };
+
+    Some(base.join("commrate"))
+}
+
+/// Derives the stable ID of the repository from its location. The FNV-1a
+/// hash is used, as the hashers of the standard library are not
+/// guaranteed to produce the same results between Rust releases.
+fn repository_id(git_dir: &Path) -> String {
+    let location = fs::canonicalize(git_dir).unwrap_or_else(|_| git_dir.to_path_buf());
+
+    let hash = location
+        .to_string_lossy()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "commrate-diff-cache 2 commrate 0.1.0";
+
+    #[test]
+    fn cache_is_parsed() {
+        let content = format!("{}\naaa 12 3 1 4 2\nbbb 1 0 1 1 0\nccc 1\n", HEADER);
+        let entries = parse_cache(&content, HEADER).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries.get("aaa"),
+            Some(&CachedDiff {
+                insertions: 12,
+                deletions: 3,
+                top_dirs: 1,
+                files: 4,
+                binary_files: 2
+            })
+        );
+    }
+
+    #[test]
+    fn cache_of_other_version_is_discarded() {
+        let content = "commrate-diff-cache 1 commrate 0.1.0\naaa 12 3 1\n";
+
+        assert_eq!(parse_cache(content, HEADER), None);
+        assert_eq!(parse_cache("", HEADER), None);
+    }
+
+    #[test]
+    fn repository_ids_are_stable() {
+        assert_eq!(
+            repository_id(Path::new("/nonexistent/repo/.git")),
+            repository_id(Path::new("/nonexistent/repo/.git"))
+        );
+        assert_ne!(
+            repository_id(Path::new("/nonexistent/repo/.git")),
+            repository_id(Path::new("/nonexistent/other/.git"))
+        );
+    }
+
+    #[test]
+    fn failed_cache_is_disabled() {
+        let cache = DiffCache {
+            path: PathBuf::from("/dev/null/commrate"),
+            entries: RefCell::new(HashMap::new()),
+            writer: RefCell::new(None),
+            disabled: Cell::new(false),
+        };
+
+        cache.put("aaa", &DiffInfo::new(1, 0));
+        assert!(cache.disabled.get());
+
+        cache.put("bbb", &DiffInfo::new(1, 0));
+        assert!(cache.get("bbb").is_none());
+    }
+}
@@ -0,0 +1,127 @@
+use crate::patterns;
+
+use regex::Regex;
+use std::fmt::{self, Display, Formatter};
+
+/// Maximum length of the subject, which is still usable as the changelog
+/// entry (the length limit of commitlint's conventional configuration).
+pub const MAX_SUBJECT_LENGTH: usize = 72;
+
+/// The reason, why the commit would break the automated generation
+/// of the changelog from the Conventional Commits.
+#[derive(Debug, PartialEq)]
+pub enum Problem {
+    /// The subject has no type prefix, e.g. `fix(parser): `.
+    NoType,
+
+    /// The subject is longer than `MAX_SUBJECT_LENGTH` characters.
+    LongSubject(usize),
+
+    /// The commit is marked as the work in progress.
+    WorkInProgress,
+
+    /// The commit was meant to be squashed into another one
+    /// (`fixup!`, `squash!` or `amend!`).
+    Fixup,
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::NoType => write!(f, "no type"),
+            Problem::LongSubject(length) => write!(f, "long subject ({})", length),
+            Problem::WorkInProgress => write!(f, "work in progress"),
+            Problem::Fixup => write!(f, "fixup"),
+        }
+    }
+}
+
+/// Extracts the scope of the Conventional Commit subject, e.g. `parser`
+/// of `fix(parser): reject the empty input`. Returns `None` for the
+/// subjects without the type prefix and `Some(None)` for the ones
+/// without the scope.
+pub fn conventional_scope(subject: &str) -> Option<Option<&str>> {
+    let captures = CONVENTIONAL_PREFIX.captures(subject)?;
+    Some(captures.get(1).map(|scope| scope.as_str()))
+}
+
+/// Whether the Conventional Commit subject marks the breaking change
+/// (`feat(api)!: ...`).
+pub fn is_breaking(subject: &str) -> bool {
+    CONVENTIONAL_PREFIX
+        .captures(subject)
+        .iter()
+        .any(|captures| captures.get(2).is_some())
+}
+
+/// Checks whether the commit subject is usable as the changelog entry.
+pub fn check_subject(subject: &str) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    if AUTOSQUASH_PREFIX.is_match(subject) {
+        problems.push(Problem::Fixup);
+    } else if !CONVENTIONAL_PREFIX.is_match(subject) {
+        problems.push(Problem::NoType);
+    }
+
+    if WORK_IN_PROGRESS.is_match(subject) {
+        problems.push(Problem::WorkInProgress);
+    }
+
+    let length = subject.chars().count();
+    if length > MAX_SUBJECT_LENGTH {
+        problems.push(Problem::LongSubject(length));
+    }
+
+    problems
+}
+
+lazy_static! {
+    /// The type with the optional scope and the breaking change mark,
+    /// followed by the description: "feat(api)!: drop the v1 endpoints".
+    static ref CONVENTIONAL_PREFIX: Regex = patterns::builtin(r"^[a-z]+(?:\(([^()]+)\))?(!)?: \S");
+
+    /// The prefixes of the commits created by `git commit --fixup`
+    /// (or `--squash`) for `git rebase --autosquash`.
+    static ref AUTOSQUASH_PREFIX: Regex = patterns::builtin(r"^(fixup|squash|amend)! ");
+
+    static ref WORK_IN_PROGRESS: Regex = patterns::builtin(r"(?i)(^|[\s:(\[])wip($|[\s:)\]])");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_are_extracted() {
+        assert_eq!(
+            conventional_scope("fix(parser)!: reject it"),
+            Some(Some("parser"))
+        );
+        assert_eq!(conventional_scope("feat: add the frobnicator"), Some(None));
+        assert_eq!(conventional_scope("Add the frobnicator"), None);
+
+        assert!(is_breaking("feat(api)!: drop the v1 endpoints"));
+        assert!(!is_breaking("feat(api): add the v2 endpoints"));
+    }
+
+    #[test]
+    fn conventional_subjects_are_ready() {
+        assert!(check_subject("feat: add the frobnicator").is_empty());
+        assert!(check_subject("fix(parser)!: reject the empty input").is_empty());
+    }
+
+    #[test]
+    fn problems_are_detected() {
+        assert_eq!(check_subject("Add the frobnicator"), [Problem::NoType]);
+        assert_eq!(check_subject("fixup! feat: add it"), [Problem::Fixup]);
+        assert_eq!(
+            check_subject("feat: [WIP] add the frobnicator"),
+            [Problem::WorkInProgress]
+        );
+        assert!(check_subject("feat: wipe the cache").is_empty());
+
+        let long = format!("feat: {}", "a".repeat(70));
+        assert_eq!(check_subject(&long), [Problem::LongSubject(76)]);
+    }
+}
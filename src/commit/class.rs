@@ -1,17 +1,17 @@
 use enumset::{EnumSet, EnumSetType};
 use regex::Regex;
-use std::fmt::{Display, Formatter};
-
-use crate::commit::{diff::DiffInfo, message::MessageInfo, metadata::Metadata};
-
-/// Maximum diff size (lines total) for short commits.
-pub const SHORT_COMMIT_LENGTH: usize = 25;
-
-/// For refactoring commits, we allow a slight difference between
-/// insertions and deletions (5% of total diff) to ensure
-/// that move-related things like fixing imports and so on
-/// do not subvert the correct classification of these commits.
-pub const REFACTOR_COMMIT_ALLOWED_DIFF: f32 = 0.05;
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use crate::{
+    commit::{
+        custom::MAX_CUSTOM_CLASSES, diff::DiffInfo, language::words, message::MessageInfo,
+        metadata::Metadata, options::ParseOptions,
+    },
+    patterns,
+};
 
 /// Commits of different nature require special treatment
 /// disregarging the fact that their actual properties like
@@ -51,22 +51,57 @@ pub enum Class {
     /// Such commits could be pretty long though, so they
     /// require special treatment.
     Refactor,
+
+    /// Squash-merged pull requests (or merge requests) are
+    /// ordinary commits whose message is generated by the
+    /// hosting platform, e.g.
+    ///
+    /// "Add the frobnicator (#1234)"
+    /// "Merge pull request #1234 from user/branch"
+    ///
+    /// In the latter case, the subject carries no information
+    /// at all, and the actual description of the change is the
+    /// pull request title in the first line of the body.
+    PullRequest,
 }
 
-/// A newtype wrapper for implementing Display.
+impl Class {
+    /// Finds the class by its full name (case-insensitive), e.g. for
+    /// referring the built-in classes in the configuration.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "merge" => Some(Class::Merge),
+            "initial" => Some(Class::Initial),
+            "short" => Some(Class::Short),
+            "refactor" => Some(Class::Refactor),
+            "pull-request" => Some(Class::PullRequest),
+            _ => None,
+        }
+    }
+}
+
+/// A set of built-in classes along with the user-defined ones.
+///
+/// The user-defined classes are stored as the bit mask of their indices
+/// in the list of custom classes (see ParseOptions), as their number
+/// is limited, and so they are not rendered by Display.
 #[derive(Clone, Copy, Debug)]
-pub struct Classes(EnumSet<Class>);
+pub struct Classes {
+    builtin: EnumSet<Class>,
+    custom: u64,
+}
 
 impl Display for Classes {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let set_len = self.as_set().len();
         let mut buf = String::with_capacity(set_len);
-        for class in self.0 {
+        for class in self.builtin {
             buf.push(match class {
                 Class::Merge => 'M',
                 Class::Initial => 'I',
                 Class::Refactor => 'R',
                 Class::Short => 'S',
+                Class::PullRequest => 'P',
             });
         }
 
@@ -74,32 +109,89 @@ impl Display for Classes {
     }
 }
 
+/// Classes are parsed from the comma-separated list of class names
+/// or their one-letter codes (as rendered by Display), case-insensitive:
+/// "merge,refactor" is the same as "M,R".
+impl FromStr for Classes {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut classes = EnumSet::new();
+
+        for item in s.split(',') {
+            let class = match item.trim().to_ascii_lowercase().as_str() {
+                "m" => Class::Merge,
+                "i" => Class::Initial,
+                "s" => Class::Short,
+                "r" => Class::Refactor,
+                "p" => Class::PullRequest,
+                name => match Class::from_name(name) {
+                    Some(class) => class,
+                    None => {
+                        return Err("class must be one of: M (merge), I (initial), S (short), \
+                                    R (refactor), P (pull-request)")
+                    }
+                },
+            };
+
+            classes.insert(class);
+        }
+
+        Ok(Self::from_set(classes))
+    }
+}
+
 impl Classes {
     pub fn classify_commit(
         metadata: &Metadata,
         diff_info: &DiffInfo,
         msg_info: &MessageInfo,
+        options: &ParseOptions,
     ) -> Self {
-        Self(classify(metadata, diff_info, msg_info))
+        let builtin = classify_with(metadata, diff_info, msg_info, options);
+
+        let custom = options
+            .custom_classes()
+            .iter()
+            .enumerate()
+            .filter(|(_, class)| class.matches(metadata, diff_info, msg_info))
+            .fold(0, |mask, (index, _)| mask | 1u64 << index);
+
+        Self { builtin, custom }
     }
 
     pub fn from_set(classes: EnumSet<Class>) -> Self {
-        Self(classes)
+        Self {
+            builtin: classes,
+            custom: 0,
+        }
     }
 
+    /// The set of built-in classes.
     pub fn as_set(self) -> EnumSet<Class> {
-        self.0
+        self.builtin
+    }
+
+    /// Checks whether the user-defined class with
+    /// the specified index is assigned.
+    pub fn has_custom(self, index: usize) -> bool {
+        index < MAX_CUSTOM_CLASSES && self.custom & (1u64 << index) != 0
     }
 }
 
-fn classify(metadata: &Metadata, diff_info: &DiffInfo, msg_info: &MessageInfo) -> EnumSet<Class> {
+fn classify_with(
+    metadata: &Metadata,
+    diff_info: &DiffInfo,
+    msg_info: &MessageInfo,
+    options: &ParseOptions,
+) -> EnumSet<Class> {
     let mut classes = EnumSet::new();
 
     if metadata.parents() == 0 {
         classes.insert(Class::Initial);
     }
 
-    if diff_info.diff_total() < SHORT_COMMIT_LENGTH {
+    if diff_info.diff_total() < options.short_commit_length() {
         classes.insert(Class::Short);
     }
 
@@ -114,31 +206,71 @@ fn classify(metadata: &Metadata, diff_info: &DiffInfo, msg_info: &MessageInfo) -
     // False positives are extremely rare, so let's pretend they
     // are absent. At the end of the day, no one will die due to
     // one commit of thousands being *overscored*.
-    let allowed_diff = (diff_info.diff_total() as f32 * REFACTOR_COMMIT_ALLOWED_DIFF) as isize;
+    let allowed_diff = (diff_info.diff_total() as f32 * options.refactor_allowed_diff()) as isize;
     let actual_diff = (diff_info.deletions() as isize - diff_info.insertions() as isize).abs();
     if actual_diff <= allowed_diff {
         if let Some(subject) = msg_info.subject() {
-            let regex = Regex::new(r#"(?i)(\bmoved?\b)|(\brenamed?\b)"#).unwrap();
-            if regex.is_match(subject) {
+            let refactor_words = msg_info.language().keywords().refactor;
+            if words(subject).any(|word| refactor_words.contains(&word.as_str())) {
                 classes.insert(Class::Refactor);
             }
         }
     }
 
+    if let Some(subject) = msg_info.subject() {
+        if PULL_REQUEST_SUFFIX.is_match(subject) || PULL_REQUEST_MERGE.is_match(subject) {
+            classes.insert(Class::PullRequest);
+        }
+    }
+
     classes
 }
 
+/// Extracts the pull request title from the message of the squash-merged
+/// pull request: this is either the first body line for "Merge pull
+/// request #N" subjects, or the subject without the pull request number.
+pub fn pull_request_title(msg_info: &MessageInfo) -> Option<&str> {
+    let subject = msg_info.subject()?;
+
+    if PULL_REQUEST_MERGE.is_match(subject) {
+        return msg_info.first_body_line();
+    }
+
+    PULL_REQUEST_SUFFIX
+        .find(subject)
+        .map(|suffix| subject[..suffix.start()].trim_end())
+}
+
+lazy_static! {
+    /// The pull request number appended to the subject, e.g. "Fix foo (#1234)"
+    /// for GitHub squash merges or "Fix foo (!1234)" for GitLab ones.
+    static ref PULL_REQUEST_SUFFIX: Regex = patterns::builtin(r"\((#|!)\d+\)\s*$");
+
+    /// The subject of the GitHub merge, which may appear in non-merge
+    /// commits after rebasing or cherry-picking.
+    static ref PULL_REQUEST_MERGE: Regex = patterns::builtin(r"^Merge pull request #\d+");
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commit::{custom::CustomClass, metadata::CommitId};
 
     const COMMIT_ID: &str = "9335a4dc0e098830dec14fe3997c6a654695b935";
 
+    fn classify(
+        metadata: &Metadata,
+        diff_info: &DiffInfo,
+        msg_info: &MessageInfo,
+    ) -> EnumSet<Class> {
+        classify_with(metadata, diff_info, msg_info, &ParseOptions::default())
+    }
+
     lazy_static! {
         /// Ordinary commit metadata.
         static ref ORDINARY_META: Metadata = {
-            let id = COMMIT_ID.to_string();
-            let author = "Leeroy Jenkins".to_string();
+            let id = CommitId::Placeholder(COMMIT_ID);
+            let author = "Leeroy Jenkins".into();
             let parents = 1;
 
             Metadata::new(id, author, parents)
@@ -146,8 +278,8 @@ mod tests {
 
         /// Initial commit metadata.
         static ref INITIAL_META: Metadata = {
-            let id = COMMIT_ID.to_string();
-            let author = "Leeroy Jenkins".to_string();
+            let id = CommitId::Placeholder(COMMIT_ID);
+            let author = "Leeroy Jenkins".into();
             let parents = 0;
 
             Metadata::new(id, author, parents)
@@ -155,8 +287,8 @@ mod tests {
 
         /// Merge commit metadata. Parents number may be huge.
         static ref MERGE_META: Metadata = {
-            let id = COMMIT_ID.to_string();
-            let author = "Leeroy Jenkins".to_string();
+            let id = CommitId::Placeholder(COMMIT_ID);
+            let author = "Leeroy Jenkins".into();
             let parents = 42;
 
             Metadata::new(id, author, parents)
@@ -165,7 +297,7 @@ mod tests {
 
     #[test]
     fn empty_classes_are_rendered_as_empty_string() {
-        let classes = Classes(EnumSet::new());
+        let classes = Classes::from_set(EnumSet::new());
         let rendered = format!("{}", classes);
 
         assert_eq!(rendered, "");
@@ -180,7 +312,7 @@ mod tests {
         classes_set.insert(Class::Refactor);
         classes_set.insert(Class::Initial);
 
-        let classes = Classes(classes_set);
+        let classes = Classes::from_set(classes_set);
         let rendered = format!("{}", classes);
 
         // XXX: here we rely on the fact that EnumSet uses the order in which
@@ -190,6 +322,23 @@ mod tests {
         assert_eq!(rendered, "MISR");
     }
 
+    #[test]
+    fn classes_are_parsed_from_codes_and_names() {
+        let parsed = Classes::from_str("M, refactor,s").unwrap().as_set();
+        let parsed2 = Classes::from_str("Initial").unwrap().as_set();
+
+        assert_eq!(parsed, Class::Merge | Class::Refactor | Class::Short);
+        assert_eq!(parsed2, EnumSet::only(Class::Initial));
+    }
+
+    #[test]
+    fn invalid_classes_return_error() {
+        assert!(Classes::from_str("").is_err());
+        assert!(Classes::from_str("X").is_err());
+        assert!(Classes::from_str("M,").is_err());
+        assert!(Classes::from_str("MISR").is_err());
+    }
+
     #[test]
     fn ordinary_commit_gets_no_special_classes() {
         let diff = DiffInfo::new(53, 102);
@@ -335,4 +484,91 @@ mod tests {
         assert!(!classes.contains(Class::Refactor));
         assert!(!classes2.contains(Class::Refactor));
     }
+
+    #[test]
+    fn short_commit_length_is_configurable() {
+        let diff = DiffInfo::new(30, 10);
+        let msg_info = MessageInfo::new("Fix NPE in CustomMetricsController");
+        let options = ParseOptions::default().with_short_commit_length(50);
+
+        let classes = classify_with(&ORDINARY_META, &diff, &msg_info, &options);
+
+        assert!(classes.contains(Class::Short));
+    }
+
+    #[test]
+    fn refactor_allowed_diff_is_configurable() {
+        let diff = DiffInfo::new(40, 60);
+        let msg_info = MessageInfo::new("Move Snowden to Russia");
+        let options = ParseOptions::default().with_refactor_allowed_diff(0.2);
+
+        let classes = classify(&ORDINARY_META, &diff, &msg_info);
+        let classes2 = classify_with(&ORDINARY_META, &diff, &msg_info, &options);
+
+        assert!(!classes.contains(Class::Refactor));
+        assert!(classes2.contains(Class::Refactor));
+    }
+
+    #[test]
+    fn custom_classes_are_assigned_by_index() {
+        let diff = DiffInfo::new(42, 666);
+        let msg_info = MessageInfo::new("Release v1.2.3");
+        let options = ParseOptions::default().with_custom_classes(vec![
+            CustomClass::new("bot").with_author(Regex::new("bot$").unwrap()),
+            CustomClass::new("release").with_subject(Regex::new("^Release").unwrap()),
+        ]);
+
+        let classes = Classes::classify_commit(&ORDINARY_META, &diff, &msg_info, &options);
+
+        assert!(!classes.has_custom(0));
+        assert!(classes.has_custom(1));
+        assert!(!classes.has_custom(2));
+    }
+
+    #[test]
+    fn pull_request_commit_is_classified_with_number_suffix() {
+        let diff = DiffInfo::new(53, 102);
+        let msg_info = MessageInfo::new("Add the frobnicator (#1234)");
+        let msg_info2 = MessageInfo::new("Add the frobnicator (!1234)");
+
+        let classes = classify(&ORDINARY_META, &diff, &msg_info);
+        let classes2 = classify(&ORDINARY_META, &diff, &msg_info2);
+
+        assert!(classes.contains(Class::PullRequest));
+        assert!(classes2.contains(Class::PullRequest));
+    }
+
+    #[test]
+    fn pull_request_commit_is_classified_with_merge_subject() {
+        let diff = DiffInfo::new(53, 102);
+        let msg_info = MessageInfo::new("Merge pull request #42 from foo/bar\n\nAdd frobnicator");
+
+        let classes = classify(&ORDINARY_META, &diff, &msg_info);
+
+        assert!(classes.contains(Class::PullRequest));
+    }
+
+    #[test]
+    fn pull_request_commit_is_not_classified_for_issue_references() {
+        let diff = DiffInfo::new(53, 102);
+        let msg_info = MessageInfo::new("Fix #1234: handle empty input");
+        let msg_info2 = MessageInfo::new("Handle empty input (see #1234 for details)");
+
+        let classes = classify(&ORDINARY_META, &diff, &msg_info);
+        let classes2 = classify(&ORDINARY_META, &diff, &msg_info2);
+
+        assert!(!classes.contains(Class::PullRequest));
+        assert!(!classes2.contains(Class::PullRequest));
+    }
+
+    #[test]
+    fn pull_request_title_is_extracted() {
+        let msg_info = MessageInfo::new("Add the frobnicator (#1234)");
+        let msg_info2 = MessageInfo::new("Merge pull request #42 from foo/bar\n\nAdd frobnicator");
+        let msg_info3 = MessageInfo::new("Add the frobnicator");
+
+        assert_eq!(pull_request_title(&msg_info), Some("Add the frobnicator"));
+        assert_eq!(pull_request_title(&msg_info2), Some("Add frobnicator"));
+        assert_eq!(pull_request_title(&msg_info3), None);
+    }
 }
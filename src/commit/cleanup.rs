@@ -0,0 +1,66 @@
+/// Cleans up the raw message the same way Git does before committing
+/// it (in the default "strip" mode): the comment lines and everything
+/// below the scissors line are removed, trailing whitespace is stripped
+/// and consecutive empty lines are collapsed.
+///
+/// This is required for scoring messages straight from the editor,
+/// e.g. `COMMIT_EDITMSG` with the instructions and the status.
+pub fn cleanup_message(raw_message: &str, comment_char: char) -> String {
+    let scissors = format!("{} {}", comment_char, SCISSORS);
+    let mut lines: Vec<&str> = Vec::new();
+
+    for line in raw_message.lines() {
+        if line == scissors {
+            break;
+        }
+
+        if line.starts_with(comment_char) {
+            continue;
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() && matches!(lines.last(), None | Some(&"")) {
+            continue;
+        }
+
+        lines.push(line);
+    }
+
+    while lines.last() == Some(&"") {
+        lines.pop();
+    }
+
+    lines.join("\n")
+}
+
+/// The line, below which everything is removed by `git commit --verbose`
+/// (prepended by the comment character).
+const SCISSORS: &str = "------------------------ >8 ------------------------";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comments_are_stripped() {
+        let raw = "\nAdd the frobnicator\n# Please enter the commit message\n\n\n\
+                   It frobnicates.  \n\n# On branch master\n";
+
+        assert_eq!(
+            cleanup_message(raw, '#'),
+            "Add the frobnicator\n\nIt frobnicates."
+        );
+    }
+
+    #[test]
+    fn scissors_section_is_dropped() {
+        let raw = "Add the frobnicator\n\n; ------------------------ >8 ------------------------\n\
+                   diff --git a/foo b/foo\n";
+
+        assert_eq!(cleanup_message(raw, ';'), "Add the frobnicator");
+        assert_eq!(
+            cleanup_message("Fix #123 crash\n# comment", ';'),
+            "Fix #123 crash\n# comment"
+        );
+    }
+}
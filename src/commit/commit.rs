@@ -5,6 +5,7 @@ use crate::commit::{
     diff::DiffInfo,
     message::MessageInfo,
     metadata::Metadata,
+    options::ParseOptions,
 };
 
 /// A parsed and classified commit with all the data
@@ -17,8 +18,13 @@ pub struct Commit {
 }
 
 impl Commit {
-    pub fn new(metadata: Metadata, diff_info: DiffInfo, msg_info: MessageInfo) -> Self {
-        let classes = Classes::classify_commit(&metadata, &diff_info, &msg_info);
+    pub fn new(
+        metadata: Metadata,
+        diff_info: DiffInfo,
+        msg_info: MessageInfo,
+        options: &ParseOptions,
+    ) -> Self {
+        let classes = Classes::classify_commit(&metadata, &diff_info, &msg_info, options);
 
         Self {
             metadata,
@@ -39,6 +45,17 @@ impl Commit {
         }
     }
 
+    /// Creates the commit from the message alone, when the diff
+    /// is unknown, e.g. for the message being composed.
+    pub fn new_from_message(metadata: Metadata, msg_info: MessageInfo) -> Self {
+        Self {
+            metadata,
+            diff_info: None,
+            msg_info,
+            classes: Classes::from_set(EnumSet::new()),
+        }
+    }
+
     pub fn metadata(&self) -> &Metadata {
         &self.metadata
     }
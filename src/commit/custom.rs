@@ -0,0 +1,144 @@
+use regex::Regex;
+
+use crate::commit::{diff::DiffInfo, message::MessageInfo, metadata::Metadata};
+
+/// Maximum number of user-defined classes.
+pub const MAX_CUSTOM_CLASSES: usize = 64;
+
+/// A user-defined commit class, which allows organizations to model
+/// their own special categories of commits, e.g. commits by bots or
+/// updates of vendored code.
+///
+/// The class is assigned to the commit if it matches all the specified
+/// patterns. The class without any patterns matches nothing.
+#[derive(Clone, Debug)]
+pub struct CustomClass {
+    name: String,
+    subject: Option<Regex>,
+    author: Option<Regex>,
+    path: Option<Regex>,
+}
+
+impl CustomClass {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            subject: None,
+            author: None,
+            path: None,
+        }
+    }
+
+    /// Sets the pattern for the commit subject.
+    pub fn with_subject(mut self, regex: Regex) -> Self {
+        self.subject = Some(regex);
+        self
+    }
+
+    /// Sets the pattern for the commit author name.
+    pub fn with_author(mut self, regex: Regex) -> Self {
+        self.author = Some(regex);
+        self
+    }
+
+    /// Sets the pattern for the changed paths. The pattern
+    /// matches if it matches any of the changed paths.
+    pub fn with_path(mut self, regex: Regex) -> Self {
+        self.path = Some(regex);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the list of changed paths is required for matching.
+    pub fn needs_paths(&self) -> bool {
+        self.path.is_some()
+    }
+
+    pub fn matches(
+        &self,
+        metadata: &Metadata,
+        diff_info: &DiffInfo,
+        msg_info: &MessageInfo,
+    ) -> bool {
+        if self.subject.is_none() && self.author.is_none() && self.path.is_none() {
+            return false;
+        }
+
+        if let Some(regex) = &self.subject {
+            if !regex.is_match(msg_info.subject().unwrap_or("")) {
+                return false;
+            }
+        }
+
+        if let Some(regex) = &self.author {
+            if !regex.is_match(metadata.author()) {
+                return false;
+            }
+        }
+
+        if let Some(regex) = &self.path {
+            if !diff_info.paths().iter().any(|path| regex.is_match(path)) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commit::metadata::CommitId;
+
+    fn metadata(author: &str) -> Metadata {
+        Metadata::new(CommitId::Placeholder("9335a4dc"), author.into(), 1)
+    }
+
+    fn diff(paths: &[&str]) -> DiffInfo {
+        let paths = paths.iter().map(|path| path.to_string()).collect();
+
+        DiffInfo::new(100, 10).with_paths(paths)
+    }
+
+    #[test]
+    fn class_without_patterns_matches_nothing() {
+        let class = CustomClass::new("empty");
+        let msg_info = MessageInfo::new("Update dependencies");
+
+        assert!(!class.matches(&metadata("dependabot"), &diff(&[]), &msg_info));
+    }
+
+    #[test]
+    fn class_matches_when_all_patterns_match() {
+        let class = CustomClass::new("vendor")
+            .with_author(Regex::new("bot$").unwrap())
+            .with_path(Regex::new("^vendor/").unwrap());
+        let msg_info = MessageInfo::new("Update libfoo to 1.2.3");
+
+        assert!(class.matches(
+            &metadata("vendor-bot"),
+            &diff(&["src/main.rs", "vendor/libfoo/foo.c"]),
+            &msg_info
+        ));
+        assert!(!class.matches(
+            &metadata("Leeroy Jenkins"),
+            &diff(&["vendor/libfoo/foo.c"]),
+            &msg_info
+        ));
+        assert!(!class.matches(&metadata("vendor-bot"), &diff(&["src/main.rs"]), &msg_info));
+    }
+
+    #[test]
+    fn class_matches_subject() {
+        let class = CustomClass::new("release").with_subject(Regex::new("^Release v").unwrap());
+        let msg_info = MessageInfo::new("Release v1.2.3");
+        let msg_info2 = MessageInfo::new("Fix release script");
+
+        assert!(class.matches(&metadata("Leeroy Jenkins"), &diff(&[]), &msg_info));
+        assert!(!class.matches(&metadata("Leeroy Jenkins"), &diff(&[]), &msg_info2));
+    }
+}
@@ -3,6 +3,10 @@ pub struct DiffInfo {
     insertions: usize,
     deletions: usize,
     diff_total: usize,
+    top_dirs: usize,
+    files: usize,
+    binary_files: usize,
+    paths: Vec<String>,
 }
 
 impl DiffInfo {
@@ -11,9 +15,35 @@ impl DiffInfo {
             insertions,
             deletions,
             diff_total: insertions + deletions,
+            top_dirs: 0,
+            files: 0,
+            binary_files: 0,
+            paths: Vec::new(),
         }
     }
 
+    /// Sets the number of distinct top-level directories touched
+    /// by the diff. Files in the repository root count as one more.
+    pub fn with_top_dirs(mut self, top_dirs: usize) -> Self {
+        self.top_dirs = top_dirs;
+        self
+    }
+
+    /// Sets the number of changed files and how many of them are binary.
+    /// The binary files (e.g. images) add no lines to the diff stats.
+    pub fn with_files(mut self, files: usize, binary_files: usize) -> Self {
+        self.files = files;
+        self.binary_files = binary_files;
+        self
+    }
+
+    /// Attaches the list of changed paths. The list is collected
+    /// only when required, as it is pretty expensive for huge diffs.
+    pub fn with_paths(mut self, paths: Vec<String>) -> Self {
+        self.paths = paths;
+        self
+    }
+
     pub fn insertions(&self) -> usize {
         self.insertions
     }
@@ -23,4 +53,69 @@ impl DiffInfo {
     pub fn diff_total(&self) -> usize {
         self.diff_total
     }
+
+    pub fn top_dirs(&self) -> usize {
+        self.top_dirs
+    }
+
+    pub fn files(&self) -> usize {
+        self.files
+    }
+
+    pub fn binary_files(&self) -> usize {
+        self.binary_files
+    }
+
+    /// Whether the most of the changed files are binary, so the line
+    /// counts do not represent the size of the change.
+    pub fn is_mostly_binary(&self) -> bool {
+        self.binary_files * 2 > self.files
+    }
+
+    pub fn paths(&self) -> &[String] {
+        &self.paths
+    }
+
+    /// The sorted names of the top-level directories touched by the diff,
+    /// where the files in the repository root are listed as ".". The names
+    /// are known only if the paths are collected.
+    pub fn top_dir_names(&self) -> Vec<&str> {
+        let mut names: Vec<_> = self
+            .paths
+            .iter()
+            .map(|path| match path.find('/') {
+                Some(pos) => &path[..pos],
+                None => ".",
+            })
+            .collect();
+
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn top_dir_names_are_distinct() {
+        let paths = vec![
+            "src/main.rs".to_string(),
+            "README.md".to_string(),
+            "docs/usage.md".to_string(),
+            "src/commit/diff.rs".to_string(),
+        ];
+
+        let diff_info = DiffInfo::new(10, 2).with_paths(paths);
+        assert_eq!(diff_info.top_dir_names(), vec![".", "docs", "src"]);
+    }
+
+    #[test]
+    fn binary_files_are_counted() {
+        assert!(!DiffInfo::new(10, 2).is_mostly_binary());
+        assert!(!DiffInfo::new(10, 2).with_files(4, 2).is_mostly_binary());
+        assert!(DiffInfo::new(10, 2).with_files(3, 2).is_mostly_binary());
+    }
 }
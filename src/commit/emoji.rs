@@ -0,0 +1,189 @@
+use std::str::FromStr;
+
+/// Defines, how emoji prefixes of subjects (like "🐛 Fix the crash"
+/// or ":bug: Fix the crash") are handled during scoring.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EmojiMode {
+    /// The emoji is scored like the ordinary part of the subject.
+    Keep,
+
+    /// The emoji is stripped before scoring the subject length.
+    Strip,
+
+    /// The emoji is stripped too, but it must be present
+    /// and must belong to the gitmoji set.
+    Gitmoji,
+}
+
+impl FromStr for EmojiMode {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "keep" => Ok(Self::Keep),
+            "strip" => Ok(Self::Strip),
+            "gitmoji" => Ok(Self::Gitmoji),
+            _ => Err("emoji mode must be one of: keep, strip, gitmoji"),
+        }
+    }
+}
+
+/// Splits the subject into the leading emoji (either the character
+/// or the `:shortcode:`) and the rest of the subject.
+pub fn split_emoji(subject: &str) -> Option<(&str, &str)> {
+    let mut parts = subject.splitn(3, ':');
+
+    let prefix_len = if parts.next() == Some("") {
+        let code = parts.next()?;
+        parts.next()?;
+
+        let valid_code = !code.is_empty()
+            && code
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "_+-".contains(c));
+
+        if !valid_code {
+            return None;
+        }
+
+        code.len() + 2
+    } else {
+        subject
+            .char_indices()
+            .find(|(_, c)| !is_emoji_char(*c))
+            .map_or(subject.len(), |(index, _)| index)
+    };
+
+    if prefix_len == 0 {
+        return None;
+    }
+
+    Some((&subject[..prefix_len], subject[prefix_len..].trim_start()))
+}
+
+/// Checks whether the emoji (or the shortcode) belongs to the gitmoji set.
+pub fn is_gitmoji(emoji: &str) -> bool {
+    let emoji = emoji.trim_end_matches('\u{fe0f}');
+
+    GITMOJI
+        .iter()
+        .any(|(symbol, code)| symbol.trim_end_matches('\u{fe0f}') == emoji || *code == emoji)
+}
+
+fn is_emoji_char(c: char) -> bool {
+    matches!(c as u32,
+        0x1f000..=0x1faff
+        | 0x2600..=0x27bf
+        | 0x2b00..=0x2bff
+        | 0x2190..=0x21ff
+        | 0x23e9..=0x23fa
+        | 0x200d
+        | 0xfe0f)
+}
+
+const GITMOJI: &[(&str, &str)] = &[
+    ("🎨", ":art:"),
+    ("⚡️", ":zap:"),
+    ("🔥", ":fire:"),
+    ("🐛", ":bug:"),
+    ("🚑️", ":ambulance:"),
+    ("✨", ":sparkles:"),
+    ("📝", ":memo:"),
+    ("🚀", ":rocket:"),
+    ("💄", ":lipstick:"),
+    ("🎉", ":tada:"),
+    ("✅", ":white_check_mark:"),
+    ("🔒️", ":lock:"),
+    ("🔐", ":closed_lock_with_key:"),
+    ("🔖", ":bookmark:"),
+    ("🚨", ":rotating_light:"),
+    ("🚧", ":construction:"),
+    ("💚", ":green_heart:"),
+    ("⬇️", ":arrow_down:"),
+    ("⬆️", ":arrow_up:"),
+    ("📌", ":pushpin:"),
+    ("👷", ":construction_worker:"),
+    ("📈", ":chart_with_upwards_trend:"),
+    ("♻️", ":recycle:"),
+    ("➕", ":heavy_plus_sign:"),
+    ("➖", ":heavy_minus_sign:"),
+    ("🔧", ":wrench:"),
+    ("🔨", ":hammer:"),
+    ("🌐", ":globe_with_meridians:"),
+    ("✏️", ":pencil2:"),
+    ("💩", ":poop:"),
+    ("⏪️", ":rewind:"),
+    ("🔀", ":twisted_rightwards_arrows:"),
+    ("📦️", ":package:"),
+    ("👽️", ":alien:"),
+    ("🚚", ":truck:"),
+    ("📄", ":page_facing_up:"),
+    ("💥", ":boom:"),
+    ("🍱", ":bento:"),
+    ("♿️", ":wheelchair:"),
+    ("💡", ":bulb:"),
+    ("🍻", ":beers:"),
+    ("💬", ":speech_balloon:"),
+    ("🗃️", ":card_file_box:"),
+    ("🔊", ":loud_sound:"),
+    ("🔇", ":mute:"),
+    ("👥", ":busts_in_silhouette:"),
+    ("🚸", ":children_crossing:"),
+    ("🏗️", ":building_construction:"),
+    ("📱", ":iphone:"),
+    ("🤡", ":clown_face:"),
+    ("🥚", ":egg:"),
+    ("🙈", ":see_no_evil:"),
+    ("📸", ":camera_flash:"),
+    ("⚗️", ":alembic:"),
+    ("🔍️", ":mag:"),
+    ("🏷️", ":label:"),
+    ("🌱", ":seedling:"),
+    ("🚩", ":triangular_flag_on_post:"),
+    ("🥅", ":goal_net:"),
+    ("💫", ":dizzy:"),
+    ("🗑️", ":wastebasket:"),
+    ("🛂", ":passport_control:"),
+    ("🩹", ":adhesive_bandage:"),
+    ("🧐", ":monocle_face:"),
+    ("⚰️", ":coffin:"),
+    ("🧪", ":test_tube:"),
+    ("👔", ":necktie:"),
+    ("🩺", ":stethoscope:"),
+    ("🧱", ":bricks:"),
+    ("🧑‍💻", ":technologist:"),
+    ("💸", ":money_with_wings:"),
+    ("🧵", ":thread:"),
+    ("🦺", ":safety_vest:"),
+    ("✈️", ":airplane:"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emoji_prefixes_are_split() {
+        assert_eq!(
+            split_emoji("🐛 Fix the crash"),
+            Some(("🐛", "Fix the crash"))
+        );
+        assert_eq!(split_emoji("⚡️ Speed up"), Some(("⚡️", "Speed up")));
+        assert_eq!(
+            split_emoji(":bug: Fix the crash"),
+            Some((":bug:", "Fix the crash"))
+        );
+        assert_eq!(split_emoji("Fix the crash"), None);
+        assert_eq!(split_emoji(":: Fix the crash"), None);
+        assert_eq!(split_emoji(":Fix the crash: now"), None);
+    }
+
+    #[test]
+    fn gitmoji_set_is_checked() {
+        assert!(is_gitmoji("🐛"));
+        assert!(is_gitmoji("⚡"));
+        assert!(is_gitmoji(":sparkles:"));
+        assert!(!is_gitmoji("🦄"));
+        assert!(!is_gitmoji(":unicorn:"));
+    }
+}
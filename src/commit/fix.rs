@@ -0,0 +1,139 @@
+use crate::patterns;
+
+use regex::Regex;
+
+lazy_static! {
+    /// The list item marker along with the spaces following it.
+    static ref BULLET_MARKER: Regex = patterns::builtin(r"^\s*([*\-•]|\d{1,2}[.)])\s+");
+    static ref TRAILER_PAIR: Regex = patterns::builtin(r"^([A-Za-z][\w-]*):(.*\S.*)$");
+    static ref DIFF_START: Regex =
+        patterns::builtin(r"^(diff --git |@@ -\d+(,\d+)? \+\d+(,\d+)? @@)");
+}
+
+/// Suggests the fixed version of the (cleaned up) message: the trailing
+/// whitespace is stripped, the subject is followed by the blank line, and
+/// the paragraphs with the lines longer than the wrap width are re-wrapped.
+///
+/// Only the prose and the list items are re-wrapped, while the paragraphs
+/// with the code, the quotes, the pasted diffs and the trailers are kept
+/// as is, as well as the words longer than the width (e.g. the URLs).
+pub fn suggest_fix(message: &str, wrap_width: usize) -> String {
+    let mut lines = message.lines().map(str::trim_end);
+    let mut fixed = vec![lines.next().unwrap_or("").to_string()];
+
+    let body: Vec<_> = lines.collect();
+    if matches!(body.first(), Some(line) if !line.is_empty()) {
+        fixed.push(String::new());
+    }
+
+    for (index, paragraph) in body.split(|line| line.is_empty()).enumerate() {
+        if index > 0 {
+            fixed.push(String::new());
+        }
+
+        let too_long = paragraph
+            .iter()
+            .any(|line| line.chars().count() > wrap_width);
+        if too_long && is_prose(paragraph) {
+            fixed.extend(wrap_paragraph(paragraph, wrap_width));
+        } else {
+            fixed.extend(paragraph.iter().map(|line| line.to_string()));
+        }
+    }
+
+    fixed.join("\n")
+}
+
+/// Checks whether the paragraph may be re-wrapped without breaking it.
+fn is_prose(paragraph: &[&str]) -> bool {
+    let is_trailers = paragraph.iter().all(|line| TRAILER_PAIR.is_match(line));
+
+    !is_trailers
+        && paragraph.iter().all(|line| {
+            let indented = line.starts_with(' ') || line.starts_with('\t');
+            (!indented || BULLET_MARKER.is_match(line))
+                && !line.starts_with('>')
+                && !DIFF_START.is_match(line)
+        })
+}
+
+/// Re-wraps the paragraph, keeping its list items apart: the item lines
+/// after the first one are indented by the width of the marker.
+fn wrap_paragraph(paragraph: &[&str], wrap_width: usize) -> Vec<String> {
+    let mut items: Vec<(String, Vec<&str>)> = Vec::new();
+
+    for line in paragraph {
+        match BULLET_MARKER.find(line) {
+            Some(marker) => items.push((
+                marker.as_str().to_string(),
+                line[marker.end()..].split_whitespace().collect(),
+            )),
+            None if items.is_empty() => {
+                items.push((String::new(), line.split_whitespace().collect()))
+            }
+            None => items.last_mut().unwrap().1.extend(line.split_whitespace()),
+        }
+    }
+
+    let mut wrapped = Vec::new();
+    for (marker, words) in items {
+        let indent = " ".repeat(marker.chars().count());
+        let mut line = marker;
+        let mut line_len = line.chars().count();
+        let mut line_words = 0;
+
+        for word in words {
+            let word_len = word.chars().count();
+            if line_words > 0 && line_len + 1 + word_len > wrap_width {
+                wrapped.push(line);
+                line = indent.clone();
+                line_len = indent.len();
+                line_words = 0;
+            }
+
+            if line_words > 0 {
+                line.push(' ');
+                line_len += 1;
+            }
+            line.push_str(word);
+            line_len += word_len;
+            line_words += 1;
+        }
+
+        wrapped.push(line);
+    }
+
+    wrapped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn break_is_inserted() {
+        assert_eq!(
+            suggest_fix("Fix the parser  \nIt crashed.", 72),
+            "Fix the parser\n\nIt crashed."
+        );
+        assert_eq!(suggest_fix("Fix the parser", 72), "Fix the parser");
+    }
+
+    #[test]
+    fn long_paragraphs_are_rewrapped() {
+        let message = "Fix the parser\n\n\
+                       The parser crashed on the empty input, so it is checked first.\n\n\
+                       * The input is checked before parsing, as required.\n\
+                       - Short item\n\n    let code = \"is kept as is, however long it is\";\n\n\
+                       Reviewed-by: Foo Bar <foo@example.com>";
+
+        assert_eq!(
+            suggest_fix(message, 30),
+            "Fix the parser\n\n\
+             The parser crashed on the\nempty input, so it is checked\nfirst.\n\n\
+             * The input is checked before\n  parsing, as required.\n\
+             - Short item\n\n    let code = \"is kept as is, however long it is\";\n\n\
+             Reviewed-by: Foo Bar <foo@example.com>"
+        );
+    }
+}
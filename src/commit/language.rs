@@ -0,0 +1,250 @@
+/// The natural language of the commit message. The language defines
+/// the keywords, which are used by heuristics for classifying and
+/// scoring commits.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+    French,
+    Spanish,
+    Russian,
+}
+
+/// The language-specific keywords. All the words are in lowercase.
+pub struct Keywords {
+    /// The most frequent words of the language, which are
+    /// used only for detecting the language.
+    common: &'static [&'static str],
+
+    /// Words, which indicate moving or renaming things.
+    pub refactor: &'static [&'static str],
+
+    /// Words, which join multiple clauses of the subject.
+    pub conjunctions: &'static [&'static str],
+
+    /// Words, which say nothing specific about the change
+    /// ("fix", "update", "stuff").
+    pub vague: &'static [&'static str],
+}
+
+impl Language {
+    const ALL: [Language; 5] = [
+        Language::English,
+        Language::German,
+        Language::French,
+        Language::Spanish,
+        Language::Russian,
+    ];
+
+    /// Detects the language of the text, which is split into the words.
+    /// The language with the most keywords found wins, and English
+    /// is assumed when nothing is found.
+    pub fn detect<'a, I>(words: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut hits = [0usize; 5];
+
+        for word in words {
+            for (index, language) in Self::ALL.iter().enumerate() {
+                let keywords = language.keywords();
+
+                let found = [
+                    keywords.common,
+                    keywords.refactor,
+                    keywords.conjunctions,
+                    keywords.vague,
+                ]
+                .iter()
+                .any(|list| list.contains(&word));
+
+                if found {
+                    hits[index] += 1;
+                }
+            }
+        }
+
+        let mut detected = 0;
+        for index in 1..hits.len() {
+            if hits[index] > hits[detected] {
+                detected = index;
+            }
+        }
+
+        Self::ALL[detected]
+    }
+
+    pub fn keywords(self) -> &'static Keywords {
+        match self {
+            Self::English => &ENGLISH,
+            Self::German => &GERMAN,
+            Self::French => &FRENCH,
+            Self::Spanish => &SPANISH,
+            Self::Russian => &RUSSIAN,
+        }
+    }
+}
+
+/// Splits the text into lowercase words, ignoring the punctuation.
+pub fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+}
+
+static ENGLISH: Keywords = Keywords {
+    common: &[
+        "the", "of", "to", "in", "for", "is", "with", "on", "this", "that", "it", "from", "be",
+        "as", "not", "when",
+    ],
+    refactor: &["move", "moved", "moves", "rename", "renamed", "renames"],
+    conjunctions: &["and", "also", "plus"],
+    vague: &[
+        "fix", "fixes", "fixed", "update", "updates", "updated", "change", "changes", "changed",
+        "stuff", "misc", "wip", "minor", "small", "some", "things", "tweak", "tweaks", "cleanup",
+    ],
+};
+
+static GERMAN: Keywords = Keywords {
+    common: &[
+        "der", "die", "das", "ist", "nicht", "mit", "für", "von", "den", "dem", "zu", "auf", "ein",
+        "eine", "im", "wenn",
+    ],
+    refactor: &[
+        "verschieben",
+        "verschoben",
+        "verschiebt",
+        "umbenennen",
+        "umbenannt",
+        "benennt",
+    ],
+    conjunctions: &["und", "auch", "sowie"],
+    vague: &[
+        "korrektur",
+        "änderung",
+        "änderungen",
+        "anpassung",
+        "anpassungen",
+        "kleinigkeiten",
+        "diverses",
+        "sachen",
+    ],
+};
+
+static FRENCH: Keywords = Keywords {
+    common: &[
+        "le", "la", "les", "des", "du", "un", "une", "pour", "dans", "est", "pas", "sur", "avec",
+        "au", "quand",
+    ],
+    refactor: &[
+        "déplacer",
+        "déplacé",
+        "déplace",
+        "renommer",
+        "renommé",
+        "renomme",
+    ],
+    conjunctions: &["et", "aussi", "ainsi"],
+    vague: &[
+        "correction",
+        "corrections",
+        "modification",
+        "modifications",
+        "changement",
+        "changements",
+        "divers",
+        "trucs",
+        "maj",
+    ],
+};
+
+static SPANISH: Keywords = Keywords {
+    common: &[
+        "el", "los", "las", "del", "en", "que", "para", "con", "por", "un", "una", "es", "cuando",
+    ],
+    refactor: &[
+        "mover",
+        "movido",
+        "mueve",
+        "renombrar",
+        "renombrado",
+        "renombra",
+    ],
+    conjunctions: &["y", "también", "además"],
+    vague: &[
+        "arreglo",
+        "arreglos",
+        "cambio",
+        "cambios",
+        "corrección",
+        "varios",
+        "cosas",
+        "actualización",
+    ],
+};
+
+static RUSSIAN: Keywords = Keywords {
+    common: &[
+        "в", "не", "на", "что", "для", "с", "по", "из", "при", "это", "как", "если",
+    ],
+    refactor: &[
+        "перенос",
+        "перенесен",
+        "перенесён",
+        "переместить",
+        "перемещение",
+        "переименование",
+        "переименовать",
+        "переименован",
+    ],
+    conjunctions: &["и", "также", "плюс"],
+    vague: &[
+        "исправление",
+        "исправления",
+        "правки",
+        "изменения",
+        "обновление",
+        "фикс",
+        "мелочи",
+        "разное",
+    ],
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(text: &str) -> Language {
+        let words: Vec<String> = words(text).collect();
+
+        Language::detect(words.iter().map(String::as_str))
+    }
+
+    #[test]
+    fn language_is_detected() {
+        assert_eq!(detect("Fix the crash on empty input"), Language::English);
+        assert_eq!(
+            detect("Behebe den Absturz mit der leeren Eingabe"),
+            Language::German
+        );
+        assert_eq!(
+            detect("Corriger le plantage avec une entrée vide"),
+            Language::French
+        );
+        assert_eq!(
+            detect("Corregir el fallo con la entrada vacía"),
+            Language::Spanish
+        );
+        assert_eq!(
+            detect("Исправить падение при пустом вводе"),
+            Language::Russian
+        );
+    }
+
+    #[test]
+    fn english_is_assumed_by_default() {
+        assert_eq!(detect("Frobnicate"), Language::English);
+        assert_eq!(detect(""), Language::English);
+    }
+}
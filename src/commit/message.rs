@@ -1,25 +1,113 @@
-use std::collections::HashSet;
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{Hash, Hasher},
+};
+
+use regex::Regex;
+
+use crate::{
+    commit::{
+        language::{words, Language},
+        options::ParseOptions,
+    },
+    patterns,
+};
 
 /// `MessageInfo` contains the metrics obtained from
 /// the commit message for scoring.
 #[derive(Default, Debug)]
 pub struct MessageInfo {
     subject: Option<String>,
+    first_body_line: Option<String>,
+    body_preview: Vec<String>,
     break_after_subject: bool,
-    body_len: usize,
-    body_lines: usize,
-    body_unwrapped_lines: usize,
+    body: BodyCounters,
     metadata_lines: usize,
+    reference_lines: usize,
+    malformed_metadata_lines: usize,
+    breaking_change_len: Option<usize>,
+    dirty_lines: usize,
+    trailers: Vec<Trailer>,
+    ignore_requested: bool,
+    language: Language,
+    digest: u64,
+}
+
+/// The metrics of the body text, which are counted together,
+/// so these are reset together when the body is disregarded.
+#[derive(Default, Debug)]
+struct BodyCounters {
+    len: usize,
+    lines: usize,
+    unwrapped_lines: usize,
+    paragraphs: usize,
+    longest_paragraph_lines: usize,
+    bullets: usize,
+    sections: usize,
+    pasted_diff_len: usize,
+    conflicts_len: usize,
+}
+
+/// The `Key: Value` trailer of the message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trailer {
+    key: String,
+    value: String,
+}
+
+impl Trailer {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
 }
 
 impl MessageInfo {
+    #[cfg(test)]
     pub fn new(raw_message: &str) -> Self {
+        Self::with_options(raw_message, &ParseOptions::default())
+    }
+
+    pub fn with_options(raw_message: &str, options: &ParseOptions) -> Self {
         let mut subject: Option<String> = None;
+        let mut first_body_line: Option<String> = None;
         let mut break_after_subject = false;
-        let mut body_len = 0;
-        let mut body_lines = 0;
-        let mut body_unwrapped_lines = 0;
+        let mut body = BodyCounters::default();
         let mut metadata_lines = 0;
+        let mut reference_lines = 0;
+        let mut malformed_metadata_lines = 0;
+        let mut in_paragraph = false;
+        let mut paragraph_lines = 0;
+        let mut in_pasted_diff = false;
+        let mut body_words = Vec::new();
+        let mut in_conflicts = false;
+        let mut breaking_change_len = None;
+        let mut in_breaking_change = false;
+        let mut ignore_requested = false;
+        let trailers_start = final_trailers_start(raw_message);
+
+        // The trailers are the lines of the last paragraph consisting
+        // of the trailers alone, as Git has it, so the prose like "Note:"
+        // or "Reported-by:" in the middle of the body is not one of them.
+        let mut trailers = Vec::new();
+
+        // The body is not kept as a whole, as it is not needed for scoring.
+        let body_preview = raw_message
+            .lines()
+            .skip(1)
+            .skip_while(|line| line.trim().is_empty())
+            .take(options.body_preview_lines())
+            .map(str::to_string)
+            .collect();
+
+        let dirty_lines = raw_message
+            .lines()
+            .enumerate()
+            .filter(|(line_num, line)| is_dirty(line, *line_num == 0))
+            .count();
 
         // Here we rely on line numbers, as Git strips
         // leading and trailing empty lines during commit.
@@ -34,34 +122,161 @@ impl MessageInfo {
                 break_after_subject = line.is_empty();
             }
 
+            // Older Git versions list the conflicting files in the
+            // message of the merge commit: the "Conflicts:" line is
+            // followed by the file paths indented with tabs.
+            if line == "Conflicts:" {
+                in_conflicts = true;
+            } else if in_conflicts && !line.starts_with('\t') {
+                in_conflicts = false;
+            }
+
+            if in_conflicts {
+                body.conflicts_len += line.len();
+            }
+
+            // The description of the breaking change may continue on
+            // the following lines, up to the next trailer or paragraph.
+            if let Some(captures) = BREAKING_CHANGE.captures(line) {
+                breaking_change_len = Some(captures[1].trim().len());
+                in_breaking_change = true;
+            } else if in_breaking_change {
+                if line.trim().is_empty() || TRAILER.is_match(line) {
+                    in_breaking_change = false;
+                } else if let Some(len) = breaking_change_len.as_mut() {
+                    *len += line.trim().len();
+                }
+            }
+
+            // The references count wherever these are: in the prose
+            // or in the trailers like "Link:" or "Fixes:".
+            if URL.is_match(line) || options.issue_pattern().is_match(line) {
+                reference_lines += 1;
+            }
+
+            let in_trailers = matches!(trailers_start, Some(start) if line_num >= start);
+            if let Some(captures) = TRAILER_PAIR.captures(line).filter(|_| in_trailers) {
+                let key = captures[1].to_string();
+                let value = captures[2].trim().to_string();
+                trailers.push(Trailer { key, value });
+            }
+
             if let Some(meta_key) = line.split(':').next() {
                 let key_lower = meta_key.trim().to_ascii_lowercase();
+
+                // The ignore directive is neither the part of the body,
+                // nor the useful metadata. It is honored only among the
+                // final trailers, so that the prose mentioning it is safe.
+                if in_trailers && key_lower == options.ignore_marker() {
+                    ignore_requested = true;
+                    continue;
+                }
+
                 if META_KEYS.contains(key_lower.as_str()) {
                     metadata_lines += 1;
+                    if !is_well_formed_trailer(line, &key_lower) {
+                        malformed_metadata_lines += 1;
+                    }
                     continue;
                 }
             }
 
+            if first_body_line.is_none() && !line.trim().is_empty() {
+                first_body_line = Some(line.to_string());
+            }
+
+            if line.trim().is_empty() {
+                in_paragraph = false;
+            } else if !in_paragraph {
+                body.paragraphs += 1;
+                in_paragraph = true;
+            }
+
+            // The pasted diff starts with the diff or hunk header
+            // and lasts until the first line, which cannot belong
+            // to the patch.
+            if DIFF_START.is_match(line) {
+                in_pasted_diff = true;
+            } else if in_pasted_diff && !DIFF_LINE.is_match(line) {
+                in_pasted_diff = false;
+            }
+
+            if in_pasted_diff {
+                body.pasted_diff_len += line.len();
+            } else if BULLET.is_match(line) {
+                body.bullets += 1;
+            } else if SECTION.is_match(line) {
+                body.sections += 1;
+            }
+
+            // The lines of prose are counted up to the paragraph break;
+            // the list items, sections and pasted diffs break the text
+            // into the readable chunks too.
+            let breaks_text =
+                !in_paragraph || in_pasted_diff || BULLET.is_match(line) || SECTION.is_match(line);
+
+            if breaks_text {
+                paragraph_lines = 0;
+            } else {
+                paragraph_lines += 1;
+                body.longest_paragraph_lines = body.longest_paragraph_lines.max(paragraph_lines);
+            }
+
+            body_words.extend(words(line));
+
             let line_len = line.len();
-            body_len += line_len;
-            body_lines += 1;
-            if line_len > 80 {
-                body_unwrapped_lines += 1;
+            body.len += line_len;
+            body.lines += 1;
+            if line_len > options.wrap_width() {
+                body.unwrapped_lines += 1;
             }
         }
 
+        // The body which just repeats the subject carries no information,
+        // so let's consider it absent instead of rewarding it.
+        if matches!(subject.as_deref(), Some(subject) if restates_subject(subject, &body_words)) {
+            body = BodyCounters::default();
+        }
+
+        let subject_words = subject.as_deref().into_iter().flat_map(words);
+        let all_words: Vec<String> = subject_words.chain(body_words).collect();
+        let language = Language::detect(all_words.iter().map(String::as_str));
+
+        let mut hasher = DefaultHasher::new();
+        raw_message.trim().hash(&mut hasher);
+        let digest = hasher.finish();
+
         Self {
             subject,
+            first_body_line,
+            body_preview,
             break_after_subject,
-            body_len,
-            body_lines,
-            body_unwrapped_lines,
+            body,
             metadata_lines,
+            reference_lines,
+            malformed_metadata_lines,
+            breaking_change_len,
+            dirty_lines,
+            trailers,
+            ignore_requested,
+            language,
+            digest,
         }
     }
 
     pub fn subject(&self) -> Option<&str> {
-        self.subject.as_ref().map(|ref s| s.as_str())
+        self.subject.as_deref()
+    }
+
+    /// The first non-empty line of the body (excluding metadata).
+    pub fn first_body_line(&self) -> Option<&str> {
+        self.first_body_line.as_deref()
+    }
+
+    /// The first lines of the body (starting from the first non-empty one),
+    /// which are kept only if requested via the parse options.
+    pub fn body_preview(&self) -> &[String] {
+        &self.body_preview
     }
 
     pub fn break_after_subject(&self) -> bool {
@@ -69,20 +284,198 @@ impl MessageInfo {
     }
 
     pub fn body_len(&self) -> usize {
-        self.body_len
+        self.body.len
     }
 
     pub fn body_lines(&self) -> usize {
-        self.body_lines
+        self.body.lines
     }
 
     pub fn body_unwrapped_lines(&self) -> usize {
-        self.body_unwrapped_lines
+        self.body.unwrapped_lines
     }
 
     pub fn metadata_lines(&self) -> usize {
         self.metadata_lines
     }
+
+    /// The number of body lines with the URLs or the issue IDs.
+    pub fn reference_lines(&self) -> usize {
+        self.reference_lines
+    }
+
+    /// The trailers in the order of appearance: the lines with the
+    /// well-known metadata keys and the lines of the trailing block of
+    /// `Key: Value` lines (e.g. `Change-Id`).
+    pub fn trailers(&self) -> &[Trailer] {
+        &self.trailers
+    }
+
+    /// Checks whether the message has the trailer with the key
+    /// (case-insensitive).
+    pub fn has_trailer(&self, key: &str) -> bool {
+        self.trailers
+            .iter()
+            .any(|trailer| trailer.key.eq_ignore_ascii_case(key))
+    }
+
+    /// The number of metadata lines, which are not in the canonical
+    /// `Key: Value` form (see `is_well_formed_trailer()`).
+    pub fn malformed_metadata_lines(&self) -> usize {
+        self.malformed_metadata_lines
+    }
+
+    pub fn body_paragraphs(&self) -> usize {
+        self.body.paragraphs
+    }
+
+    /// The number of lines in the longest run of prose in the body,
+    /// not broken by the empty lines, list items or sections.
+    pub fn longest_paragraph_lines(&self) -> usize {
+        self.body.longest_paragraph_lines
+    }
+
+    /// The number of bullet list items (`*`, `-` or numbered) in the body.
+    pub fn body_bullets(&self) -> usize {
+        self.body.bullets
+    }
+
+    /// The number of section headers (like "Why:" or "Testing:") in the body.
+    pub fn body_sections(&self) -> usize {
+        self.body.sections
+    }
+
+    /// The length of the diff (patch) content pasted into the body.
+    /// This is counted as the part of the body.
+    pub fn pasted_diff_len(&self) -> usize {
+        self.body.pasted_diff_len
+    }
+
+    /// The length of the conflicting files list generated by Git
+    /// for merge commits. This is counted as the part of the body.
+    pub fn conflicts_len(&self) -> usize {
+        self.body.conflicts_len
+    }
+
+    /// The length of the description in the `BREAKING CHANGE:` footer
+    /// of the Conventional Commit, `None` if there is no such footer.
+    pub fn breaking_change_len(&self) -> Option<usize> {
+        self.breaking_change_len
+    }
+
+    /// The number of lines with the trailing whitespace, the control
+    /// characters or, for the subject, the tabs.
+    pub fn dirty_lines(&self) -> usize {
+        self.dirty_lines
+    }
+
+    /// Whether the message contains the directive for
+    /// excluding the commit from scoring.
+    pub fn ignore_requested(&self) -> bool {
+        self.ignore_requested
+    }
+
+    /// The detected natural language of the message.
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// The hash of the whole message text, which allows
+    /// detecting identical messages cheaply.
+    pub fn digest(&self) -> u64 {
+        self.digest
+    }
+}
+
+/// The fraction of the body words, which must be taken from
+/// the subject for considering the body as the restatement.
+const RESTATEMENT_SIMILARITY: f32 = 0.8;
+
+/// Checks whether the words are the same, not taking the endings into
+/// account ("fix" and "fixed" are the same for this purpose).
+fn same_word(word: &str, other: &str) -> bool {
+    if word.len().min(other.len()) < 3 {
+        return word == other;
+    }
+
+    word.starts_with(other) || other.starts_with(word)
+}
+
+/// Finds the line number, where the last paragraph of the body starts,
+/// if the paragraph consists of the trailers alone.
+fn final_trailers_start(raw_message: &str) -> Option<usize> {
+    let lines: Vec<&str> = raw_message.trim_end().lines().collect();
+    let start = lines.iter().rposition(|line| line.trim().is_empty())? + 1;
+
+    let all_trailers = lines[start..].iter().all(|line| {
+        TRAILER_PAIR
+            .captures(line)
+            .filter(|captures| !captures[2].starts_with("//"))
+            .is_some()
+    });
+
+    Some(start).filter(|_| all_trailers && start < lines.len())
+}
+
+/// Checks whether the body merely restates the subject: almost all the
+/// body words are taken from the subject, and the body is not much longer.
+fn restates_subject(subject: &str, body_words: &[String]) -> bool {
+    if body_words.is_empty() {
+        return false;
+    }
+
+    let subject_words: Vec<String> = words(subject).collect();
+    let borrowed = body_words
+        .iter()
+        .filter(|word| subject_words.iter().any(|other| same_word(word, other)))
+        .count();
+
+    body_words.len() <= 2 * subject_words.len()
+        && borrowed as f32 >= RESTATEMENT_SIMILARITY * body_words.len() as f32
+}
+
+lazy_static! {
+    static ref BULLET: Regex = patterns::builtin(r"^\s*([*\-•]|\d{1,2}[.)])\s+\S");
+    static ref SECTION: Regex = patterns::builtin(r"^[A-Z][A-Za-z ]{1,30}:(\s|$)");
+    static ref DIFF_START: Regex =
+        patterns::builtin(r"^(diff --git |@@ -\d+(,\d+)? \+\d+(,\d+)? @@)");
+    static ref DIFF_LINE: Regex = patterns::builtin(r"^([+\- ]|@@ |index |diff )");
+    static ref BREAKING_CHANGE: Regex = patterns::builtin(r"^BREAKING[ -]CHANGE:(.*)$");
+    static ref TRAILER: Regex = patterns::builtin(r"^[\w-]+(: | #)");
+    static ref TRAILER_PAIR: Regex = patterns::builtin(r"^([A-Za-z][\w-]*):(.*\S.*)$");
+    static ref URL: Regex = patterns::builtin(r"\b[a-z][a-z0-9+.-]*://\S");
+    static ref PERSON: Regex = patterns::builtin(r"^[^<>@]*\S <[^<>@\s]+@[^<>@\s]+\.[^<>@\s]+>$");
+}
+
+/// Checks whether the line has the trailing whitespace or the control
+/// characters (the tabs are allowed in the body, e.g. for the indented
+/// code or the conflict lists, but not in the subject).
+fn is_dirty(line: &str, is_subject: bool) -> bool {
+    line.ends_with(char::is_whitespace)
+        || line
+            .chars()
+            .any(|c| c.is_control() && (c != '\t' || is_subject))
+}
+
+/// Checks whether the recognized trailer line has the form, which Git and
+/// the tools parsing the trailers expect: the key capitalized like
+/// `Signed-off-by` at the line start, a single space after the colon,
+/// the non-empty value and, for the `-by` trailers, the person given
+/// as `Name <email>`.
+fn is_well_formed_trailer(line: &str, key_lower: &str) -> bool {
+    let mut canonical_key = key_lower.to_string();
+    canonical_key[..1].make_ascii_uppercase();
+
+    let value = match line.find(": ").map(|pos| &line[pos + 2..]) {
+        Some(value) if line.starts_with(&canonical_key) => value,
+        _ => return false,
+    };
+
+    if value.trim().is_empty() || value.starts_with(' ') || value.ends_with(' ') {
+        return false;
+    }
+
+    !key_lower.ends_with("-by") || PERSON.is_match(value)
 }
 
 lazy_static! {
@@ -122,5 +515,235 @@ lazy_static! {
 
 #[cfg(test)]
 mod tests {
-    // TODO: test message info parsing.
+    use super::*;
+
+    // TODO: test the rest of message info parsing.
+
+    #[test]
+    fn ignore_directive_is_detected_in_trailers() {
+        let msg_info = MessageInfo::new("Import vendored libfoo\n\nCommrate-Ignore: vendored");
+        let msg_info2 = MessageInfo::new("Import vendored libfoo\n\ncommrate-ignore: vendored");
+
+        assert!(msg_info.ignore_requested());
+        assert!(msg_info2.ignore_requested());
+        assert_eq!(msg_info.body_lines(), 1);
+        assert_eq!(msg_info.metadata_lines(), 0);
+    }
+
+    #[test]
+    fn references_are_counted() {
+        let references = |message: &str| MessageInfo::new(message).reference_lines();
+
+        assert_eq!(references("Fix #12\n\nNo references here."), 0);
+        assert_eq!(
+            references("Rework the parser\n\nSee https://example.com/design.\n\nRefs: PROJ-12"),
+            2
+        );
+    }
+
+    #[test]
+    fn dirty_lines_are_counted() {
+        let dirty = |message: &str| MessageInfo::new(message).dirty_lines();
+
+        assert_eq!(dirty("Fix the parser\n\n\tindented code\n"), 0);
+        assert_eq!(dirty("Fix\tthe parser \n\nSome text. \nBell\u{7}"), 3);
+    }
+
+    #[test]
+    fn longest_paragraph_is_measured() {
+        let message = "Fix the parser\n\n\
+            The parser failed\non the empty input.\n\n\
+            It is checked now:\n\
+            - first\n\
+            - second\n\
+            and then\nparsed\nas usual.";
+
+        assert_eq!(MessageInfo::new(message).longest_paragraph_lines(), 3);
+    }
+
+    #[test]
+    fn trailers_are_parsed() {
+        let message = "Fix the parser\n\n\
+            Note: the empty input is valid.\n\
+            Reviewed-by: Jane Roe <jane@example.com>\n\n\
+            Signed-off-by: John Doe <john@example.com>\n\
+            Change-Id: I1234\n\
+            https://example.com/issue";
+        let trailers = MessageInfo::new(message).trailers().to_vec();
+
+        // The last block is not the trailer block due to the URL.
+        assert!(trailers.is_empty());
+
+        let msg_info = MessageInfo::new(&message.replace("\nhttps://example.com/issue", ""));
+        let trailers = msg_info.trailers();
+        assert_eq!(trailers.len(), 2);
+        assert_eq!(trailers[0].value(), "John Doe <john@example.com>");
+        assert!(msg_info.has_trailer("change-id"));
+        assert!(!msg_info.has_trailer("reviewed-by"));
+        assert!(!msg_info.has_trailer("note"));
+    }
+
+    #[test]
+    fn malformed_trailers_are_counted() {
+        let message = "Fix the parser\n\n\
+            The parser failed on the empty input.\n\n\
+            Signed-off-by: John Doe <john@example.com>\n\
+            Reviewed-By: Jane Roe <jane@example.com>\n\
+            Acked-by: Jane Roe\n\
+            Fixes:  1234abcd\n\
+            Link: https://example.com/1234";
+
+        let msg_info = MessageInfo::new(message);
+
+        assert_eq!(msg_info.metadata_lines(), 5);
+        assert_eq!(msg_info.malformed_metadata_lines(), 3);
+    }
+
+    #[test]
+    fn breaking_change_footer_is_measured() {
+        let footer = |message: &str| MessageInfo::new(message).breaking_change_len();
+
+        assert_eq!(
+            footer(
+                "feat!: drop v1
+
+BREAKING CHANGE: v1 is gone"
+            ),
+            Some(10)
+        );
+        assert_eq!(
+            footer(
+                "feat!: drop v1
+
+BREAKING CHANGE:
+v1 is gone
+Refs: #12"
+            ),
+            Some(10)
+        );
+        assert_eq!(
+            footer(
+                "feat: drop v1
+
+BREAKING-CHANGE:
+Refs: #12"
+            ),
+            Some(0)
+        );
+        assert_eq!(footer("feat!: drop v1"), None);
+    }
+
+    #[test]
+    fn ignore_directive_is_absent_by_default() {
+        let msg_info = MessageInfo::new("Import vendored libfoo\n\nSigned-off-by: Foo");
+
+        assert!(!msg_info.ignore_requested());
+    }
+
+    #[test]
+    fn custom_ignore_marker_is_respected() {
+        let options = ParseOptions::default().with_ignore_marker("Skip-Rating");
+        let msg_info = MessageInfo::with_options("Add migration\n\nSkip-Rating: yes", &options);
+        let msg_info2 =
+            MessageInfo::with_options("Add migration\n\nCommrate-Ignore: yes", &options);
+
+        assert!(msg_info.ignore_requested());
+        assert!(!msg_info2.ignore_requested());
+    }
+
+    #[test]
+    fn ignore_directive_in_prose_is_not_honored() {
+        let msg_info = MessageInfo::new(
+            "Document the directive\n\ncommrate-ignore: skips the commit, if set.\n\nRefs: #12",
+        );
+        let msg_info2 =
+            MessageInfo::new("Document the directive\n\nSee below.\ncommrate-ignore: vendored");
+
+        assert!(!msg_info.ignore_requested());
+        assert!(!msg_info2.ignore_requested());
+    }
+
+    #[test]
+    fn conflicts_list_is_measured() {
+        let msg_info = MessageInfo::new("Merge branch 'foo'\n\nConflicts:\n\tsrc/a.rs\n\tsrc/b.rs");
+        let msg_info2 = MessageInfo::new(
+            "Merge branch 'foo'\n\nConflicts:\n\tsrc/a.rs\n\nKept the new API in a.rs.",
+        );
+
+        assert_eq!(msg_info.conflicts_len(), msg_info.body_len());
+        assert_eq!(msg_info2.conflicts_len(), 19);
+        assert!(msg_info2.body_len() > msg_info2.conflicts_len());
+    }
+
+    #[test]
+    fn body_preview_is_kept_only_if_requested() {
+        let message = "Fix parser\n\nFirst line\nSecond line\n\nThird line";
+        let options = ParseOptions::default().with_body_preview(2);
+
+        assert!(MessageInfo::new(message).body_preview().is_empty());
+        assert_eq!(
+            MessageInfo::with_options(message, &options).body_preview(),
+            &["First line".to_string(), "Second line".to_string()]
+        );
+    }
+
+    #[test]
+    fn digest_distinguishes_messages() {
+        let msg_info = MessageInfo::new("Fix\n\nFix the thing.");
+        let msg_info2 = MessageInfo::new("Fix\n\nFix the thing.\n");
+        let msg_info3 = MessageInfo::new("Fix\n\nFix another thing.");
+
+        assert_eq!(msg_info.digest(), msg_info2.digest());
+        assert_ne!(msg_info.digest(), msg_info3.digest());
+    }
+
+    #[test]
+    fn body_restating_subject_is_considered_absent() {
+        let msg_info = MessageInfo::new("Fix the parser crash\n\nFixed the parser crash.");
+        let msg_info2 = MessageInfo::new(
+            "Fix the parser crash\n\nThe parser crashed on empty input, as the buffer\n\
+             was never checked for the length. Check it.",
+        );
+
+        assert_eq!(msg_info.body_len(), 0);
+        assert_eq!(msg_info.body_lines(), 0);
+        assert!(msg_info2.body_len() > 0);
+    }
+
+    #[test]
+    fn restated_body_counters_are_reset() {
+        let msg_info = MessageInfo::new("Merge the main conflicts\n\nConflicts:\n\tmain");
+
+        assert_eq!(msg_info.body_len(), 0);
+        assert_eq!(msg_info.conflicts_len(), 0);
+    }
+
+    #[test]
+    fn body_structure_is_detected() {
+        let msg_info = MessageInfo::new(
+            "Rework the cache\n\nWhy:\nThe cache was too slow.\n\nWhat:\n* use the map;\n\
+             - drop the list;\n1. measure.\n\nSigned-off-by: Foo",
+        );
+        let msg_info2 =
+            MessageInfo::new("Rework the cache\n\nThe cache was slow; rework it:\nuse map.");
+
+        assert_eq!(msg_info.body_paragraphs(), 2);
+        assert_eq!(msg_info.body_bullets(), 3);
+        assert_eq!(msg_info.body_sections(), 2);
+        assert_eq!(msg_info2.body_paragraphs(), 1);
+        assert_eq!(msg_info2.body_bullets(), 0);
+        assert_eq!(msg_info2.body_sections(), 0);
+    }
+
+    #[test]
+    fn pasted_diff_is_measured() {
+        let msg_info = MessageInfo::new(
+            "Fix the parser\n\nApply this:\n\n@@ -1,2 +1,2 @@\n-old\n+new\n context\n\n- not a diff",
+        );
+        let msg_info2 = MessageInfo::new("Fix the parser\n\n- drop the list;\n+ add the map.");
+
+        assert_eq!(msg_info.pasted_diff_len(), 31);
+        assert_eq!(msg_info.body_bullets(), 1);
+        assert_eq!(msg_info2.pasted_diff_len(), 0);
+    }
 }
@@ -1,21 +1,39 @@
+use git2::Oid;
+
+use std::{collections::HashSet, fmt, str, sync::Arc};
+
 /// A commit metadata, which is easy to obtain from
 /// the repository without any heavy processing.
+///
+/// The metadata is created for every traversed commit, including the
+/// ones dropped by the pre-filters, so it is kept cheap: the ID is stored
+/// in the raw form and formatted only when printed, while the author
+/// names are shared between the commits (see `AuthorInterner`).
 pub struct Metadata {
-    id: String,
-    author: String,
+    id: CommitId,
+    author: Arc<str>,
     parents: usize,
+    time: Option<AuthorTime>,
 }
 
 impl Metadata {
-    pub fn new(id: String, author: String, parents: usize) -> Self {
+    pub fn new(id: CommitId, author: Arc<str>, parents: usize) -> Self {
         Self {
             id,
             author,
             parents,
+            time: None,
         }
     }
 
-    pub fn id(&self) -> &str {
+    /// Sets the time the commit was authored at. It is known only
+    /// for the commits from the repository.
+    pub fn with_time(mut self, time: AuthorTime) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    pub fn id(&self) -> &CommitId {
         &self.id
     }
 
@@ -23,7 +41,161 @@ impl Metadata {
         &self.author
     }
 
+    /// The shared author name, which is cheap to clone.
+    pub fn author_shared(&self) -> &Arc<str> {
+        &self.author
+    }
+
     pub fn parents(&self) -> usize {
         self.parents
     }
+
+    pub fn time(&self) -> Option<AuthorTime> {
+        self.time
+    }
+}
+
+/// The time the commit was authored at, along with the offset of
+/// the author's timezone, so the local time of the author is known.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AuthorTime {
+    /// Seconds since the Unix epoch.
+    seconds: i64,
+
+    /// Offset of the timezone, in minutes.
+    offset: i32,
+}
+
+impl AuthorTime {
+    const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+    pub fn new(seconds: i64, offset: i32) -> Self {
+        Self { seconds, offset }
+    }
+
+    /// The local day of the week, from 0 for Monday to 6 for Sunday.
+    pub fn weekday(self) -> usize {
+        let days = self.local_seconds().div_euclid(Self::SECONDS_PER_DAY);
+
+        // The epoch was on Thursday.
+        (days + 3).rem_euclid(7) as usize
+    }
+
+    /// The local hour of the day, from 0 to 23.
+    pub fn hour(self) -> usize {
+        (self.local_seconds().rem_euclid(Self::SECONDS_PER_DAY) / 3600) as usize
+    }
+
+    fn local_seconds(self) -> i64 {
+        self.seconds + i64::from(self.offset) * 60
+    }
+}
+
+/// The ID of the commit: the object ID for the commits from the
+/// repository, or the placeholder for the commits, which do not
+/// exist yet (e.g. the message being composed).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CommitId {
+    Object(Oid),
+    Placeholder(&'static str),
+}
+
+impl CommitId {
+    /// Calls the function with the hexadecimal form of the object ID
+    /// (or with the placeholder), which is formatted on the stack.
+    pub fn with_str<R, F>(&self, action: F) -> R
+    where
+        F: FnOnce(&str) -> R,
+    {
+        const DIGITS: &[u8] = b"0123456789abcdef";
+
+        match self {
+            CommitId::Object(oid) => {
+                let mut hex = [0u8; 40];
+                for (index, byte) in oid.as_bytes().iter().enumerate() {
+                    hex[index * 2] = DIGITS[(byte >> 4) as usize];
+                    hex[index * 2 + 1] = DIGITS[(byte & 0xf) as usize];
+                }
+
+                let len = oid.as_bytes().len() * 2;
+                action(str::from_utf8(&hex[..len]).unwrap())
+            }
+            CommitId::Placeholder(placeholder) => action(placeholder),
+        }
+    }
+}
+
+/// Respects the width and the precision, so the IDs may be
+/// abbreviated like `format!("{:.12}", id)`.
+impl fmt::Display for CommitId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.with_str(|id| f.pad(id))
+    }
+}
+
+/// The storage of the author names, which makes all the commits
+/// of the same author share the single copy of the name.
+#[derive(Default)]
+pub struct AuthorInterner {
+    authors: HashSet<Arc<str>>,
+}
+
+impl AuthorInterner {
+    pub fn intern(&mut self, author: &str) -> Arc<str> {
+        if let Some(interned) = self.authors.get(author) {
+            return interned.clone();
+        }
+
+        let interned: Arc<str> = Arc::from(author);
+        self.authors.insert(interned.clone());
+
+        interned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_are_formatted() {
+        let oid = Oid::from_str("9335a4dc0e098830dec14fe3997c6a654695b935").unwrap();
+        let id = CommitId::Object(oid);
+
+        assert_eq!(id.to_string(), oid.to_string());
+        assert_eq!(format!("{:.12}", id), "9335a4dc0e09");
+        assert_eq!(
+            format!("{:12}|", CommitId::Placeholder("(staged)")),
+            "(staged)    |"
+        );
+    }
+
+    #[test]
+    fn local_time_is_calculated() {
+        // Friday, 2019-11-15 23:30 UTC.
+        let seconds = 1_573_860_600;
+
+        let utc = AuthorTime::new(seconds, 0);
+        assert_eq!((utc.weekday(), utc.hour()), (4, 23));
+
+        let moscow = AuthorTime::new(seconds, 180);
+        assert_eq!((moscow.weekday(), moscow.hour()), (5, 2));
+
+        let new_york = AuthorTime::new(seconds, -300);
+        assert_eq!((new_york.weekday(), new_york.hour()), (4, 18));
+
+        let before_epoch = AuthorTime::new(-1, 0);
+        assert_eq!((before_epoch.weekday(), before_epoch.hour()), (2, 23));
+    }
+
+    #[test]
+    fn authors_are_shared() {
+        let mut interner = AuthorInterner::default();
+        let first = interner.intern("Leeroy Jenkins");
+        let second = interner.intern("Leeroy Jenkins");
+        let other = interner.intern("Foo");
+
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(!Arc::ptr_eq(&first, &other));
+    }
 }
@@ -1,15 +1,33 @@
 mod class;
-pub use class::{Class, Classes};
+pub use class::{pull_request_title, Class, Classes};
+
+mod cleanup;
+pub use cleanup::cleanup_message;
 
 #[allow(clippy::module_inception)]
 mod commit;
 pub use commit::Commit;
 
+mod custom;
+pub use custom::{CustomClass, MAX_CUSTOM_CLASSES};
+
 mod diff;
 pub use diff::DiffInfo;
 
+mod emoji;
+pub use emoji::{is_gitmoji, split_emoji, EmojiMode};
+
+mod fix;
+pub use fix::suggest_fix;
+
+mod language;
+pub use language::words;
+
 mod message;
 pub use message::MessageInfo;
 
 mod metadata;
-pub use metadata::Metadata;
+pub use metadata::{AuthorInterner, AuthorTime, CommitId, Metadata};
+
+mod options;
+pub use options::{ParseOptions, DEFAULT_ISSUE_ID};
@@ -0,0 +1,153 @@
+use regex::Regex;
+
+use crate::{commit::custom::CustomClass, patterns};
+
+/// Maximum length of the message body line which is still
+/// considered wrapped, unless configured otherwise.
+pub const DEFAULT_WRAP_WIDTH: usize = 80;
+
+/// The trailer key which marks the commit as deliberately
+/// excluded from scoring, unless configured otherwise.
+pub const DEFAULT_IGNORE_MARKER: &str = "Commrate-Ignore";
+
+lazy_static! {
+    /// The issue IDs of the popular trackers: JIRA-like project keys
+    /// with numbers and GitHub/GitLab-like numbers, unless configured
+    /// otherwise.
+    pub static ref DEFAULT_ISSUE_ID: Regex =
+        patterns::builtin(r"\b[A-Z][A-Z0-9_]+-\d+\b|#\d+\b");
+}
+
+/// Maximum diff size (lines total) for short commits,
+/// unless configured otherwise.
+pub const SHORT_COMMIT_LENGTH: usize = 25;
+
+/// For refactoring commits, we allow a slight difference between
+/// insertions and deletions (5% of total diff) to ensure
+/// that move-related things like fixing imports and so on
+/// do not subvert the correct classification of these commits.
+///
+/// This is the default value, which may be configured otherwise.
+pub const REFACTOR_COMMIT_ALLOWED_DIFF: f32 = 0.05;
+
+/// Settings of the commit parsing and classification.
+#[derive(Clone, Debug)]
+pub struct ParseOptions {
+    wrap_width: usize,
+    ignore_marker: String,
+    short_commit_length: usize,
+    refactor_allowed_diff: f32,
+    custom_classes: Vec<CustomClass>,
+    paths_required: bool,
+    body_preview_lines: usize,
+    issue_pattern: Regex,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            wrap_width: DEFAULT_WRAP_WIDTH,
+            ignore_marker: DEFAULT_IGNORE_MARKER.to_ascii_lowercase(),
+            short_commit_length: SHORT_COMMIT_LENGTH,
+            refactor_allowed_diff: REFACTOR_COMMIT_ALLOWED_DIFF,
+            custom_classes: Vec::new(),
+            paths_required: false,
+            body_preview_lines: 0,
+            issue_pattern: DEFAULT_ISSUE_ID.clone(),
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Sets the maximum length of the message body line
+    /// which is still considered wrapped.
+    pub fn with_wrap_width(mut self, wrap_width: usize) -> Self {
+        self.wrap_width = wrap_width;
+        self
+    }
+
+    /// Sets the trailer key (matched case-insensitively), which marks
+    /// the commit as deliberately excluded from scoring.
+    pub fn with_ignore_marker(mut self, ignore_marker: &str) -> Self {
+        self.ignore_marker = ignore_marker.to_ascii_lowercase();
+        self
+    }
+
+    /// Sets the diff size (lines total), starting from which
+    /// commits are not considered short anymore.
+    pub fn with_short_commit_length(mut self, length: usize) -> Self {
+        self.short_commit_length = length;
+        self
+    }
+
+    /// Sets the allowed difference between insertions and deletions
+    /// of refactoring commits as a fraction of the total diff size.
+    pub fn with_refactor_allowed_diff(mut self, allowed_diff: f32) -> Self {
+        self.refactor_allowed_diff = allowed_diff;
+        self
+    }
+
+    /// Sets the user-defined classes, which should be assigned
+    /// to commits in addition to the built-in ones.
+    pub fn with_custom_classes(mut self, classes: Vec<CustomClass>) -> Self {
+        self.custom_classes = classes;
+        self
+    }
+
+    /// Makes the changed paths collected for all the commits, even
+    /// if these are not needed for the classification (e.g. for
+    /// aggregating the scores by paths).
+    pub fn with_paths_required(mut self) -> Self {
+        self.paths_required = true;
+        self
+    }
+
+    /// Makes the first lines of the message body kept for displaying
+    /// them along with the commits.
+    pub fn with_body_preview(mut self, lines: usize) -> Self {
+        self.body_preview_lines = lines;
+        self
+    }
+
+    /// Sets the regex recognizing the issue IDs in the message.
+    pub fn with_issue_pattern(mut self, issue_pattern: Regex) -> Self {
+        self.issue_pattern = issue_pattern;
+        self
+    }
+
+    pub fn wrap_width(&self) -> usize {
+        self.wrap_width
+    }
+
+    /// The lowercase trailer key of the ignore directive.
+    pub fn ignore_marker(&self) -> &str {
+        &self.ignore_marker
+    }
+
+    pub fn short_commit_length(&self) -> usize {
+        self.short_commit_length
+    }
+
+    pub fn refactor_allowed_diff(&self) -> f32 {
+        self.refactor_allowed_diff
+    }
+
+    /// The number of the first body lines kept in the parsed message.
+    pub fn body_preview_lines(&self) -> usize {
+        self.body_preview_lines
+    }
+
+    pub fn issue_pattern(&self) -> &Regex {
+        &self.issue_pattern
+    }
+
+    pub fn custom_classes(&self) -> &[CustomClass] {
+        &self.custom_classes
+    }
+
+    /// Whether the list of changed paths should be collected
+    /// for the diff of each commit.
+    pub fn collect_paths(&self) -> bool {
+        self.paths_required || self.custom_classes.iter().any(CustomClass::needs_paths)
+    }
+}
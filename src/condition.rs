@@ -0,0 +1,410 @@
+use crate::{scoring::Grade, summary::Summary};
+
+use std::{
+    fmt::{self, Display, Formatter},
+    iter::Peekable,
+    str::{Chars, FromStr},
+};
+
+/// A condition over the summary of the run, e.g. the one for failing
+/// the CI job: `count(F) > 0 || avg < 70`.
+///
+/// The condition consists of comparisons (`<`, `<=`, `>`, `>=`, `==`, `!=`)
+/// of numbers and the summary values, which are combined via `&&`, `||`,
+/// `!` and the parentheses. The summary values are:
+///
+/// * `avg` is the average score of the scored commits;
+/// * `count(X)` is the number of commits graded as X;
+/// * `scored` and `ignored` are the numbers of scored and ignored commits.
+///
+/// The average of the run without the scored commits is undefined,
+/// so any comparison with it is false.
+#[derive(Debug)]
+pub struct Condition {
+    source: String,
+    expr: Expr,
+}
+
+impl Condition {
+    pub fn evaluate(&self, summary: &Summary) -> bool {
+        self.expr.evaluate(summary)
+    }
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            chars: s.chars().peekable(),
+        };
+
+        let expr = parser.parse_or()?;
+        parser.skip_whitespace();
+
+        match parser.chars.next() {
+            None => Ok(Condition {
+                source: s.trim().to_string(),
+                expr,
+            }),
+            Some(chr) => Err(format!("unexpected '{}' in the condition", chr)),
+        }
+    }
+}
+
+/// Displays the condition the way it was written.
+impl Display for Condition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// The maximum numbers of commits of the grades, e.g. `F=0,D=3`,
+/// which is the shorthand for the common conditions.
+#[derive(Debug)]
+pub struct GradeLimits {
+    limits: Vec<(Grade, usize)>,
+}
+
+impl GradeLimits {
+    /// Returns the first of the grades, whose limit is exceeded,
+    /// along with the number of commits and the limit.
+    pub fn exceeded(&self, summary: &Summary) -> Option<(Grade, usize, usize)> {
+        self.limits.iter().find_map(|&(grade, limit)| {
+            let count = summary.grade_count(grade);
+            if count > limit {
+                Some((grade, count, limit))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl FromStr for GradeLimits {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut limits = Vec::new();
+
+        for term in s.split(',') {
+            let mut parts = term.splitn(2, '=');
+            let (grade, limit) = match (parts.next(), parts.next()) {
+                (Some(grade), Some(limit)) => (grade, limit),
+                _ => return Err(format!("'{}' must be like F=0", term.trim())),
+            };
+
+            let grade = grade.parse().map_err(str::to_string)?;
+            let limit = limit
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid number of commits '{}'", limit.trim()))?;
+
+            limits.push((grade, limit));
+        }
+
+        Ok(GradeLimits { limits })
+    }
+}
+
+#[derive(Debug)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Value, Relation, Value),
+}
+
+impl Expr {
+    fn evaluate(&self, summary: &Summary) -> bool {
+        match self {
+            Expr::Or(left, right) => left.evaluate(summary) || right.evaluate(summary),
+            Expr::And(left, right) => left.evaluate(summary) && right.evaluate(summary),
+            Expr::Not(expr) => !expr.evaluate(summary),
+            Expr::Compare(left, relation, right) => {
+                match (left.evaluate(summary), right.evaluate(summary)) {
+                    (Some(left), Some(right)) => relation.holds(left, right),
+                    _ => false,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Value {
+    Number(f64),
+    Average,
+    Count(Grade),
+    Scored,
+    Ignored,
+}
+
+impl Value {
+    fn evaluate(&self, summary: &Summary) -> Option<f64> {
+        match self {
+            Value::Number(number) => Some(*number),
+            Value::Average => summary.average().map(f64::from),
+            Value::Count(grade) => Some(summary.grade_count(*grade) as f64),
+            Value::Scored => Some(summary.scored() as f64),
+            Value::Ignored => Some(summary.ignored() as f64),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Relation {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Relation {
+    fn holds(&self, left: f64, right: f64) -> bool {
+        match self {
+            Relation::Lt => left < right,
+            Relation::Le => left <= right,
+            Relation::Gt => left > right,
+            Relation::Ge => left >= right,
+            Relation::Eq => (left - right).abs() < f64::EPSILON,
+            Relation::Ne => (left - right).abs() >= f64::EPSILON,
+        }
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_and()?;
+
+        while self.skip_operator("||") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_unary()?;
+
+        while self.skip_operator("&&") {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        self.skip_whitespace();
+
+        match self.chars.peek() {
+            // Not the beginning of "!=", as the values go first.
+            Some('!') => {
+                self.chars.next();
+                Ok(Expr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some('(') => {
+                self.chars.next();
+                let expr = self.parse_or()?;
+                self.expect(')')?;
+                Ok(expr)
+            }
+            _ => self.parse_comparison(),
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, String> {
+        let left = self.parse_value()?;
+        let relation = self.parse_relation()?;
+        let right = self.parse_value()?;
+
+        Ok(Expr::Compare(left, relation, right))
+    }
+
+    fn parse_relation(&mut self) -> Result<Relation, String> {
+        self.skip_whitespace();
+
+        let first = self.chars.next();
+        let equals = self.chars.peek() == Some(&'=');
+
+        let relation = match (first, equals) {
+            (Some('<'), false) => Relation::Lt,
+            (Some('<'), true) => Relation::Le,
+            (Some('>'), false) => Relation::Gt,
+            (Some('>'), true) => Relation::Ge,
+            (Some('='), true) => Relation::Eq,
+            (Some('!'), true) => Relation::Ne,
+            _ => return Err("expected comparison operator in the condition".to_string()),
+        };
+
+        if equals {
+            self.chars.next();
+        }
+
+        Ok(relation)
+    }
+
+    fn parse_value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+
+        match self.chars.peek() {
+            Some(chr) if chr.is_ascii_digit() => self.parse_number(),
+            Some(chr) if chr.is_ascii_alphabetic() => self.parse_name(),
+            Some(chr) => Err(format!("unexpected '{}' in the condition", chr)),
+            None => Err("unexpected end of the condition".to_string()),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Value, String> {
+        let text = self.take_while(|chr| chr.is_ascii_digit() || chr == '.');
+
+        text.parse()
+            .map(Value::Number)
+            .map_err(|_| format!("invalid number '{}' in the condition", text))
+    }
+
+    fn parse_name(&mut self) -> Result<Value, String> {
+        let name = self.take_while(|chr| chr.is_ascii_alphabetic());
+
+        match name.as_str() {
+            "avg" => Ok(Value::Average),
+            "scored" => Ok(Value::Scored),
+            "ignored" => Ok(Value::Ignored),
+            "count" => {
+                self.expect('(')?;
+                self.skip_whitespace();
+                let grade = self.take_while(|chr| chr.is_ascii_alphabetic());
+                let grade = grade.parse().map_err(str::to_string)?;
+                self.expect(')')?;
+
+                Ok(Value::Count(grade))
+            }
+            _ => Err(format!("unknown value '{}' in the condition", name)),
+        }
+    }
+
+    fn take_while<P>(&mut self, predicate: P) -> String
+    where
+        P: Fn(char) -> bool,
+    {
+        let mut text = String::new();
+
+        while let Some(&chr) = self.chars.peek() {
+            if !predicate(chr) {
+                break;
+            }
+
+            text.push(chr);
+            self.chars.next();
+        }
+
+        text
+    }
+
+    /// Skips the two-character operator, if it goes next.
+    fn skip_operator(&mut self, operator: &str) -> bool {
+        self.skip_whitespace();
+
+        let mut lookahead = self.chars.clone();
+        if !operator.chars().all(|chr| lookahead.next() == Some(chr)) {
+            return false;
+        }
+
+        self.chars = lookahead;
+        true
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+
+        match self.chars.next() {
+            Some(chr) if chr == expected => Ok(()),
+            Some(chr) => Err(format!(
+                "expected '{}' instead of '{}' in the condition",
+                expected, chr
+            )),
+            None => Err(format!("expected '{}' in the condition", expected)),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(chr) = self.chars.peek() {
+            if !chr.is_whitespace() {
+                break;
+            }
+
+            self.chars.next();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::Score;
+
+    fn summary(scores: &[(u8, Grade)]) -> Summary {
+        let mut summary = Summary::new();
+        for &(score, grade) in scores {
+            summary.add(Score::Scored { score, grade });
+        }
+
+        summary
+    }
+
+    fn holds(condition: &str, summary: &Summary) -> bool {
+        condition.parse::<Condition>().unwrap().evaluate(summary)
+    }
+
+    #[test]
+    fn conditions_are_evaluated() {
+        let summary = summary(&[(90, Grade::A), (30, Grade::F)]);
+
+        assert!(holds("count(F) > 0", &summary));
+        assert!(holds("count(F) > 0 || avg < 70", &summary));
+        assert!(!holds("count(D) > 0 || avg < 50", &summary));
+        assert!(holds("avg == 60 && scored >= 2", &summary));
+        assert!(holds("!(ignored != 0) && (count( A ) <= 1)", &summary));
+    }
+
+    #[test]
+    fn undefined_average_is_never_compared() {
+        let summary = Summary::new();
+
+        assert!(!holds("avg < 70", &summary));
+        assert!(!holds("avg >= 70", &summary));
+        assert!(holds("scored == 0", &summary));
+    }
+
+    #[test]
+    fn grade_limits_are_checked() {
+        let limits: GradeLimits = "F=0, D = 1".parse().unwrap();
+
+        let passing = summary(&[(90, Grade::A), (45, Grade::D)]);
+        assert_eq!(limits.exceeded(&passing), None);
+
+        let failing = summary(&[(45, Grade::D), (45, Grade::D), (10, Grade::F)]);
+        assert_eq!(limits.exceeded(&failing), Some((Grade::F, 1, 0)));
+
+        assert!("F".parse::<GradeLimits>().is_err());
+        assert!("F=-1".parse::<GradeLimits>().is_err());
+        assert!("X=1".parse::<GradeLimits>().is_err());
+    }
+
+    #[test]
+    fn invalid_conditions_are_rejected() {
+        assert!("avg".parse::<Condition>().is_err());
+        assert!("avg < ".parse::<Condition>().is_err());
+        assert!("count(X) > 0".parse::<Condition>().is_err());
+        assert!("median > 0".parse::<Condition>().is_err());
+        assert!("(avg < 70".parse::<Condition>().is_err());
+        assert!("avg < 70 ||".parse::<Condition>().is_err());
+        assert!("avg < 70 extra".parse::<Condition>().is_err());
+    }
+}
@@ -1,25 +1,366 @@
 use crate::{
-    commit::Metadata,
-    filter::{AuthorPreFilter, Filter, FilterChain, GradePostFilter, MergePreFilter},
-    scoring::{GradeSpec, ScoredCommit},
+    bitbucket::CLOUD_API_URL,
+    commit::{
+        Class, Classes, Commit, CustomClass, EmojiMode, Metadata, ParseOptions, MAX_CUSTOM_CLASSES,
+    },
+    condition::{Condition, GradeLimits},
+    error::fatal,
+    filter::{
+        AuthorPreFilter, ClassMidFilter, DiffSizeMidFilter, ExcludeClassMidFilter, Filter,
+        FilterChain, GradePostFilter, IgnoreListPreFilter, MergePreFilter, SubjectMidFilter,
+        TrailerMidFilter,
+    },
+    git::{self, GitRepository},
+    github,
+    ignore::IgnoreList,
+    patterns,
+    printer::{DigestFormat, OutputFormat},
+    scoring::{
+        BodyLenModel, ClassRef, Grade, GradeSpec, GradeStyle, RuleSeverity, ScoreFormat,
+        ScoreScale, ScoredCommit, SubjectLimits,
+    },
+    template::Template,
+    theme::ColorTheme,
 };
 
-use clap::{App, Arg, ArgMatches};
-use std::str::FromStr;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use git2::{Config, Error, ErrorCode};
+use regex::Regex;
+use std::{
+    cell::RefCell,
+    collections::BTreeSet,
+    env,
+    fmt::{self, Display, Formatter},
+    fs,
+    io::{self, BufRead},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Default history depth for cloning remote repositories.
+const DEFAULT_CLONE_DEPTH: usize = 1000;
+
+/// The environment variable with the access token for Bitbucket.
+pub const BITBUCKET_TOKEN_VARIABLE: &str = "BITBUCKET_TOKEN";
+
+/// The environment variables with the access token for Azure DevOps:
+/// the personal access token or the token of the pipeline.
+pub const AZURE_TOKEN_VARIABLES: [&str; 2] = ["AZURE_DEVOPS_TOKEN", "SYSTEM_ACCESSTOKEN"];
+
+/// The environment variables of GitHub Actions for the check run:
+/// the token, the `OWNER/REPO` repository and the URL of the API.
+pub const GITHUB_TOKEN_VARIABLE: &str = "GITHUB_TOKEN";
+pub const GITHUB_REPOSITORY_VARIABLE: &str = "GITHUB_REPOSITORY";
+const GITHUB_API_URL_VARIABLE: &str = "GITHUB_API_URL";
+
+/// Command line options, whose defaults may be set via the `commrate.*`
+/// git configuration keys. The key name is the option name in camel case,
+/// e.g. `--wrap-width` is backed by `commrate.wrapWidth`.
+const GIT_CONFIG_OPTIONS: &[&str] = &[
+    "azure-url",
+    "bitbucket-url",
+    "body-len-model",
+    "depth",
+    "emoji",
+    "fail-if",
+    "format",
+    "gerrit-url",
+    "grade-colors",
+    "grade-style",
+    "grades",
+    "ignore-file",
+    "ignore-marker",
+    "issue-pattern",
+    "low-memory",
+    "max-grade-count",
+    "merges",
+    "notify-below",
+    "notify-webhook",
+    "number",
+    "pr-titles",
+    "quiet",
+    "recurse-submodules",
+    "refactor-tolerance",
+    "relative",
+    "require-scope",
+    "scale",
+    "score",
+    "score-merges",
+    "scopes",
+    "short-diff",
+    "show-body",
+    "show-dirs",
+    "show-failures",
+    "strict",
+    "subject-limits",
+    "summary",
+    "template",
+    "wrap-width",
+];
+
+/// The negations of the flags, which may be enabled in the git
+/// configuration, along with their help: the command line overrides
+/// the configuration for a single run, e.g. `--no-merges`.
+const NEGATED_FLAGS: &[(&str, &str, &str)] = &[
+    ("low-memory", "no-low-memory", "Keeps the history in memory, even if enabled in the configuration"),
+    ("merges", "no-merges", "Skips merge commits, even if enabled in the configuration"),
+    ("pr-titles", "no-pr-titles", "Scores subjects of squash-merged pull requests, even if titles are enabled in the configuration"),
+    ("quiet", "no-quiet", "Shows the progress of long runs, even if disabled in the configuration"),
+    ("recurse-submodules", "no-recurse-submodules", "Does not score the submodules, even if enabled in the configuration"),
+    ("relative", "no-relative", "Grades commits by their scores, even if relative grades are enabled in the configuration"),
+    ("require-scope", "no-require-scope", "Does not penalize missing scopes, even if enabled in the configuration"),
+    ("score", "no-score", "Shows grades instead of numeric scores, even if enabled in the configuration"),
+    ("score-merges", "no-score-merges", "Does not score merge commits, even if enabled in the configuration"),
+    ("show-dirs", "no-show-dirs", "Does not show the directories, even if enabled in the configuration"),
+    ("strict", "no-strict", "Forgives short, refactoring and initial commits, even if strict scoring is enabled in the configuration"),
+    ("summary", "no-summary", "Does not print the totals, even if enabled in the configuration"),
+];
+
+/// The action requested in the command line.
+pub enum Command {
+    /// Score the commits of the repositories (the default one).
+    Log,
+
+    /// Score the message being composed along with the staged
+    /// changes. The message is read from `COMMIT_EDITMSG`,
+    /// unless specified explicitly. The scores of the individual
+    /// rules are shown too, if requested.
+    Preview {
+        message: Option<String>,
+        explain: bool,
+    },
+
+    /// Compare the summaries of commits unique to each of the branches.
+    Compare { first: String, second: String },
+
+    /// Report the quality of the release: the commits since the previous
+    /// release (detected by tags, unless specified explicitly).
+    Release {
+        previous: Option<String>,
+        release: String,
+    },
+
+    /// Report the commits of the release, which would break
+    /// the automated generation of the changelog.
+    ChangelogCheck {
+        previous: Option<String>,
+        release: String,
+    },
+
+    /// Report the commits, whose grades changed between two runs
+    /// (the files with the JSON output).
+    DiffRuns { old: String, new: String },
+
+    /// Explain the score of the single commit in detail.
+    Show { commit: String },
+
+    /// Score the commits of the Bitbucket pull request fetched via
+    /// the REST API, posting their summary to it, if requested.
+    Bitbucket {
+        repository: String,
+        pull_request: u32,
+        post_comment: bool,
+    },
+
+    /// Score the commits of the Azure DevOps pull request fetched via
+    /// the REST API, posting their summary to it, if requested.
+    Azure {
+        repository: String,
+        pull_request: u32,
+        post_comment: bool,
+    },
+
+    /// Score the patch sets of the Gerrit change (or only the latest one)
+    /// fetched via the REST API.
+    Gerrit { change: String, latest: bool },
+
+    /// Scan the repositories periodically, appending their summaries
+    /// to the history file.
+    Monitor { interval: u64, history: PathBuf },
+
+    /// List the rules of the scorer with their descriptions.
+    Rules,
+
+    /// Check the configuration, reporting the effective settings
+    /// and all the problems found.
+    ConfigCheck {
+        settings: Vec<Setting>,
+        problems: Vec<String>,
+    },
+}
+
+/// The explicitly configured option along with its effective value.
+pub struct Setting {
+    pub key: String,
+    pub value: String,
+    pub source: SettingSource,
+}
+
+/// Where the value of the setting comes from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SettingSource {
+    CommandLine,
+    GitConfig,
+}
+
+impl Display for SettingSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingSource::CommandLine => write!(f, "command line"),
+            SettingSource::GitConfig => write!(f, "git config"),
+        }
+    }
+}
+
+/// A repository to be scored.
+#[derive(Clone)]
+pub enum RepositoryLocation {
+    /// A path to the repository on the local file system.
+    Local(String),
+
+    /// An URL of the remote repository, which should be cloned first.
+    Remote(String),
+
+    /// A path to the initialized submodule of the local repository (or of
+    /// the submodule), which range is mapped from the superproject one.
+    Submodule {
+        path: String,
+        superproject: Box<RepositoryLocation>,
+        /// The path of the submodule in the superproject tree.
+        gitlink: PathBuf,
+    },
+}
+
+impl RepositoryLocation {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Local(path) => path,
+            Self::Remote(url) => url,
+            Self::Submodule { path, .. } => path,
+        }
+    }
+}
 
 pub struct AppConfig {
+    command: Command,
+    repositories: Vec<RepositoryLocation>,
+    message_file: Option<String>,
+    fix_suggest: bool,
+    json_rpc: bool,
+    reword_threshold: Option<Grade>,
+    show_failures: Option<Grade>,
+    show_dirs: bool,
+    clone_depth: Option<usize>,
     pre_filters: FilterChain<Metadata>,
+    mid_filters: FilterChain<Commit>,
     post_filters: FilterChain<ScoredCommit>,
-    start_commit: String,
+    start_commits: Vec<String>,
+    excluded_commits: Vec<String>,
+    local: bool,
+    cherry: bool,
+    stdin_commits: Option<Vec<String>>,
     max_commits: Option<usize>,
-    show_score: bool,
+    skip: usize,
+    score_format: ScoreFormat,
+    theme: ColorTheme,
+    template: Option<Template>,
+    output: Option<PathBuf>,
+    format: OutputFormat,
+    relative: bool,
+    email_report: Option<DigestFormat>,
+    annotate_notes: bool,
+    path_prefix_depth: Option<usize>,
+    time_stats: bool,
+    review_coverage_depth: Option<usize>,
+    summary_footer: bool,
+    diff_cache: bool,
+    replace_objects: bool,
+    check_identities: bool,
+    low_memory: bool,
+    quiet: bool,
+    timing: bool,
+    fail_if: Option<Condition>,
+    max_grade_count: Option<GradeLimits>,
+    notify_webhook: Option<String>,
+    github_check: Option<String>,
+    github_repository: Option<String>,
+    github_token: Option<String>,
+    github_api_url: Option<String>,
+    gerrit_url: Option<String>,
+    azure_url: Option<String>,
+    azure_token: Option<String>,
+    bitbucket_url: Option<String>,
+    bitbucket_token: Option<String>,
+    notify_below: Option<Grade>,
+    strict: bool,
+    score_merges: bool,
+    pull_request_titles: bool,
+    issue_pattern: Option<Regex>,
+    scopes: Vec<String>,
+    require_scope: bool,
+    subject_limits: Option<SubjectLimits>,
+    body_len_model: BodyLenModel,
+    emoji_mode: EmojiMode,
+    parse_options: ParseOptions,
+    class_policies: Vec<ClassPolicy>,
+    rule_severities: Vec<RuleSeverity>,
 }
 
 impl AppConfig {
+    pub fn command(&self) -> &Command {
+        &self.command
+    }
+
+    /// Locations of the repositories to score. There is always
+    /// at least one repository.
+    pub fn repositories(&self) -> &[RepositoryLocation] {
+        &self.repositories
+    }
+
+    /// The file with the message to be scored instead of commits
+    /// ("-" for the standard input).
+    pub fn message_file(&self) -> Option<&str> {
+        self.message_file.as_deref()
+    }
+
+    /// Whether the fixed version of the message from the file should
+    /// be printed instead of its score.
+    pub fn fix_suggest(&self) -> bool {
+        self.fix_suggest
+    }
+
+    /// Whether the messages are scored on the requests over JSON-RPC
+    /// (from the editor) instead of commits.
+    pub fn json_rpc(&self) -> bool {
+        self.json_rpc
+    }
+
+    /// The grade, below which the unpushed commits should
+    /// be suggested for rewording.
+    pub fn reword_threshold(&self) -> Option<Grade> {
+        self.reword_threshold
+    }
+
+    /// The grade, below which the commits are shown in full
+    /// after the table.
+    pub fn show_failures(&self) -> Option<Grade> {
+        self.show_failures
+    }
+
+    /// History depth for cloning remote repositories. `None`
+    /// means that the whole history should be cloned.
+    pub fn clone_depth(&self) -> Option<usize> {
+        self.clone_depth
+    }
+
     pub fn pre_filters(&self) -> &FilterChain<Metadata> {
         &self.pre_filters
     }
 
+    pub fn mid_filters(&self) -> &FilterChain<Commit> {
+        &self.mid_filters
+    }
+
     pub fn post_filters(&self) -> &FilterChain<ScoredCommit> {
         &self.post_filters
     }
@@ -28,42 +369,916 @@ impl AppConfig {
         self.max_commits
     }
 
-    pub fn show_score(&self) -> bool {
-        self.show_score
+    /// The number of the first commits (which pass the filters)
+    /// to skip before the output is started.
+    pub fn skip(&self) -> usize {
+        self.skip
+    }
+
+    /// The way the scores are displayed in the table output.
+    pub fn score_format(&self) -> ScoreFormat {
+        self.score_format
+    }
+
+    /// The file, to which the output is written instead of the terminal.
+    pub fn output(&self) -> Option<&Path> {
+        self.output.as_deref()
+    }
+
+    /// The template of the commits, which implies the template format.
+    pub fn template(&self) -> Option<&Template> {
+        self.template.as_ref()
+    }
+
+    /// Whether the top-level directories touched by the commits
+    /// are shown along with them.
+    pub fn show_dirs(&self) -> bool {
+        self.show_dirs
+    }
+
+    pub fn theme(&self) -> ColorTheme {
+        self.theme
+    }
+
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
+    /// Whether the grades should be assigned relative to the score
+    /// distribution of the scanned commits instead of the fixed scale.
+    pub fn relative(&self) -> bool {
+        self.relative
+    }
+
+    /// The format of the digest for mailing, which should
+    /// be printed instead of the commits.
+    pub fn email_report(&self) -> Option<DigestFormat> {
+        self.email_report
+    }
+
+    /// The URL of the webhook, to which the summary of the run is posted.
+    pub fn notify_webhook(&self) -> Option<&str> {
+        self.notify_webhook.as_deref()
+    }
+
+    /// The commit, to which the GitHub check run with the results
+    /// is attached.
+    pub fn github_check(&self) -> Option<&str> {
+        self.github_check.as_deref()
+    }
+
+    /// The `OWNER/REPO` repository of the check run, which is set
+    /// by GitHub Actions.
+    pub fn github_repository(&self) -> Option<&str> {
+        self.github_repository.as_deref()
+    }
+
+    /// The token for the GitHub API, which is read from the environment
+    /// only, like the other tokens.
+    pub fn github_token(&self) -> Option<&str> {
+        self.github_token.as_deref()
+    }
+
+    /// The URL of the GitHub API: of github.com, unless the one
+    /// of GitHub Enterprise Server is set.
+    pub fn github_api_url(&self) -> &str {
+        self.github_api_url.as_deref().unwrap_or(github::API_URL)
+    }
+
+    /// The base URL of the Gerrit server, from which the changes are fetched.
+    pub fn gerrit_url(&self) -> Option<&str> {
+        self.gerrit_url.as_deref()
+    }
+
+    /// The URL of the Azure DevOps organization, e.g.
+    /// `https://dev.azure.com/ORG`.
+    pub fn azure_url(&self) -> Option<&str> {
+        self.azure_url.as_deref()
+    }
+
+    /// The access token for Azure DevOps, which is read from the
+    /// environment only, like the one for Bitbucket.
+    pub fn azure_token(&self) -> Option<&str> {
+        self.azure_token.as_deref()
+    }
+
+    /// The URL of the Bitbucket API: of Bitbucket Cloud, unless the URL
+    /// of Bitbucket Server is configured.
+    pub fn bitbucket_url(&self) -> &str {
+        self.bitbucket_url.as_deref().unwrap_or(CLOUD_API_URL)
+    }
+
+    /// The access token for Bitbucket, which is read from the environment
+    /// only, so it is never stored in the repository configuration.
+    pub fn bitbucket_token(&self) -> Option<&str> {
+        self.bitbucket_token.as_deref()
+    }
+
+    /// The grade, below which the commits are listed in the webhook
+    /// notification.
+    pub fn notify_below(&self) -> Option<Grade> {
+        self.notify_below
+    }
+
+    /// Whether the low-graded commits should be annotated
+    /// with the notes explaining the grade.
+    pub fn annotate_notes(&self) -> bool {
+        self.annotate_notes
+    }
+
+    /// The depth of the directories, by which the commits should be
+    /// summarized instead of printing them.
+    pub fn path_prefix_depth(&self) -> Option<usize> {
+        self.path_prefix_depth
+    }
+
+    /// Whether the commits should be summarized by the local time
+    /// they were authored at instead of printing them.
+    pub fn time_stats(&self) -> bool {
+        self.time_stats
+    }
+
+    /// The depth of the directories, by which the review coverage of
+    /// the commits should be summarized instead of printing them.
+    pub fn review_coverage_depth(&self) -> Option<usize> {
+        self.review_coverage_depth
+    }
+
+    /// Whether the totals of the scored commits are printed after them.
+    pub fn summary_footer(&self) -> bool {
+        self.summary_footer
+    }
+
+    /// Whether the memory usage must not grow with the number of the
+    /// scanned commits, even at the cost of the speed or the precision.
+    pub fn low_memory(&self) -> bool {
+        self.low_memory
+    }
+
+    /// Whether the time spent in the stages of the run should be reported.
+    pub fn timing(&self) -> bool {
+        self.timing
+    }
+
+    /// The condition over the summary of the scored commits,
+    /// which makes the run fail (e.g. in the CI job).
+    pub fn fail_if(&self) -> Option<&Condition> {
+        self.fail_if.as_ref()
+    }
+
+    /// The maximum numbers of the scored commits of the grades,
+    /// which make the run fail, if exceeded.
+    pub fn max_grade_count(&self) -> Option<&GradeLimits> {
+        self.max_grade_count.as_ref()
+    }
+
+    /// Whether the progress indicator should be suppressed.
+    pub fn quiet(&self) -> bool {
+        self.quiet
+    }
+
+    /// Whether the diffs of commits should be cached on disk.
+    pub fn diff_cache(&self) -> bool {
+        self.diff_cache
+    }
+
+    /// Whether the author identities should be checked instead of
+    /// scoring the commits.
+    pub fn check_identities(&self) -> bool {
+        self.check_identities
+    }
+
+    /// Whether the replace refs and the grafts should be applied
+    /// to the traversed history.
+    pub fn replace_objects(&self) -> bool {
+        self.replace_objects
+    }
+
+    /// The commits to traverse the history from.
+    pub fn start_commits(&self) -> &[String] {
+        &self.start_commits
+    }
+
+    /// The commits, which history is excluded from the traversal
+    /// (`--not REF` or `^REF`).
+    pub fn excluded_commits(&self) -> &[String] {
+        &self.excluded_commits
+    }
+
+    /// Whether only the commits of the current branch, which are
+    /// not pushed yet, should be scored (`@{upstream}..HEAD`).
+    pub fn local(&self) -> bool {
+        self.local
+    }
+
+    /// Whether the commits, which are already cherry-picked to the excluded
+    /// history (the other branch), should be skipped.
+    pub fn cherry(&self) -> bool {
+        self.cherry
+    }
+
+    /// The commits listed in stdin, which should be scored
+    /// instead of traversing the history.
+    pub fn stdin_commits(&self) -> Option<&[String]> {
+        self.stdin_commits.as_deref()
+    }
+
+    /// Whether commits of special classes should be scored
+    /// like the ordinary ones.
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Whether merge commits should be scored with the merge rules
+    /// instead of being ignored.
+    pub fn score_merges(&self) -> bool {
+        self.score_merges
+    }
+
+    /// Whether the pull request title should be scored instead
+    /// of the subject of squash-merged pull requests.
+    pub fn pull_request_titles(&self) -> bool {
+        self.pull_request_titles
+    }
+
+    /// The allowed scopes of the Conventional Commits (any scope is
+    /// allowed, if the list is empty).
+    pub fn scopes(&self) -> &[String] {
+        &self.scopes
+    }
+
+    /// Whether the Conventional Commits must have the scope.
+    pub fn require_scope(&self) -> bool {
+        self.require_scope
+    }
+
+    /// The curve of the subject length scoring, if configured
+    /// instead of the default one.
+    pub fn subject_limits(&self) -> Option<SubjectLimits> {
+        self.subject_limits
+    }
+
+    /// The model of the body length expected for the diff size.
+    pub fn body_len_model(&self) -> BodyLenModel {
+        self.body_len_model
+    }
+
+    /// The regex of the issue IDs, which do not count as the subject
+    /// words, if configured instead of the default one.
+    pub fn issue_pattern(&self) -> Option<&Regex> {
+        self.issue_pattern.as_ref()
+    }
+
+    pub fn emoji_mode(&self) -> EmojiMode {
+        self.emoji_mode
+    }
+
+    pub fn parse_options(&self) -> &ParseOptions {
+        &self.parse_options
+    }
+
+    /// The scoring overrides of the built-in and the user-defined classes.
+    pub fn class_policies(&self) -> &[ClassPolicy] {
+        &self.class_policies
+    }
+
+    /// The severities of the rules, which are not scored as usual,
+    /// in the order of precedence (the last one wins).
+    pub fn rule_severities(&self) -> &[RuleSeverity] {
+        &self.rule_severities
+    }
+}
+
+/// Command line arguments backed by the git configuration.
+///
+/// Explicitly specified arguments always take precedence over the git
+/// configuration keys, which, in turn, take precedence over the defaults.
+///
+/// The git configuration is read from the repository in the current
+/// directory, or the one given by `GIT_DIR`, like the scored one
+/// (including global and system-wide configuration files),
+/// or just from global and system-wide files, if the current directory
+/// is not inside a repository.
+struct Options<'a> {
+    matches: ArgMatches<'a>,
+    git_config: Option<Config>,
+
+    /// Whether the invalid values are collected as the problems instead
+    /// of failing the run, so all of them are reported at once when the
+    /// configuration is checked.
+    lenient: bool,
+    problems: RefCell<Vec<String>>,
+}
+
+impl<'a> Options<'a> {
+    fn new(matches: ArgMatches<'a>) -> Self {
+        // The configuration is read from the repository, which is scored.
+        let git_config = git::discover_repository(Path::new("."))
+            .and_then(|repo| repo.config())
+            .or_else(|_| Config::open_default())
+            .ok();
+
+        let lenient = matches!(matches.subcommand(), ("config", Some(_)));
+
+        Self {
+            matches,
+            git_config,
+            lenient,
+            problems: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Reports the invalid configuration value.
+    fn invalid(&self, message: String) {
+        if !self.lenient {
+            fatal(message);
+        }
+
+        self.problems.borrow_mut().push(message);
+    }
+
+    fn value_of(&self, name: &str) -> Option<String> {
+        match self.matches.value_of(name) {
+            Some(value) => Some(value.to_string()),
+            None => self.git_value(name, |config, key| config.get_string(key)),
+        }
+    }
+
+    fn is_present(&self, name: &str) -> bool {
+        let negated = NEGATED_FLAGS
+            .iter()
+            .any(|&(flag, negation, _)| flag == name && self.matches.is_present(negation));
+
+        self.matches.is_present(name)
+            || !negated
+                && self
+                    .git_value(name, |config, key| config.get_bool(key))
+                    .unwrap_or(false)
     }
 
-    pub fn start_commit(&self) -> &str {
-        &self.start_commit
+    /// Parses the option value. Values specified in the command
+    /// line are already validated by Clap, so the parsing may fail
+    /// only for values taken from the git configuration.
+    fn parse<T>(&self, name: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let value = self.value_of(name)?;
+
+        match value.parse() {
+            Ok(parsed) => Some(parsed),
+            Err(err) => {
+                self.invalid(format!(
+                    "invalid value '{}' of {}: {}",
+                    value,
+                    git_config_key(name),
+                    err
+                ));
+                None
+            }
+        }
+    }
+
+    /// Returns all the git configuration entries whose
+    /// names match the regex, in the order of appearance.
+    fn git_entries(&self, regex: &str) -> Vec<(String, String)> {
+        let config = match &self.git_config {
+            Some(config) => config,
+            None => return Vec::new(),
+        };
+
+        let entries = config
+            .entries(Some(regex))
+            .unwrap_or_else(|err| fatal(err.message()));
+
+        let mut result = Vec::new();
+        for entry in &entries {
+            let entry = entry.unwrap_or_else(|err| fatal(err.message()));
+            if let (Some(name), Some(value)) = (entry.name(), entry.value()) {
+                result.push((name.to_string(), value.to_string()));
+            }
+        }
+
+        result
+    }
+
+    fn git_value<T, F>(&self, name: &str, getter: F) -> Option<T>
+    where
+        F: Fn(&Config, &str) -> Result<T, Error>,
+    {
+        if !GIT_CONFIG_OPTIONS.contains(&name) {
+            return None;
+        }
+
+        let config = self.git_config.as_ref()?;
+        let key = git_config_key(name);
+
+        match getter(config, &key) {
+            Ok(value) => Some(value),
+            Err(ref err) if err.code() == ErrorCode::NotFound => None,
+            Err(err) => {
+                self.invalid(format!("invalid value of {}: {}", key, err.message()));
+                None
+            }
+        }
     }
 }
 
+/// Converts the option name to the git configuration key,
+/// e.g. "wrap-width" to "commrate.wrapWidth".
+fn git_config_key(name: &str) -> String {
+    let mut key = String::from("commrate.");
+    let mut capitalize = false;
+
+    for chr in name.chars() {
+        if chr == '-' {
+            capitalize = true;
+        } else if capitalize {
+            key.push(chr.to_ascii_uppercase());
+            capitalize = false;
+        } else {
+            key.push(chr);
+        }
+    }
+
+    key
+}
+
 pub fn read_config() -> AppConfig {
     let app = init_clap_app();
-    let matches = app.get_matches();
-    let pre_filters = create_pre_filters(&matches);
-    let post_filters = create_post_filters(&matches);
-    let max_commits = read_commits_number(&matches);
-    let start_commit = matches.value_of("commit").unwrap_or("HEAD").to_string();
-    let show_score = matches.occurrences_of("score") > 0;
+    let options = Options::new(app.get_matches());
+    let mut command = read_command(&options);
+    let repositories = read_repositories(&options);
+    let message_file = options.value_of("message-file");
+    let fix_suggest = options.matches.is_present("fix-suggest");
+    let json_rpc = options.matches.is_present("json-rpc");
+    let reword_threshold = options.parse("suggest-reword");
+    let show_failures = options.parse("show-failures");
+    let clone_depth = read_clone_depth(&options);
+    let pre_filters = create_pre_filters(&options);
+    let mid_filters = create_mid_filters(&options);
+    let post_filters = create_post_filters(&options);
+    let max_commits = options.parse("number");
+    let skip = options.parse("skip").unwrap_or(0);
+    let (start_commits, excluded_commits) = read_start_commits(&options);
+    let local = options.is_present("local");
+    let cherry = options.is_present("cherry");
+    let stdin_commits = if options.matches.is_present("stdin-commits") {
+        Some(read_stdin_commits())
+    } else {
+        None
+    };
+    // The scale makes sense only for the numeric scores.
+    let scale = options.parse("scale");
+    let score_format = if options.is_present("score") || scale.is_some() {
+        ScoreFormat::Score(scale.unwrap_or(ScoreScale::Hundred))
+    } else {
+        ScoreFormat::Grade(options.parse("grade-style").unwrap_or(GradeStyle::Letter))
+    };
+    let theme = options.parse("grade-colors").unwrap_or_default();
+    let template = options
+        .value_of("template")
+        .and_then(|path| read_template(&options, &path));
+    let output = options.value_of("output").map(PathBuf::from);
+    let output_format = output.as_deref().and_then(OutputFormat::from_extension);
+    let format = if options.is_present("porcelain") {
+        OutputFormat::Porcelain
+    } else if let Some(format) = output_format.filter(|_| options.value_of("format").is_none()) {
+        format
+    } else if template.is_some() {
+        options.parse("format").unwrap_or(OutputFormat::Template)
+    } else {
+        options.parse("format").unwrap_or(OutputFormat::Table)
+    };
+    if format == OutputFormat::Template && template.is_none() {
+        options.invalid("template format requires --template FILE".to_string());
+    }
+    let relative = options.is_present("relative");
+    let email_report = options.parse("email-report");
+    let annotate_notes = options.is_present("annotate-notes");
+    let path_prefix_depth = options.parse("by-path-prefix");
+    let time_stats = options.is_present("time-stats");
+    let review_coverage_depth = options.parse("review-coverage");
+    let summary_footer = options.is_present("summary");
+    let low_memory = options.is_present("low-memory");
+    // The cache is loaded into memory as a whole.
+    let diff_cache = !options.is_present("no-cache") && !low_memory;
+    let replace_objects = !options.is_present("no-replace-objects");
+    let check_identities = options.matches.is_present("check-identities");
+    let quiet = options.is_present("quiet");
+    let timing = options.is_present("timing");
+    let fail_if = options.parse("fail-if");
+    let max_grade_count = options.parse("max-grade-count");
+    let notify_webhook = options.value_of("notify-webhook");
+    let github_check = options.matches.value_of("github-check").map(String::from);
+    let github_repository = env::var(GITHUB_REPOSITORY_VARIABLE)
+        .ok()
+        .filter(|repository| !repository.is_empty());
+    let github_token = env::var(GITHUB_TOKEN_VARIABLE)
+        .ok()
+        .filter(|token| !token.is_empty());
+    let github_api_url = env::var(GITHUB_API_URL_VARIABLE)
+        .ok()
+        .filter(|url| !url.is_empty());
+    let gerrit_url = options.value_of("gerrit-url");
+    let azure_url = options.value_of("azure-url");
+    let azure_token = AZURE_TOKEN_VARIABLES
+        .iter()
+        .filter_map(|variable| env::var(variable).ok())
+        .find(|token| !token.is_empty());
+    let bitbucket_url = options.value_of("bitbucket-url");
+    let bitbucket_token = env::var(BITBUCKET_TOKEN_VARIABLE)
+        .ok()
+        .filter(|token| !token.is_empty());
+    let notify_below = options.parse("notify-below");
+    let strict = options.is_present("strict");
+    let score_merges = options.is_present("score-merges");
+    let pull_request_titles = options.is_present("pr-titles");
+    let scopes = options
+        .value_of("scopes")
+        .map(|scopes| {
+            scopes
+                .split(',')
+                .map(str::trim)
+                .filter(|scope| !scope.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default();
+    let require_scope = options.is_present("require-scope");
+    let subject_limits = options.parse("subject-limits");
+    let body_len_model = options.parse("body-len-model").unwrap_or_default();
+    let issue_pattern = options.value_of("issue-pattern").and_then(|pattern| {
+        patterns::compile(&pattern)
+            .map_err(|err| {
+                options.invalid(format!(
+                    "invalid value of {}: {}",
+                    git_config_key("issue-pattern"),
+                    err
+                ))
+            })
+            .ok()
+    });
+    let emoji_mode = options.parse("emoji").unwrap_or(EmojiMode::Keep);
+    let (custom_classes, class_policies) = read_custom_classes(&options);
+    let rule_severities = read_rule_severities(&options);
+    let mut parse_options = read_parse_options(&options).with_custom_classes(custom_classes);
+    let show_dirs = options.is_present("show-dirs");
+    let template_dirs = template.as_ref().filter(|template| template.needs_dirs());
+    if path_prefix_depth.is_some()
+        || review_coverage_depth.is_some()
+        || show_dirs
+        || template_dirs.is_some()
+    {
+        parse_options = parse_options.with_paths_required();
+    }
+    if let Some(pattern) = &issue_pattern {
+        parse_options = parse_options.with_issue_pattern(pattern.clone());
+    }
+
+    // All the options are read by now, so all the invalid values
+    // are collected, if the configuration is checked.
+    if let Command::ConfigCheck { settings, problems } = &mut command {
+        *settings = read_settings(&options);
+        problems.extend(find_unknown_keys(&options));
+        problems.extend(options.problems.borrow_mut().drain(..));
+    }
 
     AppConfig {
+        command,
+        repositories,
+        message_file,
+        fix_suggest,
+        json_rpc,
+        reword_threshold,
+        show_failures,
+        show_dirs,
+        clone_depth,
         pre_filters,
+        mid_filters,
         post_filters,
-        start_commit,
+        start_commits,
+        excluded_commits,
+        local,
+        cherry,
+        stdin_commits,
         max_commits,
-        show_score,
+        skip,
+        score_format,
+        theme,
+        template,
+        output,
+        format,
+        relative,
+        email_report,
+        annotate_notes,
+        path_prefix_depth,
+        time_stats,
+        review_coverage_depth,
+        summary_footer,
+        diff_cache,
+        replace_objects,
+        check_identities,
+        low_memory,
+        quiet,
+        timing,
+        fail_if,
+        max_grade_count,
+        notify_webhook,
+        github_check,
+        github_repository,
+        github_token,
+        github_api_url,
+        gerrit_url,
+        azure_url,
+        azure_token,
+        bitbucket_url,
+        bitbucket_token,
+        notify_below,
+        strict,
+        score_merges,
+        pull_request_titles,
+        issue_pattern,
+        scopes,
+        require_scope,
+        subject_limits,
+        body_len_model,
+        emoji_mode,
+        parse_options,
+        class_policies,
+        rule_severities,
     }
 }
 
 fn init_clap_app() -> App<'static, 'static> {
-    App::new("commrate")
+    let app = App::new("commrate")
         .version(env!("CARGO_PKG_VERSION"))
         .about("The tool for scoring and rating Git commits.")
+        .subcommand(
+            SubCommand::with_name("changelog-check")
+                .about("Reports the commits, which are not usable as changelog entries")
+                .arg(
+                    Arg::with_name("range")
+                        .value_name("RANGE")
+                        .default_value("HEAD")
+                        .help("The release range, e.g. v1.2.0..v1.3.0, or the release tag alone"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("compare")
+                .about("Compares the commits unique to each of the branches")
+                .arg(
+                    Arg::with_name("first")
+                        .value_name("BRANCH_A")
+                        .required(true)
+                        .help("The first branch (or any commit reference)"),
+                )
+                .arg(
+                    Arg::with_name("second")
+                        .value_name("BRANCH_B")
+                        .required(true)
+                        .help("The second branch (or any commit reference)"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("diff-runs")
+                .about("Reports the commits, whose grades changed between two runs")
+                .arg(
+                    Arg::with_name("old")
+                        .value_name("OLD_JSON")
+                        .required(true)
+                        .help("The JSON output of the old run (--format json)"),
+                )
+                .arg(
+                    Arg::with_name("new")
+                        .value_name("NEW_JSON")
+                        .required(true)
+                        .help("The JSON output of the new run (--format json)"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("release")
+                .about("Reports the quality of the commits of the release")
+                .arg(
+                    Arg::with_name("range")
+                        .value_name("RANGE")
+                        .required(true)
+                        .help("The release range, e.g. v1.2.0..v1.3.0, or the release tag alone"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("config")
+                .about("Works with the configuration")
+                .setting(AppSettings::SubcommandRequired)
+                .subcommand(
+                    SubCommand::with_name("check")
+                        .about("Validates the configuration and prints the effective settings"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("rules")
+                .about("Lists the scoring rules with their weights and descriptions"),
+        )
+        .subcommand(
+            SubCommand::with_name("show")
+                .about("Explains the score of the commit rule by rule")
+                .arg(
+                    Arg::with_name("commit")
+                        .value_name("COMMIT")
+                        .default_value("HEAD")
+                        .help("The commit to inspect"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("azure")
+                .about("Scores the commits of the Azure DevOps pull request")
+                .arg(
+                    Arg::with_name("repository")
+                        .value_name("REPOSITORY")
+                        .required(true)
+                        .help("The repository: PROJECT/REPO"),
+                )
+                .arg(
+                    Arg::with_name("pull-request")
+                        .value_name("PR")
+                        .required(true)
+                        .validator(try_parse::<u32>)
+                        .help("The pull request ID"),
+                )
+                .arg(
+                    Arg::with_name("post-comment")
+                        .long("post-comment")
+                        .help("Posts the summary of the commits to the pull request as the comment"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("bitbucket")
+                .about("Scores the commits of the Bitbucket pull request")
+                .arg(
+                    Arg::with_name("repository")
+                        .value_name("REPOSITORY")
+                        .required(true)
+                        .help("The repository: WORKSPACE/REPO for Cloud, PROJECT/REPO for Server"),
+                )
+                .arg(
+                    Arg::with_name("pull-request")
+                        .value_name("PR")
+                        .required(true)
+                        .validator(try_parse::<u32>)
+                        .help("The pull request number"),
+                )
+                .arg(
+                    Arg::with_name("post-comment")
+                        .long("post-comment")
+                        .help("Posts the summary of the commits to the pull request as the comment"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("gerrit")
+                .about("Scores the patch sets of the Gerrit change")
+                .arg(
+                    Arg::with_name("change")
+                        .value_name("CHANGE")
+                        .required(true)
+                        .help("The change number, the Change-Id or project~number"),
+                )
+                .arg(
+                    Arg::with_name("latest")
+                        .long("latest")
+                        .help("Scores only the latest patch set, e.g. for the CI verifier"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("monitor")
+                .about("Scans the repositories periodically, keeping the history of their summaries")
+                .arg(
+                    Arg::with_name("history")
+                        .long("history")
+                        .value_name("FILE")
+                        .required(true)
+                        .help("The JSON Lines file, to which the summaries are appended"),
+                )
+                .arg(
+                    Arg::with_name("interval")
+                        .long("interval")
+                        .value_name("SECONDS")
+                        .default_value("3600")
+                        .validator(try_parse::<u64>)
+                        .help("The interval between the scans"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("preview")
+                .about("Scores the message being composed along with the staged changes")
+                .arg(
+                    Arg::with_name("message")
+                        .short("m")
+                        .long("message")
+                        .value_name("MESSAGE")
+                        .help("Message to score instead of the one from .git/COMMIT_EDITMSG"),
+                )
+                .arg(
+                    Arg::with_name("explain")
+                        .long("explain")
+                        .help("Explains the score by the scores of the individual rules, e.g. for editors"),
+                ),
+        )
         .arg(
             Arg::with_name("commit")
                 .value_name("START_COMMIT")
-                .default_value("HEAD")
-                .help("Commit ID or reference to start from"),
+                .multiple(true)
+                .help("Commit IDs or references to start from (HEAD by default, ^REF excludes)"),
+        )
+        .arg(
+            Arg::with_name("not")
+                .long("not")
+                .value_name("REF")
+                .multiple(true)
+                .number_of_values(1)
+                .conflicts_with_all(&["local", "stdin-commits"])
+                .help("Excludes the commits reachable from the reference (may be repeated)"),
+        )
+        .arg(
+            Arg::with_name("repo")
+                .short("r")
+                .long("repo")
+                .value_name("PATH")
+                .multiple(true)
+                .number_of_values(1)
+                .help("Repository to score (may be repeated)"),
+        )
+        .arg(
+            Arg::with_name("repo-manifest")
+                .long("repo-manifest")
+                .value_name("FILE")
+                .help("File with repository paths to score, one per line"),
+        )
+        .arg(
+            Arg::with_name("remote")
+                .long("remote")
+                .value_name("URL")
+                .multiple(true)
+                .number_of_values(1)
+                .help("Remote repository to clone and score (may be repeated)"),
+        )
+        .arg(
+            Arg::with_name("recurse-submodules")
+                .long("recurse-submodules")
+                .conflicts_with_all(&["local", "stdin-commits"])
+                .help("Also scores the commits of the initialized submodules"),
+        )
+        .arg(
+            Arg::with_name("message-file")
+                .long("message-file")
+                .value_name("FILE")
+                .conflicts_with_all(&["repo", "repo-manifest", "remote"])
+                .help("Scores the message from the file (or stdin for \"-\") instead of commits"),
+        )
+        .arg(
+            Arg::with_name("fix-suggest")
+                .long("fix-suggest")
+                .requires("message-file")
+                .help("Prints the message from --message-file with its formatting fixed instead of scoring it"),
+        )
+        .arg(
+            Arg::with_name("json-rpc")
+                .long("json-rpc")
+                .conflicts_with_all(&["repo", "repo-manifest", "remote", "message-file"])
+                .help("Scores the messages requested via JSON-RPC on stdin, e.g. by the editor"),
+        )
+        .arg(
+            Arg::with_name("local")
+                .short("l")
+                .long("local")
+                .help("Scores only unpushed commits of the current branch (@{upstream}..HEAD)"),
+        )
+        .arg(
+            Arg::with_name("cherry")
+                .long("cherry")
+                .conflicts_with("stdin-commits")
+                .help("Skips the commits already cherry-picked to the excluded history (by patch IDs)"),
+        )
+        .arg(
+            Arg::with_name("stdin-commits")
+                .long("stdin-commits")
+                .conflicts_with_all(&["commit", "local", "message-file", "suggest-reword"])
+                .help("Scores the commits listed in stdin (e.g. by git rev-list), one per line"),
+        )
+        .arg(
+            Arg::with_name("suggest-reword")
+                .long("suggest-reword")
+                .value_name("GRADE")
+                .validator(try_parse::<Grade>)
+                .conflicts_with_all(&["repo", "repo-manifest", "remote", "message-file"])
+                .help("Prints the rebase todo list for rewording unpushed commits graded below GRADE"),
+        )
+        .arg(
+            Arg::with_name("depth")
+                .long("depth")
+                .value_name("DEPTH")
+                .validator(try_parse::<usize>)
+                .help("History depth for --remote clones, 0 for full history [default: 1000]"),
         )
         .arg(
             Arg::with_name("author")
@@ -72,13 +1287,73 @@ fn init_clap_app() -> App<'static, 'static> {
                 .value_name("AUTHOR")
                 .help("Filters by commit author"),
         )
+        .arg(
+            Arg::with_name("ignore-file")
+                .long("ignore-file")
+                .value_name("FILE")
+                .help("File with IDs of commits to exclude from scoring"),
+        )
+        .arg(
+            Arg::with_name("class")
+                .short("c")
+                .long("class")
+                .value_name("CLASSES")
+                .validator(try_parse::<Classes>)
+                .help("Shows only commits of any of the classes, e.g. M or S,R"),
+        )
+        .arg(
+            Arg::with_name("exclude-class")
+                .short("x")
+                .long("exclude-class")
+                .value_name("CLASSES")
+                .validator(try_parse::<Classes>)
+                .help("Hides commits of any of the classes, e.g. M or S,R"),
+        )
+        .arg(
+            Arg::with_name("grep")
+                .long("grep")
+                .value_name("REGEX")
+                .validator(patterns::validate)
+                .help("Shows only commits whose subject matches the regex"),
+        )
+        .arg(
+            Arg::with_name("with-trailer")
+                .long("with-trailer")
+                .value_name("KEY")
+                .multiple(true)
+                .number_of_values(1)
+                .help("Shows only commits having the trailer with the key, e.g. Reviewed-by (may be repeated)"),
+        )
+        .arg(
+            Arg::with_name("issue-pattern")
+                .long("issue-pattern")
+                .value_name("REGEX")
+                .validator(patterns::validate)
+                .help("Regex of the issue IDs, which the subject must not consist of"),
+        )
+        .arg(
+            Arg::with_name("min-diff")
+                .long("min-diff")
+                .value_name("LINES")
+                .validator(try_parse::<usize>)
+                .help("Shows only commits with at least LINES lines changed"),
+        )
+        .arg(
+            Arg::with_name("max-diff")
+                .long("max-diff")
+                .value_name("LINES")
+                .validator(try_parse::<usize>)
+                .help("Shows only commits with at most LINES lines changed"),
+        )
         .arg(
             Arg::with_name("grades")
                 .short("g")
                 .long("grades")
                 .value_name("GRADE_SPEC")
+                .multiple(true)
+                .number_of_values(1)
                 .validator(try_parse::<GradeSpec>)
-                .help("Filters by commit grade"),
+                .help("Filters by commit grade, e.g. C, C+, C-, C-A or D,F (may be repeated)"),
         )
         .arg(
             Arg::with_name("merges")
@@ -94,12 +1369,338 @@ fn init_clap_app() -> App<'static, 'static> {
                 .validator(try_parse::<usize>)
                 .help("Maximum number of commits to show"),
         )
+        .arg(
+            Arg::with_name("skip")
+                .long("skip")
+                .value_name("NUMBER")
+                .validator(try_parse::<usize>)
+                .help("Skips the first NUMBER commits before showing the rest ones"),
+        )
         .arg(
             Arg::with_name("score")
                 .short("s")
                 .long("score")
                 .help("Shows numeric scores instead of discrete grades"),
         )
+        .arg(
+            Arg::with_name("scale")
+                .long("scale")
+                .value_name("SCALE")
+                .validator(try_parse::<ScoreScale>)
+                .help("Shows numeric scores in the scale: 100, 10 or stars (implies --score)"),
+        )
+        .arg(
+            Arg::with_name("grade-style")
+                .long("grade-style")
+                .value_name("STYLE")
+                .validator(try_parse::<GradeStyle>)
+                .help("Shows grades as plain letters, refined with +/- or as emoji: letter, plus-minus or emoji"),
+        )
+        .arg(
+            Arg::with_name("grade-colors")
+                .long("grade-colors")
+                .value_name("SPEC")
+                .validator(try_parse::<ColorTheme>)
+                .help("Overrides the colors of the grades, e.g. A=green,B=blue,C=#af8700,D=208"),
+        )
+        .arg(
+            Arg::with_name("score-merges")
+                .long("score-merges")
+                .help("Includes merge commits into the output and scores their messages"),
+        )
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .value_name("FORMAT")
+                .validator(try_parse::<OutputFormat>)
+                .help("Output format: table, json, porcelain, template, csv, markdown or html [default: table]"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .value_name("FILE")
+                .help("Writes the output to the file, inferring the format from the extension (.json, .csv, .md, .html)"),
+        )
+        .arg(
+            Arg::with_name("template")
+                .long("template")
+                .value_name("FILE")
+                .conflicts_with("porcelain")
+                .help("Renders each commit through the template with {{field}} placeholders"),
+        )
+        .arg(
+            Arg::with_name("porcelain")
+                .long("porcelain")
+                .conflicts_with("format")
+                .help("Prints tab-separated records in the stable format for scripts"),
+        )
+        .arg(
+            Arg::with_name("relative")
+                .long("relative")
+                .conflicts_with_all(&["suggest-reword", "message-file"])
+                .help("Grades commits by their rank in the scanned range (top 20% are graded A, etc.)"),
+        )
+        .arg(
+            Arg::with_name("email-report")
+                .long("email-report")
+                .value_name("FORMAT")
+                .validator(try_parse::<DigestFormat>)
+                .conflicts_with_all(&["suggest-reword", "message-file", "by-path-prefix"])
+                .help("Prints the digest of the worst commits for mailing: text or html"),
+        )
+        .arg(
+            Arg::with_name("annotate-notes")
+                .long("annotate-notes")
+                .conflicts_with_all(&["suggest-reword", "message-file"])
+                .help("Writes the notes explaining the grade to the commits graded D or F"),
+        )
+        .arg(
+            Arg::with_name("by-path-prefix")
+                .long("by-path-prefix")
+                .value_name("DEPTH")
+                .validator(try_parse::<usize>)
+                .conflicts_with_all(&["suggest-reword", "message-file"])
+                .help("Summarizes commits by the directories (up to DEPTH levels) they touch"),
+        )
+        .arg(
+            Arg::with_name("summary")
+                .long("summary")
+                .help("Prints the totals (counts, average score, grades) after the commits"),
+        )
+        .arg(
+            Arg::with_name("time-stats")
+                .long("time-stats")
+                .conflicts_with_all(&[
+                    "suggest-reword",
+                    "message-file",
+                    "by-path-prefix",
+                    "email-report",
+                ])
+                .help("Summarizes commits by the weekday and the hour (author's local time)"),
+        )
+        .arg(
+            Arg::with_name("review-coverage")
+                .long("review-coverage")
+                .value_name("DEPTH")
+                .validator(try_parse::<usize>)
+                .conflicts_with_all(&[
+                    "suggest-reword",
+                    "message-file",
+                    "by-path-prefix",
+                    "email-report",
+                    "time-stats",
+                ])
+                .help("Summarizes the share of reviewed commits by the authors and the directories (up to DEPTH levels)"),
+        )
+        .arg(
+            Arg::with_name("check-identities")
+                .long("check-identities")
+                .conflicts_with_all(&["message-file", "suggest-reword", "time-stats"])
+                .help("Reports the authors appearing under multiple name/email combinations"),
+        )
+        .arg(
+            Arg::with_name("no-cache")
+                .long("no-cache")
+                .help("Disables the on-disk cache of commit diffs"),
+        )
+        .arg(
+            Arg::with_name("no-replace-objects")
+                .long("no-replace-objects")
+                .help("Ignores the replace refs and the grafts, traversing the original history"),
+        )
+        .arg(
+            Arg::with_name("low-memory")
+                .long("low-memory")
+                .help("Scores commits in memory independent of the history size, even if slower"),
+        )
+        .arg(
+            Arg::with_name("show-body")
+                .long("show-body")
+                .value_name("N")
+                .validator(try_parse::<usize>)
+                .help("Shows the first N lines of the message body under each commit"),
+        )
+        .arg(
+            Arg::with_name("show-dirs")
+                .long("show-dirs")
+                .help("Shows the top-level directories touched by each commit"),
+        )
+        .arg(
+            Arg::with_name("show-failures")
+                .long("show-failures")
+                .value_name("GRADE")
+                .validator(try_parse::<Grade>)
+                .help("Shows the full messages of the commits graded below GRADE after the table"),
+        )
+        .arg(
+            Arg::with_name("quiet")
+                .short("q")
+                .long("quiet")
+                .help("Does not show the progress of long runs"),
+        )
+        .arg(
+            Arg::with_name("timing")
+                .long("timing")
+                .help("Reports the time spent in each stage of the run to stderr"),
+        )
+        .arg(
+            Arg::with_name("fail-if")
+                .long("fail-if")
+                .value_name("CONDITION")
+                .validator(try_parse::<Condition>)
+                .help("Fails the run if the condition holds, e.g. \"count(F) > 0 || avg < 70\""),
+        )
+        .arg(
+            Arg::with_name("max-grade-count")
+                .long("max-grade-count")
+                .value_name("LIMITS")
+                .validator(try_parse::<GradeLimits>)
+                .help("Fails the run if there are too many commits of the grades, e.g. F=0,D=3"),
+        )
+        .arg(
+            Arg::with_name("notify-webhook")
+                .long("notify-webhook")
+                .value_name("URL")
+                .conflicts_with_all(&["suggest-reword", "message-file"])
+                .help("Posts the summary of the run as JSON to the webhook (e.g. Slack or Teams)"),
+        )
+        .arg(
+            Arg::with_name("github-check")
+                .long("github-check")
+                .value_name("SHA")
+                .conflicts_with_all(&["suggest-reword", "message-file"])
+                .help("Publishes the results as the GitHub check run of the commit (e.g. the pull request head)"),
+        )
+        .arg(
+            Arg::with_name("azure-url")
+                .long("azure-url")
+                .value_name("URL")
+                .help("URL of the Azure DevOps organization for the azure command"),
+        )
+        .arg(
+            Arg::with_name("bitbucket-url")
+                .long("bitbucket-url")
+                .value_name("URL")
+                .help("Base URL of Bitbucket Server for the bitbucket command [default: Bitbucket Cloud]"),
+        )
+        .arg(
+            Arg::with_name("gerrit-url")
+                .long("gerrit-url")
+                .value_name("URL")
+                .help("Base URL of the Gerrit server for the gerrit command (ending with /a for authentication)"),
+        )
+        .arg(
+            Arg::with_name("notify-below")
+                .long("notify-below")
+                .value_name("GRADE")
+                .validator(try_parse::<Grade>)
+                .help("Lists the commits graded below GRADE in the webhook notification"),
+        )
+        .arg(
+            Arg::with_name("strict")
+                .long("strict")
+                .help("Scores short, refactoring and initial commits like the ordinary ones"),
+        )
+        .arg(
+            Arg::with_name("short-diff")
+                .long("short-diff")
+                .value_name("LINES")
+                .validator(try_parse::<usize>)
+                .help("Commits with less lines changed are classified as short [default: 25]"),
+        )
+        .arg(
+            Arg::with_name("refactor-tolerance")
+                .long("refactor-tolerance")
+                .value_name("PERCENTS")
+                .validator(try_parse::<Percentage>)
+                .help("Allowed insertions/deletions imbalance of refactoring commits [default: 5]"),
+        )
+        .arg(
+            Arg::with_name("pr-titles")
+                .long("pr-titles")
+                .help("Scores pull request titles instead of subjects of squash-merged pull requests"),
+        )
+        .arg(
+            Arg::with_name("subject-limits")
+                .long("subject-limits")
+                .value_name("MIN,GOOD_MIN,GOOD_MAX,MAX")
+                .validator(try_parse::<SubjectLimits>)
+                .help("Breakpoints of the subject length scoring [default: 10,20,70,100]"),
+        )
+        .arg(
+            Arg::with_name("body-len-model")
+                .long("body-len-model")
+                .value_name("MODEL")
+                .validator(try_parse::<BodyLenModel>)
+                .help("Body length expected for the diff: log[:COEFFICIENT] or lines:DIFF_LINES [default: log]"),
+        )
+        .arg(
+            Arg::with_name("scopes")
+                .long("scopes")
+                .value_name("LIST")
+                .help("Allowed scopes of Conventional Commits, e.g. api,cli,docs"),
+        )
+        .arg(
+            Arg::with_name("require-scope")
+                .long("require-scope")
+                .help("Penalizes Conventional Commits without the scope"),
+        )
+        .arg(
+            Arg::with_name("rule")
+                .long("rule")
+                .value_name("NAME=SEVERITY")
+                .multiple(true)
+                .number_of_values(1)
+                .validator(try_parse::<RuleSeverity>)
+                .help("Sets the severity of the rule: score, warn (no points) or off (may be repeated)"),
+        )
+        .arg(
+            Arg::with_name("emoji")
+                .long("emoji")
+                .value_name("MODE")
+                .validator(try_parse::<EmojiMode>)
+                .help("Emoji prefixes of subjects: keep, strip or gitmoji (required) [default: keep]"),
+        )
+        .arg(
+            Arg::with_name("wrap-width")
+                .long("wrap-width")
+                .value_name("WIDTH")
+                .validator(try_parse::<usize>)
+                .help("Maximum length of wrapped message body lines [default: 80]"),
+        )
+        .arg(
+            Arg::with_name("ignore-marker")
+                .long("ignore-marker")
+                .value_name("KEY")
+                .help("Message trailer which excludes the commit from scoring [default: Commrate-Ignore]"),
+        );
+
+    // The flag and its negation override each other, so the last one wins.
+    NEGATED_FLAGS
+        .iter()
+        .fold(app, |app, &(flag, negation, help)| {
+            app.arg(
+                Arg::with_name(negation)
+                    .long(negation)
+                    .overrides_with(flag)
+                    .help(help),
+            )
+        })
+}
+
+/// A percentage value from 0 to 100.
+struct Percentage(f32);
+
+impl FromStr for Percentage {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<f32>() {
+            Ok(value) if (0.0..=100.0).contains(&value) => Ok(Percentage(value)),
+            _ => Err("value must be a number from 0 to 100"),
+        }
+    }
 }
 
 /// A generic parseability validator for Clap arguments.
@@ -120,26 +1721,75 @@ where
     arg.parse::<T>().map_err(|s| s.to_string()).map(|_| ())
 }
 
-fn create_pre_filters(matches: &ArgMatches<'_>) -> FilterChain<Metadata> {
+fn create_pre_filters(options: &Options<'_>) -> FilterChain<Metadata> {
     let mut filters: Vec<Box<dyn Filter<Descriptor = Metadata>>> = Vec::new();
 
-    if let Some(author) = matches.value_of("author") {
-        let filter = AuthorPreFilter::new(author);
+    if let Some(author) = options.value_of("author") {
+        let filter = AuthorPreFilter::new(&author);
         filters.push(Box::new(filter));
     }
 
-    if matches.occurrences_of("merges") == 0 {
+    if let Some(ignore_file) = options.value_of("ignore-file") {
+        let list = IgnoreList::read(Path::new(&ignore_file));
+        filters.push(Box::new(IgnoreListPreFilter::new(list)));
+    }
+
+    // Merges have to pass the pre-filters, if they are requested explicitly.
+    let merges_requested = matches!(
+        options.parse::<Classes>("class"),
+        Some(classes) if classes.as_set().contains(Class::Merge)
+    );
+
+    if !options.is_present("merges") && !options.is_present("score-merges") && !merges_requested {
         filters.push(Box::new(MergePreFilter));
     }
 
     FilterChain::new(filters)
 }
 
-fn create_post_filters(matches: &ArgMatches<'_>) -> FilterChain<ScoredCommit> {
+fn create_mid_filters(options: &Options<'_>) -> FilterChain<Commit> {
+    let mut filters: Vec<Box<dyn Filter<Descriptor = Commit>>> = Vec::new();
+
+    if let Some(classes) = options.parse::<Classes>("class") {
+        let filter = ClassMidFilter::new(classes.as_set());
+        filters.push(Box::new(filter));
+    }
+
+    if let Some(classes) = options.parse::<Classes>("exclude-class") {
+        let filter = ExcludeClassMidFilter::new(classes.as_set());
+        filters.push(Box::new(filter));
+    }
+
+    let min_diff = options.parse("min-diff");
+    let max_diff = options.parse("max-diff");
+    if min_diff.is_some() || max_diff.is_some() {
+        let filter = DiffSizeMidFilter::new(min_diff, max_diff);
+        filters.push(Box::new(filter));
+    }
+
+    // The subject regex is a one-off query, so it is not read from the
+    // git configuration, where it would hide most of the commits.
+    if let Some(pattern) = options.matches.value_of("grep") {
+        let regex = patterns::compile(pattern)
+            .unwrap_or_else(|err| fatal(format!("invalid value of --grep: {}", err)));
+        let filter = SubjectMidFilter::new(regex);
+        filters.push(Box::new(filter));
+    }
+
+    // Each of the trailers is required.
+    if let Some(keys) = options.matches.values_of("with-trailer") {
+        for key in keys {
+            filters.push(Box::new(TrailerMidFilter::new(key)));
+        }
+    }
+
+    FilterChain::new(filters)
+}
+
+fn create_post_filters(options: &Options<'_>) -> FilterChain<ScoredCommit> {
     let mut filters: Vec<Box<dyn Filter<Descriptor = ScoredCommit>>> = Vec::new();
 
-    if let Some(grades) = matches.value_of("grades") {
-        let spec = grades.parse::<GradeSpec>().unwrap();
+    if let Some(spec) = read_grade_spec(options) {
         let filter = GradePostFilter::new(spec);
         filters.push(Box::new(filter));
     }
@@ -147,6 +1797,570 @@ fn create_post_filters(matches: &ArgMatches<'_>) -> FilterChain<ScoredCommit> {
     FilterChain::new(filters)
 }
 
-fn read_commits_number(matches: &ArgMatches<'_>) -> Option<usize> {
-    matches.value_of("number").map(|arg| arg.parse().unwrap())
+/// Reads the grade spec, which may be specified multiple times.
+/// The resulting spec matches the commit if any of these does.
+fn read_grade_spec(options: &Options<'_>) -> Option<GradeSpec> {
+    match options.matches.values_of("grades") {
+        Some(values) => values
+            .map(|value| value.parse::<GradeSpec>().unwrap())
+            .fold(None, |union, spec| match union {
+                Some(union) => Some(spec.union(union)),
+                None => Some(spec),
+            }),
+        None => options.parse("grades"),
+    }
+}
+
+/// The scoring overrides configured for the built-in or the user-defined
+/// class: whether the built-in class is scored in the relaxed fashion,
+/// the rules the commits of the class are exempted from and the weights
+/// of the rules replaced for them.
+pub struct ClassPolicy {
+    pub class: ClassRef,
+    pub name: String,
+    pub special: Option<bool>,
+    pub exempt: Vec<String>,
+    pub weights: Vec<(String, f32)>,
+}
+
+impl ClassPolicy {
+    fn new(class: ClassRef, name: &str) -> Self {
+        Self {
+            class,
+            name: name.to_string(),
+            special: None,
+            exempt: Vec::new(),
+            weights: Vec::new(),
+        }
+    }
+}
+
+/// Reads the user-defined classes from the git configuration, e.g.:
+///
+/// ```text
+/// [commrate "class.vendor"]
+///     path = ^vendor/
+///     author = bot$
+///     exempt = BodyLenRule,BodyPresenceRule
+///     weight = BodyWrappingRule=0.05
+/// ```
+///
+/// Patterns are regexes matched against the commit subject, the author
+/// name and the changed paths respectively; the class is assigned if
+/// all the specified patterns match. Besides the classes themselves,
+/// the scoring overrides are returned for each class.
+///
+/// The sections named after the built-in classes (e.g. `class.refactor`)
+/// configure the overrides of these classes, along with the `special` key
+/// defining whether the class is scored in the relaxed fashion.
+fn read_custom_classes(options: &Options<'_>) -> (Vec<CustomClass>, Vec<ClassPolicy>) {
+    let mut classes: Vec<CustomClass> = Vec::new();
+    let mut policies: Vec<ClassPolicy> = Vec::new();
+
+    for (key, value) in options.git_entries(r"^commrate\.class\..+\..+$") {
+        let suffix = &key["commrate.class.".len()..];
+        let (name, field) = match suffix.rfind('.') {
+            Some(pos) => (&suffix[..pos], &suffix[pos + 1..]),
+            None => continue,
+        };
+
+        let builtin = Class::from_name(name).map(ClassRef::Builtin);
+        let class_ref = match builtin {
+            Some(class_ref) => class_ref,
+            None => ClassRef::Custom(custom_class_index(&mut classes, name)),
+        };
+
+        let policy = match policies.iter().position(|policy| policy.class == class_ref) {
+            Some(index) => &mut policies[index],
+            None => {
+                policies.push(ClassPolicy::new(class_ref, name));
+                policies.last_mut().unwrap()
+            }
+        };
+
+        match field {
+            "exempt" => {
+                let rules = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|rule| !rule.is_empty());
+                policy.exempt.extend(rules.map(str::to_string));
+                continue;
+            }
+            "weight" => {
+                match parse_rule_weight(&value) {
+                    Ok(weight) => policy.weights.push(weight),
+                    Err(err) => {
+                        options.invalid(format!("invalid value '{}' of {}: {}", value, key, err))
+                    }
+                }
+                continue;
+            }
+            "special" if builtin.is_some() => {
+                match Config::parse_bool(value.as_str()) {
+                    Ok(special) => policy.special = Some(special),
+                    Err(err) => options.invalid(format!(
+                        "invalid value '{}' of {}: {}",
+                        value,
+                        key,
+                        err.message()
+                    )),
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        let index = match class_ref {
+            ClassRef::Custom(index) => index,
+            // The built-in classes are detected by commrate itself, so
+            // the custom classes cannot be named like these.
+            ClassRef::Builtin(_) if ["subject", "author", "path"].contains(&field) => {
+                options.invalid(format!(
+                    "invalid configuration key {}: {} is the built-in class, so only its exempt, weight and special keys may be set",
+                    key, name
+                ));
+                continue;
+            }
+            ClassRef::Builtin(_) => {
+                options.invalid(format!("unknown configuration key {}", key));
+                continue;
+            }
+        };
+
+        let regex = match field {
+            "subject" | "author" | "path" => match patterns::compile(&value) {
+                Ok(regex) => Some(regex),
+                Err(err) => {
+                    options.invalid(format!("invalid value of {}: {}", key, err));
+                    continue;
+                }
+            },
+            _ => None,
+        };
+
+        let class = classes[index].clone();
+        classes[index] = match (field, regex) {
+            ("subject", Some(regex)) => class.with_subject(regex),
+            ("author", Some(regex)) => class.with_author(regex),
+            ("path", Some(regex)) => class.with_path(regex),
+            _ => {
+                options.invalid(format!("unknown configuration key {}", key));
+                continue;
+            }
+        };
+    }
+
+    (classes, policies)
+}
+
+/// Finds the user-defined class by name, adding it, if not found yet.
+fn custom_class_index(classes: &mut Vec<CustomClass>, name: &str) -> usize {
+    match classes.iter().position(|class| class.name() == name) {
+        Some(index) => index,
+        None => {
+            if classes.len() == MAX_CUSTOM_CLASSES {
+                fatal(format!(
+                    "too many classes, the limit is {}",
+                    MAX_CUSTOM_CLASSES
+                ));
+            }
+
+            classes.push(CustomClass::new(name));
+            classes.len() - 1
+        }
+    }
+}
+
+/// Parses the weight of the rule overridden for the class: `NAME=WEIGHT`.
+fn parse_rule_weight(value: &str) -> Result<(String, f32), String> {
+    let pos = value
+        .find('=')
+        .ok_or_else(|| "must be in the NAME=WEIGHT form".to_string())?;
+    let weight: f32 = value[pos + 1..]
+        .trim()
+        .parse()
+        .map_err(|err| format!("{}", err))?;
+
+    if !weight.is_finite() || weight < 0.0 {
+        return Err("the weight must not be negative".to_string());
+    }
+
+    Ok((value[..pos].trim().to_string(), weight))
+}
+
+/// Reads the severities of the rules from the git configuration, e.g.:
+///
+/// ```text
+/// [commrate "rule.SubjectStyleRule"]
+///     severity = warn
+/// ```
+///
+/// and then from the command line (`--rule SubjectStyleRule=warn`),
+/// so the latter take precedence.
+fn read_rule_severities(options: &Options<'_>) -> Vec<RuleSeverity> {
+    let mut severities = Vec::new();
+
+    for (key, value) in options.git_entries(r"^commrate\.rule\..+\..+$") {
+        let suffix = &key["commrate.rule.".len()..];
+        let (name, field) = match suffix.rfind('.') {
+            Some(pos) => (&suffix[..pos], &suffix[pos + 1..]),
+            None => continue,
+        };
+
+        if field != "severity" {
+            options.invalid(format!("unknown configuration key {}", key));
+            continue;
+        }
+
+        match value.parse() {
+            Ok(severity) => severities.push(RuleSeverity {
+                name: name.to_string(),
+                severity,
+            }),
+            Err(err) => options.invalid(format!("invalid value '{}' of {}: {}", value, key, err)),
+        }
+    }
+
+    if let Some(values) = options.matches.values_of("rule") {
+        severities.extend(values.filter_map(|value| value.parse().ok()));
+    }
+
+    severities
+}
+
+fn read_parse_options(options: &Options<'_>) -> ParseOptions {
+    let mut parse_options = ParseOptions::default();
+
+    if let Some(wrap_width) = options.parse("wrap-width") {
+        parse_options = parse_options.with_wrap_width(wrap_width);
+    }
+
+    if let Some(marker) = options.value_of("ignore-marker") {
+        parse_options = parse_options.with_ignore_marker(&marker);
+    }
+
+    if let Some(length) = options.parse("short-diff") {
+        parse_options = parse_options.with_short_commit_length(length);
+    }
+
+    if let Some(Percentage(percents)) = options.parse("refactor-tolerance") {
+        parse_options = parse_options.with_refactor_allowed_diff(percents / 100.0);
+    }
+
+    if let Some(lines) = options.parse("show-body") {
+        parse_options = parse_options.with_body_preview(lines);
+    }
+
+    parse_options
+}
+
+fn read_command(options: &Options<'_>) -> Command {
+    match options.matches.subcommand() {
+        ("preview", Some(matches)) => Command::Preview {
+            message: matches.value_of("message").map(str::to_string),
+            explain: matches.is_present("explain"),
+        },
+        ("compare", Some(matches)) => Command::Compare {
+            first: matches.value_of("first").unwrap().to_string(),
+            second: matches.value_of("second").unwrap().to_string(),
+        },
+        ("release", Some(matches)) => {
+            let (previous, release) = parse_release_range(matches.value_of("range").unwrap());
+            Command::Release { previous, release }
+        }
+        ("changelog-check", Some(matches)) => {
+            let (previous, release) = parse_release_range(matches.value_of("range").unwrap());
+            Command::ChangelogCheck { previous, release }
+        }
+        ("diff-runs", Some(matches)) => Command::DiffRuns {
+            old: matches.value_of("old").unwrap().to_string(),
+            new: matches.value_of("new").unwrap().to_string(),
+        },
+        ("show", Some(matches)) => Command::Show {
+            commit: matches.value_of("commit").unwrap().to_string(),
+        },
+        ("azure", Some(matches)) => Command::Azure {
+            repository: matches.value_of("repository").unwrap().to_string(),
+            pull_request: matches.value_of("pull-request").unwrap().parse().unwrap(),
+            post_comment: matches.is_present("post-comment"),
+        },
+        ("bitbucket", Some(matches)) => Command::Bitbucket {
+            repository: matches.value_of("repository").unwrap().to_string(),
+            pull_request: matches.value_of("pull-request").unwrap().parse().unwrap(),
+            post_comment: matches.is_present("post-comment"),
+        },
+        ("gerrit", Some(matches)) => Command::Gerrit {
+            change: matches.value_of("change").unwrap().to_string(),
+            latest: matches.is_present("latest"),
+        },
+        ("monitor", Some(matches)) => Command::Monitor {
+            interval: matches.value_of("interval").unwrap().parse().unwrap(),
+            history: PathBuf::from(matches.value_of("history").unwrap()),
+        },
+        ("rules", Some(_)) => Command::Rules,
+        ("config", Some(_)) => Command::ConfigCheck {
+            settings: Vec::new(),
+            problems: Vec::new(),
+        },
+        _ => Command::Log,
+    }
+}
+
+/// Parses the release range: `PREVIOUS..RELEASE` or `RELEASE` alone.
+/// Like in Git, the omitted end of the range is `HEAD`.
+fn parse_release_range(range: &str) -> (Option<String>, String) {
+    let (previous, release) = match range.find("..") {
+        Some(index) => (&range[..index], &range[index + 2..]),
+        None => ("", range),
+    };
+
+    let previous = Some(previous)
+        .filter(|previous| !previous.is_empty())
+        .map(str::to_string);
+    let release = if release.is_empty() { "HEAD" } else { release };
+
+    (previous, release.to_string())
+}
+
+fn read_repositories(options: &Options<'_>) -> Vec<RepositoryLocation> {
+    let matches = &options.matches;
+
+    let mut paths: Vec<String> = matches
+        .values_of("repo")
+        .map(|values| values.map(str::to_string).collect())
+        .unwrap_or_default();
+
+    if let Some(manifest) = matches.value_of("repo-manifest") {
+        paths.extend(read_manifest(manifest));
+    }
+
+    if paths.is_empty() && !matches.is_present("remote") {
+        paths.push(".".to_string());
+    }
+
+    let recurse_submodules = options.is_present("recurse-submodules");
+    let mut repositories = Vec::new();
+
+    for path in paths {
+        let location = RepositoryLocation::Local(path);
+        let submodules = if recurse_submodules {
+            find_submodules(&location)
+        } else {
+            Vec::new()
+        };
+
+        repositories.push(location);
+        repositories.extend(submodules);
+    }
+
+    if let Some(urls) = matches.values_of("remote") {
+        repositories.extend(urls.map(|url| RepositoryLocation::Remote(url.to_string())));
+    }
+
+    repositories
+}
+
+/// Finds the initialized submodules of the repository, recursively.
+/// The paths are relative to the current directory, when possible.
+fn find_submodules(superproject: &RepositoryLocation) -> Vec<RepositoryLocation> {
+    let current_dir = env::current_dir().unwrap_or_default();
+    let repo = GitRepository::open(superproject.name());
+    let workdir = match repo.workdir() {
+        Some(workdir) => workdir,
+        None => return Vec::new(),
+    };
+    let mut submodules = Vec::new();
+
+    for gitlink in repo.submodule_paths() {
+        let submodule = workdir.join(&gitlink);
+        let relative = submodule.strip_prefix(&current_dir).unwrap_or(&submodule);
+        let location = RepositoryLocation::Submodule {
+            path: relative.to_string_lossy().into_owned(),
+            superproject: Box::new(superproject.clone()),
+            gitlink,
+        };
+
+        let nested = find_submodules(&location);
+        submodules.push(location);
+        submodules.extend(nested);
+    }
+
+    submodules
+}
+
+fn read_clone_depth(options: &Options<'_>) -> Option<usize> {
+    let depth = options.parse("depth").unwrap_or(DEFAULT_CLONE_DEPTH);
+
+    if depth == 0 {
+        None
+    } else {
+        Some(depth)
+    }
+}
+
+/// Reads the explicitly configured options backed by the git configuration
+/// (see `GIT_CONFIG_OPTIONS`) and the custom classes.
+fn read_settings(options: &Options<'_>) -> Vec<Setting> {
+    let mut settings = Vec::new();
+
+    for &name in GIT_CONFIG_OPTIONS {
+        let key = git_config_key(name);
+
+        if options.matches.occurrences_of(name) > 0 {
+            let value = options.matches.value_of(name).unwrap_or("true");
+            settings.push(Setting {
+                key,
+                value: value.to_string(),
+                source: SettingSource::CommandLine,
+            });
+        } else if let Some(value) = options.git_value(name, |config, key| config.get_string(key)) {
+            settings.push(Setting {
+                key,
+                value,
+                source: SettingSource::GitConfig,
+            });
+        }
+    }
+
+    for (key, value) in options.git_entries(r"^commrate\.(class|rule)\.") {
+        settings.push(Setting {
+            key,
+            value,
+            source: SettingSource::GitConfig,
+        });
+    }
+
+    settings
+}
+
+/// Finds the `commrate.*` git configuration keys, which are not used,
+/// e.g. the misspelled ones, which would be silently ignored otherwise.
+fn find_unknown_keys(options: &Options<'_>) -> Vec<String> {
+    // Git does not preserve the case of the key names.
+    let known: Vec<_> = GIT_CONFIG_OPTIONS
+        .iter()
+        .map(|name| git_config_key(name).to_ascii_lowercase())
+        .collect();
+    let is_class_key = |key: &str| {
+        ["commrate.class.", "commrate.rule."]
+            .iter()
+            .any(|prefix| key.starts_with(prefix) && key[prefix.len()..].contains('.'))
+    };
+
+    // The key may be set several times, e.g. in the global and
+    // the repository configuration, but it is reported once.
+    let keys: BTreeSet<_> = options
+        .git_entries(r"^commrate\.")
+        .into_iter()
+        .map(|(key, _)| key)
+        .filter(|key| !known.contains(&key.to_ascii_lowercase()) && !is_class_key(key))
+        .collect();
+
+    keys.into_iter()
+        .map(|key| format!("unknown configuration key {}", key))
+        .collect()
+}
+
+/// Reads the template of the commits for the template output format.
+fn read_template(options: &Options<'_>, path: &str) -> Option<Template> {
+    let parsed = fs::read_to_string(path)
+        .map_err(|err| format!("cannot read template {}: {}", path, err))
+        .and_then(|content| content.parse::<Template>());
+
+    match parsed {
+        Ok(template) => Some(template),
+        Err(err) => {
+            options.invalid(err);
+            None
+        }
+    }
+}
+
+/// Reads the start commits and the excluded ones: like in `git log`,
+/// the start commits prefixed with `^` are excluded along with the
+/// `--not` ones. The history is traversed from `HEAD` by default.
+fn read_start_commits(options: &Options<'_>) -> (Vec<String>, Vec<String>) {
+    let mut start_commits = Vec::new();
+    let mut excluded_commits = Vec::new();
+
+    for commit in options.matches.values_of("commit").into_iter().flatten() {
+        let mut chars = commit.chars();
+
+        if chars.next() == Some('^') {
+            excluded_commits.push(chars.as_str().to_string());
+        } else {
+            start_commits.push(commit.to_string());
+        }
+    }
+
+    if let Some(excluded) = options.matches.values_of("not") {
+        excluded_commits.extend(excluded.map(String::from));
+    }
+
+    if start_commits.is_empty() {
+        start_commits.push("HEAD".to_string());
+    }
+
+    (start_commits, excluded_commits)
+}
+
+/// Reads the commits (IDs or any other revisions), one per line.
+fn read_stdin_commits() -> Vec<String> {
+    let stdin = io::stdin();
+    let mut commits = Vec::new();
+
+    for line in stdin.lock().lines() {
+        let line = line.unwrap_or_else(|err| fatal(format!("cannot read stdin: {}", err)));
+        let commit = line.trim();
+
+        if !commit.is_empty() {
+            commits.push(commit.to_string());
+        }
+    }
+
+    commits
+}
+
+/// Reads the repository manifest: each non-empty line which does not
+/// start with '#' is a repository path. Relative paths are resolved
+/// against the directory containing the manifest.
+fn read_manifest(manifest: &str) -> Vec<String> {
+    let content = fs::read_to_string(manifest)
+        .unwrap_or_else(|err| fatal(format!("cannot read manifest {}: {}", manifest, err)));
+
+    let base = Path::new(manifest)
+        .parent()
+        .unwrap_or_else(|| Path::new(""));
+
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| base.join(line).to_string_lossy().into_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn git_config_key_is_camel_cased() {
+        assert_eq!(git_config_key("score"), "commrate.score");
+        assert_eq!(git_config_key("wrap-width"), "commrate.wrapWidth");
+    }
+
+    #[test]
+    fn release_ranges_are_parsed() {
+        let (previous, release) = parse_release_range("v1.2.0..v1.3.0");
+        assert_eq!(previous.as_deref(), Some("v1.2.0"));
+        assert_eq!(release, "v1.3.0");
+
+        assert_eq!(parse_release_range("v1.3.0"), (None, "v1.3.0".to_string()));
+        assert_eq!(
+            parse_release_range("v1.3.0.."),
+            (Some("v1.3.0".to_string()), "HEAD".to_string())
+        );
+    }
 }
@@ -0,0 +1,14 @@
+use colored::Colorize;
+use std::{fmt::Display, process::exit};
+
+/// Prints the error message in the common format and terminates
+/// the process with non-zero exit code.
+pub fn fatal<M: Display>(message: M) -> ! {
+    eprintln!("{}: {}", "error".red(), message);
+    exit(1);
+}
+
+/// Prints the warning message in the common format.
+pub fn warning<M: Display>(message: M) {
+    eprintln!("{}: {}", "warning".yellow(), message);
+}
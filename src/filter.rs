@@ -1,5 +1,9 @@
+use enumset::EnumSet;
+use regex::Regex;
+
 use crate::{
-    commit::Metadata,
+    commit::{Class, Commit, Metadata},
+    ignore::IgnoreList,
     scoring::{GradeSpec, Score, ScoredCommit},
 };
 
@@ -21,15 +25,20 @@ impl<D> FilterChain<D> {
     pub fn new(filters: Vec<Box<dyn Filter<Descriptor = D>>>) -> Self {
         Self(filters)
     }
+}
 
-    pub fn accept(&self, descriptor: &D) -> bool {
-        for filter in &self.0 {
-            if !filter.accept(descriptor) {
-                return false;
-            }
-        }
+/// A chain is a filter itself, which accepts the commits accepted by all
+/// its filters, so the chains from different sources (e.g. the global and
+/// the per-repository ones) may be combined at the same stage.
+impl<D> Filter for FilterChain<D> {
+    type Descriptor = D;
 
-        true
+    fn accept(&self, descriptor: &D) -> bool {
+        self.0.iter().all(|filter| filter.accept(descriptor))
+    }
+
+    fn needs_diff(&self) -> bool {
+        self.0.iter().any(|filter| filter.needs_diff())
     }
 }
 
@@ -46,6 +55,13 @@ pub trait Filter {
     type Descriptor;
 
     fn accept(&self, descriptor: &Self::Descriptor) -> bool;
+
+    /// Whether the filter inspects the diff of the commit or the classes
+    /// derived from it. This matters only for the mid-filters, as the diff
+    /// is not parsed at all if neither the filters nor the rules need it.
+    fn needs_diff(&self) -> bool {
+        false
+    }
 }
 
 /// A filter which accepts only commits with specific author.
@@ -80,6 +96,149 @@ impl Filter for MergePreFilter {
     }
 }
 
+/// A filter which discards commits from the ignore list.
+pub struct IgnoreListPreFilter {
+    list: IgnoreList,
+}
+
+impl IgnoreListPreFilter {
+    pub fn new(list: IgnoreList) -> Self {
+        Self { list }
+    }
+}
+
+impl Filter for IgnoreListPreFilter {
+    type Descriptor = Metadata;
+
+    fn accept(&self, metadata: &Metadata) -> bool {
+        !metadata.id().with_str(|id| self.list.contains(id))
+    }
+}
+
+/// A filter which accepts only commits having any of the specified classes.
+pub struct ClassMidFilter {
+    classes: EnumSet<Class>,
+}
+
+impl ClassMidFilter {
+    pub fn new(classes: EnumSet<Class>) -> Self {
+        Self { classes }
+    }
+}
+
+impl Filter for ClassMidFilter {
+    type Descriptor = Commit;
+
+    fn accept(&self, commit: &Commit) -> bool {
+        !commit.classes().as_set().is_disjoint(self.classes)
+    }
+
+    fn needs_diff(&self) -> bool {
+        true
+    }
+}
+
+/// A filter which discards commits having any of the specified classes.
+pub struct ExcludeClassMidFilter {
+    classes: EnumSet<Class>,
+}
+
+impl ExcludeClassMidFilter {
+    pub fn new(classes: EnumSet<Class>) -> Self {
+        Self { classes }
+    }
+}
+
+impl Filter for ExcludeClassMidFilter {
+    type Descriptor = Commit;
+
+    fn accept(&self, commit: &Commit) -> bool {
+        commit.classes().as_set().is_disjoint(self.classes)
+    }
+
+    fn needs_diff(&self) -> bool {
+        true
+    }
+}
+
+/// A filter which accepts only commits whose subject matches the regex.
+pub struct SubjectMidFilter {
+    regex: Regex,
+}
+
+impl SubjectMidFilter {
+    pub fn new(regex: Regex) -> Self {
+        Self { regex }
+    }
+}
+
+impl Filter for SubjectMidFilter {
+    type Descriptor = Commit;
+
+    fn accept(&self, commit: &Commit) -> bool {
+        let subject = commit.msg_info().subject().unwrap_or("");
+
+        self.regex.is_match(subject)
+    }
+}
+
+/// A filter which accepts only commits having the trailer with the key
+/// (case-insensitive), e.g. `Reviewed-by`.
+pub struct TrailerMidFilter {
+    key: String,
+}
+
+impl TrailerMidFilter {
+    pub fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+        }
+    }
+}
+
+impl Filter for TrailerMidFilter {
+    type Descriptor = Commit;
+
+    fn accept(&self, commit: &Commit) -> bool {
+        commit.msg_info().has_trailer(&self.key)
+    }
+}
+
+/// A filter which accepts only commits with the diff size (lines total)
+/// within the specified bounds (inclusive). Commits without diff, i.e.
+/// merges, are always accepted.
+pub struct DiffSizeMidFilter {
+    min: usize,
+    max: usize,
+}
+
+impl DiffSizeMidFilter {
+    pub fn new(min: Option<usize>, max: Option<usize>) -> Self {
+        Self {
+            min: min.unwrap_or(0),
+            max: max.unwrap_or(usize::MAX),
+        }
+    }
+}
+
+impl Filter for DiffSizeMidFilter {
+    type Descriptor = Commit;
+
+    fn accept(&self, commit: &Commit) -> bool {
+        match commit.diff_info() {
+            Some(diff_info) => {
+                let diff_total = diff_info.diff_total();
+                diff_total >= self.min && diff_total <= self.max
+            }
+            None => true,
+        }
+    }
+
+    fn needs_diff(&self) -> bool {
+        true
+    }
+}
+
 /// A post-filter for discarding commits based on their score.
 pub struct GradePostFilter {
     spec: GradeSpec,
@@ -90,7 +249,7 @@ impl Filter for GradePostFilter {
 
     fn accept(&self, commit: &ScoredCommit) -> bool {
         match commit.score() {
-            Score::Ignored => true,
+            Score::Ignored(_) => true,
             Score::Scored { grade, .. } => self.spec.matches(grade),
         }
     }
@@ -0,0 +1,157 @@
+use crate::{
+    http::{self, encode_component, Auth},
+    json::Json,
+};
+
+/// The prefix of the Gerrit responses protecting against XSSI,
+/// which precedes the JSON itself.
+const XSSI_PREFIX: &str = ")]}'";
+
+/// The files Gerrit lists along with the changed ones, which are
+/// not the part of the diff.
+const MAGIC_FILES: &[&str] = &["/COMMIT_MSG", "/MERGE_LIST", "/PATCHSET_LEVEL"];
+
+/// The patch set of the Gerrit change: the commit uploaded for review.
+pub struct PatchSet {
+    pub number: u32,
+    pub revision: String,
+    pub author: String,
+    pub message: String,
+    pub parents: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub paths: Vec<String>,
+}
+
+/// Fetches the patch sets of the change (anything Gerrit accepts as
+/// the change ID: the number, the Change-Id or `project~number`) via
+/// the REST API, from the oldest patch set to the newest one.
+///
+/// The base URL should end with `/a` for the authenticated access, in
+/// which case the credentials are taken by `curl` from `~/.netrc`.
+pub fn fetch_change(base_url: &str, change: &str) -> Result<Vec<PatchSet>, String> {
+    let url = format!(
+        "{}/changes/{}?o=ALL_REVISIONS&o=ALL_COMMITS&o=ALL_FILES",
+        base_url.trim_end_matches('/'),
+        encode_component(change)
+    );
+
+    parse_change(&http::get(&url, Auth::None)?)
+}
+
+/// Parses the `ChangeInfo` entity with all the revisions, their commits
+/// and their files.
+fn parse_change(content: &str) -> Result<Vec<PatchSet>, String> {
+    let json = Json::parse(content.trim_start().trim_start_matches(XSSI_PREFIX))?;
+    let revisions = json
+        .get("revisions")
+        .and_then(Json::as_object)
+        .ok_or("the change without revisions")?;
+
+    let mut patch_sets = revisions
+        .iter()
+        .map(|(revision, info)| parse_revision(revision, info))
+        .collect::<Result<Vec<_>, String>>()?;
+    patch_sets.sort_by_key(|patch_set| patch_set.number);
+
+    Ok(patch_sets)
+}
+
+fn parse_revision(revision: &str, info: &Json) -> Result<PatchSet, String> {
+    let missing = |field| format!("the revision {} without {}", revision, field);
+
+    let number = info
+        .get("_number")
+        .and_then(Json::as_u32)
+        .ok_or_else(|| missing("the number"))?;
+    let commit = info.get("commit").ok_or_else(|| missing("the commit"))?;
+    let message = commit
+        .get("message")
+        .and_then(Json::as_str)
+        .ok_or_else(|| missing("the message"))?;
+    let author = commit
+        .get("author")
+        .and_then(|author| author.get("name"))
+        .and_then(Json::as_str)
+        .unwrap_or("");
+    let parents = commit
+        .get("parents")
+        .and_then(Json::as_array)
+        .map_or(1, <[Json]>::len);
+
+    let mut insertions = 0;
+    let mut deletions = 0;
+    let mut paths = Vec::new();
+
+    // The binary files have no line counts.
+    let files = info.get("files").and_then(Json::as_object).unwrap_or(&[]);
+    for (path, file) in files {
+        if MAGIC_FILES.contains(&path.as_str()) {
+            continue;
+        }
+
+        let lines = |field| file.get(field).and_then(Json::as_u32).unwrap_or(0) as usize;
+        insertions += lines("lines_inserted");
+        deletions += lines("lines_deleted");
+        paths.push(path.clone());
+    }
+
+    Ok(PatchSet {
+        number,
+        revision: revision.to_string(),
+        author: author.to_string(),
+        message: message.to_string(),
+        parents,
+        insertions,
+        deletions,
+        paths,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_are_parsed() {
+        let patch_sets = parse_change(
+            r#")]}'
+{
+  "_number": 42,
+  "revisions": {
+    "bbb": {
+      "_number": 2,
+      "commit": {
+        "parents": [{"commit": "ccc"}],
+        "author": {"name": "Foo", "email": "foo@example.com"},
+        "message": "Fix the parser\n\nChange-Id: I123\n"
+      },
+      "files": {
+        "/COMMIT_MSG": {"lines_inserted": 7},
+        "src/parser.rs": {"lines_inserted": 10, "lines_deleted": 2},
+        "logo.png": {"binary": true}
+      }
+    },
+    "aaa": {
+      "_number": 1,
+      "commit": {"author": {"name": "Foo"}, "message": "fix\n\nChange-Id: I123\n"}
+    }
+  }
+}"#,
+        )
+        .unwrap();
+
+        assert_eq!(patch_sets.len(), 2);
+        assert_eq!(patch_sets[0].revision, "aaa");
+        assert!(patch_sets[0].paths.is_empty());
+
+        let latest = &patch_sets[1];
+        assert_eq!(latest.number, 2);
+        assert_eq!(latest.author, "Foo");
+        assert_eq!((latest.insertions, latest.deletions), (10, 2));
+        assert_eq!(latest.paths, ["src/parser.rs", "logo.png"]);
+
+        assert!(parse_change(r#"{"_number": 42}"#).is_err());
+        assert!(parse_change(r#"{"revisions": {"aaa": {"_number": 1}}}"#).is_err());
+    }
+}
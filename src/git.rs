@@ -1,83 +1,617 @@
-use crate::commit::{Commit, DiffInfo, MessageInfo, Metadata};
+use crate::{
+    cache::DiffCache,
+    commit::{
+        AuthorInterner, AuthorTime, Commit, CommitId, DiffInfo, MessageInfo, Metadata, ParseOptions,
+    },
+    error::fatal,
+    graph::CommitGraph,
+    identity::Identity,
+    replace::{ReplacedWalk, Replacements},
+    timing::{self, Stage},
+};
 
-use colored::Colorize;
-use git2::{Commit as GitCommit, DiffStats, Error, Repository, Revwalk};
-use std::process::exit;
+use git2::{
+    Commit as GitCommit, DescribeFormatOptions, DescribeOptions, Diff, DiffDelta, Error, ErrorCode,
+    ObjectType, Oid, Repository, Revwalk,
+};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    env, fs,
+    path::{Path, PathBuf},
+    vec,
+};
 
 pub struct GitRepository {
     repo: Repository,
+    common_dir: PathBuf,
+    shallow_boundary: HashSet<Oid>,
+    diff_cache: Option<DiffCache>,
+    replacements: Replacements,
 }
 
 impl GitRepository {
-    pub fn open(location: &str) -> Self {
-        Self {
-            repo: git_expect(Repository::discover(location)),
+    /// Opens the repository containing the location. Like git itself, the
+    /// current directory (`.`) is opened according to the `GIT_DIR` and
+    /// `GIT_WORK_TREE` environment variables, if these are set.
+    pub fn open<P: AsRef<Path>>(location: P) -> Self {
+        git_expect(Self::try_open(location))
+    }
+
+    /// Opens the repository like `open()` does, but returns the error
+    /// instead of terminating, if the repository cannot be opened.
+    pub fn try_open<P: AsRef<Path>>(location: P) -> Result<Self, Error> {
+        let repo = discover_repository(location.as_ref())?;
+        let common_dir = read_common_dir(&repo);
+        let shallow_boundary = read_shallow_boundary(&common_dir);
+
+        Ok(Self {
+            repo,
+            common_dir,
+            shallow_boundary,
+            diff_cache: None,
+            replacements: Replacements::default(),
+        })
+    }
+
+    /// Enables the on-disk cache of the commit diffs, which
+    /// speeds up the repeated traversals of the same history.
+    pub fn with_diff_cache(mut self) -> Self {
+        self.diff_cache = DiffCache::open(&self.common_dir);
+        self
+    }
+
+    /// Makes the replace refs and the grafts applied to the traversed
+    /// history, like Git does (see `Replacements`).
+    pub fn with_replacements(mut self) -> Self {
+        self.replacements = Replacements::read(&self.repo, &self.common_dir);
+        self
+    }
+
+    /// Whether the repository is a shallow clone, i.e. its history
+    /// is truncated at the boundary commits.
+    pub fn is_shallow(&self) -> bool {
+        !self.shallow_boundary.is_empty()
+    }
+
+    /// Paths of the initialized submodules (not recursively), relative
+    /// to the working tree, which is empty for the bare repositories.
+    pub fn submodule_paths(&self) -> Vec<PathBuf> {
+        if self.repo.workdir().is_none() {
+            return Vec::new();
+        }
+
+        git_expect(self.repo.submodules())
+            .iter()
+            .filter(|submodule| submodule.open().is_ok())
+            .map(|submodule| submodule.path().to_path_buf())
+            .collect()
+    }
+
+    /// The commits of the submodule at the path, which are recorded in the
+    /// listed commits. The commits without the submodule are skipped.
+    pub fn submodule_commits(&self, commits: &[Oid], path: &Path) -> Vec<Oid> {
+        commits
+            .iter()
+            .filter_map(|&id| {
+                let commit = git_expect(self.repo.find_commit(self.replacements.object_id(id)));
+                let entry = git_expect(commit.tree()).get_path(path).ok()?;
+                Some(entry.id()).filter(|_| entry.kind() == Some(ObjectType::Commit))
+            })
+            .collect()
+    }
+
+    /// Checks whether the commit is present in the repository,
+    /// e.g. the one recorded in the superproject may be not fetched.
+    pub fn has_commit(&self, id: Oid) -> bool {
+        self.repo.find_commit(id).is_ok()
+    }
+
+    /// Working tree of the repository, `None` for bare repositories.
+    pub fn workdir(&self) -> Option<&Path> {
+        self.repo.workdir()
+    }
+
+    /// The nearest tag preceding the commit (i.e. reachable from its first
+    /// parent), like `git describe --tags --abbrev=0 COMMIT^` reports.
+    pub fn previous_tag(&self, reference: &str) -> Option<String> {
+        let object = git_expect(self.repo.revparse_single(reference));
+        let commit = git_expect(object.peel_to_commit());
+        let parent = commit.parents().next()?;
+
+        let describe = parent
+            .as_object()
+            .describe(DescribeOptions::new().describe_tags())
+            .ok()?;
+
+        describe
+            .format(Some(DescribeFormatOptions::new().abbreviated_size(0)))
+            .ok()
+    }
+
+    /// Sets the note of the commit in the notes ref, or removes the note,
+    /// if `None`. The same note is not rewritten, so the repeated runs
+    /// do not make the commits in the notes ref.
+    pub fn set_note(&self, notes_ref: &str, id: &CommitId, note: Option<&str>) {
+        let oid = match id {
+            CommitId::Object(oid) => *oid,
+            CommitId::Placeholder(_) => return,
+        };
+
+        let current = self.repo.find_note(Some(notes_ref), oid).ok();
+        if current.as_ref().and_then(|current| current.message()) == note {
+            return;
         }
+
+        let signature = git_expect(self.repo.signature());
+        match note {
+            Some(note) => {
+                git_expect(self.repo.note(
+                    &signature,
+                    &signature,
+                    Some(notes_ref),
+                    oid,
+                    note,
+                    true,
+                ));
+            }
+            None => git_expect(
+                self.repo
+                    .note_delete(oid, Some(notes_ref), &signature, &signature),
+            ),
+        }
+    }
+
+    /// The character, which starts comment lines in the messages
+    /// being edited (`core.commentChar`).
+    pub fn comment_char(&self) -> char {
+        let config = git_expect(self.repo.config());
+
+        match config.get_string("core.commentChar") {
+            // The "auto" value makes Git pick the character, which is
+            // not used in the message. Well, "#" is the most likely one.
+            Ok(value) if value != "auto" => value.chars().next().unwrap_or('#'),
+            _ => '#',
+        }
+    }
+
+    /// The name of the user (`user.name`), who is going to commit.
+    pub fn user_name(&self) -> String {
+        let config = git_expect(self.repo.config());
+
+        config.get_string("user.name").unwrap_or_default()
+    }
+
+    /// The directory of the repository itself (`.git`).
+    pub fn git_dir(&self) -> &Path {
+        self.repo.path()
+    }
+
+    /// The diff of the changes staged for the commit (the index
+    /// against `HEAD`) and the number of the would-be commit parents.
+    pub fn staged_diff(&self, options: &ParseOptions) -> (DiffInfo, usize) {
+        let head_tree = match self.repo.head() {
+            Ok(head) => Some(git_expect(head.peel_to_tree())),
+            Err(ref err) if err.code() == ErrorCode::UnbornBranch => None,
+            Err(err) => fatal(err.message()),
+        };
+
+        let diff = git_expect(self.repo.diff_tree_to_index(head_tree.as_ref(), None, None));
+        let parents = if head_tree.is_some() { 1 } else { 0 };
+
+        (parse_diff(&diff, options), parents)
     }
 
     pub fn traverse(&self, start_commit: &str) -> GitTraversal<'_> {
+        self.walk(&[self.resolve(start_commit)], &[])
+    }
+
+    /// Traverses the commits reachable from any of the start commits,
+    /// but not from the excluded ones (like `git log start ^excluded`).
+    pub fn traverse_refs(
+        &self,
+        start_commits: &[String],
+        excluded_commits: &[String],
+    ) -> GitTraversal<'_> {
+        let (starts, excluded) = self.resolve_refs(start_commits, excluded_commits);
+        self.walk(&starts, &excluded)
+    }
+
+    /// Collects the patch IDs of the non-merge commits reachable from any
+    /// of the start commits, but not from the excluded ones. The commits
+    /// of the other side having the same patch IDs are cherry-picked, so
+    /// these may be skipped (see `GitTraversal::without_patches`).
+    pub fn patch_ids(&self, start_commits: &[String], excluded_commits: &[String]) -> PatchIds {
+        let (starts, excluded) = self.resolve_refs(start_commits, excluded_commits);
+        self.patch_ids_between(&starts, &excluded)
+    }
+
+    /// Collects the patch IDs like `patch_ids()` does, but for the
+    /// already resolved commits.
+    pub fn patch_ids_between(&self, starts: &[Oid], excluded: &[Oid]) -> PatchIds {
+        let mut patch_ids = PatchIds::default();
+
+        // The commits are walked the same way as the scored ones,
+        // so the replacements and the grafts are honored here too.
+        for id in self.commit_source(starts, excluded).filter_map(Result::ok) {
+            if self.shallow_boundary.contains(&id) {
+                continue;
+            }
+
+            if let Some(diff) = commit_diff(&self.repo, &self.replacements, id) {
+                let paths_id = paths_id(&diff);
+                patch_ids.by_paths.entry(paths_id).or_default().push(id);
+            }
+        }
+
+        patch_ids
+    }
+
+    /// Traverses the commits reachable from any of the start commits, but
+    /// not from the excluded ones, like `traverse_refs()` does, but for the
+    /// already resolved commits.
+    pub fn traverse_ids(&self, starts: &[Oid], excluded: &[Oid]) -> GitTraversal<'_> {
+        self.walk(starts, excluded)
+    }
+
+    /// Traverses exactly the listed commits in the given order.
+    pub fn traverse_list(&self, revisions: &[String]) -> GitTraversal<'_> {
+        let ids: Vec<_> = revisions
+            .iter()
+            .map(|revision| {
+                let object = git_expect(self.repo.revparse_single(revision));
+                git_expect(object.peel_to_commit()).id()
+            })
+            .collect();
+
+        self.traverse_source(CommitSource::List(ids.into_iter()))
+    }
+
+    /// Looks up the single commit, e.g. for the detailed inspection.
+    pub fn find(&self, revision: &str) -> GitRepositoryItem<'_> {
+        let id = self.resolve(revision);
+        let shallow_boundary = self.shallow_boundary.contains(&id);
+
+        GitRepositoryItem::new(
+            &self.repo,
+            self.diff_cache.as_ref(),
+            &mut AuthorInterner::default(),
+            &self.replacements,
+            id,
+        )
+        .with_shallow_boundary(shallow_boundary)
+    }
+
+    /// Traverses the commits reachable from the start commit,
+    /// but not from the excluded one (`excluded..start`).
+    pub fn traverse_range(&self, start_commit: &str, excluded_commit: &str) -> GitTraversal<'_> {
+        let start = self.resolve(start_commit);
+        let excluded = self.resolve(excluded_commit);
+
+        self.walk(&[start], &[excluded])
+    }
+
+    /// Traverses the commits of the current branch, which are not
+    /// pushed yet, i.e. `@{upstream}..HEAD`.
+    pub fn traverse_unpushed(&self) -> GitTraversal<'_> {
+        let upstream = match self.repo.revparse_single("@{upstream}") {
+            Ok(upstream) => upstream,
+            Err(_) => fatal("the current branch has no upstream"),
+        };
+
+        let head = git_expect(self.repo.head().and_then(|head| head.peel_to_commit()));
+        let upstream = git_expect(upstream.peel_to_commit());
+
+        self.walk(&[head.id()], &[upstream.id()])
+    }
+
+    /// Resolves the revision to the ID of the commit it refers to.
+    fn resolve(&self, revision: &str) -> Oid {
+        let object = git_expect(self.repo.revparse_single(revision));
+        git_expect(object.peel_to_commit()).id()
+    }
+
+    /// Resolves the start and the excluded commits of the range.
+    pub fn resolve_refs(
+        &self,
+        start_commits: &[String],
+        excluded_commits: &[String],
+    ) -> (Vec<Oid>, Vec<Oid>) {
+        let resolve = |commit: &String| self.resolve(commit);
+
+        (
+            start_commits.iter().map(resolve).collect(),
+            excluded_commits.iter().map(resolve).collect(),
+        )
+    }
+
+    /// Hides the commits reachable from the excluded one. If the commit-graph
+    /// is available, the merge bases of the start and the excluded commits
+    /// are hidden instead: this results in the same range, but the walk does
+    /// not have to go through the excluded history, which may be huge (e.g.
+    /// for the old branch compared against the current master).
+    fn hide_excluded(
+        revwalk: &mut Revwalk<'_>,
+        graph: Option<&CommitGraph>,
+        start: Oid,
+        excluded: Oid,
+    ) {
+        let bases = graph.and_then(|graph| graph.merge_bases(start, excluded));
+
+        match bases {
+            Some(bases) => {
+                for base in bases {
+                    git_expect(revwalk.hide(base));
+                }
+            }
+            None => git_expect(revwalk.hide(excluded)),
+        }
+    }
+
+    fn revwalk(&self, starts: &[Oid], excluded: &[Oid]) -> Revwalk<'_> {
         let mut revwalk = git_expect(self.repo.revwalk());
-        let rev = git_expect(self.repo.revparse_single(start_commit));
-        git_expect(revwalk.push(rev.id()));
 
+        for &start in starts {
+            git_expect(revwalk.push(start));
+        }
+
+        // The graph is read once for all the pairs, and only if needed.
+        let graph = if excluded.is_empty() {
+            None
+        } else {
+            CommitGraph::open(&self.common_dir)
+        };
+
+        for &excluded in excluded {
+            for &start in starts {
+                Self::hide_excluded(&mut revwalk, graph.as_ref(), start, excluded);
+            }
+        }
+
+        revwalk
+    }
+
+    /// Traverses the commits reachable from any of the start commits, but
+    /// not from the excluded ones. libgit2 ignores the replacements, so the
+    /// history is walked separately when there are any. The same walk is
+    /// used for the shallow repositories, as the libgit2 one fails on the
+    /// absent parents before yielding the boundary commits.
+    fn walk(&self, starts: &[Oid], excluded: &[Oid]) -> GitTraversal<'_> {
+        self.traverse_source(self.commit_source(starts, excluded))
+    }
+
+    fn commit_source(&self, starts: &[Oid], excluded: &[Oid]) -> CommitSource<'_> {
+        if self.replacements.is_empty() && !self.is_shallow() {
+            CommitSource::Walk(self.revwalk(starts, excluded))
+        } else {
+            CommitSource::Replaced(ReplacedWalk::new(
+                &self.repo,
+                &self.replacements,
+                starts,
+                excluded,
+            ))
+        }
+    }
+
+    fn traverse_source<'repo>(&'repo self, source: CommitSource<'repo>) -> GitTraversal<'repo> {
         GitTraversal {
             repo: &self.repo,
-            revwalk,
+            shallow_boundary: &self.shallow_boundary,
+            diff_cache: self.diff_cache.as_ref(),
+            replacements: &self.replacements,
+            authors: AuthorInterner::default(),
+            source,
+            skipped_patches: PatchIds::default(),
+        }
+    }
+}
+
+/// The source of the traversed commits.
+enum CommitSource<'repo> {
+    /// The history walk from the start commit.
+    Walk(Revwalk<'repo>),
+
+    /// The explicitly listed commits.
+    List(vec::IntoIter<Oid>),
+
+    /// The history walk with the replacements applied.
+    Replaced(ReplacedWalk<'repo>),
+}
+
+impl Iterator for CommitSource<'_> {
+    type Item = Result<Oid, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            CommitSource::Walk(revwalk) => revwalk.next(),
+            CommitSource::List(ids) => ids.next().map(Ok),
+            CommitSource::Replaced(walk) => walk.next().map(Ok),
         }
     }
 }
 
 pub struct GitTraversal<'repo> {
     repo: &'repo Repository,
-    revwalk: Revwalk<'repo>,
+    shallow_boundary: &'repo HashSet<Oid>,
+    diff_cache: Option<&'repo DiffCache>,
+    replacements: &'repo Replacements,
+    authors: AuthorInterner,
+    source: CommitSource<'repo>,
+    skipped_patches: PatchIds,
 }
 
 impl<'repo> Iterator for GitTraversal<'repo> {
     type Item = GitRepositoryItem<'repo>;
 
     fn next(&mut self) -> Option<GitRepositoryItem<'repo>> {
-        self.revwalk.next().map(|commit_id| {
-            let id = git_expect(commit_id);
-            let commit = git_expect(self.repo.find_commit(id));
-
-            let metadata = Metadata::new(
-                commit.id().to_string(),
-                commit.author().name().unwrap().to_string(),
-                commit.parent_count(),
-            );
-
-            GitRepositoryItem {
-                repo: self.repo,
-                metadata,
-                commit,
+        timing::measure(Stage::Traversal, || self.next_item())
+    }
+}
+
+impl<'repo> GitTraversal<'repo> {
+    /// Skips the commits having the given patch IDs, i.e. the ones which
+    /// are already cherry-picked to the other branch (like `git log --cherry`).
+    pub fn without_patches(mut self, patch_ids: PatchIds) -> Self {
+        self.skipped_patches = patch_ids;
+        self
+    }
+
+    fn next_item(&mut self) -> Option<GitRepositoryItem<'repo>> {
+        loop {
+            let id = git_expect(self.source.next()?);
+
+            // The parents of boundary commits are absent, so the diff of such
+            // commits cannot be calculated and they are scored without it.
+            let shallow_boundary = self.shallow_boundary.contains(&id);
+            let item = GitRepositoryItem::new(
+                self.repo,
+                self.diff_cache,
+                &mut self.authors,
+                self.replacements,
+                id,
+            )
+            .with_shallow_boundary(shallow_boundary);
+
+            if !shallow_boundary
+                && !self.skipped_patches.is_empty()
+                && self
+                    .skipped_patches
+                    .contains(self.repo, self.replacements, id)
+            {
+                continue;
             }
-        })
+
+            return Some(item);
+        }
     }
 }
 
 pub struct GitRepositoryItem<'repo> {
     repo: &'repo Repository,
+    diff_cache: Option<&'repo DiffCache>,
     metadata: Metadata,
     commit: GitCommit<'repo>,
+    first_parent: Option<Oid>,
+    shallow_boundary: bool,
 }
 
-impl GitRepositoryItem<'_> {
+impl<'repo> GitRepositoryItem<'repo> {
+    /// Reads the commit, applying the replacements: the content of the
+    /// replaced commit is read from its replacement, while the grafted
+    /// commit gets the grafted parents. The ID is the original one anyway.
+    fn new(
+        repo: &'repo Repository,
+        diff_cache: Option<&'repo DiffCache>,
+        authors: &mut AuthorInterner,
+        replacements: &Replacements,
+        id: Oid,
+    ) -> Self {
+        let commit = git_expect(repo.find_commit(replacements.object_id(id)));
+        let parents = replacements.parents(id, &commit);
+
+        // The cached diffs are the ones of the original commits.
+        let replaced = commit.id() != id || !parents.iter().copied().eq(commit.parent_ids());
+        let diff_cache = diff_cache.filter(|_| !replaced);
+
+        let metadata = {
+            let author = commit.author();
+            let when = author.when();
+
+            Metadata::new(
+                CommitId::Object(id),
+                authors.intern(author.name().unwrap()),
+                parents.len(),
+            )
+            .with_time(AuthorTime::new(when.seconds(), when.offset_minutes()))
+        };
+
+        Self {
+            repo,
+            diff_cache,
+            metadata,
+            commit,
+            first_parent: parents
+                .first()
+                .map(|&parent| replacements.object_id(parent)),
+            shallow_boundary: false,
+        }
+    }
+
+    /// Marks the commit as the one at the boundary of the shallow
+    /// repository, which parents are absent.
+    fn with_shallow_boundary(mut self, shallow_boundary: bool) -> Self {
+        self.shallow_boundary = shallow_boundary;
+        self
+    }
+
+    /// Whether the commit is at the boundary of the shallow repository,
+    /// so its diff is unknown.
+    pub fn is_shallow_boundary(&self) -> bool {
+        self.shallow_boundary
+    }
+
     pub fn metadata(&self) -> &Metadata {
         &self.metadata
     }
 
-    pub fn parse(self) -> Commit {
-        let msg_info = self
-            .commit
-            .message()
-            .map(MessageInfo::new)
-            .unwrap_or_default();
+    /// The author name and email, as recorded in the commit.
+    pub fn identity(&self) -> Identity {
+        let author = self.commit.author();
+
+        Identity {
+            name: String::from_utf8_lossy(author.name_bytes()).into_owned(),
+            email: String::from_utf8_lossy(author.email_bytes()).into_owned(),
+        }
+    }
+
+    /// The raw message of the commit, as stored in the repository.
+    pub fn message(&self) -> &str {
+        self.commit.message().unwrap_or("")
+    }
+
+    pub fn parse(self, options: &ParseOptions) -> Commit {
+        let msg_info = self.parse_message(options);
 
         if self.metadata.parents() >= 2 {
             return Commit::new_from_merge(self.metadata, msg_info);
         }
 
-        let parent = self.commit.parents().next();
+        // The parents of boundary commits are absent, so these would be
+        // either failed to diff or misclassified as the initial commits.
+        if self.shallow_boundary {
+            return Commit::new_from_message(self.metadata, msg_info);
+        }
+
+        let diff_info = timing::measure(Stage::Diffing, || self.diff_info(options));
+
+        timing::measure(Stage::Parsing, || {
+            Commit::new(self.metadata, diff_info, msg_info, options)
+        })
+    }
+
+    /// Obtains the diff statistics from the cache, or calculates them.
+    fn diff_info(&self, options: &ParseOptions) -> DiffInfo {
+        // The cache does not contain the changed paths.
+        let diff_cache = self.diff_cache.filter(|_| !options.collect_paths());
+
+        let id = self.metadata.id();
+        let cached = diff_cache.and_then(|cache| id.with_str(|id| cache.get(id)));
+
+        cached.unwrap_or_else(|| {
+            let diff_info = self.parse_diff(options);
+
+            if let Some(cache) = diff_cache {
+                id.with_str(|id| cache.put(id, &diff_info));
+            }
+
+            diff_info
+        })
+    }
+
+    fn parse_diff(&self, options: &ParseOptions) -> DiffInfo {
+        let parent = self
+            .first_parent
+            .map(|parent| git_expect(self.repo.find_commit(parent)));
 
         let tree = git_expect(self.commit.tree());
         let parent_tree = git_expect(parent.as_ref().map(|p| p.tree()).transpose());
@@ -87,26 +621,257 @@ impl GitRepositoryItem<'_> {
                 .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None),
         );
 
-        let diff_stats = git_expect(diff.stats());
-        let diff_info = parse_diff_stats(&diff_stats);
+        parse_diff(&diff, options)
+    }
+
+    /// Parses the commit without calculating the diff, so the commit
+    /// gets no classes except for the merge one.
+    pub fn parse_without_diff(self, options: &ParseOptions) -> Commit {
+        let msg_info = self.parse_message(options);
 
-        Commit::new(self.metadata, diff_info, msg_info)
+        if self.metadata.parents() >= 2 {
+            return Commit::new_from_merge(self.metadata, msg_info);
+        }
+
+        Commit::new_from_message(self.metadata, msg_info)
+    }
+
+    fn parse_message(&self, options: &ParseOptions) -> MessageInfo {
+        timing::measure(Stage::Parsing, || {
+            self.commit
+                .message()
+                .map(|message| MessageInfo::with_options(message, options))
+                .unwrap_or_default()
+        })
     }
 }
 
 fn git_expect<T>(wrapped: Result<T, Error>) -> T {
     match wrapped {
         Ok(value) => value,
-        Err(err) => {
-            eprintln!("{}: {}", "error".red(), err.message());
-            exit(1);
+        Err(err) => fatal(err.message()),
+    }
+}
+
+/// The patch IDs of the commits (see `patch_id()`), which are compared
+/// lazily, like Git does: the commits are indexed by their changed paths,
+/// which are found without diffing the content, so the full patch IDs are
+/// calculated only for the commits changing the same paths.
+#[derive(Default)]
+pub struct PatchIds {
+    by_paths: HashMap<Oid, Vec<Oid>>,
+    patch_ids: HashMap<Oid, Option<Oid>>,
+}
+
+impl PatchIds {
+    fn is_empty(&self) -> bool {
+        self.by_paths.is_empty()
+    }
+
+    /// Checks whether the commit has the same patch ID as some of the
+    /// indexed ones. Merge commits have no patch ID, so never match.
+    fn contains(&mut self, repo: &Repository, replacements: &Replacements, id: Oid) -> bool {
+        let diff = match commit_diff(repo, replacements, id) {
+            Some(diff) => diff,
+            None => return false,
+        };
+        let candidates = match self.by_paths.get(&paths_id(&diff)) {
+            Some(candidates) => candidates,
+            None => return false,
+        };
+
+        let commit_patch_id = patch_id(&diff);
+        let patch_ids = &mut self.patch_ids;
+        candidates.iter().any(|&candidate| {
+            let candidate_id = patch_ids.entry(candidate).or_insert_with(|| {
+                commit_diff(repo, replacements, candidate).map(|diff| patch_id(&diff))
+            });
+            *candidate_id == Some(commit_patch_id)
+        })
+    }
+}
+
+/// The diff of the commit against its parent with the replacements
+/// applied, unless it is the merge commit.
+fn commit_diff<'repo>(
+    repo: &'repo Repository,
+    replacements: &Replacements,
+    id: Oid,
+) -> Option<Diff<'repo>> {
+    let commit = git_expect(repo.find_commit(replacements.object_id(id)));
+    let parents = replacements.parents(id, &commit);
+    if parents.len() > 1 {
+        return None;
+    }
+
+    let parent_tree = parents.first().map(|&parent| {
+        let parent = git_expect(repo.find_commit(replacements.object_id(parent)));
+        git_expect(parent.tree())
+    });
+    let tree = git_expect(commit.tree());
+
+    Some(git_expect(repo.diff_tree_to_tree(
+        parent_tree.as_ref(),
+        Some(&tree),
+        None,
+    )))
+}
+
+/// Calculates the hash of the paths changed by the diff, which
+/// is the cheap prefilter for the patch IDs of the commits.
+fn paths_id(diff: &Diff<'_>) -> Oid {
+    let mut paths = Vec::new();
+    for delta in diff.deltas() {
+        write_paths(&mut paths, &delta);
+    }
+
+    git_expect(Oid::hash_object(ObjectType::Blob, &paths))
+}
+
+/// Calculates the ID of the commit changes (similar to `git patch-id`):
+/// the hash of the changed paths and lines with the whitespace removed,
+/// so the same changes applied on top of the different history get the
+/// same ID. The binary changes have no lines, so the IDs of the old and
+/// new blobs are hashed instead.
+fn patch_id(diff: &Diff<'_>) -> Oid {
+    let patch = RefCell::new(Vec::new());
+    git_expect(diff.foreach(
+        &mut |delta, _| {
+            write_paths(&mut patch.borrow_mut(), &delta);
+            true
+        },
+        Some(&mut |delta, _| {
+            let mut patch = patch.borrow_mut();
+            for file in &[delta.old_file(), delta.new_file()] {
+                patch.extend_from_slice(file.id().as_bytes());
+            }
+            patch.push(b'\n');
+            true
+        }),
+        None,
+        Some(&mut |_, _, line| {
+            if let origin @ '+' | origin @ '-' = line.origin() {
+                let mut patch = patch.borrow_mut();
+                patch.push(origin as u8);
+                patch.extend(line.content().iter().filter(|b| !b.is_ascii_whitespace()));
+                patch.push(b'\n');
+            }
+            true
+        }),
+    ));
+
+    git_expect(Oid::hash_object(ObjectType::Blob, &patch.into_inner()))
+}
+
+/// Writes the old and the new paths of the changed file.
+fn write_paths(buffer: &mut Vec<u8>, delta: &DiffDelta<'_>) {
+    for file in &[delta.old_file(), delta.new_file()] {
+        if let Some(path) = file.path() {
+            buffer.extend_from_slice(path.to_string_lossy().as_bytes());
         }
+        buffer.push(b'\n');
     }
 }
 
-fn parse_diff_stats(stats: &DiffStats) -> DiffInfo {
-    let insertions = stats.insertions();
-    let deletions = stats.deletions();
+/// Collects the diff statistics (and the changed paths, if required).
+fn parse_diff(diff: &Diff<'_>, options: &ParseOptions) -> DiffInfo {
+    let delta_paths = || {
+        diff.deltas()
+            .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+    };
+
+    // Files in the root have the single component,
+    // so these are counted as the single "directory".
+    let top_dirs: HashSet<_> = delta_paths()
+        .map(|path| {
+            let mut components = path.components();
+            let first = components.next();
+            components.next().and(first)
+        })
+        .collect();
+
+    // The lines and the binary files are counted in the single pass over
+    // the patches: the binary callback is issued for the binary deltas only.
+    // The binary flags of the deltas are not exposed by git2 as of 0.10.
+    let (mut insertions, mut deletions, mut binary_files) = (0, 0, 0);
+    git_expect(diff.foreach(
+        &mut |_, _| true,
+        Some(&mut |_, _| {
+            binary_files += 1;
+            true
+        }),
+        None,
+        Some(&mut |_, _, line| {
+            match line.origin() {
+                '+' => insertions += 1,
+                '-' => deletions += 1,
+                _ => {}
+            }
+            true
+        }),
+    ));
+
+    let mut diff_info = DiffInfo::new(insertions, deletions)
+        .with_top_dirs(top_dirs.len())
+        .with_files(diff.deltas().len(), binary_files);
+
+    if options.collect_paths() {
+        let paths = delta_paths()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+
+        diff_info = diff_info.with_paths(paths);
+    }
+
+    diff_info
+}
+
+/// Opens the repository containing the location. Like git itself, the
+/// current directory (`.`) is opened according to the `GIT_DIR` and
+/// `GIT_WORK_TREE` environment variables, if these are set, so both the
+/// scored repository and its configuration are found the same way.
+pub fn discover_repository(location: &Path) -> Result<Repository, Error> {
+    let from_env = location == Path::new(".");
+    let git_dir = env::var_os("GIT_DIR").filter(|_| from_env);
+
+    let repo = match git_dir {
+        Some(git_dir) => Repository::open(git_dir)?,
+        None => Repository::discover(location)?,
+    };
+
+    // libgit2 does not honor GIT_WORK_TREE by itself.
+    if let Some(workdir) = env::var_os("GIT_WORK_TREE").filter(|_| from_env) {
+        repo.set_workdir(Path::new(&workdir), false)?;
+    }
+
+    Ok(repo)
+}
+
+/// Finds the directory shared by all the worktrees of the repository,
+/// which contains the objects and the rest of the common data. For the
+/// linked worktrees, it differs from the worktree-specific git directory.
+fn read_common_dir(repo: &Repository) -> PathBuf {
+    let git_dir = repo.path();
+
+    if !repo.is_worktree() {
+        return git_dir.to_path_buf();
+    }
+
+    match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(common_dir) => {
+            let common_dir = git_dir.join(common_dir.trim());
+            fs::canonicalize(&common_dir).unwrap_or(common_dir)
+        }
+        Err(_) => git_dir.to_path_buf(),
+    }
+}
 
-    DiffInfo::new(insertions, deletions)
+/// Reads the IDs of commits at the boundary of the shallow repository.
+/// The list is empty for complete repositories.
+fn read_shallow_boundary(common_dir: &Path) -> HashSet<Oid> {
+    fs::read_to_string(common_dir.join("shallow"))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| Oid::from_str(line.trim()).ok())
+        .collect()
 }
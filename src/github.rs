@@ -0,0 +1,195 @@
+use crate::{
+    commit::DiffInfo,
+    http::{self, Auth},
+    json::quote,
+    scoring::{Grade, Score, ScoredCommit},
+    summary::Summary,
+};
+
+/// The API of GitHub, which is used unless `GITHUB_API_URL` is set
+/// (by GitHub Enterprise Server).
+pub const API_URL: &str = "https://api.github.com";
+
+/// The name of the check run shown in the "Checks" tab.
+const CHECK_NAME: &str = "commrate";
+
+/// The commits graded below are annotated.
+const ANNOTATED_BELOW: Grade = Grade::C;
+
+/// GitHub accepts at most 50 annotations per request.
+const MAX_ANNOTATIONS: usize = 50;
+
+/// The path of the annotation of the commit changing no files:
+/// GitHub requires the path, though the commits are not the files.
+const FALLBACK_PATH: &str = ".github";
+
+/// The GitHub check run reporting the scored commits: the summary of the
+/// run along with the annotation per low-graded commit. The check run is
+/// completed at once, failed if the run is failed by the conditions.
+pub struct CheckRun {
+    /// The commit the check run belongs to, i.e. the head of the pull
+    /// request.
+    head_sha: String,
+
+    /// The JSON objects of the annotations.
+    annotations: Vec<String>,
+    omitted: usize,
+}
+
+impl CheckRun {
+    pub fn new(head_sha: &str) -> Self {
+        Self {
+            head_sha: head_sha.to_string(),
+            annotations: Vec::new(),
+            omitted: 0,
+        }
+    }
+
+    /// Annotates the commit, if it is graded below C.
+    pub fn add(&mut self, scored: &ScoredCommit) {
+        let (score, grade) = match scored.score() {
+            Score::Scored { score, grade } if grade < ANNOTATED_BELOW => (score, grade),
+            _ => return,
+        };
+
+        if self.annotations.len() == MAX_ANNOTATIONS {
+            self.omitted += 1;
+            return;
+        }
+
+        let commit = scored.commit();
+        let path = commit
+            .diff_info()
+            .as_ref()
+            .map(DiffInfo::paths)
+            .and_then(|paths| paths.first())
+            .map_or(FALLBACK_PATH, String::as_str);
+        let level = if grade == Grade::F {
+            "failure"
+        } else {
+            "warning"
+        };
+
+        self.annotations.push(format!(
+            "{{\"path\": {}, \"start_line\": 1, \"end_line\": 1, \"annotation_level\": \"{}\", \"title\": {}, \"message\": {}}}",
+            quote(path),
+            level,
+            quote(&format!(
+                "{:.12} graded {:?} ({})",
+                commit.metadata().id(),
+                grade,
+                score
+            )),
+            quote(&format!(
+                "{}\n\n{}",
+                commit.msg_info().subject().unwrap_or(""),
+                scored.problems().join(", ")
+            ))
+        ));
+    }
+
+    /// Creates the completed check run in the `OWNER/REPO` repository.
+    /// The failure is the reason the run is failed, if it is.
+    pub fn publish(
+        &self,
+        api_url: &str,
+        repository: &str,
+        token: &str,
+        summary: &Summary,
+        failure: Option<&str>,
+    ) -> Result<(), String> {
+        let url = format!(
+            "{}/repos/{}/check-runs",
+            api_url.trim_end_matches('/'),
+            repository
+        );
+
+        http::send_json(
+            "POST",
+            &url,
+            Auth::Bearer(token),
+            &self.payload(summary, failure),
+        )
+        .map(|_| ())
+    }
+
+    fn payload(&self, summary: &Summary, failure: Option<&str>) -> String {
+        let mut title = format!("{} commits scored", summary.scored());
+        if let Some(average) = summary.average() {
+            title.push_str(&format!(", average score {:.1}", average));
+        }
+
+        let grades: Vec<_> = Grade::ALL
+            .iter()
+            .map(|&grade| format!("{:?}: {}", grade, summary.grade_count(grade)))
+            .collect();
+        let mut text = format!("Grades: {}.", grades.join(", "));
+        if let Some(failure) = failure {
+            text.push_str(&format!("\n\nThe run failed: {}.", failure));
+        }
+        if self.omitted > 0 {
+            text.push_str(&format!(
+                "\n\n{} more commits graded below {:?} are not annotated.",
+                self.omitted, ANNOTATED_BELOW
+            ));
+        }
+
+        format!(
+            "{{\"name\": \"{}\", \"head_sha\": {}, \"status\": \"completed\", \"conclusion\": \"{}\", \"output\": {{\"title\": {}, \"summary\": {}, \"annotations\": [{}]}}}}",
+            CHECK_NAME,
+            quote(&self.head_sha),
+            if failure.is_some() { "failure" } else { "success" },
+            quote(&title),
+            quote(&text),
+            self.annotations.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        commit::{Commit, CommitId, MessageInfo, Metadata},
+        json::Json,
+        scoring::{ScorerBuilder, SubjectRule},
+    };
+
+    #[test]
+    fn payload_is_valid_json() {
+        let scorer = ScorerBuilder::new()
+            .with_rule(SubjectRule::default(), 1.0)
+            .build();
+
+        let mut check_run = CheckRun::new("abc");
+        let mut summary = Summary::new();
+        for message in &["fix", "Fix the parser on the empty input"] {
+            let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+            let scored = scorer.score(Commit::new_from_message(
+                metadata,
+                MessageInfo::new(message),
+            ));
+
+            check_run.add(&scored);
+            summary.add(scored.score());
+        }
+
+        let payload = Json::parse(&check_run.payload(&summary, Some("too many F"))).unwrap();
+        assert_eq!(payload.get("head_sha").and_then(Json::as_str), Some("abc"));
+        assert_eq!(
+            payload.get("conclusion").and_then(Json::as_str),
+            Some("failure")
+        );
+
+        let annotations = payload
+            .get("output")
+            .and_then(|output| output.get("annotations"))
+            .and_then(Json::as_array)
+            .unwrap();
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(
+            annotations[0].get("path").and_then(Json::as_str),
+            Some(FALLBACK_PATH)
+        );
+    }
+}
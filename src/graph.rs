@@ -0,0 +1,467 @@
+use git2::Oid;
+
+use std::{
+    collections::{BinaryHeap, HashMap},
+    fs,
+    path::Path,
+};
+
+/// Size of SHA-1 object IDs, the only ones supported for now.
+const OID_LEN: usize = 20;
+
+const HEADER_LEN: usize = 8;
+const CHUNK_ENTRY_LEN: usize = 12;
+const FANOUT_LEN: usize = 256 * 4;
+const COMMIT_DATA_LEN: usize = OID_LEN + 16;
+
+const CHUNK_OID_FANOUT: &[u8] = b"OIDF";
+const CHUNK_OID_LOOKUP: &[u8] = b"OIDL";
+const CHUNK_COMMIT_DATA: &[u8] = b"CDAT";
+const CHUNK_EXTRA_EDGES: &[u8] = b"EDGE";
+
+/// The parent position value of the commit without such parent.
+const PARENT_NONE: u32 = 0x7000_0000;
+
+/// The flag of the second parent position, which makes it refer to
+/// the list of parents in the extra edges chunk (for octopus merges),
+/// and of the last item of that list.
+const PARENT_EXTRA: u32 = 0x8000_0000;
+
+/// The commit-graph file, which Git writes (e.g. on `git gc` or via
+/// `git commit-graph write`) to speed up the history traversals. It
+/// contains the parents and the generation numbers of the commits,
+/// so these are obtained without inflating the commit objects.
+///
+/// Only the single-file graph (`objects/info/commit-graph`) of the
+/// repositories with SHA-1 object IDs is supported; the split graph
+/// chains are ignored, as well as the graphs written without the
+/// generation numbers (by the ancient versions of Git).
+///
+/// Reference: Documentation/technical/commit-graph-format.txt in Git.
+pub struct CommitGraph {
+    data: Vec<u8>,
+    commits: usize,
+    fanout: usize,
+    oid_lookup: usize,
+    commit_data: usize,
+    extra_edges: Option<usize>,
+}
+
+impl CommitGraph {
+    /// Reads the commit-graph of the repository, `None` if there
+    /// is no graph or it cannot be used for some reason.
+    pub fn open(git_dir: &Path) -> Option<Self> {
+        let data = fs::read(git_dir.join("objects/info/commit-graph")).ok()?;
+        let graph = Self::parse(data)?;
+
+        // The generation number is zero for the commits written
+        // by the versions of Git, which do not calculate it.
+        if graph.commits == 0 || graph.generation(0) == 0 {
+            return None;
+        }
+
+        Some(graph)
+    }
+
+    fn parse(data: Vec<u8>) -> Option<Self> {
+        // Signature, version 1, SHA-1, no base graphs.
+        if data.len() < HEADER_LEN || &data[0..4] != b"CGPH" || data[4] != 1 || data[5] != 1 {
+            return None;
+        }
+
+        if data[7] != 0 {
+            return None;
+        }
+
+        let chunk_count = data[6] as usize;
+        let table_end = HEADER_LEN + (chunk_count + 1) * CHUNK_ENTRY_LEN;
+        if data.len() < table_end {
+            return None;
+        }
+
+        let mut chunks = HashMap::new();
+        for index in 0..chunk_count {
+            let entry = HEADER_LEN + index * CHUNK_ENTRY_LEN;
+            let offset = read_u64(&data, entry + 4)? as usize;
+            chunks.insert(&data[entry..entry + 4], offset);
+        }
+
+        let fanout = *chunks.get(CHUNK_OID_FANOUT)?;
+        let oid_lookup = *chunks.get(CHUNK_OID_LOOKUP)?;
+        let commit_data = *chunks.get(CHUNK_COMMIT_DATA)?;
+        let extra_edges = chunks.get(CHUNK_EXTRA_EDGES).copied();
+
+        // The offsets and the counts come from the file, which may be
+        // corrupt, so the chunk ends are computed without overflowing.
+        let chunk_fits = |offset: usize, count: usize, len: usize| {
+            let end = count
+                .checked_mul(len)
+                .and_then(|size| offset.checked_add(size));
+            matches!(end, Some(end) if end <= data.len())
+        };
+
+        if !chunk_fits(fanout, 1, FANOUT_LEN) {
+            return None;
+        }
+
+        let commits = read_u32(&data, fanout + FANOUT_LEN - 4)? as usize;
+        if !chunk_fits(oid_lookup, commits, OID_LEN)
+            || !chunk_fits(commit_data, commits, COMMIT_DATA_LEN)
+            || matches!(extra_edges, Some(edges) if edges > data.len())
+        {
+            return None;
+        }
+
+        Some(Self {
+            data,
+            commits,
+            fanout,
+            oid_lookup,
+            commit_data,
+            extra_edges,
+        })
+    }
+
+    /// The position of the commit in the graph, `None` if the commit
+    /// is not in the graph (e.g. it is created after the graph is written).
+    fn position(&self, id: Oid) -> Option<u32> {
+        let bytes = id.as_bytes();
+        let first = bytes[0] as usize;
+
+        let start = if first == 0 {
+            0
+        } else {
+            read_u32(&self.data, self.fanout + (first - 1) * 4)? as usize
+        };
+        let end = read_u32(&self.data, self.fanout + first * 4)? as usize;
+
+        let (mut low, mut high) = (start, end.min(self.commits));
+        while low < high {
+            let middle = (low + high) / 2;
+            let offset = self.oid_lookup + middle * OID_LEN;
+
+            match self.data[offset..offset + OID_LEN].cmp(bytes) {
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+                std::cmp::Ordering::Equal => return Some(middle as u32),
+            }
+        }
+
+        None
+    }
+
+    fn oid(&self, position: u32) -> Oid {
+        let offset = self.oid_lookup + position as usize * OID_LEN;
+
+        Oid::from_bytes(&self.data[offset..offset + OID_LEN]).unwrap()
+    }
+
+    fn generation(&self, position: u32) -> u32 {
+        let offset = self.commit_data + position as usize * COMMIT_DATA_LEN + OID_LEN + 8;
+
+        // The lowest 2 bits are the highest ones of the commit time.
+        read_u32(&self.data, offset).unwrap_or(0) >> 2
+    }
+
+    /// The positions of the commit parents, `None` if the graph
+    /// is malformed (and thus should not be trusted at all).
+    fn parents(&self, position: u32) -> Option<Vec<u32>> {
+        let offset = self.commit_data + position as usize * COMMIT_DATA_LEN + OID_LEN;
+        let first = read_u32(&self.data, offset)?;
+        let second = read_u32(&self.data, offset + 4)?;
+
+        let mut parents = Vec::new();
+        if first != PARENT_NONE {
+            parents.push(first);
+        }
+
+        if second & PARENT_EXTRA == 0 {
+            if second != PARENT_NONE {
+                parents.push(second);
+            }
+        } else {
+            let edges = self.extra_edges?;
+            let mut index = (second & !PARENT_EXTRA) as usize;
+
+            loop {
+                let edge = read_u32(&self.data, edges.checked_add(index * 4)?)?;
+                parents.push(edge & !PARENT_EXTRA);
+
+                if edge & PARENT_EXTRA != 0 {
+                    break;
+                }
+
+                index += 1;
+            }
+        }
+
+        if parents
+            .iter()
+            .any(|&parent| parent as usize >= self.commits)
+        {
+            return None;
+        }
+
+        Some(parents)
+    }
+
+    /// Finds the maximal common ancestors of the commits (i.e. the ones,
+    /// which are not reachable from the others), `None` if some of the
+    /// commits is absent from the graph.
+    ///
+    /// The commits reachable from both commits are exactly the ones
+    /// reachable from these ancestors, so hiding them instead of the
+    /// excluded commit gives the same range, but lets the traversal
+    /// stop right at the boundary. Processing the commits in the order
+    /// of decreasing generation numbers makes the result exact: all the
+    /// descendants of the commit are processed before it.
+    pub fn merge_bases(&self, first: Oid, second: Oid) -> Option<Vec<Oid>> {
+        const FROM_FIRST: u8 = 1;
+        const FROM_SECOND: u8 = 2;
+        const STALE: u8 = 4;
+
+        let mut flags: HashMap<u32, u8> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+
+        // The number of the queue entries of each commit and the number
+        // of the entries, which are not stale, so the queue need not be
+        // scanned to know whether the walk may find more ancestors.
+        let mut queued: HashMap<u32, usize> = HashMap::new();
+        let mut unstale = 0;
+
+        for &(id, flag) in &[(first, FROM_FIRST), (second, FROM_SECOND)] {
+            let position = self.position(id)?;
+            *flags.entry(position).or_insert(0) |= flag;
+            *queued.entry(position).or_insert(0) += 1;
+            unstale += 1;
+            queue.push((self.generation(position), position));
+        }
+
+        let mut bases = Vec::new();
+
+        // Once all the queued commits are stale, the rest of
+        // the common ancestors are reachable from the found ones.
+        while unstale > 0 {
+            let (_, position) = queue.pop().unwrap();
+            let mut commit_flags = flags[&position];
+            let commit_queued = queued.get_mut(&position).unwrap();
+            *commit_queued -= 1;
+            if commit_flags & STALE == 0 {
+                unstale -= 1;
+            }
+
+            if commit_flags & (FROM_FIRST | FROM_SECOND) == FROM_FIRST | FROM_SECOND
+                && commit_flags & STALE == 0
+            {
+                // The commit may be queued once per child, so
+                // it must be recorded as the stale one too.
+                bases.push(self.oid(position));
+                commit_flags |= STALE;
+                flags.insert(position, commit_flags);
+                unstale -= *commit_queued;
+            }
+
+            for parent in self.parents(position)? {
+                let parent_flags = flags.entry(parent).or_insert(0);
+                if *parent_flags | commit_flags != *parent_flags {
+                    let parent_queued = queued.entry(parent).or_insert(0);
+                    if *parent_flags & STALE == 0 && commit_flags & STALE != 0 {
+                        unstale -= *parent_queued;
+                    }
+
+                    *parent_flags |= commit_flags;
+                    *parent_queued += 1;
+                    if *parent_flags & STALE == 0 {
+                        unstale += 1;
+                    }
+                    queue.push((self.generation(parent), parent));
+                }
+            }
+        }
+
+        Some(bases)
+    }
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+
+    Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    let high = read_u32(data, offset)? as u64;
+    let low = read_u32(data, offset.checked_add(4)?)? as u64;
+
+    Some(high << 32 | low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes the graph of the commits with the specified parents
+    /// (by index). The commit IDs are `00..00XX` with the index as
+    /// the last byte, so these are already sorted.
+    fn write_graph(parents: &[&[u32]]) -> Vec<u8> {
+        let commits = parents.len();
+        let mut generations = vec![0u32; commits];
+        for (index, commit_parents) in parents.iter().enumerate() {
+            generations[index] = 1 + commit_parents
+                .iter()
+                .map(|&parent| generations[parent as usize])
+                .max()
+                .unwrap_or(0);
+        }
+
+        let mut fanout = Vec::new();
+        for _ in 0..256 {
+            fanout.extend_from_slice(&(commits as u32).to_be_bytes());
+        }
+
+        let mut lookup = Vec::new();
+        let mut commit_data = Vec::new();
+        let mut edges: Vec<u32> = Vec::new();
+
+        for (index, commit_parents) in parents.iter().enumerate() {
+            let mut oid = [0u8; OID_LEN];
+            oid[OID_LEN - 1] = index as u8;
+            lookup.extend_from_slice(&oid);
+
+            commit_data.extend_from_slice(&[0u8; OID_LEN]);
+            let first = commit_parents.first().copied().unwrap_or(PARENT_NONE);
+            let second = match commit_parents.len() {
+                0 | 1 => PARENT_NONE,
+                2 => commit_parents[1],
+                _ => {
+                    let start = edges.len() as u32;
+                    edges.extend_from_slice(&commit_parents[1..]);
+                    *edges.last_mut().unwrap() |= PARENT_EXTRA;
+                    start | PARENT_EXTRA
+                }
+            };
+            commit_data.extend_from_slice(&first.to_be_bytes());
+            commit_data.extend_from_slice(&second.to_be_bytes());
+            commit_data.extend_from_slice(&(generations[index] << 2).to_be_bytes());
+            commit_data.extend_from_slice(&0u32.to_be_bytes());
+        }
+
+        let edge_data: Vec<u8> = edges
+            .iter()
+            .flat_map(|edge| edge.to_be_bytes().to_vec())
+            .collect();
+        let chunks: Vec<(&[u8], Vec<u8>)> = vec![
+            (CHUNK_OID_FANOUT, fanout),
+            (CHUNK_OID_LOOKUP, lookup),
+            (CHUNK_COMMIT_DATA, commit_data),
+            (CHUNK_EXTRA_EDGES, edge_data),
+        ];
+
+        let mut data = b"CGPH".to_vec();
+        data.extend_from_slice(&[1, 1, chunks.len() as u8, 0]);
+
+        let mut offset = HEADER_LEN + (chunks.len() + 1) * CHUNK_ENTRY_LEN;
+        for (id, content) in &chunks {
+            data.extend_from_slice(id);
+            data.extend_from_slice(&(offset as u64).to_be_bytes());
+            offset += content.len();
+        }
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&(offset as u64).to_be_bytes());
+
+        for (_, content) in chunks {
+            data.extend_from_slice(&content);
+        }
+
+        data
+    }
+
+    fn oid(index: u8) -> Oid {
+        let mut bytes = [0u8; OID_LEN];
+        bytes[OID_LEN - 1] = index;
+        Oid::from_bytes(&bytes).unwrap()
+    }
+
+    #[test]
+    fn commits_are_looked_up() {
+        let graph = CommitGraph::parse(write_graph(&[&[], &[0], &[1]])).unwrap();
+
+        assert_eq!(graph.position(oid(2)), Some(2));
+        assert_eq!(graph.position(oid(3)), None);
+        assert_eq!(graph.parents(2), Some(vec![1]));
+        assert_eq!(graph.generation(2), 3);
+    }
+
+    #[test]
+    fn merge_bases_are_found() {
+        // 0 - 1 - 2 - 3 - 6 (first)
+        //      \   \     /
+        //       4 - 5 --+--- 7 (second)
+        let graph = CommitGraph::parse(write_graph(&[
+            &[],
+            &[0],
+            &[1],
+            &[2],
+            &[1],
+            &[4, 2],
+            &[3, 5],
+            &[5],
+        ]))
+        .unwrap();
+
+        assert_eq!(graph.merge_bases(oid(6), oid(7)), Some(vec![oid(5)]));
+        assert_eq!(graph.merge_bases(oid(3), oid(7)), Some(vec![oid(2)]));
+        assert_eq!(graph.merge_bases(oid(2), oid(3)), Some(vec![oid(2)]));
+        assert_eq!(graph.merge_bases(oid(6), oid(9)), None);
+    }
+
+    #[test]
+    fn criss_cross_merge_bases_are_found() {
+        //   1 - 3 (first)
+        //  / \ /
+        // 0   X
+        //  \ / \
+        //   2 - 4 (second)
+        let graph = CommitGraph::parse(write_graph(&[&[], &[0], &[0], &[1, 2], &[2, 1]])).unwrap();
+
+        let mut bases = graph.merge_bases(oid(3), oid(4)).unwrap();
+        bases.sort();
+        assert_eq!(bases, vec![oid(1), oid(2)]);
+    }
+
+    #[test]
+    fn octopus_parents_are_read() {
+        let graph = CommitGraph::parse(write_graph(&[&[], &[], &[], &[0, 1, 2]])).unwrap();
+
+        assert_eq!(graph.parents(3), Some(vec![0, 1, 2]));
+        assert_eq!(graph.merge_bases(oid(3), oid(1)), Some(vec![oid(1)]));
+    }
+
+    #[test]
+    fn malformed_graphs_are_rejected() {
+        let mut data = write_graph(&[&[]]);
+        data[4] = 2;
+
+        assert!(CommitGraph::parse(data).is_none());
+        assert!(CommitGraph::parse(b"CGPH".to_vec()).is_none());
+    }
+
+    #[test]
+    fn corrupt_offsets_are_rejected() {
+        // The offset of the commit data chunk (the third entry of the
+        // chunk table) is the largest possible one.
+        let mut data = write_graph(&[&[], &[0]]);
+        let entry = HEADER_LEN + 2 * CHUNK_ENTRY_LEN + 4;
+        data[entry..entry + 8].copy_from_slice(&u64::MAX.to_be_bytes());
+
+        assert!(CommitGraph::parse(data).is_none());
+
+        // The commit count in the fanout table is the largest possible one.
+        let mut data = write_graph(&[&[], &[0]]);
+        let fanout = HEADER_LEN + 5 * CHUNK_ENTRY_LEN;
+        let count = fanout + FANOUT_LEN - 4;
+        data[count..count + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+
+        assert!(CommitGraph::parse(data).is_none());
+    }
+}
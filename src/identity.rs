@@ -0,0 +1,135 @@
+use std::{collections::HashMap, fmt};
+
+/// The author identity exactly as it is recorded in the commits
+/// (the mailmap is not applied).
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Identity {
+    pub name: String,
+    pub email: String,
+}
+
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} <{}>", self.name, self.email)
+    }
+}
+
+/// The identity along with the number of commits authored under it.
+pub type IdentityCount = (Identity, usize);
+
+/// Collects the author identities of commits to find the persons, who
+/// appear under multiple identities. These split the per-author summaries
+/// (e.g. of the release reports), so these should be merged via mailmap
+/// or fixed in the Git configuration of the authors.
+#[derive(Default)]
+pub struct IdentityReport {
+    commits: HashMap<Identity, usize>,
+}
+
+impl IdentityReport {
+    pub fn add(&mut self, identity: Identity) {
+        *self.commits.entry(identity).or_insert(0) += 1;
+    }
+
+    /// Groups the identities belonging to the same person, i.e. sharing
+    /// the name or the email (compared case-insensitively), and returns
+    /// the groups of more than one identity.
+    pub fn ambiguous(&self) -> Vec<Vec<IdentityCount>> {
+        let mut identities: Vec<_> = self
+            .commits
+            .iter()
+            .map(|(identity, &commits)| (identity.clone(), commits))
+            .collect();
+        identities.sort();
+
+        // Union-find over the identity indices, joined by the shared keys.
+        let mut groups: Vec<usize> = (0..identities.len()).collect();
+        let mut owners: HashMap<String, usize> = HashMap::new();
+
+        for (index, (identity, _)) in identities.iter().enumerate() {
+            let keys = [
+                format!("name:{}", normalize(&identity.name)),
+                format!("email:{}", normalize(&identity.email)),
+            ];
+
+            for key in keys.iter() {
+                match owners.get(key) {
+                    Some(&owner) => {
+                        let (root, other) =
+                            (find_root(&mut groups, owner), find_root(&mut groups, index));
+                        groups[other] = root;
+                    }
+                    None => {
+                        owners.insert(key.clone(), index);
+                    }
+                }
+            }
+        }
+
+        let mut by_root = vec![Vec::new(); identities.len()];
+        for (index, identity) in identities.into_iter().enumerate() {
+            let root = find_root(&mut groups, index);
+            by_root[root].push(identity);
+        }
+
+        let mut ambiguous: Vec<_> = by_root
+            .into_iter()
+            .filter(|group| group.len() > 1)
+            .collect();
+
+        ambiguous.sort();
+        ambiguous
+    }
+}
+
+fn find_root(groups: &mut [usize], mut index: usize) -> usize {
+    while groups[index] != index {
+        groups[index] = groups[groups[index]];
+        index = groups[index];
+    }
+
+    index
+}
+
+/// Normalizes the name or the email for comparison: the case and
+/// the extra whitespace are ignored.
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(name: &str, email: &str) -> Identity {
+        Identity {
+            name: name.to_string(),
+            email: email.to_string(),
+        }
+    }
+
+    #[test]
+    fn identities_sharing_name_or_email_are_grouped() {
+        let mut report = IdentityReport::default();
+        report.add(identity("John Doe", "john@example.com"));
+        report.add(identity("John Doe", "john@example.com"));
+        report.add(identity("john", "John@Example.com"));
+        report.add(identity("john", "jd@home.net"));
+        report.add(identity("Jane Roe", "jane@example.com"));
+
+        let ambiguous = report.ambiguous();
+
+        assert_eq!(ambiguous.len(), 1);
+        assert_eq!(
+            ambiguous[0],
+            vec![
+                (identity("John Doe", "john@example.com"), 2),
+                (identity("john", "John@Example.com"), 1),
+                (identity("john", "jd@home.net"), 1),
+            ]
+        );
+    }
+}
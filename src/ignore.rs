@@ -0,0 +1,118 @@
+use crate::error::fatal;
+
+use std::{fs, io::ErrorKind, path::Path};
+
+/// Name of the ignore file looked up in the root of the working tree.
+pub const IGNORE_FILE_NAME: &str = ".commrateignore";
+
+/// Minimum length of the commit ID prefix in the ignore file.
+/// Shorter prefixes are too likely to match unrelated commits.
+const MIN_PREFIX_LEN: usize = 4;
+
+/// A list of commit IDs (or their prefixes) which should be excluded
+/// from scoring.
+///
+/// The ignore file contains one commit ID per line. Anything after the
+/// ID (separated by whitespace) is treated as a comment, as well as lines
+/// starting with '#', so it is possible to explain why a commit is ignored:
+///
+/// ```text
+/// # Imported from the legacy SVN repository.
+/// 9335a4dc0e09 Initial import
+/// ```
+#[derive(Debug, Default)]
+pub struct IgnoreList {
+    prefixes: Vec<String>,
+}
+
+impl IgnoreList {
+    /// Reads the ignore list from the file, terminating the process
+    /// if the file is absent or malformed.
+    pub fn read(path: &Path) -> Self {
+        let content = fs::read_to_string(path).unwrap_or_else(|err| {
+            fatal(format!("cannot read {}: {}", path.display(), err));
+        });
+
+        Self::parse(&content).unwrap_or_else(|err| fatal(format!("{}: {}", path.display(), err)))
+    }
+
+    /// Reads the ignore list from the file if it exists.
+    pub fn read_optional(path: &Path) -> Option<Self> {
+        match fs::metadata(path) {
+            Ok(_) => Some(Self::read(path)),
+            Err(ref err) if err.kind() == ErrorKind::NotFound => None,
+            Err(err) => fatal(format!("cannot read {}: {}", path.display(), err)),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut prefixes = Vec::new();
+
+        for (line_num, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let prefix = line.split_whitespace().next().unwrap();
+
+            if prefix.len() < MIN_PREFIX_LEN || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!(
+                    "line {}: '{}' is not a commit ID or its prefix of {} characters at least",
+                    line_num + 1,
+                    prefix,
+                    MIN_PREFIX_LEN
+                ));
+            }
+
+            prefixes.push(prefix.to_ascii_lowercase());
+        }
+
+        Ok(Self { prefixes })
+    }
+
+    pub fn contains(&self, commit_id: &str) -> bool {
+        self.prefixes
+            .iter()
+            .any(|prefix| commit_id.starts_with(prefix.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMMIT_ID: &str = "9335a4dc0e098830dec14fe3997c6a654695b935";
+
+    #[test]
+    fn full_ids_and_prefixes_are_matched() {
+        let list = IgnoreList::parse("9335a4dc0e098830dec14fe3997c6a654695b935").unwrap();
+        let list2 = IgnoreList::parse("9335a4").unwrap();
+
+        assert!(list.contains(COMMIT_ID));
+        assert!(list2.contains(COMMIT_ID));
+    }
+
+    #[test]
+    fn prefixes_are_case_insensitive() {
+        let list = IgnoreList::parse("9335A4DC").unwrap();
+
+        assert!(list.contains(COMMIT_ID));
+    }
+
+    #[test]
+    fn comments_and_empty_lines_are_skipped() {
+        let content = "# Legacy imports\n\n9335a4dc Initial import\n  \n";
+        let list = IgnoreList::parse(content).unwrap();
+
+        assert!(list.contains(COMMIT_ID));
+        assert!(!list.contains("0000000000000000000000000000000000000000"));
+    }
+
+    #[test]
+    fn malformed_ids_are_rejected() {
+        assert!(IgnoreList::parse("933").is_err());
+        assert!(IgnoreList::parse("HEAD~2").is_err());
+        assert!(IgnoreList::parse("9335a4dc\nnot-an-id").is_err());
+    }
+}
@@ -0,0 +1,302 @@
+use std::{iter::Peekable, str::Chars};
+
+/// A minimal JSON value, which is enough for reading back
+/// the JSON output of commrate itself.
+#[derive(Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = Parser {
+            chars: text.chars().peekable(),
+        };
+
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+
+        match parser.chars.next() {
+            None => Ok(value),
+            Some(chr) => Err(format!("unexpected '{}' after the value", chr)),
+        }
+    }
+
+    /// The value of the object field, `None` for absent
+    /// fields and for values other than objects.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value of the number, if it is a non-negative integer.
+    pub fn as_u32(&self) -> Option<u32> {
+        match self {
+            Json::Number(value)
+                if value.fract() == 0.0 && *value >= 0.0 && *value <= u32::MAX as f64 =>
+            {
+                Some(*value as u32)
+            }
+            _ => None,
+        }
+    }
+
+    /// The value of the number, if it is a non-negative integer,
+    /// which is exactly representable (e.g. the ID).
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Json::Number(value)
+                if value.fract() == 0.0 && *value >= 0.0 && *value < (1u64 << 53) as f64 =>
+            {
+                Some(*value as u64)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// The fields of the object in the order of appearance.
+    pub fn as_object(&self) -> Option<&[(String, Json)]> {
+        match self {
+            Json::Object(fields) => Some(fields),
+            _ => None,
+        }
+    }
+}
+
+/// Quotes the string as JSON string literal.
+pub fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+
+    quoted.push('"');
+    for chr in value.chars() {
+        match chr {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            chr if (chr as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", chr as u32)),
+            chr => quoted.push(chr),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn parse_value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+
+        match self.chars.peek() {
+            Some('{') => self.parse_object(),
+            Some('[') => self.parse_array(),
+            Some('"') => self.parse_string().map(Json::String),
+            Some('t') => self.parse_literal("true", Json::Bool(true)),
+            Some('f') => self.parse_literal("false", Json::Bool(false)),
+            Some('n') => self.parse_literal("null", Json::Null),
+            Some(chr) if *chr == '-' || chr.is_ascii_digit() => self.parse_number(),
+            Some(chr) => Err(format!("unexpected '{}'", chr)),
+            None => Err("unexpected end of input".to_string()),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Json, String> {
+        let mut fields = Vec::new();
+        self.chars.next();
+
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&'}') {
+            self.chars.next();
+            return Ok(Json::Object(fields));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+
+            self.skip_whitespace();
+            self.expect(':')?;
+
+            let value = self.parse_value()?;
+            fields.push((key, value));
+
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(fields)),
+                _ => return Err("expected ',' or '}' in object".to_string()),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Json, String> {
+        let mut items = Vec::new();
+        self.chars.next();
+
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&']') {
+            self.chars.next();
+            return Ok(Json::Array(items));
+        }
+
+        loop {
+            items.push(self.parse_value()?);
+
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(items)),
+                _ => return Err("expected ',' or ']' in array".to_string()),
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+
+        let mut value = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(value),
+                Some('\\') => value.push(self.parse_escape()?),
+                Some(chr) => value.push(chr),
+                None => return Err("unterminated string".to_string()),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<char, String> {
+        let chr = match self.chars.next() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let code: String = (0..4).filter_map(|_| self.chars.next()).collect();
+                let code = u32::from_str_radix(&code, 16)
+                    .map_err(|_| format!("invalid unicode escape '{}'", code))?;
+
+                // Surrogate pairs are not produced by commrate,
+                // and are replaced here for simplicity.
+                std::char::from_u32(code).unwrap_or('\u{fffd}')
+            }
+            _ => return Err("invalid escape sequence".to_string()),
+        };
+
+        Ok(chr)
+    }
+
+    fn parse_number(&mut self) -> Result<Json, String> {
+        let mut number = String::new();
+
+        while let Some(&chr) = self.chars.peek() {
+            if chr.is_ascii_digit() || "+-.eE".contains(chr) {
+                number.push(chr);
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+
+        number
+            .parse()
+            .map(Json::Number)
+            .map_err(|_| format!("invalid number '{}'", number))
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: Json) -> Result<Json, String> {
+        for expected in literal.chars() {
+            if self.chars.next() != Some(expected) {
+                return Err(format!("invalid literal, expected '{}'", literal));
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.chars.next() {
+            Some(chr) if chr == expected => Ok(()),
+            _ => Err(format!("expected '{}'", expected)),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(chr) = self.chars.peek() {
+            if !chr.is_whitespace() {
+                break;
+            }
+
+            self.chars.next();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_parsed() {
+        let json = Json::parse(r#"[{"id": "abc", "score": 87, "grade": null, "ok": true}, []]"#);
+        let expected = Json::Array(vec![
+            Json::Object(vec![
+                ("id".to_string(), Json::String("abc".to_string())),
+                ("score".to_string(), Json::Number(87.0)),
+                ("grade".to_string(), Json::Null),
+                ("ok".to_string(), Json::Bool(true)),
+            ]),
+            Json::Array(Vec::new()),
+        ]);
+
+        assert_eq!(json, Ok(expected));
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        assert!(Json::parse(r#"{"id": }"#).is_err());
+        assert!(Json::parse(r#"["abc""#).is_err());
+        assert!(Json::parse("[] []").is_err());
+    }
+
+    #[test]
+    fn quoted_strings_are_parsed_back() {
+        let value = "Fix \"quotes\"\n\tand \\ slashes \u{1}";
+        let json = Json::parse(&quote(value)).unwrap();
+
+        assert_eq!(json.as_str(), Some(value));
+    }
+}
@@ -3,54 +3,1724 @@
 #[macro_use]
 extern crate lazy_static;
 
+mod azure;
+mod bitbucket;
+mod cache;
+mod changelog;
 mod commit;
+mod condition;
 mod config;
+mod error;
 mod filter;
+mod gerrit;
 mod git;
+mod github;
+mod graph;
+mod http;
+mod identity;
+mod ignore;
+mod json;
+mod monitor;
+mod patterns;
+mod pipeline;
 mod platform;
 mod printer;
+mod progress;
+mod pull_request;
+mod remote;
+mod replace;
+mod rpc;
+mod runs;
 mod scoring;
+mod summary;
+mod template;
+mod theme;
+mod timing;
+mod webhook;
 
-use config::read_config;
-use git::GitRepository;
+use commit::{
+    cleanup_message, suggest_fix, Commit, CommitId, DiffInfo, MessageInfo, Metadata, ParseOptions,
+};
+use config::{
+    read_config, AppConfig, Command, RepositoryLocation, Setting, AZURE_TOKEN_VARIABLES,
+    BITBUCKET_TOKEN_VARIABLE, GITHUB_REPOSITORY_VARIABLE, GITHUB_TOKEN_VARIABLE,
+};
+use error::{fatal, warning};
+use filter::{Filter, FilterChain, IgnoreListPreFilter, MergePreFilter};
+use git::{GitRepository, GitTraversal};
+use github::CheckRun;
+use identity::IdentityReport;
+use ignore::{IgnoreList, IGNORE_FILE_NAME};
+use monitor::History;
+use pipeline::Pipeline;
 use platform::platform_init;
-use printer::Printer;
+use printer::{DigestFormat, OutputFormat, Printer, TemplateSink};
+use progress::Progress;
+use pull_request::{PullRequestApi, PullRequestComment};
+use remote::RemoteClone;
+use rpc::{LineLimits, RpcServer};
 use scoring::{
-    BodyLenRule, BodyPresenceRule, BodyWrappingRule, MetadataLinesRule, Scorer, ScorerBuilder,
-    SubjectBodyBreakRule, SubjectRule,
+    BodyLenRule, BodyPresenceRule, BodyWrappingRule, BranchSubjectRule, BreakingChangeRule,
+    ClassRef, DuplicateMessageRule, Grade, IssueSubjectRule, MergeBodyRule, MergeSubjectRule,
+    MessageHygieneRule, MetadataLinesRule, MultiPurposeRule, ParagraphLenRule, ReferenceRule,
+    RelativeGrading, ScopeRule, Score, ScoredCommit, Scorer, ScorerBuilder, ScoringPolicy,
+    StructuredBodyRule, SubjectBodyBreakRule, SubjectRule, SubjectStyleRule, TrailerFormatRule,
+    VagueSubjectRule, SCORING_VERSION,
+};
+use summary::{PathSummaries, ReviewCoverage, Summary, TimeSummaries, WorstCommits};
+use webhook::WebhookNotification;
+
+use atty::Stream;
+use git2::Oid;
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Read},
+    path::Path,
+    thread,
+    time::{Duration, Instant},
 };
 
 fn main() {
+    let start = Instant::now();
     platform_init();
 
     let config = read_config();
-    let scorer = init_scorer();
+    if config.timing() {
+        timing::enable();
+    }
+
+    let summary = run(&config);
+    timing::report(start.elapsed());
+
+    if let Some(summary) = summary {
+        check_summary(&config, &summary);
+    }
+}
+
+/// Fails the run, if the summary of the scored commits
+/// matches the configured failure conditions.
+fn check_summary(config: &AppConfig, summary: &Summary) {
+    if let Some(failure) = check_failure(config, summary) {
+        fatal(failure);
+    }
+}
+
+/// Checks the summary of the scored commits against the configured
+/// failure conditions, returning the reason of the failure, if any.
+fn check_failure(config: &AppConfig, summary: &Summary) -> Option<String> {
+    if let Some(condition) = config.fail_if() {
+        if condition.evaluate(summary) {
+            return Some(format!("the scored commits match '{}'", condition));
+        }
+    }
+
+    if let Some(limits) = config.max_grade_count() {
+        if let Some((grade, count, limit)) = limits.exceeded(summary) {
+            return Some(format!(
+                "too many commits graded {:?}: {} (at most {} allowed)",
+                grade, count, limit
+            ));
+        }
+    }
+
+    None
+}
+
+/// Runs the requested command. Returns the summary of all the scored
+/// commits, if these are printed (i.e. the run may be checked against
+/// the failure conditions).
+fn run(config: &AppConfig) -> Option<Summary> {
+    // The scorer is a part of the checked configuration.
+    if let Command::ConfigCheck { settings, problems } = config.command() {
+        check_config(settings, problems, config);
+        return None;
+    }
+
+    let scorer = init_scorer(config);
+    let mut printer = Printer::new(config.score_format(), config.format(), scorer.version())
+        .with_theme(config.theme());
+    if config.show_dirs() {
+        printer = printer.with_dirs();
+    }
+    if let Some(template) = config
+        .template()
+        .filter(|_| config.format() == OutputFormat::Template)
+    {
+        printer = printer.with_sink(Box::new(TemplateSink::new(template.clone())));
+    }
+    if let Some(path) = config.output() {
+        let file = File::create(path)
+            .unwrap_or_else(|err| fatal(format!("cannot create {}: {}", path.display(), err)));
+        printer = printer.with_output(Box::new(file));
+    }
+
+    match config.command() {
+        Command::Preview {
+            message,
+            explain: true,
+        } => {
+            explain_preview(message.as_deref(), config, &scorer, &printer);
+            return None;
+        }
+        Command::Preview {
+            message,
+            explain: false,
+        } => {
+            printer.print_header();
+            let summary = score_preview(message.as_deref(), config, &scorer, &printer);
+            printer.finish();
+            return Some(summary);
+        }
+        Command::Release { previous, release } => {
+            let summary = report_release(previous.as_deref(), release, config, &scorer, &printer);
+            return Some(summary);
+        }
+        Command::ChangelogCheck { previous, release } => {
+            check_changelog(previous.as_deref(), release, config, &printer);
+            return None;
+        }
+        Command::Compare { first, second } => {
+            compare_branches(first, second, config, &scorer, &printer);
+            return None;
+        }
+        Command::DiffRuns { old, new } => {
+            let (old_run, new_run) = (runs::read_run(old), runs::read_run(new));
+            if let Some(message) = runs::check_versions(&old_run, &new_run) {
+                warning(message);
+            }
+
+            let changes = runs::diff_runs(&old_run, &new_run);
+            printer.print_grade_changes(&changes);
+            return None;
+        }
+        Command::Show { commit } => {
+            show_commit(commit, config, &scorer, &printer);
+            return None;
+        }
+        Command::Bitbucket {
+            repository,
+            pull_request,
+            post_comment,
+        } => {
+            printer.print_header();
+            let summary = score_bitbucket_pull_request(
+                repository,
+                *pull_request,
+                *post_comment,
+                config,
+                &scorer,
+                &printer,
+            );
+            printer.finish();
+            return Some(summary);
+        }
+        Command::Azure {
+            repository,
+            pull_request,
+            post_comment,
+        } => {
+            printer.print_header();
+            let summary = score_azure_pull_request(
+                repository,
+                *pull_request,
+                *post_comment,
+                config,
+                &scorer,
+                &printer,
+            );
+            printer.finish();
+            return Some(summary);
+        }
+        Command::Gerrit { change, latest } => {
+            printer.print_header();
+            let summary = score_gerrit_change(change, *latest, config, &scorer, &printer);
+            printer.finish();
+            return Some(summary);
+        }
+        Command::Monitor { interval, history } => {
+            monitor_repositories(*interval, history, config, &printer);
+        }
+        // The configuration is checked before building the scorer,
+        // as the latter fails on the problems the check reports.
+        Command::ConfigCheck { .. } => return None,
+        Command::Rules => {
+            printer.print_rules(&scorer.rules());
+            return None;
+        }
+        Command::Log => {}
+    }
+
+    if let Some(threshold) = config.reword_threshold() {
+        suggest_reword(threshold, config, &scorer, &printer);
+        return None;
+    }
+
+    if config.check_identities() {
+        check_identities(config, &printer);
+        return None;
+    }
+
+    if config.json_rpc() {
+        serve_json_rpc(config);
+        return None;
+    }
+
+    if let Some(path) = config.message_file() {
+        if config.fix_suggest() {
+            suggest_message_fix(path, config, &printer);
+            return None;
+        }
+
+        printer.print_header();
+        let summary = score_message(path, config, &scorer, &printer);
+        printer.finish();
+        return Some(summary);
+    }
+
+    let github_configured = config.github_repository().is_some() && config.github_token().is_some();
+    if config.github_check().is_some() && !github_configured {
+        fatal(format!(
+            "--github-check requires {} and {} in the environment",
+            GITHUB_REPOSITORY_VARIABLE, GITHUB_TOKEN_VARIABLE
+        ));
+    }
+
+    let mut notifications = Notifications {
+        webhook: config
+            .notify_webhook()
+            .map(|url| WebhookNotification::new(url, config.notify_below())),
+        check_run: config.github_check().map(CheckRun::new),
+    };
+
+    let summary = score_log(config, &scorer, &printer, &mut notifications);
+    if let Some(webhook) = notifications.webhook {
+        webhook.send(&summary, scorer.version());
+    }
+    if let Some(check_run) = notifications.check_run {
+        let failure = check_failure(config, &summary);
+        let published = check_run.publish(
+            config.github_api_url(),
+            config.github_repository().unwrap_or_default(),
+            config.github_token().unwrap_or_default(),
+            &summary,
+            failure.as_deref(),
+        );
+        if let Err(err) = published {
+            warning(format!("cannot publish the check run: {}", err));
+        }
+    }
+
+    // The commits went to the file, so only their summary is shown.
+    if let Some(path) = config.output() {
+        let terminal = Printer::new(config.score_format(), OutputFormat::Table, scorer.version());
+        let name = path.to_string_lossy();
+        terminal.print_summaries("OUTPUT", &[(&name, summary.clone())], None);
+    }
+
+    Some(summary)
+}
+
+/// Scores the configured ranges of the repositories, reporting
+/// the commits the requested way, and returns their total summary.
+fn score_log(
+    config: &AppConfig,
+    scorer: &Scorer,
+    printer: &Printer,
+    notifications: &mut Notifications,
+) -> Summary {
+    if let Some(format) = config.email_report() {
+        return print_digest(format, config, scorer, printer, notifications);
+    }
+
+    if config.time_stats() {
+        return summarize_times(config, scorer, printer, notifications);
+    }
+
+    if let Some(depth) = config.path_prefix_depth() {
+        return summarize_paths(depth, config, scorer, printer, notifications);
+    }
+
+    if let Some(depth) = config.review_coverage_depth() {
+        return summarize_reviews(depth, config, scorer, printer, notifications);
+    }
+
+    let repositories = config.repositories();
+    if repositories.len() == 1 {
+        printer.print_header();
+        let summary = score_repository(
+            &repositories[0],
+            config,
+            scorer,
+            &mut CommitSink::Printer(printer),
+            notifications,
+        );
+        if config.summary_footer() {
+            printer.print_footer(&summary);
+        }
+        printer.finish();
+        return summary;
+    }
+
+    let mut summaries = Vec::with_capacity(repositories.len());
+    let mut total = Summary::new();
+
+    for location in repositories {
+        printer.print_section(location.name());
+        printer.print_header();
+
+        let summary = score_repository(
+            location,
+            config,
+            scorer,
+            &mut CommitSink::Printer(printer),
+            notifications,
+        );
+        total.merge(&summary);
+        summaries.push((location.name(), summary));
+
+        if printer.format() == OutputFormat::Table {
+            printer.print_separator();
+        }
+    }
+
+    // The machine-readable outputs contain only commits, so the
+    // summaries are easily calculated from them if needed.
+    printer.print_repository_summaries(&summaries, &total);
+
+    printer.finish();
+    total
+}
+
+/// Scans the configured repositories every interval, forever: the summary
+/// of each of them is printed and appended to the history, and posted
+/// to the webhook, if configured.
+fn monitor_repositories(interval: u64, history: &Path, config: &AppConfig, printer: &Printer) -> ! {
+    let history = History::new(history);
+
+    loop {
+        // Some rules remember the commits seen during the run,
+        // so each scan is the separate run.
+        let scorer = init_scorer(config);
+        let mut summaries = Vec::with_capacity(config.repositories().len());
+        let mut total = Summary::new();
+        let mut notifications = Notifications {
+            webhook: config
+                .notify_webhook()
+                .map(|url| WebhookNotification::new(url, config.notify_below())),
+            check_run: None,
+        };
+
+        for location in config.repositories() {
+            // The repository may be unavailable for a while (e.g. the
+            // network is down), so it is scanned again the next time.
+            let scored = try_score_repository(
+                location,
+                config,
+                &scorer,
+                &mut CommitSink::Summary,
+                &mut notifications,
+            );
+            let summary = match scored {
+                Ok(summary) => summary,
+                Err(err) => {
+                    warning(format!("{}: {}", location.name(), err));
+                    continue;
+                }
+            };
+            if let Err(err) = history.append(location.name(), &summary) {
+                warning(err);
+            }
+
+            total.merge(&summary);
+            summaries.push((location.name(), summary));
+        }
+
+        printer.print_summaries("REPOSITORY", &summaries, Some(&total));
+        printer.flush();
+        if let Some(webhook) = notifications.webhook {
+            webhook.send(&total, scorer.version());
+        }
+
+        thread::sleep(Duration::from_secs(interval));
+    }
+}
+
+/// The receivers of all the scored commits, which report the run
+/// when it is over.
+struct Notifications {
+    webhook: Option<WebhookNotification>,
+    check_run: Option<CheckRun>,
+}
+
+impl Notifications {
+    fn add(&mut self, scored: &ScoredCommit) {
+        if let Some(webhook) = &mut self.webhook {
+            webhook.add(scored);
+        }
+        if let Some(check_run) = &mut self.check_run {
+            check_run.add(scored);
+        }
+    }
+}
+
+/// The destination of the scored commits of the repositories.
+enum CommitSink<'a> {
+    /// The commits are printed as soon as they are scored.
+    Printer(&'a Printer),
+
+    /// The commits are summarized by the directories they touch.
+    Paths(&'a mut PathSummaries),
+
+    /// The commits are summarized by the time they were authored at.
+    Times(&'a mut TimeSummaries),
+
+    /// Only the worst commits are kept for the report.
+    Worst(&'a mut WorstCommits),
+
+    /// The commits are summarized by whether they were reviewed.
+    Coverage(&'a mut ReviewCoverage),
+
+    /// Only the summary of the commits is kept.
+    Summary,
+}
+
+impl CommitSink<'_> {
+    fn add(&mut self, scored: ScoredCommit) {
+        match self {
+            CommitSink::Printer(printer) => printer.print_commit(&scored),
+            CommitSink::Paths(path_summaries) => {
+                let paths = scored.commit().diff_info().as_ref().map(DiffInfo::paths);
+                path_summaries.add(paths.unwrap_or_default(), scored.score());
+            }
+            CommitSink::Times(time_summaries) => {
+                if let Some(time) = scored.commit().metadata().time() {
+                    time_summaries.add(time, scored.score());
+                }
+            }
+            CommitSink::Worst(worst) => worst.add(scored),
+            CommitSink::Coverage(coverage) => coverage.add(scored.commit()),
+            CommitSink::Summary => {}
+        }
+    }
+
+    /// Whether nothing is printed until all the commits are scored.
+    fn is_deferred(&self) -> bool {
+        !matches!(self, CommitSink::Printer(_))
+    }
+}
+
+/// Scores the commits of the repository, passing them to the sink,
+/// and returns their summary.
+fn score_repository(
+    location: &RepositoryLocation,
+    config: &AppConfig,
+    scorer: &Scorer,
+    sink: &mut CommitSink<'_>,
+    notifications: &mut Notifications,
+) -> Summary {
+    try_score_repository(location, config, scorer, sink, notifications)
+        .unwrap_or_else(|err| fatal(err))
+}
+
+/// Scores the repository like `score_repository()` does, but returns
+/// the error instead of terminating, if the repository cannot be opened.
+fn try_score_repository(
+    location: &RepositoryLocation,
+    config: &AppConfig,
+    scorer: &Scorer,
+    sink: &mut CommitSink<'_>,
+    notifications: &mut Notifications,
+) -> Result<Summary, String> {
+    // The clone must outlive the repository opened from it.
+    let mut clone = None;
+    let repo = try_open_location(location, config, &mut clone)?;
+
+    let repo_filters = create_repository_filters(&repo);
+    let max_commits = config.max_commits().unwrap_or(usize::MAX);
+    let skip = config.skip();
+
+    // Nothing is printed until the whole range is scored.
+    let deferred_output = config.relative() || sink.is_deferred();
+
+    // Annotating the temporary clone makes no sense.
+    let annotate_notes =
+        config.annotate_notes() && !matches!(location, RepositoryLocation::Remote(_));
+
+    // The messages are not kept by the parsed commits, so these
+    // are read again for the failing ones after the table.
+    let failure_threshold = match sink {
+        CommitSink::Printer(printer) if printer.format() == OutputFormat::Table => {
+            config.show_failures()
+        }
+        _ => None,
+    };
+    let mut failures = Vec::new();
+    let mut omitted_failures = 0;
+    let failure_limit = if config.low_memory() {
+        LOW_MEMORY_FAILURES
+    } else {
+        usize::MAX
+    };
+
+    let mut summary = Summary::new();
+    let mut report = |scored: ScoredCommit| {
+        summary.add(scored.score());
+
+        match (scored.score(), failure_threshold) {
+            (Score::Scored { grade, .. }, Some(threshold)) if grade < threshold => {
+                if failures.len() < failure_limit {
+                    let id = *scored.commit().metadata().id();
+                    failures.push((id, scored.score(), scored.problems().to_vec()));
+                } else {
+                    omitted_failures += 1;
+                }
+            }
+            _ => {}
+        }
+
+        if annotate_notes {
+            let note = grade_note(&scored);
+            repo.set_note(NOTES_REF, scored.commit().metadata().id(), note.as_deref());
+        }
+
+        notifications.add(&scored);
+
+        sink.add(scored);
+    };
+
+    let progress = Progress::new(show_progress(config, deferred_output));
+
+    if config.relative() && config.low_memory() {
+        // The range is scored twice instead of keeping all the commits
+        // in memory: the first pass collects the score distribution only.
+        // The separate scorer is used for it, as some rules remember
+        // the commits they have seen.
+        let counting_scorer = init_scorer(config);
+        let pipeline = create_unfiltered_pipeline(config, &counting_scorer, &repo_filters);
+        let grading = RelativeGrading::new(pipeline.run(traverse(&repo, location, config)).map(
+            |scored| {
+                progress.tick();
+                scored.score()
+            },
+        ));
+        progress.finish();
+
+        create_unfiltered_pipeline(config, scorer, &repo_filters)
+            .run(traverse(&repo, location, config))
+            .map(|scored| scored.regrade(&grading))
+            .filter(|scored| config.post_filters().accept(scored))
+            .skip(skip)
+            .take(max_commits)
+            .for_each(&mut report);
+    } else if config.relative() {
+        // The grades are not known until the whole range is scored,
+        // so the grade filters are applied after the regrading.
+        let pipeline = create_unfiltered_pipeline(config, scorer, &repo_filters);
+        let commits: Vec<_> = pipeline
+            .run(traverse(&repo, location, config))
+            .inspect(|_| progress.tick())
+            .collect();
+        progress.finish();
+
+        let grading = RelativeGrading::new(commits.iter().map(ScoredCommit::score));
+
+        commits
+            .into_iter()
+            .map(|scored| scored.regrade(&grading))
+            .filter(|scored| config.post_filters().accept(scored))
+            .skip(skip)
+            .take(max_commits)
+            .for_each(&mut report);
+    } else {
+        create_pipeline(config, scorer, &repo_filters)
+            .run(traverse(&repo, location, config))
+            .skip(skip)
+            .take(max_commits)
+            .for_each(|scored| {
+                progress.tick();
+                report(scored);
+            });
+    }
+
+    if let CommitSink::Printer(printer) = sink {
+        for (id, score, problems) in failures {
+            let item = repo.find(&id.to_string());
+            printer.print_failure(item.metadata(), item.message(), score, &problems);
+        }
+    }
+
+    if omitted_failures > 0 {
+        warning(format!(
+            "{} more failing commits are not shown in the low-memory mode",
+            omitted_failures
+        ));
+    }
+
+    Ok(summary)
+}
+
+/// The note explaining the grade of the commit, e.g. "commrate: D (38) —
+/// body missing, subject too long". The commits graded not lower than
+/// `NOTES_THRESHOLD` get no note.
+fn grade_note(scored: &ScoredCommit) -> Option<String> {
+    let (score, grade) = match scored.score() {
+        Score::Scored { score, grade } if grade < NOTES_THRESHOLD => (score, grade),
+        _ => return None,
+    };
+
+    let mut note = format!("commrate: {:?} ({})", grade, score);
+    if !scored.problems().is_empty() {
+        note.push_str(" — ");
+        note.push_str(&scored.problems().join(", "));
+    }
+    note.push('\n');
+
+    Some(note)
+}
+
+/// Prints the digest of the worst commits (of all the repositories
+/// together) for mailing.
+fn print_digest(
+    format: DigestFormat,
+    config: &AppConfig,
+    scorer: &Scorer,
+    printer: &Printer,
+    notifications: &mut Notifications,
+) -> Summary {
+    let mut worst = WorstCommits::new(WORST_COMMITS);
+    let mut total = Summary::new();
+
+    for location in config.repositories() {
+        let summary = score_repository(
+            location,
+            config,
+            scorer,
+            &mut CommitSink::Worst(&mut worst),
+            notifications,
+        );
+        total.merge(&summary);
+    }
+
+    let names: Vec<_> = config
+        .repositories()
+        .iter()
+        .map(RepositoryLocation::name)
+        .collect();
+    printer.print_digest(format, &names, &total, &worst.into_sorted());
+
+    total
+}
+
+/// Prints the summaries of the commits (of all the repositories
+/// together) by the directories they touch, from the worst ones.
+fn summarize_paths(
+    depth: usize,
+    config: &AppConfig,
+    scorer: &Scorer,
+    printer: &Printer,
+    notifications: &mut Notifications,
+) -> Summary {
+    let mut path_summaries = PathSummaries::new(depth);
+    let mut total = Summary::new();
+
+    for location in config.repositories() {
+        let summary = score_repository(
+            location,
+            config,
+            scorer,
+            &mut CommitSink::Paths(&mut path_summaries),
+            notifications,
+        );
+        total.merge(&summary);
+    }
+
+    let summaries = path_summaries.into_sorted();
+    let rows: Vec<_> = summaries
+        .iter()
+        .map(|(path, summary)| (path.as_str(), summary.clone()))
+        .collect();
+
+    printer.print_summaries("PATH", &rows, Some(&total));
+
+    total
+}
+
+/// Prints the summaries of the commits (of all the repositories
+/// together) by the local weekday and hour they were authored at.
+fn summarize_times(
+    config: &AppConfig,
+    scorer: &Scorer,
+    printer: &Printer,
+    notifications: &mut Notifications,
+) -> Summary {
+    let mut time_summaries = TimeSummaries::default();
+    let mut total = Summary::new();
+
+    for location in config.repositories() {
+        let summary = score_repository(
+            location,
+            config,
+            scorer,
+            &mut CommitSink::Times(&mut time_summaries),
+            notifications,
+        );
+        total.merge(&summary);
+    }
+
+    let print = |title, summaries: Vec<(String, Summary)>| {
+        let rows: Vec<_> = summaries
+            .iter()
+            .map(|(name, summary)| (name.as_str(), summary.clone()))
+            .collect();
+        printer.print_summaries(title, &rows, Some(&total));
+    };
+
+    print("WEEKDAY", time_summaries.weekdays());
+    printer.print_separator();
+    print("HOUR", time_summaries.hours());
+
+    total
+}
+
+/// Prints the review coverage of the commits (of all the repositories
+/// together) by the authors and by the directories they touch.
+fn summarize_reviews(
+    depth: usize,
+    config: &AppConfig,
+    scorer: &Scorer,
+    printer: &Printer,
+    notifications: &mut Notifications,
+) -> Summary {
+    let mut coverage = ReviewCoverage::new(depth);
+    let mut total = Summary::new();
+
+    for location in config.repositories() {
+        let summary = score_repository(
+            location,
+            config,
+            scorer,
+            &mut CommitSink::Coverage(&mut coverage),
+            notifications,
+        );
+        total.merge(&summary);
+    }
+
+    printer.print_coverage("AUTHOR", &coverage.authors(), coverage.total());
+    printer.print_separator();
+    printer.print_coverage("PATH", &coverage.paths(), coverage.total());
+
+    total
+}
+
+/// Prints the effective settings and fails the run, if the configuration
+/// (including the scorer created from it) has any problems.
+fn check_config(settings: &[Setting], problems: &[String], config: &AppConfig) {
+    let mut problems = problems.to_vec();
+    if let Err(err) = create_scorer_builder(config).and_then(ScorerBuilder::build_checked) {
+        problems.push(err);
+    }
+
+    let printer = Printer::new(config.score_format(), OutputFormat::Table, SCORING_VERSION);
+    printer.print_settings(settings);
+    printer.flush();
+
+    for problem in &problems {
+        warning(problem);
+    }
+
+    if !problems.is_empty() {
+        fatal(format!(
+            "problems found in the configuration: {}",
+            problems.len()
+        ));
+    }
+}
+
+/// Explains the score of the single commit rule by rule.
+fn show_commit(revision: &str, config: &AppConfig, scorer: &Scorer, printer: &Printer) {
+    let repo = open_repository(".", config);
+    let item = repo.find(revision);
+    let message = item.message().to_string();
+
+    if item.is_shallow_boundary() {
+        warning("the commit is at the boundary of the shallow repository, so its diff is unknown");
+    }
+
+    let options = config.parse_options();
+    let (scored, rule_scores) = scorer.score_explained(item.parse(options));
+    let class_names = class_names(scored.commit(), options);
+
+    printer.print_explanation(&scored, &message, &class_names, &rule_scores);
+}
+
+/// The names of the classes of the commit, both built-in and custom ones.
+fn class_names(commit: &Commit, options: &ParseOptions) -> Vec<String> {
+    let classes = commit.classes();
+    let builtin = classes
+        .as_set()
+        .iter()
+        .map(|class| format!("{:?}", class).to_ascii_lowercase());
+    let custom = options
+        .custom_classes()
+        .iter()
+        .enumerate()
+        .filter(|&(index, _)| classes.has_custom(index))
+        .map(|(_, class)| class.name().to_string());
+
+    builtin.chain(custom).collect()
+}
+
+/// Traverses the configured range of the repository. The configured
+/// references may be absent in the submodules, so these are traversed
+/// from the checked out commit (still limited by the filters).
+fn traverse<'repo>(
+    repo: &'repo GitRepository,
+    location: &RepositoryLocation,
+    config: &AppConfig,
+) -> GitTraversal<'repo> {
+    if let RepositoryLocation::Submodule { .. } = location {
+        return traverse_submodule(repo, location, config);
+    }
+
+    if let Some(commits) = config.stdin_commits() {
+        return repo.traverse_list(commits);
+    }
+
+    let traversal = if config.local() {
+        repo.traverse_unpushed()
+    } else {
+        repo.traverse_refs(config.start_commits(), config.excluded_commits())
+    };
+
+    if !config.cherry() {
+        return traversal;
+    }
+
+    let patch_ids = if config.local() {
+        repo.patch_ids(&["@{upstream}".to_string()], &["HEAD".to_string()])
+    } else {
+        repo.patch_ids(config.excluded_commits(), config.start_commits())
+    };
+
+    traversal.without_patches(patch_ids)
+}
+
+/// Traverses the range of the submodule, which is mapped from the one of
+/// its superproject: the submodule commits recorded in the start and the
+/// excluded commits of the superproject are the start and the excluded
+/// commits of the submodule. The commits listed on the standard input
+/// belong to the superproject, so nothing is traversed in its submodules.
+fn traverse_submodule<'repo>(
+    repo: &'repo GitRepository,
+    location: &RepositoryLocation,
+    config: &AppConfig,
+) -> GitTraversal<'repo> {
+    if config.stdin_commits().is_some() {
+        return repo.traverse_list(&[]);
+    }
+
+    // The recorded commits may be not fetched into the submodule yet.
+    let fetched = |ids: Vec<Oid>| -> Vec<Oid> {
+        ids.into_iter()
+            .filter(|&id| {
+                let present = repo.has_commit(id);
+                if !present {
+                    warning(format!(
+                        "{}: the recorded commit {} is not fetched, so it is ignored",
+                        location.name(),
+                        id
+                    ));
+                }
+                present
+            })
+            .collect()
+    };
+    let (starts, excluded) = resolve_range(location, config);
+    let (starts, excluded) = (fetched(starts), fetched(excluded));
+
+    let traversal = repo.traverse_ids(&starts, &excluded);
+    if !config.cherry() {
+        return traversal;
+    }
+
+    traversal.without_patches(repo.patch_ids_between(&excluded, &starts))
+}
+
+/// Resolves the start and the excluded commits of the configured range
+/// in the repository at the location, mapping the superproject range
+/// to the recorded commits for the submodules (recursively).
+fn resolve_range(location: &RepositoryLocation, config: &AppConfig) -> (Vec<Oid>, Vec<Oid>) {
+    let (path, range) = match location {
+        RepositoryLocation::Submodule {
+            superproject,
+            gitlink,
+            ..
+        } => (
+            superproject.name(),
+            Some((resolve_range(superproject, config), gitlink)),
+        ),
+        _ => (location.name(), None),
+    };
+
+    let repo = GitRepository::open(path);
+    let repo = if config.replace_objects() {
+        repo.with_replacements()
+    } else {
+        repo
+    };
+
+    match range {
+        Some(((starts, excluded), gitlink)) => (
+            repo.submodule_commits(&starts, gitlink),
+            repo.submodule_commits(&excluded, gitlink),
+        ),
+        None if config.local() => {
+            repo.resolve_refs(&["HEAD".to_string()], &["@{upstream}".to_string()])
+        }
+        None => repo.resolve_refs(config.start_commits(), config.excluded_commits()),
+    }
+}
+
+/// Traverses the commits of the branch, which are not reachable from the
+/// other one, skipping the cherry-picked ones if configured.
+fn traverse_branch<'repo>(
+    repo: &'repo GitRepository,
+    branch: &str,
+    other: &str,
+    config: &AppConfig,
+) -> GitTraversal<'repo> {
+    let traversal = repo.traverse_range(branch, other);
+
+    if !config.cherry() {
+        return traversal;
+    }
+
+    traversal.without_patches(repo.patch_ids(&[other.to_string()], &[branch.to_string()]))
+}
+
+/// Prints the summaries of the commits unique to each of the branches.
+fn compare_branches(
+    first: &str,
+    second: &str,
+    config: &AppConfig,
+    scorer: &Scorer,
+    printer: &Printer,
+) {
+    let repo = open_repository(".", config);
+    let repo_filters = create_repository_filters(&repo);
+    let pipeline = create_pipeline(config, scorer, &repo_filters);
+
+    let progress = Progress::new(show_progress(config, true));
+
+    let mut first_summary = Summary::new();
+    pipeline
+        .run(traverse_branch(&repo, first, second, config))
+        .for_each(|scored| {
+            progress.tick();
+            first_summary.add(scored.score());
+        });
+
+    let mut second_summary = Summary::new();
+    pipeline
+        .run(traverse_branch(&repo, second, first, config))
+        .for_each(|scored| {
+            progress.tick();
+            second_summary.add(scored.score());
+        });
+
+    progress.finish();
+
+    printer.print_summaries(
+        "BRANCH",
+        &[(first, first_summary), (second, second_summary)],
+        None,
+    );
+}
+
+/// Prints the report on the commits of the release: the summary,
+/// the worst commits and the summaries by authors. If the previous
+/// release is not specified, it is the nearest preceding tag (if there
+/// is no such tag, the release is the first one with the whole history).
+fn report_release(
+    previous: Option<&str>,
+    release: &str,
+    config: &AppConfig,
+    scorer: &Scorer,
+    printer: &Printer,
+) -> Summary {
+    let repo = open_repository(".", config);
+    let (range, commits) = traverse_release(&repo, previous, release);
+
+    let repo_filters = create_repository_filters(&repo);
+    let pipeline = create_pipeline(config, scorer, &repo_filters);
+    let progress = Progress::new(show_progress(config, true));
+
+    let mut summary = Summary::new();
+    let mut authors: HashMap<String, Summary> = HashMap::new();
+    let mut worst = WorstCommits::new(WORST_COMMITS);
+
+    for scored in pipeline.run(commits) {
+        progress.tick();
+        summary.add(scored.score());
+
+        let author = scored.commit().metadata().author();
+        match authors.get_mut(author) {
+            Some(author_summary) => author_summary.add(scored.score()),
+            None => {
+                let mut author_summary = Summary::new();
+                author_summary.add(scored.score());
+                authors.insert(author.to_string(), author_summary);
+            }
+        }
+
+        worst.add(scored);
+    }
+
+    progress.finish();
+    let worst = worst.into_sorted();
+
+    printer.print_section(&range);
+    printer.print_summaries("RELEASE", &[(&range, summary.clone())], None);
+
+    if !worst.is_empty() {
+        printer.print_separator();
+        printer.print_header();
+        for scored in &worst {
+            printer.print_commit(scored);
+        }
+    }
+
+    let mut authors: Vec<_> = authors.into_iter().collect();
+    summary::sort_worst_first(&mut authors);
+    let authors: Vec<_> = authors
+        .iter()
+        .map(|(author, summary)| (author.as_str(), summary.clone()))
+        .collect();
+
+    printer.print_separator();
+    printer.print_summaries("AUTHOR", &authors, None);
+
+    summary
+}
+
+/// Traverses the commits of the release, returning the range title too.
+/// If the previous release is not specified, it is the nearest preceding
+/// tag (if there is no such tag, the release has the whole history).
+fn traverse_release<'repo>(
+    repo: &'repo GitRepository,
+    previous: Option<&str>,
+    release: &str,
+) -> (String, GitTraversal<'repo>) {
+    let previous = previous
+        .map(str::to_string)
+        .or_else(|| repo.previous_tag(release));
+
+    match &previous {
+        Some(previous) => (
+            format!("{}..{}", previous, release),
+            repo.traverse_range(release, previous),
+        ),
+        None => (release.to_string(), repo.traverse(release)),
+    }
+}
+
+/// Prints the commits of the release, which are not usable as
+/// changelog entries, and fails the run, if there are any.
+fn check_changelog(previous: Option<&str>, release: &str, config: &AppConfig, printer: &Printer) {
+    let repo = open_repository(".", config);
+    let (range, commits) = traverse_release(&repo, previous, release);
+    let repo_filters = create_repository_filters(&repo);
+
+    // The subjects are enough for the check, so the
+    // commits are neither scored nor even diffed.
+    let unready: Vec<_> = commits
+        .filter(|item| config.pre_filters().accept(item.metadata()))
+        .filter(|item| repo_filters.accept(item.metadata()))
+        .filter_map(|item| {
+            let commit = item.parse_without_diff(config.parse_options());
+            let problems = changelog::check_subject(commit.msg_info().subject().unwrap_or(""));
+
+            if problems.is_empty() {
+                None
+            } else {
+                Some((commit, problems))
+            }
+        })
+        .collect();
+
+    if unready.is_empty() {
+        return;
+    }
+
+    printer.print_changelog_problems(&unready);
+    printer.flush();
+
+    fatal(format!(
+        "{} commits of {} are not ready for the changelog",
+        unready.len(),
+        range
+    ));
+}
+
+/// Reports the authors of the configured ranges, who appear under
+/// multiple identities, and fails the run if there are any.
+fn check_identities(config: &AppConfig, printer: &Printer) {
+    let mut report = IdentityReport::default();
+
+    for location in config.repositories() {
+        let mut clone = None;
+        let repo = open_location(location, config, &mut clone);
+        let repo_filters = create_repository_filters(&repo);
+
+        // The identities are enough for the check, so the
+        // commits are neither scored nor even parsed.
+        traverse(&repo, location, config)
+            .filter(|item| config.pre_filters().accept(item.metadata()))
+            .filter(|item| repo_filters.accept(item.metadata()))
+            .for_each(|item| report.add(item.identity()));
+    }
+
+    let ambiguous = report.ambiguous();
+    if ambiguous.is_empty() {
+        return;
+    }
 
+    printer.print_identities(&ambiguous);
+    printer.flush();
+
+    fatal(format!(
+        "{} authors appear under multiple identities",
+        ambiguous.len()
+    ));
+}
+
+/// Prints the rebase todo list for rewording the unpushed commits
+/// of the current branch, if some of them are graded below the threshold.
+fn suggest_reword(threshold: Grade, config: &AppConfig, scorer: &Scorer, printer: &Printer) {
+    let repo = GitRepository::open(".");
+
+    // Merges are dropped by the interactive rebase by default,
+    // so these should not appear in the todo list too. The rest
+    // of the filters are not applied, as the commits omitted from
+    // the todo list would be dropped.
+    let merge_filter: Box<dyn Filter<Descriptor = Metadata>> = Box::new(MergePreFilter);
+    let filters = FilterChain::new(vec![merge_filter]);
+    let pipeline = Pipeline::new(config.parse_options(), scorer).with_pre_filters(&filters);
+
+    let mut commits: Vec<_> = pipeline.run(repo.traverse_unpushed()).collect();
+    commits.reverse();
+
+    let needs_reword = commits
+        .iter()
+        .any(|scored| matches!(scored.score(), Score::Scored { grade, .. } if grade < threshold));
+
+    if needs_reword {
+        printer.print_reword_todo(&commits, threshold);
+    }
+}
+
+/// Prints the message from the file with its formatting fixed, so it
+/// may be piped back into the editor.
+fn suggest_message_fix(path: &str, config: &AppConfig, printer: &Printer) {
+    let repo = GitRepository::open(".");
+    let message = cleanup_message(&read_message(path), repo.comment_char());
+
+    printer.print_text(&suggest_fix(&message, config.parse_options().wrap_width()));
+    printer.flush();
+}
+
+/// Scores the message being composed (e.g. in the commit-msg hook),
+/// which is read from the file or from the standard input. The message
+/// is cleaned up like Git does, as it may come straight from the editor.
+fn score_message(path: &str, config: &AppConfig, scorer: &Scorer, printer: &Printer) -> Summary {
     let repo = GitRepository::open(".");
-    let printer = Printer::new(config.show_score());
-
-    printer.print_header();
-
-    let pre_filters = config.pre_filters();
-    let post_filters = config.post_filters();
-    let max_commits = config.max_commits().unwrap_or(std::usize::MAX);
-
-    repo.traverse(config.start_commit())
-        .filter(|item| pre_filters.accept(item.metadata()))
-        .map(|item| item.parse())
-        .map(|info| scorer.score(info))
-        .filter(|scored| post_filters.accept(&scored))
-        .take(max_commits)
-        .for_each(|scored| printer.print_commit(&scored));
-}
-
-fn init_scorer() -> Scorer {
-    ScorerBuilder::new()
-        .with_rule(SubjectRule, 0.3)
-        .with_rule(BodyPresenceRule, 0.1)
-        .with_rule(SubjectBodyBreakRule, 0.1)
-        .with_rule(BodyLenRule, 0.25)
-        .with_rule(BodyWrappingRule, 0.25)
-        .with_rule(MetadataLinesRule, 0.05)
+    let raw_message = read_message(path);
+    let message = cleanup_message(&raw_message, repo.comment_char());
+
+    let msg_info = MessageInfo::with_options(&message, config.parse_options());
+    let metadata = Metadata::new(MESSAGE_ID, repo.user_name().into(), 1);
+    let commit = Commit::new_from_message(metadata, msg_info);
+
+    score_single(commit, scorer, printer)
+}
+
+/// Scores the commit, which would be made with the staged changes
+/// and the specified message (or the one being edited).
+fn score_preview(
+    message: Option<&str>,
+    config: &AppConfig,
+    scorer: &Scorer,
+    printer: &Printer,
+) -> Summary {
+    let (commit, _) = preview_commit(message, config);
+
+    score_single(commit, scorer, printer)
+}
+
+/// Builds the commit, which would be made with the staged changes and
+/// the specified message (or the one being edited), by the current user.
+/// The cleaned up message is returned too.
+fn preview_commit(message: Option<&str>, config: &AppConfig) -> (Commit, String) {
+    let repo = GitRepository::open(".");
+    let raw_message = match message {
+        Some(message) => message.to_string(),
+        None => read_message(&repo.git_dir().join(EDITED_MESSAGE_FILE).to_string_lossy()),
+    };
+    let message = cleanup_message(&raw_message, repo.comment_char());
+
+    let options = config.parse_options();
+    let msg_info = MessageInfo::with_options(&message, options);
+    let (diff_info, parents) = repo.staged_diff(options);
+    let metadata = Metadata::new(PREVIEW_ID, repo.user_name().into(), parents);
+
+    (Commit::new(metadata, diff_info, msg_info, options), message)
+}
+
+/// Scores the messages requested by the editor over JSON-RPC on the
+/// standard input, until it is closed.
+fn serve_json_rpc(config: &AppConfig) {
+    let repo = GitRepository::open(".");
+    let options = config.parse_options();
+    let subject_rule = config
+        .subject_limits()
+        .map_or_else(SubjectRule::default, SubjectRule::new);
+    let limits = LineLimits {
+        subject: subject_rule.max_len(),
+        wrap_width: options.wrap_width(),
+    };
+
+    let stdin = io::stdin();
+    let server = RpcServer::new(repo.comment_char(), limits);
+    let served = server.serve(stdin.lock(), io::stdout(), |message| {
+        // Some rules remember the messages seen before,
+        // while each request is the new version of the same one.
+        init_scorer(config).score_message(message, None, options)
+    });
+
+    if let Err(err) = served {
+        fatal(err);
+    }
+}
+
+/// Explains the score of the message being composed (see `score_preview()`)
+/// by the scores of the individual rules, so the editors may show what
+/// to fix while the message is being written.
+fn explain_preview(message: Option<&str>, config: &AppConfig, scorer: &Scorer, printer: &Printer) {
+    let (commit, message) = preview_commit(message, config);
+    let (scored, rule_scores) = scorer.score_explained(commit);
+    let class_names = class_names(scored.commit(), config.parse_options());
+
+    printer.print_explanation(&scored, &message, &class_names, &rule_scores);
+}
+
+/// Scores the commits of the Bitbucket pull request, from the oldest
+/// one, and posts their summary to it as the comment, if requested.
+fn score_bitbucket_pull_request(
+    repository: &str,
+    id: u32,
+    post_comment: bool,
+    config: &AppConfig,
+    scorer: &Scorer,
+    printer: &Printer,
+) -> Summary {
+    if post_comment && config.bitbucket_token().is_none() {
+        fatal(format!(
+            "--post-comment requires the token in {}",
+            BITBUCKET_TOKEN_VARIABLE
+        ));
+    }
+
+    let pull_request = bitbucket::PullRequest::new(
+        config.bitbucket_url(),
+        repository,
+        id,
+        config.bitbucket_token(),
+    )
+    .unwrap_or_else(|err| fatal(err));
+
+    score_pull_request(&pull_request, post_comment, config, scorer, printer)
+}
+
+/// Scores the commits of the Azure DevOps pull request, from the oldest
+/// one, and posts their summary to it as the comment, if requested.
+fn score_azure_pull_request(
+    repository: &str,
+    id: u32,
+    post_comment: bool,
+    config: &AppConfig,
+    scorer: &Scorer,
+    printer: &Printer,
+) -> Summary {
+    let base_url = config.azure_url().unwrap_or_else(|| {
+        fatal(
+            "the Azure DevOps organization is not configured: use --azure-url or commrate.azureUrl",
+        )
+    });
+    if post_comment && config.azure_token().is_none() {
+        fatal(format!(
+            "--post-comment requires the token in {}",
+            AZURE_TOKEN_VARIABLES.join(" or ")
+        ));
+    }
+
+    let pull_request = azure::PullRequest::new(base_url, repository, id, config.azure_token())
+        .unwrap_or_else(|err| fatal(err));
+
+    score_pull_request(&pull_request, post_comment, config, scorer, printer)
+}
+
+/// Scores the commits of the pull request fetched from the hosting API.
+/// The APIs do not provide the diffs along with the commits, so only the
+/// messages are scored, like the ones of the commits being made.
+///
+/// The comment with the summary and the fix hints replaces the one posted
+/// by the previous run, so the pull request is not flooded on each push.
+fn score_pull_request(
+    pull_request: &dyn PullRequestApi,
+    post_comment: bool,
+    config: &AppConfig,
+    scorer: &Scorer,
+    printer: &Printer,
+) -> Summary {
+    let commits = pull_request
+        .fetch_commits()
+        .unwrap_or_else(|err| fatal(err));
+
+    let options = config.parse_options();
+    let mut summary = Summary::new();
+    let mut comment = PullRequestComment::new(&scorer.rules());
+
+    for pr_commit in commits {
+        let id = Oid::from_str(&pr_commit.id)
+            .map(CommitId::Object)
+            .unwrap_or_else(|_| fatal(format!("invalid commit ID {}", pr_commit.id)));
+        let metadata = Metadata::new(id, pr_commit.author.as_str().into(), pr_commit.parents);
+        let msg_info = MessageInfo::with_options(&pr_commit.message, options);
+        let commit = if pr_commit.parents > 1 {
+            Commit::new_from_merge(metadata, msg_info)
+        } else {
+            Commit::new_from_message(metadata, msg_info)
+        };
+
+        let (scored, rule_scores) = scorer.score_explained(commit);
+        printer.print_commit(&scored);
+        comment.add(&scored, &rule_scores);
+        summary.add(scored.score());
+    }
+
+    if post_comment {
+        if let Err(err) = pull_request.post_comment(&comment.text(&summary)) {
+            warning(format!("cannot post the comment: {}", err));
+        }
+    }
+
+    summary
+}
+
+/// Scores the patch sets of the Gerrit change, from the oldest one,
+/// so the summary (checked by the failure conditions) covers either
+/// all of them or only the latest one.
+fn score_gerrit_change(
+    change: &str,
+    latest: bool,
+    config: &AppConfig,
+    scorer: &Scorer,
+    printer: &Printer,
+) -> Summary {
+    let base_url = config.gerrit_url().unwrap_or_else(|| {
+        fatal("the Gerrit server is not configured: use --gerrit-url or commrate.gerritUrl")
+    });
+
+    let mut patch_sets = gerrit::fetch_change(base_url, change).unwrap_or_else(|err| fatal(err));
+    if latest {
+        patch_sets = patch_sets.pop().into_iter().collect();
+    }
+
+    let options = config.parse_options();
+    let mut summary = Summary::new();
+
+    for patch_set in patch_sets {
+        let id = Oid::from_str(&patch_set.revision)
+            .map(CommitId::Object)
+            .unwrap_or_else(|_| fatal(format!("invalid revision {}", patch_set.revision)));
+        let metadata = Metadata::new(id, patch_set.author.as_str().into(), patch_set.parents);
+        let msg_info = MessageInfo::with_options(&patch_set.message, options);
+        let diff_info =
+            DiffInfo::new(patch_set.insertions, patch_set.deletions).with_paths(patch_set.paths);
+        let commit = Commit::new(metadata, diff_info, msg_info, options);
+
+        let scored = scorer.score(commit);
+        printer.print_commit(&scored);
+        summary.add(scored.score());
+    }
+
+    summary
+}
+
+/// Scores and prints the single commit, returning its summary.
+fn score_single(commit: Commit, scorer: &Scorer, printer: &Printer) -> Summary {
+    let scored = scorer.score(commit);
+    printer.print_commit(&scored);
+
+    let mut summary = Summary::new();
+    summary.add(scored.score());
+
+    summary
+}
+
+fn read_message(path: &str) -> String {
+    let result = if path == "-" {
+        let mut message = String::new();
+        io::stdin().read_to_string(&mut message).map(|_| message)
+    } else {
+        fs::read_to_string(path)
+    };
+
+    result.unwrap_or_else(|err| fatal(format!("cannot read {}: {}", path, err)))
+}
+
+/// The placeholders shown instead of IDs of the commits,
+/// which do not exist yet.
+const MESSAGE_ID: CommitId = CommitId::Placeholder("(message)");
+const PREVIEW_ID: CommitId = CommitId::Placeholder("(staged)");
+
+/// The file in the Git directory with the message being edited.
+const EDITED_MESSAGE_FILE: &str = "COMMIT_EDITMSG";
+
+/// Checks whether the progress of the run should be shown. It is drawn on
+/// the standard error, so it makes sense only if the latter is a terminal,
+/// and the commits are not printed to the same terminal during the run
+/// (i.e. the output is redirected or deferred until the scoring is done).
+fn show_progress(config: &AppConfig, deferred_output: bool) -> bool {
+    !config.quiet() && atty::is(Stream::Stderr) && (deferred_output || !atty::is(Stream::Stdout))
+}
+
+/// Opens the repository at the location. The remote repository is
+/// cloned first, and the clone must outlive the opened repository.
+/// The diff cache is not used for the temporary clones, as it
+/// would never be hit again.
+fn open_location(
+    location: &RepositoryLocation,
+    config: &AppConfig,
+    clone: &mut Option<RemoteClone>,
+) -> GitRepository {
+    try_open_location(location, config, clone).unwrap_or_else(|err| fatal(err))
+}
+
+/// Opens the repository at the location like `open_location()` does,
+/// but returns the error instead of terminating, if the repository
+/// cannot be opened or cloned.
+fn try_open_location(
+    location: &RepositoryLocation,
+    config: &AppConfig,
+    clone: &mut Option<RemoteClone>,
+) -> Result<GitRepository, String> {
+    match location {
+        RepositoryLocation::Local(path) | RepositoryLocation::Submodule { path, .. } => {
+            GitRepository::try_open(path)
+                .map(|repo| configure_repository(repo, config))
+                .map_err(|err| err.message().to_string())
+        }
+        RepositoryLocation::Remote(url) => {
+            if clone.is_none() {
+                *clone = Some(RemoteClone::new(url, config.clone_depth())?);
+            }
+            let path = clone
+                .as_ref()
+                .map(RemoteClone::path)
+                .unwrap_or_else(|| unreachable!());
+            GitRepository::try_open(path).map_err(|err| err.message().to_string())
+        }
+    }
+}
+
+/// Opens the local repository for traversal (see `configure_repository()`).
+fn open_repository(path: &str, config: &AppConfig) -> GitRepository {
+    configure_repository(GitRepository::open(path), config)
+}
+
+/// Prepares the local repository for traversal: warns about the truncated
+/// history, applies the replacements and enables the diff cache, unless
+/// these are disabled.
+fn configure_repository(repo: GitRepository, config: &AppConfig) -> GitRepository {
+    if repo.is_shallow() {
+        warning("the repository is shallow, so the history is truncated and the boundary commits are scored without their diffs (fetch it with --unshallow to score the whole history)");
+    }
+
+    let repo = if config.replace_objects() {
+        repo.with_replacements()
+    } else {
+        repo
+    };
+
+    if config.diff_cache() {
+        repo.with_diff_cache()
+    } else {
+        repo
+    }
+}
+
+/// Creates the pipeline with all the filters, both configured
+/// and the repository-specific ones.
+fn create_pipeline<'a>(
+    config: &'a AppConfig,
+    scorer: &'a Scorer,
+    repo_filters: &'a FilterChain<Metadata>,
+) -> Pipeline<'a> {
+    create_unfiltered_pipeline(config, scorer, repo_filters)
+        .with_post_filters(config.post_filters())
+}
+
+/// Creates the pipeline without the post-filters, i.e. the one
+/// yielding all the scored commits regardless of their grades.
+fn create_unfiltered_pipeline<'a>(
+    config: &'a AppConfig,
+    scorer: &'a Scorer,
+    repo_filters: &'a FilterChain<Metadata>,
+) -> Pipeline<'a> {
+    Pipeline::new(config.parse_options(), scorer)
+        .with_pre_filters(config.pre_filters())
+        .with_pre_filters(repo_filters)
+        .with_mid_filters(config.mid_filters())
+}
+
+/// Creates the pre-filters, which are specific for the repository,
+/// e.g. the filter for commits from the repository's ignore file.
+fn create_repository_filters(repo: &GitRepository) -> FilterChain<Metadata> {
+    let mut filters: Vec<Box<dyn Filter<Descriptor = Metadata>>> = Vec::new();
+
+    let ignore_list = repo
+        .workdir()
+        .and_then(|workdir| IgnoreList::read_optional(&workdir.join(IGNORE_FILE_NAME)));
+
+    if let Some(list) = ignore_list {
+        filters.push(Box::new(IgnoreListPreFilter::new(list)));
+    }
+
+    FilterChain::new(filters)
+}
+
+/// The number of the most recent messages remembered for
+/// detecting the duplicate messages in the low-memory mode.
+const LOW_MEMORY_DUPLICATE_WINDOW: usize = 10_000;
+
+/// The number of the failing commits remembered for showing
+/// them after the table (see `--show-failures`) in the low-memory mode.
+const LOW_MEMORY_FAILURES: usize = 1_000;
+
+/// The notes ref, where the notes explaining the low grades are written.
+const NOTES_REF: &str = "refs/notes/commrate";
+
+/// The grade, below which the commits are annotated with the notes.
+const NOTES_THRESHOLD: Grade = Grade::C;
+
+/// The number of the worst commits shown in the reports.
+const WORST_COMMITS: usize = 10;
+
+fn init_scorer(config: &AppConfig) -> Scorer {
+    create_scorer_builder(config)
+        .unwrap_or_else(|err| fatal(err))
         .build()
 }
+
+/// Creates the builder of the configured scorer, or returns the error
+/// if the configuration refers to the unknown rules.
+fn create_scorer_builder(config: &AppConfig) -> Result<ScorerBuilder, String> {
+    let mut policy = if config.strict() {
+        ScoringPolicy::strict()
+    } else {
+        ScoringPolicy::default()
+    }
+    .with_pull_request_titles(config.pull_request_titles())
+    .with_emoji_mode(config.emoji_mode());
+
+    let duplicate_rule = if config.low_memory() {
+        DuplicateMessageRule::bounded(LOW_MEMORY_DUPLICATE_WINDOW)
+    } else {
+        DuplicateMessageRule::new()
+    };
+
+    let issue_rule = match config.issue_pattern() {
+        Some(pattern) => IssueSubjectRule::new(pattern.clone()),
+        None => IssueSubjectRule::default(),
+    };
+
+    let subject_rule = match config.subject_limits() {
+        Some(limits) => SubjectRule::new(limits),
+        None => SubjectRule::default(),
+    };
+
+    // The core rules keep the proportions they had before the rest were
+    // added, while the rest are weighted lightly, so the grades are mostly
+    // decided by the subject and the body. The weights sum to 1.0.
+    let mut builder = ScorerBuilder::new()
+        .with_rule(subject_rule, 0.22)
+        .with_rule(VagueSubjectRule, 0.02)
+        .with_rule(issue_rule, 0.02)
+        .with_rule(BranchSubjectRule, 0.02)
+        .with_rule(SubjectStyleRule, 0.01)
+        .with_rule(BodyPresenceRule, 0.08)
+        .with_rule(SubjectBodyBreakRule, 0.08)
+        .with_rule(
+            BodyLenRule::default().with_model(config.body_len_model()),
+            0.2,
+        )
+        .with_rule(BodyWrappingRule, 0.2)
+        .with_rule(ParagraphLenRule, 0.01)
+        .with_rule(duplicate_rule, 0.02)
+        .with_rule(MultiPurposeRule, 0.02)
+        .with_rule(MetadataLinesRule, 0.04)
+        .with_rule(ReferenceRule, 0.01)
+        .with_rule(TrailerFormatRule, 0.01)
+        .with_rule(MessageHygieneRule, 0.02)
+        .with_rule(StructuredBodyRule, 0.01)
+        .with_rule(BreakingChangeRule, 0.01);
+
+    // The scopes are checked only for the teams having the conventions.
+    if !config.scopes().is_empty() || config.require_scope() {
+        let scope_rule = ScopeRule::new(config.scopes().to_vec(), config.require_scope());
+        builder = builder.with_rule(scope_rule, 0.05);
+    }
+
+    if config.score_merges() {
+        builder = builder
+            .with_merge_rule(MergeSubjectRule, 0.6)
+            .with_merge_rule(MergeBodyRule, 0.4);
+    }
+
+    for class in config.class_policies() {
+        let rules = class
+            .exempt
+            .iter()
+            .chain(class.weights.iter().map(|(name, _)| name));
+        if let Some(unknown) = rules.into_iter().find(|name| !builder.has_rule(name)) {
+            return Err(format!(
+                "unknown rule '{}' in the configuration of class '{}'",
+                unknown, class.name
+            ));
+        }
+
+        policy = policy.with_exemptions(class.class, &class.exempt);
+        for (rule, weight) in &class.weights {
+            policy = policy.with_weight(class.class, rule, *weight);
+        }
+
+        if let (ClassRef::Builtin(builtin), Some(special)) = (class.class, class.special) {
+            policy = policy.with_special_class(builtin, special);
+        }
+    }
+
+    for rule in config.rule_severities() {
+        if !builder.has_rule(&rule.name) {
+            return Err(format!(
+                "unknown rule '{}' in the rule severities",
+                rule.name
+            ));
+        }
+
+        builder = builder.with_severity(&rule.name, rule.severity);
+    }
+
+    Ok(builder.with_policy(policy))
+}
@@ -0,0 +1,90 @@
+use crate::{json::quote, scoring::Grade, summary::Summary};
+
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The history of the monitored repositories: the JSON Lines file, to which
+/// the summary of each repository is appended on each scan, so the history
+/// survives the restarts and is easily processed by the other tools.
+pub struct History<'a> {
+    path: &'a Path,
+}
+
+impl<'a> History<'a> {
+    pub fn new(path: &'a Path) -> Self {
+        Self { path }
+    }
+
+    /// Appends the summary of the repository scanned just now.
+    pub fn append(&self, repository: &str, summary: &Summary) -> Result<(), String> {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path)
+            .map_err(|err| format!("cannot open {}: {}", self.path.display(), err))?;
+
+        // The line is written at once, so the readers never see its part.
+        file.write_all(history_line(time, repository, summary).as_bytes())
+            .map_err(|err| format!("cannot write {}: {}", self.path.display(), err))
+    }
+}
+
+fn history_line(time: u64, repository: &str, summary: &Summary) -> String {
+    let average = summary
+        .average()
+        .map(|avg| format!("{:.1}", avg))
+        .unwrap_or_else(|| "null".to_string());
+
+    let grades: Vec<_> = Grade::ALL
+        .iter()
+        .map(|&grade| format!("\"{:?}\": {}", grade, summary.grade_count(grade)))
+        .collect();
+
+    format!(
+        "{{\"time\": {}, \"repository\": {}, \"scored\": {}, \"ignored\": {}, \"average\": {}, \"grades\": {{{}}}}}\n",
+        time,
+        quote(repository),
+        summary.scored(),
+        summary.ignored(),
+        average,
+        grades.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{json::Json, scoring::Score};
+
+    #[test]
+    fn history_line_is_valid_json() {
+        let mut summary = Summary::new();
+        summary.add(Score::Scored {
+            score: 80,
+            grade: Grade::B,
+        });
+
+        let line = history_line(1_600_000_000, "/src/app", &summary);
+        assert_eq!(line.matches('\n').count(), 1);
+
+        let json = Json::parse(line.trim_end()).unwrap();
+        assert_eq!(
+            json.get("repository").and_then(Json::as_str),
+            Some("/src/app")
+        );
+        assert_eq!(
+            json.get("grades")
+                .and_then(|grades| grades.get("B"))
+                .and_then(Json::as_u32),
+            Some(1)
+        );
+    }
+}
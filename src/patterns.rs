@@ -0,0 +1,66 @@
+use regex::{Error, Regex};
+use std::{collections::HashMap, sync::Mutex};
+
+lazy_static! {
+    /// The compiled regexes by their patterns. The compiled program
+    /// is shared between the clones of the regex, so the cached
+    /// regexes are cheap to hand out.
+    static ref CACHE: Mutex<HashMap<String, Regex>> = Mutex::new(HashMap::new());
+}
+
+/// Returns the regex for the pattern, which is compiled only on
+/// the first request. All the regexes of the program (the built-in
+/// ones and the configured ones) go through here, so the same pattern
+/// is never compiled twice, e.g. when validating and then parsing
+/// the option value, or when it is shared by multiple classes.
+pub fn compile(pattern: &str) -> Result<Regex, Error> {
+    let mut cache = CACHE.lock().unwrap();
+
+    if let Some(regex) = cache.get(pattern) {
+        return Ok(regex.clone());
+    }
+
+    let regex = Regex::new(pattern)?;
+    cache.insert(pattern.to_string(), regex.clone());
+
+    Ok(regex)
+}
+
+/// Like `compile`, but for the built-in patterns, which must be valid.
+/// These are meant for initializing the `lazy_static` regexes, so the
+/// hot paths (e.g. the classification) do not even lock the cache.
+pub fn builtin(pattern: &'static str) -> Regex {
+    compile(pattern).unwrap_or_else(|err| panic!("invalid built-in regex: {}", err))
+}
+
+/// Validates the pattern of the command line option, caching
+/// the compiled regex for the actual use.
+pub fn validate(pattern: String) -> Result<(), String> {
+    compile(&pattern).map(|_| ()).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_cached(pattern: &str) -> bool {
+        CACHE.lock().unwrap().contains_key(pattern)
+    }
+
+    #[test]
+    fn regexes_are_compiled_once() {
+        let first = compile("^cached (pattern)$").unwrap();
+        assert!(is_cached("^cached (pattern)$"));
+
+        let second = compile("^cached (pattern)$").unwrap();
+        assert_eq!(first.as_str(), second.as_str());
+        assert!(second.is_match("cached pattern"));
+    }
+
+    #[test]
+    fn invalid_patterns_are_not_cached() {
+        assert!(compile("(unclosed").is_err());
+        assert!(validate("(unclosed".to_string()).is_err());
+        assert!(!is_cached("(unclosed"));
+    }
+}
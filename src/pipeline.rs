@@ -0,0 +1,87 @@
+use crate::{
+    commit::{Commit, Metadata, ParseOptions},
+    filter::Filter,
+    git::GitRepositoryItem,
+    scoring::{ScoredCommit, Scorer},
+    timing::{self, Stage},
+};
+
+/// The commit evaluation pipeline.
+///
+/// Commits obtained from the repository traversal go through
+/// the following stages:
+///
+/// 1. pre-filtering by metadata, which is cheap to obtain;
+/// 2. parsing of the message and the diff (the latter is skipped if
+///    neither the mid-filters nor the scoring rules need it);
+/// 3. mid-filtering by the parsed data (message, diff, classes);
+/// 4. scoring;
+/// 5. post-filtering by the score.
+///
+/// Each stage is more expensive than the previous one (the diff
+/// calculation is the most expensive thing here), so filters should
+/// be applied at the earliest stage which provides the data they need.
+pub struct Pipeline<'a> {
+    parse_options: &'a ParseOptions,
+    scorer: &'a Scorer,
+    pre_filters: Vec<&'a dyn Filter<Descriptor = Metadata>>,
+    mid_filters: Vec<&'a dyn Filter<Descriptor = Commit>>,
+    post_filters: Vec<&'a dyn Filter<Descriptor = ScoredCommit>>,
+}
+
+impl<'a> Pipeline<'a> {
+    pub fn new(parse_options: &'a ParseOptions, scorer: &'a Scorer) -> Self {
+        Self {
+            parse_options,
+            scorer,
+            pre_filters: Vec::new(),
+            mid_filters: Vec::new(),
+            post_filters: Vec::new(),
+        }
+    }
+
+    pub fn with_pre_filters(mut self, filters: &'a dyn Filter<Descriptor = Metadata>) -> Self {
+        self.pre_filters.push(filters);
+        self
+    }
+
+    pub fn with_mid_filters(mut self, filters: &'a dyn Filter<Descriptor = Commit>) -> Self {
+        self.mid_filters.push(filters);
+        self
+    }
+
+    pub fn with_post_filters(mut self, filters: &'a dyn Filter<Descriptor = ScoredCommit>) -> Self {
+        self.post_filters.push(filters);
+        self
+    }
+
+    pub fn run<'s, I>(&'s self, items: I) -> impl Iterator<Item = ScoredCommit> + 's
+    where
+        I: Iterator<Item = GitRepositoryItem<'s>> + 's,
+    {
+        // Each run is the separate traversal, so the rules remembering
+        // the commits (like the duplicate messages) start from scratch.
+        self.scorer.reset();
+
+        let needs_diff = self.scorer.needs_diff()
+            || self.parse_options.collect_paths()
+            || self.mid_filters.iter().any(|filter| filter.needs_diff());
+
+        items
+            .filter(move |item| accept_all(&self.pre_filters, item.metadata()))
+            .map(move |item| {
+                if needs_diff {
+                    item.parse(self.parse_options)
+                } else {
+                    item.parse_without_diff(self.parse_options)
+                }
+            })
+            .filter(move |commit| accept_all(&self.mid_filters, commit))
+            .map(move |commit| timing::measure(Stage::Scoring, || self.scorer.score(commit)))
+            .filter(move |scored| accept_all(&self.post_filters, scored))
+    }
+}
+
+fn accept_all<D>(filters: &[&dyn Filter<Descriptor = D>], descriptor: &D) -> bool {
+    filters.iter().all(|filter| filter.accept(descriptor))
+}
@@ -1,4 +1,4 @@
-#[cfg(not(unix))]
+#[cfg(not(any(unix, windows)))]
 pub fn platform_init() {}
 
 #[cfg(unix)]
@@ -23,3 +23,49 @@ fn reset_sigpipe_handler() {
         libc::signal(libc::SIGPIPE, libc::SIG_DFL);
     }
 }
+
+#[cfg(windows)]
+pub fn platform_init() {
+    if !enable_virtual_terminal() {
+        colored::control::set_override(false);
+    }
+}
+
+// The Windows console prints the ANSI escape codes as is, unless the
+// virtual terminal processing is enabled for it (Windows 10 and later).
+// The older consoles do not support it at all, so the colors are just
+// disabled there, instead of printing the garbage.
+//
+// The output, which is not a console (e.g. redirected to a file), is
+// left untouched: the escape codes are printed the same way as on UNIX.
+//
+// Returns false if the console does not support the escape codes.
+#[cfg(windows)]
+fn enable_virtual_terminal() -> bool {
+    use winapi::um::{
+        consoleapi::{GetConsoleMode, SetConsoleMode},
+        handleapi::INVALID_HANDLE_VALUE,
+        processenv::GetStdHandle,
+        winbase::STD_OUTPUT_HANDLE,
+        wincon::ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+    };
+
+    unsafe {
+        let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+        if handle.is_null() || handle == INVALID_HANDLE_VALUE {
+            return true;
+        }
+
+        let mut mode = 0;
+        if GetConsoleMode(handle, &mut mode) == 0 {
+            // Not a console.
+            return true;
+        }
+
+        if mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0 {
+            return true;
+        }
+
+        SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0
+    }
+}
@@ -0,0 +1,901 @@
+use crate::{
+    changelog::Problem,
+    commit::{Commit, Metadata},
+    config::Setting,
+    error::fatal,
+    identity::IdentityCount,
+    runs::{GradeChange, RunGrade},
+    scoring::{Grade, RuleInfo, RuleScore, Score, ScoreFormat, ScoredCommit},
+    summary::{Coverage, Summary},
+    theme::ColorTheme,
+    timing::{self, Stage},
+};
+
+use colored::Colorize;
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    fmt,
+    io::{self, BufWriter, Stdout, Write},
+    path::Path,
+    str::FromStr,
+};
+
+/// The version of the porcelain output format, which is changed only
+/// when the existing records or fields change their meaning (the new
+/// record types and the trailing fields may be added at any time).
+const PORCELAIN_VERSION: u32 = 1;
+
+/// The width, to which the rule descriptions are wrapped.
+const DESCRIPTION_WIDTH: usize = 72;
+
+/// The number of commits, after which the output is flushed, so it
+/// appears progressively (e.g. in the pager) during the long runs.
+const FLUSH_INTERVAL: usize = 256;
+
+lazy_static! {
+    /// The standard output handle, which lives long enough
+    /// for the printer to hold its lock during the whole run.
+    static ref STDOUT: Stdout = io::stdout();
+}
+
+/// Prints the formatted text via the printer's buffered output.
+macro_rules! out {
+    ($printer:expr, $($arg:tt)*) => {
+        $printer.write(format_args!($($arg)*))
+    };
+}
+
+/// Like `out!`, but appends the newline.
+macro_rules! outln {
+    ($printer:expr) => {
+        $printer.write(format_args!("\n"))
+    };
+    ($printer:expr, $($arg:tt)*) => {{
+        $printer.write(format_args!($($arg)*));
+        $printer.write(format_args!("\n"));
+    }};
+}
+
+// The sinks use the macros above, so the module goes after them.
+mod sink;
+use sink::{create_sink, OutputSink};
+pub use sink::{markdown_cell, TemplateSink};
+
+/// The format of the printed commits.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// The human-readable table.
+    Table,
+
+    /// The JSON array of commit objects, which is suitable for
+    /// processing by other tools (and by `commrate diff-runs`).
+    Json,
+
+    /// The tab-separated records, one per line, whose format is stable
+    /// between releases (see `PORCELAIN_VERSION`), for the scripts.
+    Porcelain,
+
+    /// The commits rendered through the user-provided template.
+    Template,
+
+    /// The comma-separated values with the header line, for spreadsheets.
+    Csv,
+
+    /// The Markdown table, e.g. for the pull request comments.
+    Markdown,
+
+    /// The HTML document with the table of commits.
+    Html,
+}
+
+impl FromStr for OutputFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            "porcelain" => Ok(Self::Porcelain),
+            "template" => Ok(Self::Template),
+            "csv" => Ok(Self::Csv),
+            "markdown" => Ok(Self::Markdown),
+            "html" => Ok(Self::Html),
+            _ => {
+                Err("format must be one of: table, json, porcelain, template, csv, markdown, html")
+            }
+        }
+    }
+}
+
+impl OutputFormat {
+    /// The format of the output file with the extension, if it is known.
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+
+        match extension.as_str() {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            "md" | "markdown" => Some(Self::Markdown),
+            "html" | "htm" => Some(Self::Html),
+            _ => None,
+        }
+    }
+}
+
+/// The format of the digest for mailing (see `Printer::print_digest()`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DigestFormat {
+    Text,
+    Html,
+}
+
+impl FromStr for DigestFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "html" => Ok(Self::Html),
+            _ => Err("digest format must be one of: text, html"),
+        }
+    }
+}
+
+/// The printer of all the regular output. The standard output is locked
+/// once and buffered, as printing hundreds of thousands of commits via
+/// `println!()` (locking and flushing the line-buffered output for each
+/// line) is pretty slow. The output is flushed periodically, on finish
+/// and when the printer is dropped.
+pub struct Printer {
+    out: RefCell<BufWriter<Box<dyn Write>>>,
+
+    /// The number of commits printed since the last flush.
+    unflushed: Cell<usize>,
+
+    score_format: ScoreFormat,
+    theme: ColorTheme,
+    format: OutputFormat,
+
+    /// The version of the scoring algorithm, which is
+    /// included into the machine-readable output.
+    scoring_version: u32,
+
+    /// Whether the top-level directories touched by the commits
+    /// are printed (by the table and the JSON output).
+    show_dirs: bool,
+
+    /// The shape of the list of the scored commits.
+    sink: Box<dyn OutputSink>,
+
+    /// Whether the colors are kept in the output. These are decided by
+    /// the terminal, so the output redirected to the file is plain.
+    colors: bool,
+}
+
+impl Printer {
+    /// Creates the printer. The score format applies to the table output,
+    /// while the machine-readable outputs always use the plain grades and
+    /// the scores from 0 to 100.
+    pub fn new(score_format: ScoreFormat, format: OutputFormat, scoring_version: u32) -> Self {
+        Self {
+            out: RefCell::new(BufWriter::new(Box::new(STDOUT.lock()))),
+            unflushed: Cell::new(0),
+            score_format,
+            theme: ColorTheme::default(),
+            format,
+            scoring_version,
+            show_dirs: false,
+            sink: create_sink(format),
+            colors: true,
+        }
+    }
+
+    /// Sets the colors of the grades, e.g. for the light terminal themes.
+    pub fn with_theme(mut self, theme: ColorTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Redirects the output to the file, which gets no colors,
+    /// even if these are shown in the terminal.
+    pub fn with_output(mut self, out: Box<dyn Write>) -> Self {
+        self.out = RefCell::new(BufWriter::new(out));
+        self.colors = false;
+        self
+    }
+
+    /// Makes the top-level directories touched by the commits printed,
+    /// which requires the changed paths to be collected.
+    pub fn with_dirs(mut self) -> Self {
+        self.show_dirs = true;
+        self
+    }
+
+    /// Replaces the sink of the output format, e.g. with the one
+    /// which needs the additional settings.
+    pub fn with_sink(mut self, sink: Box<dyn OutputSink>) -> Self {
+        self.sink = sink;
+        self
+    }
+
+    pub fn format(&self) -> OutputFormat {
+        self.format
+    }
+
+    /// Prints the header of the commit list. For the machine-readable
+    /// formats, the header is printed only once, so the commits of
+    /// multiple repositories go to the single array (or stream).
+    pub fn print_header(&self) {
+        self.sink.header(self);
+    }
+
+    /// Finishes the output, which is required for closing the JSON array
+    /// (and for the porcelain header, if there are no commits at all).
+    pub fn finish(&self) {
+        self.sink.finish(self);
+        self.flush();
+    }
+
+    /// Prints the empty line, e.g. for separating the output sections.
+    pub fn print_separator(&self) {
+        outln!(self);
+    }
+
+    pub fn print_commit(&self, scored_commit: &ScoredCommit) {
+        timing::measure(Stage::Printing, || self.print_commit_untimed(scored_commit));
+    }
+
+    fn print_commit_untimed(&self, scored_commit: &ScoredCommit) {
+        if self.unflushed.get() >= FLUSH_INTERVAL {
+            self.flush();
+        }
+        self.unflushed.set(self.unflushed.get() + 1);
+
+        self.sink.commit(self, scored_commit);
+    }
+
+    /// Prints the commits, whose grades differ between two runs.
+    pub fn print_grade_changes(&self, changes: &[GradeChange]) {
+        outln!(self, "{:12} {:7} {:7} SUBJECT", "COMMIT", "OLD", "NEW");
+
+        for change in changes {
+            outln!(
+                self,
+                "{} {:7} {:7} {}",
+                format!("{:12.12}", change.id).yellow(),
+                run_grade_text(&change.old),
+                run_grade_text(&change.new),
+                change.subject
+            );
+        }
+    }
+
+    /// Prints the identities of the authors, who appear under multiple
+    /// ones, grouped by the person.
+    pub fn print_identities(&self, groups: &[Vec<IdentityCount>]) {
+        outln!(self, "{:60} COMMITS", "IDENTITY");
+
+        for (index, group) in groups.iter().enumerate() {
+            if index > 0 {
+                outln!(self);
+            }
+
+            for (identity, commits) in group {
+                outln!(self, "{:60} {:7}", identity.to_string(), commits);
+            }
+        }
+    }
+
+    /// Prints the commits, which are not usable as changelog
+    /// entries, along with the reasons.
+    pub fn print_changelog_problems(&self, commits: &[(Commit, Vec<Problem>)]) {
+        outln!(self, "{:12} {:30} SUBJECT", "COMMIT", "PROBLEMS");
+
+        for (commit, problems) in commits {
+            let problems: Vec<_> = problems.iter().map(Problem::to_string).collect();
+
+            outln!(
+                self,
+                "{} {:30} {}",
+                format!("{:12.12}", commit.metadata().id()).yellow(),
+                problems.join(", "),
+                commit.msg_info().subject().unwrap_or("")
+            );
+        }
+    }
+
+    /// Prints the explicitly configured settings and their sources.
+    pub fn print_settings(&self, settings: &[Setting]) {
+        if settings.is_empty() {
+            outln!(self, "All the settings have the default values.");
+            return;
+        }
+
+        outln!(self, "{:30} {:30} SOURCE", "KEY", "VALUE");
+        for setting in settings {
+            outln!(
+                self,
+                "{:30} {:30} {}",
+                setting.key,
+                setting.value,
+                setting.source
+            );
+        }
+    }
+
+    /// Prints the rules of the scorer with their weights and descriptions.
+    pub fn print_rules(&self, rules: &[RuleInfo]) {
+        for (index, rule) in rules.iter().enumerate() {
+            if index > 0 {
+                outln!(self);
+            }
+
+            let kind = if rule.merge { ", merges only" } else { "" };
+            let severity = if rule.warning { ", warning only" } else { "" };
+            outln!(
+                self,
+                "{} (weight {:.2}{}{})",
+                rule.name.bold(),
+                rule.weight,
+                kind,
+                severity
+            );
+
+            for line in wrap(rule.description, DESCRIPTION_WIDTH) {
+                outln!(self, "    {}", line);
+            }
+        }
+    }
+
+    /// Prints the full message of the commit, which failed the scoring,
+    /// along with the problems found in it, after the table of commits.
+    pub fn print_failure(
+        &self,
+        metadata: &Metadata,
+        message: &str,
+        score: Score,
+        problems: &[&'static str],
+    ) {
+        outln!(self);
+        outln!(
+            self,
+            "{} {} ({})",
+            "commit".yellow(),
+            metadata.id().to_string().yellow(),
+            self.colorize_score(score, &score.to_string(self.score_format))
+        );
+        // The message being composed has no author yet.
+        if !metadata.author().is_empty() {
+            outln!(self, "Author: {}", metadata.author());
+        }
+        outln!(self);
+
+        self.print_message(message);
+
+        if problems.is_empty() {
+            outln!(self, "Problems: none found by the rules");
+        } else {
+            outln!(self, "Problems: {}", problems.join(", "));
+        }
+    }
+
+    /// Prints the message indented, the way `git show` does.
+    fn print_message(&self, message: &str) {
+        for line in message.trim_end().lines() {
+            if line.is_empty() {
+                outln!(self);
+            } else {
+                outln!(self, "    {}", line);
+            }
+        }
+        outln!(self);
+    }
+
+    /// Prints the detailed explanation of the commit score: the full
+    /// message, the diff statistics, the classes and the score of every
+    /// rule along with the problem it found.
+    pub fn print_explanation(
+        &self,
+        scored_commit: &ScoredCommit,
+        message: &str,
+        classes: &[String],
+        rule_scores: &[RuleScore],
+    ) {
+        let commit = scored_commit.commit();
+        let metadata = commit.metadata();
+
+        outln!(
+            self,
+            "{} {}",
+            "commit".yellow(),
+            metadata.id().to_string().yellow()
+        );
+        // The message being composed has no author yet.
+        if !metadata.author().is_empty() {
+            outln!(self, "Author: {}", metadata.author());
+        }
+        outln!(self);
+
+        self.print_message(message);
+
+        if let Some(diff_info) = commit.diff_info() {
+            let binary = match diff_info.binary_files() {
+                0 => String::new(),
+                count => format!(", {} binary files", count),
+            };
+            outln!(
+                self,
+                "Changes: +{} -{} in {} top-level directories{}",
+                diff_info.insertions(),
+                diff_info.deletions(),
+                diff_info.top_dirs(),
+                binary
+            );
+        }
+
+        let classes = if classes.is_empty() {
+            "none".to_string()
+        } else {
+            classes.join(", ")
+        };
+        outln!(self, "Classes: {}", classes);
+        outln!(self);
+
+        if !rule_scores.is_empty() {
+            outln!(
+                self,
+                "{:24} {:>6} {:>6} {:>6}  PROBLEM",
+                "RULE",
+                "WEIGHT",
+                "SCORE",
+                "POINTS"
+            );
+
+            for rule in rule_scores {
+                let problem = match (rule.exempt, rule.problem) {
+                    (true, _) => "exempt".to_string(),
+                    (false, Some(problem)) if rule.warning => format!("warning: {}", problem),
+                    (false, problem) => problem.unwrap_or("").to_string(),
+                };
+
+                let line = format!(
+                    "{:24} {:>6.2} {:>6.2} {:>6.1}  {}",
+                    rule.name,
+                    rule.weight,
+                    rule.score,
+                    rule.points(),
+                    problem
+                );
+                outln!(self, "{}", line.trim_end());
+            }
+            outln!(self);
+        }
+
+        match scored_commit.score() {
+            Score::Scored { score, grade } => outln!(
+                self,
+                "Score: {} ({})",
+                score,
+                self.colorize_score(
+                    Score::Scored { score, grade },
+                    &Score::Scored { score, grade }.to_string(self.score_format)
+                )
+            ),
+            Score::Ignored(reason) => outln!(self, "Ignored: {}", reason.name()),
+        }
+    }
+
+    /// Prints the digest of the run for mailing (e.g. the weekly report
+    /// sent by cron): the summary of the scored commits of the listed
+    /// repositories and the worst commits along with their problems.
+    /// The digest is not colored regardless of the terminal.
+    pub fn print_digest(
+        &self,
+        format: DigestFormat,
+        repositories: &[&str],
+        summary: &Summary,
+        worst: &[ScoredCommit],
+    ) {
+        let average = summary
+            .average()
+            .map(|avg| format!("{:.1}", avg))
+            .unwrap_or_else(|| "-".to_string());
+        let grades: Vec<_> = Grade::ALL
+            .iter()
+            .map(|&grade| format!("{:?}: {}", grade, summary.grade_count(grade)))
+            .collect();
+
+        match format {
+            DigestFormat::Text => {
+                outln!(self, "Commit quality report");
+                outln!(self, "=====================");
+                outln!(self);
+                outln!(self, "Repositories:   {}", repositories.join(", "));
+                outln!(self, "Scored commits: {}", summary.scored());
+                outln!(self, "Ignored:        {}", summary.ignored());
+                outln!(self, "Average score:  {}", average);
+                outln!(self, "Grades:         {}", grades.join(", "));
+
+                if worst.is_empty() {
+                    return;
+                }
+
+                outln!(self);
+                outln!(self, "Worst commits");
+                outln!(self, "-------------");
+
+                for scored in worst {
+                    let commit = scored.commit();
+                    outln!(self);
+                    outln!(
+                        self,
+                        "{:.12} {} {}",
+                        commit.metadata().id(),
+                        grade_with_score(scored.score()),
+                        commit.msg_info().subject().unwrap_or("")
+                    );
+                    outln!(self, "    by {}", commit.metadata().author());
+
+                    if !scored.problems().is_empty() {
+                        outln!(self, "    {}", scored.problems().join(", "));
+                    }
+                }
+            }
+            DigestFormat::Html => {
+                outln!(self, "<html>");
+                outln!(self, "<body>");
+                outln!(self, "<h1>Commit quality report</h1>");
+                outln!(self, "<table>");
+                outln!(
+                    self,
+                    "<tr><th align=\"left\">Repositories</th><td>{}</td></tr>",
+                    escape_html(&repositories.join(", "))
+                );
+                outln!(
+                    self,
+                    "<tr><th align=\"left\">Scored commits</th><td>{}</td></tr>",
+                    summary.scored()
+                );
+                outln!(
+                    self,
+                    "<tr><th align=\"left\">Ignored</th><td>{}</td></tr>",
+                    summary.ignored()
+                );
+                outln!(
+                    self,
+                    "<tr><th align=\"left\">Average score</th><td>{}</td></tr>",
+                    average
+                );
+                outln!(
+                    self,
+                    "<tr><th align=\"left\">Grades</th><td>{}</td></tr>",
+                    grades.join(", ")
+                );
+                outln!(self, "</table>");
+
+                if !worst.is_empty() {
+                    outln!(self, "<h2>Worst commits</h2>");
+                    outln!(self, "<table>");
+                    outln!(
+                        self,
+                        "<tr><th>Commit</th><th>Score</th><th>Author</th><th>Subject</th><th>Problems</th></tr>"
+                    );
+
+                    for scored in worst {
+                        let commit = scored.commit();
+                        outln!(
+                            self,
+                            "<tr><td><code>{:.12}</code></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                            commit.metadata().id(),
+                            grade_with_score(scored.score()),
+                            escape_html(commit.metadata().author()),
+                            escape_html(commit.msg_info().subject().unwrap_or("")),
+                            escape_html(&scored.problems().join(", "))
+                        );
+                    }
+
+                    outln!(self, "</table>");
+                }
+
+                outln!(self, "</body>");
+                outln!(self, "</html>");
+            }
+        }
+    }
+
+    /// Prints the commits (from the oldest to the newest) as the todo
+    /// list for `git rebase -i`: the commits graded below the threshold
+    /// are marked for rewording, while the rest ones are just picked.
+    pub fn print_reword_todo(&self, commits: &[ScoredCommit], threshold: Grade) {
+        for scored_commit in commits {
+            let reword = match scored_commit.score() {
+                Score::Scored { grade, .. } => grade < threshold,
+                Score::Ignored(_) => false,
+            };
+
+            let action = if reword { "reword" } else { "pick" };
+
+            outln!(
+                self,
+                "{} {:.12} {}",
+                action,
+                scored_commit.commit().metadata().id(),
+                scored_commit.commit().msg_info().subject().unwrap_or("")
+            );
+        }
+    }
+
+    /// Prints the text as is, e.g. the fixed message.
+    pub fn print_text(&self, text: &str) {
+        outln!(self, "{}", text);
+    }
+
+    /// Prints the title of the output section, e.g. when the commits
+    /// of multiple repositories are printed one after another.
+    pub fn print_section(&self, title: &str) {
+        self.sink.section(self, title);
+    }
+
+    /// Prints the totals of the commits after the list of them.
+    pub fn print_footer(&self, summary: &Summary) {
+        self.sink.footer(self, summary);
+    }
+
+    /// Prints the summaries of the repositories and their total
+    /// after the commits of all of them.
+    pub fn print_repository_summaries(&self, summaries: &[(&str, Summary)], total: &Summary) {
+        self.sink.summary(self, summaries, total);
+    }
+
+    /// Prints the table with a line per each of the named summaries,
+    /// followed by the line for the grand total (if any). The title
+    /// is the header of the column with the summary names.
+    pub fn print_summaries(
+        &self,
+        title: &str,
+        summaries: &[(&str, Summary)],
+        total: Option<&Summary>,
+    ) {
+        out!(
+            self,
+            "{:30} {:>7} {:>7} {:>7}",
+            title,
+            "SCORED",
+            "IGNORED",
+            "AVERAGE"
+        );
+        for grade in Grade::ALL.iter() {
+            out!(self, " {:>5}", format!("{:?}", grade));
+        }
+        outln!(self);
+
+        for (name, summary) in summaries {
+            self.print_summary_line(name, summary);
+        }
+
+        if let Some(total) = total {
+            self.print_summary_line("TOTAL", total);
+        }
+    }
+
+    /// Prints the table of the review coverage with a line per each
+    /// of the named coverages, followed by the line for the total.
+    pub fn print_coverage(&self, title: &str, coverages: &[(String, Coverage)], total: Coverage) {
+        outln!(
+            self,
+            "{:30} {:>7} {:>8} {:>8}",
+            title,
+            "COMMITS",
+            "REVIEWED",
+            "COVERAGE"
+        );
+
+        for (name, coverage) in coverages {
+            self.print_coverage_line(name, *coverage);
+        }
+
+        self.print_coverage_line("TOTAL", total);
+    }
+
+    fn print_coverage_line(&self, name: &str, coverage: Coverage) {
+        let percent = coverage
+            .percent()
+            .map(|percent| format!("{:.1}%", percent))
+            .unwrap_or_else(|| "-".to_string());
+
+        outln!(
+            self,
+            "{:30.30} {:>7} {:>8} {:>8}",
+            name,
+            coverage.commits(),
+            coverage.reviewed(),
+            percent
+        );
+    }
+
+    fn print_summary_line(&self, name: &str, summary: &Summary) {
+        let average = summary
+            .average()
+            .map(|avg| format!("{:.1}", avg))
+            .unwrap_or_else(|| "-".to_string());
+
+        out!(
+            self,
+            "{:30.30} {:>7} {:>7} {:>7}",
+            name,
+            summary.scored(),
+            summary.ignored(),
+            average
+        );
+        for &grade in Grade::ALL.iter() {
+            out!(self, " {:>5}", summary.grade_count(grade));
+        }
+        outln!(self);
+    }
+
+    fn colorize_score(&self, score: Score, score_text: &str) -> String {
+        self.theme.color(score).paint(score_text)
+    }
+}
+
+impl Printer {
+    fn write(&self, args: fmt::Arguments<'_>) {
+        // The broken pipe does not get here on UNIX (see platform_init()),
+        // so the error is the real one, e.g. the lack of disk space.
+        let written = if self.colors {
+            self.out.borrow_mut().write_fmt(args)
+        } else {
+            let text = args.to_string();
+            self.out
+                .borrow_mut()
+                .write_all(strip_colors(&text).as_bytes())
+        };
+
+        if let Err(err) = written {
+            fatal(format!("cannot write the output: {}", err));
+        }
+    }
+
+    /// Flushes the output, e.g. before terminating the process.
+    pub fn flush(&self) {
+        self.unflushed.set(0);
+
+        if let Err(err) = timing::measure(Stage::Printing, || self.out.borrow_mut().flush()) {
+            fatal(format!("cannot write the output: {}", err));
+        }
+    }
+}
+
+impl Drop for Printer {
+    fn drop(&mut self) {
+        // The errors cannot be reported properly here,
+        // and everything is already flushed on finish.
+        let _ = self.out.get_mut().flush();
+    }
+}
+
+/// Wraps the text to the width by the words. The words longer
+/// than the width are kept on the separate lines as is.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Removes the color escape sequences (like `\x1b[1;33m`) from the text.
+fn strip_colors(text: &str) -> Cow<'_, str> {
+    if !text.contains('\x1b') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(chr) = chars.next() {
+        if chr == '\x1b' {
+            // The sequence ends with the letter after the parameters.
+            chars.by_ref().find(|chr| chr.is_ascii_alphabetic());
+        } else {
+            plain.push(chr);
+        }
+    }
+
+    Cow::Owned(plain)
+}
+
+/// Formats the score like "D (38)".
+fn grade_with_score(score: Score) -> String {
+    match score {
+        Score::Scored { score, grade } => format!("{:?} ({})", grade, score),
+        Score::Ignored(_) => "-".to_string(),
+    }
+}
+
+/// Escapes the text for the HTML element content or the attribute value.
+fn escape_html(text: &str) -> Cow<'_, str> {
+    const SPECIAL: &[char] = &['&', '<', '>', '"'];
+
+    if !text.contains(SPECIAL) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut escaped = String::with_capacity(text.len() + 16);
+    for chr in text.chars() {
+        match chr {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(chr),
+        }
+    }
+
+    Cow::Owned(escaped)
+}
+
+fn run_grade_text(grade: &RunGrade) -> &str {
+    match grade {
+        RunGrade::Absent => "absent",
+        RunGrade::Ignored => "-",
+        RunGrade::Graded(grade) => grade,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_format_is_inferred_from_extension() {
+        let infer = |path| OutputFormat::from_extension(Path::new(path));
+
+        assert_eq!(infer("report.json"), Some(OutputFormat::Json));
+        assert_eq!(infer("report.CSV"), Some(OutputFormat::Csv));
+        assert_eq!(infer("out/report.md"), Some(OutputFormat::Markdown));
+        assert_eq!(infer("report.html"), Some(OutputFormat::Html));
+        assert_eq!(infer("report.txt"), None);
+        assert_eq!(infer("report"), None);
+    }
+
+    #[test]
+    fn colors_are_stripped() {
+        assert_eq!(strip_colors("Fix the build"), "Fix the build");
+        assert_eq!(strip_colors("\x1b[33mabc123\x1b[0m D"), "abc123 D");
+        assert_eq!(strip_colors("\x1b[38;5;208mC\x1b[0m"), "C");
+    }
+
+    #[test]
+    fn html_is_escaped() {
+        assert_eq!(escape_html("Fix the build"), "Fix the build");
+        assert_eq!(
+            escape_html("Use <T> & \"U\""),
+            "Use &lt;T&gt; &amp; &quot;U&quot;"
+        );
+    }
+
+    #[test]
+    fn text_is_wrapped_by_words() {
+        assert_eq!(
+            wrap("Checks that the body  is\nwrapped.", 12),
+            ["Checks that", "the body is", "wrapped."]
+        );
+        assert_eq!(wrap("a verylongword b", 4), ["a", "verylongword", "b"]);
+        assert!(wrap("", 10).is_empty());
+    }
+}
@@ -0,0 +1,546 @@
+use super::{escape_html, OutputFormat, Printer, PORCELAIN_VERSION};
+use crate::{
+    json::quote,
+    scoring::{Grade, Score, ScoreFormat, ScoredCommit},
+    summary::Summary,
+    template::Template,
+};
+
+use colored::Colorize;
+use std::{borrow::Cow, cell::Cell};
+
+/// The shape of the list of the scored commits, one per output format.
+///
+/// The sink writes via the printer, which owns the output and reports
+/// its errors, so the sink is only responsible for the layout. The
+/// methods are called in the order: `header()` and `section()` before
+/// the commits of each repository, `commit()` for each of the commits,
+/// `summary()` after all the repositories (or `footer()` after the only
+/// one, if requested) and `finish()` at the end.
+pub trait OutputSink {
+    /// Prints the header of the commit list.
+    fn header(&self, printer: &Printer);
+
+    /// Prints the title of the section, e.g. the repository name.
+    fn section(&self, _printer: &Printer, _title: &str) {}
+
+    fn commit(&self, printer: &Printer, scored_commit: &ScoredCommit);
+
+    /// Prints the summaries of the repositories and their total,
+    /// when the multiple repositories are scored.
+    fn summary(&self, _printer: &Printer, _summaries: &[(&str, Summary)], _total: &Summary) {}
+
+    /// Prints the totals of the commits after them.
+    fn footer(&self, _printer: &Printer, _summary: &Summary) {}
+
+    /// Finishes the output, e.g. closes the JSON array.
+    fn finish(&self, _printer: &Printer) {}
+}
+
+/// Creates the sink of the built-in output format.
+pub fn create_sink(format: OutputFormat) -> Box<dyn OutputSink> {
+    match format {
+        OutputFormat::Table => Box::new(TableSink),
+        OutputFormat::Json => Box::new(JsonSink::default()),
+        OutputFormat::Porcelain => Box::new(PorcelainSink::default()),
+        OutputFormat::Template => Box::new(TemplateSink::new(Template::default())),
+        OutputFormat::Csv => Box::new(CsvSink::default()),
+        OutputFormat::Markdown => Box::new(MarkdownSink::default()),
+        OutputFormat::Html => Box::new(HtmlSink::default()),
+    }
+}
+
+/// The human-readable table, which is printed per repository.
+pub struct TableSink;
+
+impl OutputSink for TableSink {
+    fn header(&self, printer: &Printer) {
+        let score_title = match printer.score_format {
+            ScoreFormat::Score(_) => "SCORE",
+            ScoreFormat::Grade(_) => "GRADE",
+        };
+
+        let dirs_title = if printer.show_dirs {
+            format!("{:15} ", "DIRS")
+        } else {
+            String::new()
+        };
+
+        outln!(
+            printer,
+            "{:12} {:5} {:19} {}SUBJECT",
+            "COMMIT",
+            score_title,
+            "AUTHOR",
+            dirs_title
+        );
+    }
+
+    fn section(&self, printer: &Printer, title: &str) {
+        // Scoring of the section may take a while,
+        // so the previous ones are shown in full.
+        printer.flush();
+
+        outln!(printer, "{}", format!("==> {} <==", title).bold());
+    }
+
+    fn commit(&self, printer: &Printer, scored_commit: &ScoredCommit) {
+        let commit = scored_commit.commit();
+        let score = scored_commit.score();
+        let metadata = commit.metadata();
+        let msg_info = commit.msg_info();
+        let score_text = score.to_string(printer.score_format);
+
+        // The padding is computed by hand, as the emoji grades are
+        // twice as wide in the terminal as the other characters.
+        let padding = 5usize.saturating_sub(display_width(&score_text));
+
+        let dirs = if printer.show_dirs {
+            let names = dir_names(scored_commit).unwrap_or_else(|| "-".to_string());
+            format!("{:15.15} ", names)
+        } else {
+            String::new()
+        };
+
+        outln!(
+            printer,
+            "{} {}{:width$} {:19.19} {}{}",
+            format!("{:12.12}", metadata.id()).yellow(),
+            printer.colorize_score(score, &score_text),
+            "",
+            metadata.author(),
+            dirs,
+            msg_info.subject().unwrap_or(""),
+            width = padding
+        );
+
+        for line in msg_info.body_preview() {
+            outln!(printer, "    {}", line.trim_end());
+        }
+    }
+
+    fn summary(&self, printer: &Printer, summaries: &[(&str, Summary)], total: &Summary) {
+        printer.print_summaries("REPOSITORY", summaries, Some(total));
+    }
+
+    fn footer(&self, printer: &Printer, summary: &Summary) {
+        let average = summary
+            .average()
+            .map(|avg| format!("{:.1}", avg))
+            .unwrap_or_else(|| "-".to_string());
+        let grades: Vec<_> = Grade::ALL
+            .iter()
+            .map(|&grade| format!("{:?}: {}", grade, summary.grade_count(grade)))
+            .collect();
+
+        outln!(printer);
+        outln!(
+            printer,
+            "Total: {} scored, {} ignored, average {} ({})",
+            summary.scored(),
+            summary.ignored(),
+            average,
+            grades.join(", ")
+        );
+    }
+}
+
+/// The JSON array of commit objects. The array is single for all
+/// the repositories, so it is opened only once.
+#[derive(Default)]
+pub struct JsonSink {
+    started: Cell<bool>,
+    has_items: Cell<bool>,
+}
+
+impl OutputSink for JsonSink {
+    fn header(&self, printer: &Printer) {
+        if !self.started.replace(true) {
+            out!(printer, "[");
+        }
+    }
+
+    fn commit(&self, printer: &Printer, scored_commit: &ScoredCommit) {
+        let commit = scored_commit.commit();
+        let metadata = commit.metadata();
+
+        let (score, grade, ignored) = match scored_commit.score() {
+            Score::Scored { score, grade } => (
+                score.to_string(),
+                quote(&format!("{:?}", grade)),
+                "null".to_string(),
+            ),
+            Score::Ignored(reason) => {
+                ("null".to_string(), "null".to_string(), quote(reason.name()))
+            }
+        };
+
+        let trailers: Vec<_> = commit
+            .msg_info()
+            .trailers()
+            .iter()
+            .map(|trailer| {
+                format!(
+                    "{{\"key\": {}, \"value\": {}}}",
+                    quote(trailer.key()),
+                    quote(trailer.value())
+                )
+            })
+            .collect();
+
+        // The directories are listed only if requested, so the objects
+        // stay the same for the tools unaware of them.
+        let dirs = if printer.show_dirs {
+            let dirs = commit.diff_info().as_ref().map_or_else(
+                || "null".to_string(),
+                |diff| {
+                    let names: Vec<_> = diff.top_dir_names().into_iter().map(quote).collect();
+                    format!("[{}]", names.join(", "))
+                },
+            );
+            format!(", \"dirs\": {}", dirs)
+        } else {
+            String::new()
+        };
+
+        let separator = if self.has_items.replace(true) {
+            ","
+        } else {
+            ""
+        };
+
+        out!(
+            printer,
+            "{}\n  {{\"id\": {}, \"author\": {}, \"subject\": {}, \"score\": {}, \"grade\": {}, \"ignored\": {}, \"trailers\": [{}]{}, \"version\": {}}}",
+            separator,
+            quote(&metadata.id().to_string()),
+            quote(metadata.author()),
+            quote(commit.msg_info().subject().unwrap_or("")),
+            score,
+            grade,
+            ignored,
+            trailers.join(", "),
+            dirs,
+            printer.scoring_version
+        );
+    }
+
+    fn finish(&self, printer: &Printer) {
+        self.header(printer);
+        outln!(printer, "\n]");
+    }
+}
+
+/// The tab-separated records, whose format is stable between releases.
+/// The header record is printed once, even if there are no commits.
+#[derive(Default)]
+pub struct PorcelainSink {
+    started: Cell<bool>,
+}
+
+impl OutputSink for PorcelainSink {
+    fn header(&self, printer: &Printer) {
+        if !self.started.replace(true) {
+            outln!(
+                printer,
+                "porcelain\t{}\t{}",
+                PORCELAIN_VERSION,
+                printer.scoring_version
+            );
+        }
+    }
+
+    fn section(&self, printer: &Printer, title: &str) {
+        self.header(printer);
+        outln!(printer, "repository\t{}", porcelain_field(title));
+    }
+
+    fn commit(&self, printer: &Printer, scored_commit: &ScoredCommit) {
+        let commit = scored_commit.commit();
+        let metadata = commit.metadata();
+
+        let (score, grade, ignored) = match scored_commit.score() {
+            Score::Scored { score, grade } => (score.to_string(), format!("{:?}", grade), "-"),
+            Score::Ignored(reason) => ("-".to_string(), "-".to_string(), reason.name()),
+        };
+
+        // The directories are the trailing field, which is present
+        // only if requested, so the records stay the same otherwise.
+        let dirs = if printer.show_dirs {
+            let names = dir_names(scored_commit).unwrap_or_else(|| "-".to_string());
+            format!("\t{}", porcelain_field(&names))
+        } else {
+            String::new()
+        };
+
+        outln!(
+            printer,
+            "commit\t{}\t{}\t{}\t{}\t{}\t{}{}",
+            metadata.id(),
+            grade,
+            score,
+            porcelain_field(metadata.author()),
+            porcelain_field(commit.msg_info().subject().unwrap_or("")),
+            ignored,
+            dirs
+        );
+    }
+
+    fn finish(&self, printer: &Printer) {
+        self.header(printer);
+    }
+}
+
+/// The commits rendered through the user-provided template.
+pub struct TemplateSink {
+    template: Template,
+}
+
+impl TemplateSink {
+    pub fn new(template: Template) -> Self {
+        Self { template }
+    }
+}
+
+impl OutputSink for TemplateSink {
+    fn header(&self, _printer: &Printer) {}
+
+    fn commit(&self, printer: &Printer, scored_commit: &ScoredCommit) {
+        out!(
+            printer,
+            "{}",
+            self.template.render(scored_commit, printer.scoring_version)
+        );
+    }
+}
+
+/// The fields of the commit for the document formats: the score,
+/// the grade and the reason of ignoring, which are empty if missing.
+fn document_fields(scored_commit: &ScoredCommit) -> (String, String, &'static str) {
+    match scored_commit.score() {
+        Score::Scored { score, grade } => (score.to_string(), format!("{:?}", grade), ""),
+        Score::Ignored(reason) => (String::new(), String::new(), reason.name()),
+    }
+}
+
+/// The comma-separated values with the header line, for spreadsheets.
+#[derive(Default)]
+pub struct CsvSink {
+    started: Cell<bool>,
+}
+
+impl OutputSink for CsvSink {
+    fn header(&self, printer: &Printer) {
+        if !self.started.replace(true) {
+            let dirs_title = if printer.show_dirs { ",dirs" } else { "" };
+            outln!(
+                printer,
+                "id,grade,score,author,subject,ignored,problems{}",
+                dirs_title
+            );
+        }
+    }
+
+    fn commit(&self, printer: &Printer, scored_commit: &ScoredCommit) {
+        let commit = scored_commit.commit();
+        let metadata = commit.metadata();
+        let (score, grade, ignored) = document_fields(scored_commit);
+
+        let dirs = if printer.show_dirs {
+            let names = dir_names(scored_commit).unwrap_or_default();
+            format!(",{}", csv_field(&names))
+        } else {
+            String::new()
+        };
+
+        outln!(
+            printer,
+            "{},{},{},{},{},{},{}{}",
+            metadata.id(),
+            grade,
+            score,
+            csv_field(metadata.author()),
+            csv_field(commit.msg_info().subject().unwrap_or("")),
+            ignored,
+            csv_field(&scored_commit.problems().join(", ")),
+            dirs
+        );
+    }
+}
+
+/// The Markdown table, e.g. for the pull request comments.
+#[derive(Default)]
+pub struct MarkdownSink {
+    started: Cell<bool>,
+}
+
+impl OutputSink for MarkdownSink {
+    fn header(&self, printer: &Printer) {
+        if !self.started.replace(true) {
+            let (dirs_title, dirs_rule) = if printer.show_dirs {
+                (" Dirs |", "------|")
+            } else {
+                ("", "")
+            };
+            outln!(
+                printer,
+                "| Commit | Grade | Score | Author | Subject | Problems |{}",
+                dirs_title
+            );
+            outln!(
+                printer,
+                "|--------|-------|------:|--------|---------|----------|{}",
+                dirs_rule
+            );
+        }
+    }
+
+    fn commit(&self, printer: &Printer, scored_commit: &ScoredCommit) {
+        let commit = scored_commit.commit();
+        let metadata = commit.metadata();
+        let (score, grade, _) = document_fields(scored_commit);
+
+        let dirs = if printer.show_dirs {
+            let names = dir_names(scored_commit).unwrap_or_else(|| "-".to_string());
+            format!(" {} |", markdown_cell(&names))
+        } else {
+            String::new()
+        };
+
+        outln!(
+            printer,
+            "| `{:.12}` | {} | {} | {} | {} | {} |{}",
+            metadata.id(),
+            if grade.is_empty() { "-" } else { &grade },
+            score,
+            markdown_cell(metadata.author()),
+            markdown_cell(commit.msg_info().subject().unwrap_or("")),
+            markdown_cell(&scored_commit.problems().join(", ")),
+            dirs
+        );
+    }
+}
+
+/// The HTML document with the table of commits.
+#[derive(Default)]
+pub struct HtmlSink {
+    started: Cell<bool>,
+}
+
+impl OutputSink for HtmlSink {
+    fn header(&self, printer: &Printer) {
+        if !self.started.replace(true) {
+            outln!(printer, "<html>");
+            outln!(printer, "<body>");
+            outln!(printer, "<table>");
+            let dirs_title = if printer.show_dirs {
+                "<th>Dirs</th>"
+            } else {
+                ""
+            };
+            outln!(
+                printer,
+                "<tr><th>Commit</th><th>Grade</th><th>Score</th><th>Author</th><th>Subject</th><th>Problems</th>{}</tr>",
+                dirs_title
+            );
+        }
+    }
+
+    fn commit(&self, printer: &Printer, scored_commit: &ScoredCommit) {
+        let commit = scored_commit.commit();
+        let metadata = commit.metadata();
+        let (score, grade, _) = document_fields(scored_commit);
+
+        let dirs = if printer.show_dirs {
+            let names = dir_names(scored_commit).unwrap_or_default();
+            format!("<td>{}</td>", escape_html(&names))
+        } else {
+            String::new()
+        };
+
+        outln!(
+            printer,
+            "<tr><td><code>{:.12}</code></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>{}</tr>",
+            metadata.id(),
+            grade,
+            score,
+            escape_html(metadata.author()),
+            escape_html(commit.msg_info().subject().unwrap_or("")),
+            escape_html(&scored_commit.problems().join(", ")),
+            dirs
+        );
+    }
+
+    fn finish(&self, printer: &Printer) {
+        self.header(printer);
+        outln!(printer, "</table>");
+        outln!(printer, "</body>");
+        outln!(printer, "</html>");
+    }
+}
+
+/// The top-level directories touched by the commit, separated with commas,
+/// or nothing for the commits without the diff (i.e. the merges).
+fn dir_names(scored_commit: &ScoredCommit) -> Option<String> {
+    scored_commit
+        .commit()
+        .diff_info()
+        .as_ref()
+        .map(|diff| diff.top_dir_names().join(","))
+}
+
+/// The width of the text in the terminal columns, accounting for the wide
+/// emoji (the rest of the characters used in the scores are narrow).
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|chr| if chr >= '\u{1f300}' { 2 } else { 1 })
+        .sum()
+}
+
+/// Replaces the characters, which would break the porcelain records.
+fn porcelain_field(text: &str) -> Cow<'_, str> {
+    const SEPARATORS: &[char] = &['\t', '\n'];
+
+    if text.contains(SEPARATORS) {
+        Cow::Owned(text.replace(SEPARATORS, " "))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Quotes the CSV field, if it contains the separators or the quotes.
+fn csv_field(text: &str) -> Cow<'_, str> {
+    const SPECIAL: &[char] = &[',', '"', '\n', '\r'];
+
+    if text.contains(SPECIAL) {
+        Cow::Owned(format!("\"{}\"", text.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Escapes the text for the Markdown table cell.
+pub fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn porcelain_fields_are_single_line() {
+        assert_eq!(porcelain_field("Fix the build"), "Fix the build");
+        assert_eq!(porcelain_field("Leeroy\tJenkins\n"), "Leeroy Jenkins ");
+    }
+
+    #[test]
+    fn csv_fields_are_quoted() {
+        assert_eq!(csv_field("Fix the build"), "Fix the build");
+        assert_eq!(
+            csv_field("Fix \"the\" build, finally"),
+            "\"Fix \"\"the\"\" build, finally\""
+        );
+        assert_eq!(markdown_cell("Use a | b"), "Use a \\| b");
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    cell::Cell,
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+/// The delay before the progress is shown, so it does
+/// not flicker for the runs, which are fast anyway.
+const SHOW_DELAY: Duration = Duration::from_secs(1);
+
+/// The minimum interval between redraws of the progress line.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+const SPINNER: &[char] = &['|', '/', '-', '\\'];
+
+/// The progress indicator of the long runs: the spinner with the number
+/// of the scored commits and the scoring rate, which is drawn in-place on
+/// the standard error.
+///
+/// The caller decides whether the progress is enabled at all, as it must
+/// not be mixed with the output or go to files (see `main::show_progress()`).
+pub struct Progress {
+    enabled: bool,
+    start: Instant,
+    commits: Cell<usize>,
+    last_draw: Cell<Option<Instant>>,
+}
+
+impl Progress {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            start: Instant::now(),
+            commits: Cell::new(0),
+            last_draw: Cell::new(None),
+        }
+    }
+
+    /// Counts the scored commit, redrawing the progress if it is time to.
+    pub fn tick(&self) {
+        self.commits.set(self.commits.get() + 1);
+
+        if !self.enabled {
+            return;
+        }
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.start);
+        if elapsed < SHOW_DELAY {
+            return;
+        }
+
+        if let Some(last_draw) = self.last_draw.get() {
+            if now.duration_since(last_draw) < REDRAW_INTERVAL {
+                return;
+            }
+        }
+
+        let frame = self.last_draw.get().map_or(0, |_| {
+            (elapsed.as_millis() / REDRAW_INTERVAL.as_millis()) as usize % SPINNER.len()
+        });
+        self.last_draw.set(Some(now));
+
+        let commits = self.commits.get();
+        let rate = commits as f64 / elapsed.as_secs_f64();
+
+        // The progress is not worth failing the run.
+        let _ = write!(
+            io::stderr(),
+            "\r{} {} commits scored ({:.0} commits/s)\x1b[K",
+            SPINNER[frame],
+            commits,
+            rate
+        );
+    }
+
+    /// Erases the progress line, if it is drawn. This must be done
+    /// before anything else is printed to the same terminal.
+    pub fn finish(&self) {
+        if self.last_draw.replace(None).is_some() {
+            let _ = write!(io::stderr(), "\r\x1b[K");
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
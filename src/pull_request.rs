@@ -0,0 +1,171 @@
+use crate::{
+    printer::markdown_cell,
+    scoring::{Grade, RuleInfo, RuleScore, Score, ScoredCommit},
+    summary::Summary,
+};
+
+use std::collections::HashMap;
+
+/// The hidden mark of the comment posted by commrate, so the comment
+/// is updated on the next runs instead of posting the new one.
+pub const COMMENT_MARKER: &str = "<!-- commrate -->";
+
+/// The maximum number of commits listed in the comment, so the comment
+/// stays readable.
+const MAX_COMMENT_COMMITS: usize = 50;
+
+/// The commit of the pull request fetched from the hosting API.
+pub struct PullRequestCommit {
+    pub id: String,
+    pub author: String,
+    pub message: String,
+    pub parents: usize,
+}
+
+/// The pull request of the code hosting, which is accessed via its API.
+pub trait PullRequestApi {
+    /// Fetches the commits of the pull request from the oldest to the
+    /// newest one.
+    fn fetch_commits(&self) -> Result<Vec<PullRequestCommit>, String>;
+
+    /// Posts the comment to the pull request, or updates the one posted
+    /// before (marked with `COMMENT_MARKER`), so there is only one.
+    fn post_comment(&self, text: &str) -> Result<(), String>;
+}
+
+/// The Markdown comment summarizing the scored commits of the pull request:
+/// the grades and the problems of the commits, followed by the hints how
+/// to fix these problems (the descriptions of the failed rules).
+pub struct PullRequestComment {
+    descriptions: HashMap<&'static str, &'static str>,
+    rows: Vec<String>,
+    omitted: usize,
+
+    /// The failed rules with their problems, in the order of appearance.
+    hints: Vec<(&'static str, &'static str)>,
+}
+
+impl PullRequestComment {
+    pub fn new(rules: &[RuleInfo]) -> Self {
+        Self {
+            descriptions: rules
+                .iter()
+                .map(|rule| (rule.name, rule.description))
+                .collect(),
+            rows: Vec::new(),
+            omitted: 0,
+            hints: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, scored: &ScoredCommit, rule_scores: &[RuleScore]) {
+        for rule_score in rule_scores {
+            if let Some(problem) = rule_score.problem {
+                if !self.hints.iter().any(|&(name, _)| name == rule_score.name) {
+                    self.hints.push((rule_score.name, problem));
+                }
+            }
+        }
+
+        if self.rows.len() == MAX_COMMENT_COMMITS {
+            self.omitted += 1;
+            return;
+        }
+
+        let grade = match scored.score() {
+            Score::Scored { score, grade } => format!("{:?} ({})", grade, score),
+            Score::Ignored(reason) => format!("ignored ({})", reason.name()),
+        };
+
+        let commit = scored.commit();
+        self.rows.push(format!(
+            "| `{:.12}` | {} | {} | {} |",
+            commit.metadata().id(),
+            grade,
+            markdown_cell(commit.msg_info().subject().unwrap_or("")),
+            markdown_cell(&scored.problems().join(", "))
+        ));
+    }
+
+    pub fn text(&self, summary: &Summary) -> String {
+        let mut text = format!(
+            "{}\n**commrate**: {} commits scored",
+            COMMENT_MARKER,
+            summary.scored()
+        );
+
+        if let Some(average) = summary.average() {
+            text.push_str(&format!(", average score {:.1}", average));
+        }
+
+        let grades: Vec<_> = Grade::ALL
+            .iter()
+            .map(|&grade| format!("{:?}: {}", grade, summary.grade_count(grade)))
+            .collect();
+        text.push_str(&format!(" ({})\n\n", grades.join(", ")));
+
+        text.push_str("| Commit | Grade | Subject | Problems |\n");
+        text.push_str("|--------|-------|---------|----------|\n");
+        for row in &self.rows {
+            text.push_str(row);
+            text.push('\n');
+        }
+
+        if self.omitted > 0 {
+            text.push_str(&format!(
+                "\n{} more commits are not listed.\n",
+                self.omitted
+            ));
+        }
+
+        if !self.hints.is_empty() {
+            text.push_str("\n**How to fix**\n\n");
+            for (name, problem) in &self.hints {
+                let description = self.descriptions.get(name).copied().unwrap_or("");
+                text.push_str(&format!("* *{}*: {}\n", problem, description));
+            }
+        }
+
+        text
+    }
+}
+
+/// Checks whether the comment is the one posted by commrate.
+pub fn is_own_comment(text: &str) -> bool {
+    text.starts_with(COMMENT_MARKER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        commit::{Commit, CommitId, MessageInfo, Metadata},
+        scoring::{Scorer, ScorerBuilder, SubjectRule},
+    };
+
+    #[test]
+    fn comment_lists_commits_and_hints() {
+        let scorer: Scorer = ScorerBuilder::new()
+            .with_rule(SubjectRule::default(), 1.0)
+            .build();
+
+        let mut comment = PullRequestComment::new(&scorer.rules());
+        let mut summary = Summary::new();
+        for message in &["fix", "wip", "Fix the parser on the empty input"] {
+            let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+            let commit = Commit::new_from_message(metadata, MessageInfo::new(message));
+            let (scored, rule_scores) = scorer.score_explained(commit);
+
+            comment.add(&scored, &rule_scores);
+            summary.add(scored.score());
+        }
+
+        let text = comment.text(&summary);
+        assert!(is_own_comment(&text));
+        assert!(text.contains("3 commits scored"));
+        assert_eq!(text.matches("| `aaa` |").count(), 3);
+
+        // The hint is given once per failed rule.
+        assert_eq!(text.matches("* *subject too short or too long*").count(), 1);
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    collections::hash_map::RandomState,
+    env,
+    fs::{self, DirBuilder},
+    hash::{BuildHasher, Hasher},
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// The number of the attempts to pick the unused temporary directory.
+const TEMP_DIR_ATTEMPTS: u32 = 16;
+
+/// A temporary bare clone of some remote repository.
+///
+/// The clone is created via the `git` executable, as libgit2 does not
+/// support shallow cloning, and is removed as soon as this object is
+/// dropped.
+pub struct RemoteClone {
+    path: PathBuf,
+}
+
+impl RemoteClone {
+    /// Clones the repository at `url` into the temporary directory.
+    /// The history is truncated to `depth` commits, if specified.
+    pub fn new(url: &str, depth: Option<usize>) -> Result<Self, String> {
+        let path = create_temp_dir()
+            .map_err(|err| format!("cannot create the temporary directory: {}", err))?;
+
+        let mut command = Command::new("git");
+        command.arg("clone").arg("--bare").arg("--quiet");
+        if let Some(depth) = depth {
+            command.arg("--depth").arg(depth.to_string());
+        }
+        // The URL must not be taken for an option, if it starts with "-".
+        command.arg("--").arg(url).arg(&path).stdin(Stdio::null());
+
+        // The clone is not constructed yet, so clean up manually.
+        match command.status() {
+            Ok(status) if status.success() => Ok(Self { path }),
+            Ok(_) => {
+                let _ = fs::remove_dir_all(&path);
+                Err(format!("cannot clone {}", url))
+            }
+            Err(err) => {
+                let _ = fs::remove_dir_all(&path);
+                Err(format!("cannot run git: {}", err))
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for RemoteClone {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Creates the empty directory for the clone. The name is random and the
+/// directory is created atomically (failing if it exists), so the other
+/// users of the temporary directory can neither predict nor take it.
+fn create_temp_dir() -> io::Result<PathBuf> {
+    let mut builder = DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+
+    let mut attempts = 0;
+    loop {
+        // The hasher of the standard library is randomly keyed.
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(std::process::id());
+        hasher.write_u32(attempts);
+
+        let path = env::temp_dir().join(format!("commrate-{:016x}", hasher.finish()));
+        match builder.create(&path) {
+            Ok(()) => return Ok(path),
+            Err(ref err) if err.kind() == ErrorKind::AlreadyExists => {}
+            Err(err) => return Err(err),
+        }
+
+        attempts += 1;
+        if attempts == TEMP_DIR_ATTEMPTS {
+            return Err(io::Error::new(
+                ErrorKind::AlreadyExists,
+                "no unused name found",
+            ));
+        }
+    }
+}
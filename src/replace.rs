@@ -0,0 +1,405 @@
+use git2::{Commit, Oid, Repository};
+
+use std::{
+    collections::{BinaryHeap, HashMap},
+    env, fs,
+    path::Path,
+};
+
+/// The replacements altering the history as it is seen by Git: the replace
+/// refs (`refs/replace/*`, written by `git replace`), which substitute the
+/// whole commits, and the grafts (`info/grafts`), which substitute only the
+/// parents of the commits. libgit2 honors neither of these, so the history
+/// is walked with them applied by `ReplacedWalk`.
+///
+/// Like in Git, the replace refs are ignored if `core.useReplaceRefs` is
+/// false or `GIT_NO_REPLACE_OBJECTS` is set.
+#[derive(Default)]
+pub struct Replacements {
+    commits: HashMap<Oid, Oid>,
+    grafts: HashMap<Oid, Vec<Oid>>,
+}
+
+impl Replacements {
+    pub fn read(repo: &Repository, common_dir: &Path) -> Self {
+        let use_replace_refs = env::var_os("GIT_NO_REPLACE_OBJECTS").is_none()
+            && repo
+                .config()
+                .and_then(|config| config.get_bool("core.useReplaceRefs"))
+                .unwrap_or(true);
+
+        let commits = if use_replace_refs {
+            read_replace_refs(repo)
+        } else {
+            HashMap::new()
+        };
+
+        let grafts = fs::read_to_string(common_dir.join("info/grafts"))
+            .map(|content| parse_grafts(&content))
+            .unwrap_or_default();
+
+        Self { commits, grafts }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commits.is_empty() && self.grafts.is_empty()
+    }
+
+    /// The ID of the commit object, which the content of the commit
+    /// is read from (the ID itself, unless the commit is replaced).
+    pub fn object_id(&self, id: Oid) -> Oid {
+        self.commits.get(&id).copied().unwrap_or(id)
+    }
+
+    /// The parents of the commit, which content is already read
+    /// from the replacement object, unless these are grafted.
+    pub fn parents(&self, id: Oid, commit: &Commit<'_>) -> Vec<Oid> {
+        match self.grafts.get(&id) {
+            Some(parents) => parents.clone(),
+            None => commit.parent_ids().collect(),
+        }
+    }
+}
+
+/// The history walk with the replacements applied: the commits reachable
+/// from the start ones, but not from the hidden ones, are yielded from the
+/// most recent to the oldest one (by the commit time), but the children
+/// always precede their parents, even if the commit times are skewed.
+///
+/// Like in Git, the hidden history is walked along with the rest of it,
+/// only until all the queued commits are hidden, rather than to the root.
+/// So, the commit which is older than the hidden one by its time only may
+/// be yielded before it is found to be hidden.
+pub struct ReplacedWalk<'a> {
+    repo: &'a Repository,
+    replacements: &'a Replacements,
+    queue: BinaryHeap<(i64, Oid)>,
+    seen: HashMap<Oid, SeenCommit>,
+    /// The number of the visible children of each commit, which are
+    /// not yielded yet, so the commit must wait for them.
+    pending_children: HashMap<Oid, usize>,
+    /// The number of the visible commits, which are not yielded yet.
+    visible: usize,
+}
+
+/// The commit found by the walk.
+struct SeenCommit {
+    /// The commit time, but not later than the one of its child, so the
+    /// parents are queued after the children despite the skewed times.
+    time: i64,
+    parents: Vec<Oid>,
+    hidden: bool,
+    /// Whether the commit is already yielded (or its parents are hidden).
+    done: bool,
+    /// Whether the commit is waiting for some of its children.
+    waiting: bool,
+}
+
+impl<'a> ReplacedWalk<'a> {
+    pub fn new(
+        repo: &'a Repository,
+        replacements: &'a Replacements,
+        starts: &[Oid],
+        hidden: &[Oid],
+    ) -> Self {
+        let mut walk = Self {
+            repo,
+            replacements,
+            queue: BinaryHeap::new(),
+            seen: HashMap::new(),
+            pending_children: HashMap::new(),
+            visible: 0,
+        };
+
+        for &id in hidden {
+            walk.enqueue(id, true, i64::MAX);
+        }
+        for &id in starts {
+            walk.enqueue(id, false, i64::MAX);
+        }
+
+        walk
+    }
+
+    /// Queues the commit found from the child with the specified time.
+    /// The commit found before is hidden, if it is reached from the hidden
+    /// one, but the visible one is never queued twice.
+    fn enqueue(&mut self, id: Oid, hidden: bool, child_time: i64) {
+        if self.seen.contains_key(&id) {
+            if hidden {
+                self.hide(id);
+            }
+            return;
+        }
+
+        // The absent commits (e.g. beyond the shallow boundary) are skipped.
+        let commit = match self.repo.find_commit(self.replacements.object_id(id)) {
+            Ok(commit) => commit,
+            Err(_) => return,
+        };
+        let time = commit.time().seconds().min(child_time);
+        let parents = self.replacements.parents(id, &commit);
+
+        if !hidden {
+            self.visible += 1;
+            for &parent in &parents {
+                *self.pending_children.entry(parent).or_insert(0) += 1;
+            }
+        }
+
+        self.seen.insert(
+            id,
+            SeenCommit {
+                time,
+                parents,
+                hidden,
+                done: false,
+                waiting: false,
+            },
+        );
+        self.queue.push((time, id));
+    }
+
+    /// Hides the commit found as the visible one before, along with its
+    /// parents, if these are already queued.
+    fn hide(&mut self, id: Oid) {
+        let commit = match self.seen.get_mut(&id) {
+            Some(commit) if !commit.hidden => commit,
+            _ => return,
+        };
+        commit.hidden = true;
+        let parents = commit.parents.clone();
+
+        if commit.done {
+            for parent in parents {
+                self.hide(parent);
+            }
+        } else {
+            // The waiting commit is queued again to be dropped.
+            if commit.waiting {
+                commit.waiting = false;
+                self.queue.push((commit.time, id));
+            }
+
+            self.visible -= 1;
+            for parent in parents {
+                self.release(parent);
+            }
+        }
+    }
+
+    /// Lets the commit go, once all its visible children are yielded.
+    fn release(&mut self, id: Oid) {
+        let pending = self.pending_children.entry(id).or_insert(0);
+        *pending = pending.saturating_sub(1);
+        if *pending > 0 {
+            return;
+        }
+
+        if let Some(commit) = self.seen.get_mut(&id) {
+            if commit.waiting {
+                commit.waiting = false;
+                self.queue.push((commit.time, id));
+            }
+        }
+    }
+}
+
+impl Iterator for ReplacedWalk<'_> {
+    type Item = Oid;
+
+    fn next(&mut self) -> Option<Oid> {
+        // The rest of the queued commits are hidden, so is their history.
+        while self.visible > 0 {
+            let (_, id) = self.queue.pop()?;
+            let commit = self.seen.get_mut(&id)?;
+            if commit.done {
+                continue;
+            }
+
+            let (time, hidden) = (commit.time, commit.hidden);
+            if !hidden && self.pending_children.get(&id).copied().unwrap_or(0) > 0 {
+                commit.waiting = true;
+                continue;
+            }
+
+            commit.done = true;
+            let parents = commit.parents.clone();
+            if !hidden {
+                self.visible -= 1;
+            }
+
+            for parent in parents {
+                if !hidden {
+                    self.release(parent);
+                }
+                self.enqueue(parent, hidden, time);
+            }
+
+            if !hidden {
+                return Some(id);
+            }
+        }
+
+        None
+    }
+}
+
+/// Reads the replace refs, which names are the IDs of the replaced
+/// commits, while the targets are the IDs of their replacements.
+fn read_replace_refs(repo: &Repository) -> HashMap<Oid, Oid> {
+    let references = match repo.references_glob("refs/replace/*") {
+        Ok(references) => references,
+        Err(_) => return HashMap::new(),
+    };
+
+    references
+        .filter_map(Result::ok)
+        .filter_map(|reference| {
+            let replaced = reference.name()?.rsplit('/').next()?;
+            let replaced = Oid::from_str(replaced).ok()?;
+
+            Some((replaced, reference.target()?))
+        })
+        .collect()
+}
+
+/// Parses the grafts file: each line is the commit ID followed by the IDs
+/// of its parents (none for the commit made the root one).
+fn parse_grafts(content: &str) -> HashMap<Oid, Vec<Oid>> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut ids = line.split_whitespace().map(Oid::from_str);
+            let commit = ids.next()?.ok()?;
+            let parents = ids.collect::<Result<_, _>>().ok()?;
+
+            Some((commit, parents))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Signature, Time};
+    use std::process;
+
+    /// The temporary repository, which is removed when dropped.
+    struct TestRepository {
+        repo: Repository,
+    }
+
+    impl TestRepository {
+        fn new(name: &str) -> Self {
+            let path = env::temp_dir().join(format!("commrate-{}-{}", name, process::id()));
+            let _ = fs::remove_dir_all(&path);
+
+            Self {
+                repo: Repository::init_bare(path).unwrap(),
+            }
+        }
+
+        /// Writes the commit with the parents and the time.
+        fn commit(&self, message: &str, parents: &[Oid], time: i64) -> Oid {
+            let signature =
+                Signature::new("John Doe", "john@example.com", &Time::new(time, 0)).unwrap();
+            let tree_id = self.repo.treebuilder(None).unwrap().write().unwrap();
+            let tree = self.repo.find_tree(tree_id).unwrap();
+            let parents: Vec<_> = parents
+                .iter()
+                .map(|&id| self.repo.find_commit(id).unwrap())
+                .collect();
+            let parents: Vec<_> = parents.iter().collect();
+
+            self.repo
+                .commit(None, &signature, &signature, message, &tree, &parents)
+                .unwrap()
+        }
+
+        fn walk(&self, replacements: &Replacements, starts: &[Oid], hidden: &[Oid]) -> Vec<Oid> {
+            ReplacedWalk::new(&self.repo, replacements, starts, hidden).collect()
+        }
+    }
+
+    impl Drop for TestRepository {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(self.repo.path());
+        }
+    }
+
+    #[test]
+    fn children_precede_parents() {
+        // The merged branch is committed with the clock behind.
+        let test = TestRepository::new("walk-order");
+        let root = test.commit("Root", &[], 100);
+        let first = test.commit("First", &[root], 300);
+        let second = test.commit("Second", &[root], 50);
+        let merge = test.commit("Merge", &[first, second], 400);
+
+        let replacements = Replacements::default();
+        assert_eq!(
+            test.walk(&replacements, &[merge], &[]),
+            vec![merge, first, second, root]
+        );
+        assert_eq!(
+            test.walk(&replacements, &[merge], &[first]),
+            vec![merge, second]
+        );
+        assert!(test.walk(&replacements, &[first], &[merge]).is_empty());
+    }
+
+    #[test]
+    fn grafted_parents_are_walked() {
+        let test = TestRepository::new("walk-grafts");
+        let root = test.commit("Root", &[], 100);
+        let first = test.commit("First", &[root], 200);
+        let second = test.commit("Second", &[first], 300);
+
+        let mut replacements = Replacements::default();
+        replacements.grafts.insert(second, vec![root]);
+        assert_eq!(test.walk(&replacements, &[second], &[]), vec![second, root]);
+
+        replacements.grafts.insert(second, Vec::new());
+        assert_eq!(test.walk(&replacements, &[second], &[]), vec![second]);
+    }
+
+    #[test]
+    fn replaced_parents_are_walked() {
+        let test = TestRepository::new("walk-replace");
+        let root = test.commit("Root", &[], 100);
+        let first = test.commit("First", &[root], 200);
+        let second = test.commit("Second", &[first], 300);
+        let other_root = test.commit("Other root", &[], 150);
+        let replacement = test.commit("First", &[other_root], 200);
+
+        let mut replacements = Replacements::default();
+        replacements.commits.insert(first, replacement);
+        assert_eq!(
+            test.walk(&replacements, &[second], &[]),
+            vec![second, first, other_root]
+        );
+        assert_eq!(
+            test.walk(&replacements, &[second], &[other_root]),
+            vec![second, first]
+        );
+    }
+
+    #[test]
+    fn grafts_are_parsed() {
+        let commit = "1111111111111111111111111111111111111111";
+        let parent = "2222222222222222222222222222222222222222";
+        let content = format!("# Grafts\n{} {}\n\n{}\ninvalid\n", commit, parent, parent);
+
+        let grafts = parse_grafts(&content);
+
+        assert_eq!(grafts.len(), 2);
+        assert_eq!(
+            grafts[&Oid::from_str(commit).unwrap()],
+            vec![Oid::from_str(parent).unwrap()]
+        );
+        assert!(grafts[&Oid::from_str(parent).unwrap()].is_empty());
+    }
+}
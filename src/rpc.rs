@@ -0,0 +1,475 @@
+use crate::{
+    commit::cleanup_message,
+    json::{quote, Json},
+    scoring::{RuleScore, Score, ScoredMessage},
+};
+
+use std::io::{self, BufRead, Read, Write};
+
+/// The standard JSON-RPC error codes.
+const PARSE_ERROR: i32 = -32700;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+
+/// The severities of the diagnostics, as in the Language Server Protocol.
+const SEVERITY_WARNING: u32 = 2;
+const SEVERITY_INFORMATION: u32 = 3;
+
+/// The longest message content read, the commit messages are far shorter.
+const MAX_FRAME_LENGTH: usize = 4 * 1024 * 1024;
+
+/// The limits of the line lengths, past which the lines are reported.
+#[derive(Clone, Copy)]
+pub struct LineLimits {
+    /// The longest subject getting the full score, if limited.
+    pub subject: Option<usize>,
+    pub wrap_width: usize,
+}
+
+/// The JSON-RPC server scoring the messages being typed in the editor
+/// (e.g. `COMMIT_EDITMSG`) and returning the problems as the diagnostics
+/// with the character ranges, so the editor extension may underline them.
+///
+/// The messages are framed like in the Language Server Protocol (by the
+/// `Content-Length` header), so the extensions may reuse their LSP clients.
+/// The methods are:
+///
+/// * `score` with the `message` parameter: the raw text of the message;
+/// * `shutdown`, answered with `null`;
+/// * `exit` (the notification), which stops the server.
+pub struct RpcServer {
+    comment_char: char,
+    limits: LineLimits,
+}
+
+impl RpcServer {
+    pub fn new(comment_char: char, limits: LineLimits) -> Self {
+        Self {
+            comment_char,
+            limits,
+        }
+    }
+
+    /// Serves the requests until the input is over or the `exit`
+    /// notification arrives. The messages are scored by the callback
+    /// after the cleanup.
+    pub fn serve<R, W, F>(&self, mut input: R, mut output: W, mut score: F) -> Result<(), String>
+    where
+        R: BufRead,
+        W: Write,
+        F: FnMut(&str) -> ScoredMessage,
+    {
+        while let Some(content) = read_frame(&mut input)? {
+            let request = match content.and_then(|content| Json::parse(&content)) {
+                Ok(request) => request,
+                Err(err) => {
+                    write_frame(&mut output, &error_response("null", PARSE_ERROR, &err))?;
+                    continue;
+                }
+            };
+
+            let method = request.get("method").and_then(Json::as_str).unwrap_or("");
+            if method == "exit" {
+                break;
+            }
+
+            // Nothing is answered to the notifications.
+            let id = match request.get("id") {
+                Some(id) => render_id(id),
+                None => continue,
+            };
+
+            let response = match method {
+                "score" => match request
+                    .get("params")
+                    .and_then(|params| params.get("message"))
+                    .and_then(Json::as_str)
+                {
+                    Some(message) => {
+                        let scored = score(&cleanup_message(message, self.comment_char));
+                        result_response(&id, &self.score_result(message, &scored))
+                    }
+                    None => error_response(&id, INVALID_PARAMS, "the message is required"),
+                },
+                "shutdown" => result_response(&id, "null"),
+                method => error_response(
+                    &id,
+                    METHOD_NOT_FOUND,
+                    &format!("unknown method '{}'", method),
+                ),
+            };
+
+            write_frame(&mut output, &response)?;
+        }
+
+        Ok(())
+    }
+
+    fn score_result(&self, message: &str, scored: &ScoredMessage) -> String {
+        let (score, grade, ignored) = match scored.scored().score() {
+            Score::Scored { score, grade } => (
+                score.to_string(),
+                quote(&format!("{:?}", grade)),
+                "null".to_string(),
+            ),
+            Score::Ignored(reason) => {
+                ("null".to_string(), "null".to_string(), quote(reason.name()))
+            }
+        };
+
+        let diagnostics: Vec<_> = self
+            .diagnose(message, scored.rule_scores())
+            .iter()
+            .map(Diagnostic::to_json)
+            .collect();
+
+        format!(
+            "{{\"score\": {}, \"grade\": {}, \"ignored\": {}, \"diagnostics\": [{}]}}",
+            score,
+            grade,
+            ignored,
+            diagnostics.join(", ")
+        )
+    }
+
+    /// Places the problems of the failed rules in the raw message: the ones
+    /// of the lengths and of the missing break are placed exactly, while the
+    /// rest ones are reported for the whole subject.
+    fn diagnose(&self, message: &str, rule_scores: &[RuleScore]) -> Vec<Diagnostic> {
+        let lines = MessageLines::new(message, self.comment_char);
+        let mut diagnostics = Vec::new();
+
+        for rule_score in rule_scores {
+            let problem = match rule_score.problem {
+                Some(problem) if !rule_score.exempt => problem,
+                _ => continue,
+            };
+            let severity = if rule_score.warning {
+                SEVERITY_INFORMATION
+            } else {
+                SEVERITY_WARNING
+            };
+            let diagnostic = |(line, start, end)| Diagnostic {
+                line,
+                start,
+                end,
+                severity,
+                code: rule_score.name,
+                message: problem,
+            };
+
+            let ranges: Vec<_> = match rule_score.name {
+                "SubjectRule" => self
+                    .limits
+                    .subject
+                    .and_then(|limit| lines.subject.and_then(|line| past_limit(line, limit)))
+                    .into_iter()
+                    .collect(),
+                "SubjectBodyBreakRule" => lines.after_subject.map(whole_line).into_iter().collect(),
+                "BodyWrappingRule" => lines
+                    .body
+                    .iter()
+                    .filter_map(|&line| past_limit(line, self.limits.wrap_width))
+                    .collect(),
+                _ => Vec::new(),
+            };
+
+            if ranges.is_empty() {
+                diagnostics.extend(lines.subject.map(whole_line).map(diagnostic));
+            } else {
+                diagnostics.extend(ranges.into_iter().map(diagnostic));
+            }
+        }
+
+        diagnostics
+    }
+}
+
+/// The problem placed in the message: the line number and the range
+/// of the characters (in UTF-16 code units, as LSP counts them).
+#[derive(Debug, PartialEq)]
+struct Diagnostic {
+    line: usize,
+    start: usize,
+    end: usize,
+    severity: u32,
+    code: &'static str,
+    message: &'static str,
+}
+
+impl Diagnostic {
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"range\": {{\"start\": {{\"line\": {0}, \"character\": {1}}}, \"end\": {{\"line\": {0}, \"character\": {2}}}}}, \"severity\": {3}, \"code\": {4}, \"source\": \"commrate\", \"message\": {5}}}",
+            self.line,
+            self.start,
+            self.end,
+            self.severity,
+            quote(self.code),
+            quote(self.message)
+        )
+    }
+}
+
+/// The lines of the raw message, as these are seen by Git after the
+/// cleanup: the comments and everything below the scissors are skipped.
+/// Each line is its number in the raw message along with its text.
+struct MessageLines<'a> {
+    subject: Option<(usize, &'a str)>,
+
+    /// The line following the subject, unless it is empty,
+    /// as it must be.
+    after_subject: Option<(usize, &'a str)>,
+
+    body: Vec<(usize, &'a str)>,
+}
+
+impl<'a> MessageLines<'a> {
+    fn new(message: &'a str, comment_char: char) -> Self {
+        let scissors = format!("{} ------------------------ >8", comment_char);
+        let mut lines = message
+            .lines()
+            .enumerate()
+            .take_while(|(_, line)| !line.starts_with(&scissors))
+            .filter(|(_, line)| !line.starts_with(comment_char))
+            .skip_while(|(_, line)| line.trim().is_empty());
+
+        let subject = lines.next();
+        let body: Vec<_> = lines.collect();
+        let after_subject = body
+            .first()
+            .copied()
+            .filter(|(_, line)| !line.trim().is_empty());
+
+        Self {
+            subject,
+            after_subject,
+            body,
+        }
+    }
+}
+
+fn whole_line((line, text): (usize, &str)) -> (usize, usize, usize) {
+    (line, 0, utf16_len(text.trim_end()))
+}
+
+/// The range of the line past the limit of characters, if it is longer.
+fn past_limit((line, text): (usize, &str), limit: usize) -> Option<(usize, usize, usize)> {
+    let text = text.trim_end();
+    let head = text.char_indices().nth(limit)?.0;
+
+    Some((line, utf16_len(&text[..head]), utf16_len(text)))
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// The request ID as it is echoed in the response.
+fn render_id(id: &Json) -> String {
+    match id {
+        Json::String(id) => quote(id),
+        Json::Number(id) => id.to_string(),
+        _ => "null".to_string(),
+    }
+}
+
+fn result_response(id: &str, result: &str) -> String {
+    format!(
+        "{{\"jsonrpc\": \"2.0\", \"id\": {}, \"result\": {}}}",
+        id, result
+    )
+}
+
+fn error_response(id: &str, code: i32, message: &str) -> String {
+    format!(
+        "{{\"jsonrpc\": \"2.0\", \"id\": {}, \"error\": {{\"code\": {}, \"message\": {}}}}}",
+        id,
+        code,
+        quote(message)
+    )
+}
+
+/// Reads the content of the next message, `None` at the end of the input.
+/// The content longer than `MAX_FRAME_LENGTH` is skipped and reported
+/// as the error of the message, so the following ones are still served.
+fn read_frame<R: BufRead>(input: &mut R) -> Result<Option<Result<String, String>>, String> {
+    let mut length = None;
+
+    loop {
+        let mut header = String::new();
+        let read = input
+            .read_line(&mut header)
+            .map_err(|err| format!("cannot read the request: {}", err))?;
+        if read == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some(pos) = header.find(':') {
+            if header[..pos].eq_ignore_ascii_case("Content-Length") {
+                length = header[pos + 1..].trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = length.ok_or("the request without Content-Length")?;
+    if length > MAX_FRAME_LENGTH {
+        let skipped = io::copy(&mut input.take(length as u64), &mut io::sink())
+            .map_err(|err| format!("cannot read the request: {}", err))?;
+        if skipped < length as u64 {
+            return Err("cannot read the request: unexpected end of input".to_string());
+        }
+        return Ok(Some(Err(format!(
+            "the request is longer than {} bytes",
+            MAX_FRAME_LENGTH
+        ))));
+    }
+
+    let mut content = vec![0; length];
+    input
+        .read_exact(&mut content)
+        .map_err(|err| format!("cannot read the request: {}", err))?;
+
+    String::from_utf8(content)
+        .map(|content| Some(Ok(content)))
+        .map_err(|_| "the request is not UTF-8".to_string())
+}
+
+fn write_frame<W: Write>(output: &mut W, content: &str) -> Result<(), String> {
+    write!(
+        output,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )
+    .and_then(|_| output.flush())
+    .map_err(|err| format!("cannot write the response: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        commit::ParseOptions,
+        scoring::{
+            BodyWrappingRule, ScorerBuilder, ScoringPolicy, SubjectBodyBreakRule, SubjectRule,
+        },
+    };
+
+    const LIMITS: LineLimits = LineLimits {
+        subject: Some(20),
+        wrap_width: 10,
+    };
+
+    fn diagnose(message: &str) -> Vec<Diagnostic> {
+        let scorer = ScorerBuilder::new()
+            .with_rule(SubjectRule::default(), 1.0)
+            .with_rule(SubjectBodyBreakRule, 1.0)
+            .with_rule(BodyWrappingRule, 1.0)
+            .with_policy(ScoringPolicy::strict())
+            .build();
+        let options = ParseOptions::default().with_wrap_width(LIMITS.wrap_width);
+        let scored = scorer.score_message(&cleanup_message(message, '#'), None, &options);
+
+        RpcServer::new('#', LIMITS).diagnose(message, scored.rule_scores())
+    }
+
+    #[test]
+    fn problems_are_placed() {
+        let diagnostics = diagnose("# Comment\nFix the parser on the empty input\nCrashed.\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            (
+                diagnostics[0].line,
+                diagnostics[0].start,
+                diagnostics[0].end
+            ),
+            (2, 0, 8)
+        );
+        assert_eq!(diagnostics[0].code, "SubjectBodyBreakRule");
+
+        // The unwrapped lines are reported past the wrap width,
+        // the vague subject is reported as a whole.
+        let diagnostics = diagnose(
+            "Fix stuff\n\nThe parser crashed on the empty input.\nNow it is checked first.\n",
+        );
+        let ranges: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.code,
+                    diagnostic.line,
+                    diagnostic.start,
+                    diagnostic.end,
+                )
+            })
+            .collect();
+        assert!(ranges.contains(&("SubjectRule", 0, 0, 9)));
+        assert!(ranges.contains(&("BodyWrappingRule", 2, 10, 38)));
+        assert!(ranges.contains(&("BodyWrappingRule", 3, 10, 24)));
+    }
+
+    #[test]
+    fn requests_are_answered() {
+        let frame = |content: &str| format!("Content-Length: {}\r\n\r\n{}", content.len(), content);
+        let input = [
+            frame(r#"{"jsonrpc": "2.0", "id": 1, "method": "score", "params": {"message": "Fix the parser on the empty input"}}"#),
+            frame(r#"{"jsonrpc": "2.0", "id": "a", "method": "lint"}"#),
+            frame("{"),
+            frame(&" ".repeat(MAX_FRAME_LENGTH + 1)),
+            frame(r#"{"jsonrpc": "2.0", "id": 3, "method": "shutdown"}"#),
+            frame(r#"{"jsonrpc": "2.0", "method": "exit"}"#),
+            frame(r#"{"jsonrpc": "2.0", "id": 2, "method": "shutdown"}"#),
+        ]
+        .concat();
+
+        let scorer = ScorerBuilder::new()
+            .with_rule(SubjectRule::default(), 1.0)
+            .build();
+        let mut output = Vec::new();
+        RpcServer::new('#', LIMITS)
+            .serve(input.as_bytes(), &mut output, |message| {
+                scorer.score_message(message, None, &Default::default())
+            })
+            .unwrap();
+
+        let mut output = output.as_slice();
+        let mut responses = Vec::new();
+        while let Some(content) = read_frame(&mut output).unwrap() {
+            responses.push(Json::parse(&content.unwrap()).unwrap());
+        }
+
+        // Nothing is answered after the exit.
+        assert_eq!(responses.len(), 5);
+        assert_eq!(
+            responses[0]
+                .get("result")
+                .and_then(|result| result.get("diagnostics"))
+                .and_then(Json::as_array)
+                .map(<[Json]>::len),
+            Some(0)
+        );
+
+        let code = |response: &Json| {
+            response
+                .get("error")
+                .and_then(|error| error.get("code"))
+                .and_then(|code| match code {
+                    Json::Number(code) => Some(*code as i32),
+                    _ => None,
+                })
+        };
+        assert_eq!(responses[1].get("id").and_then(Json::as_str), Some("a"));
+        assert_eq!(code(&responses[1]), Some(METHOD_NOT_FOUND));
+        assert_eq!(code(&responses[2]), Some(PARSE_ERROR));
+
+        // The oversized request is skipped, the next one is still answered.
+        assert_eq!(code(&responses[3]), Some(PARSE_ERROR));
+        assert_eq!(responses[4].get("id"), Some(&Json::Number(3.0)));
+    }
+}
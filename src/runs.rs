@@ -0,0 +1,238 @@
+use crate::{error::fatal, json::Json};
+
+use std::{collections::HashMap, fs};
+
+/// A commit from the JSON output of the previous run.
+pub struct RunEntry {
+    id: String,
+    subject: String,
+
+    /// The grade, `None` for ignored commits.
+    grade: Option<String>,
+
+    /// The version of the scoring algorithm, `None` for
+    /// the runs made before the versioning was introduced.
+    version: Option<u32>,
+}
+
+/// A commit, whose grade differs between two runs. Commits absent
+/// from one of the runs (e.g. after history rewrites) are reported too.
+pub struct GradeChange {
+    pub id: String,
+    pub subject: String,
+    pub old: RunGrade,
+    pub new: RunGrade,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RunGrade {
+    /// The commit is absent from the run.
+    Absent,
+
+    /// The commit is present, but is not scored.
+    Ignored,
+
+    Graded(String),
+}
+
+impl RunGrade {
+    fn of(entry: Option<&RunEntry>) -> Self {
+        match entry {
+            None => Self::Absent,
+            Some(RunEntry { grade: None, .. }) => Self::Ignored,
+            Some(RunEntry {
+                grade: Some(grade), ..
+            }) => Self::Graded(grade.clone()),
+        }
+    }
+}
+
+/// Reads the JSON output of the run (`--format json`).
+pub fn read_run(path: &str) -> Vec<RunEntry> {
+    let content = fs::read_to_string(path)
+        .unwrap_or_else(|err| fatal(format!("cannot read {}: {}", path, err)));
+
+    parse_run(&content).unwrap_or_else(|err| fatal(format!("{}: {}", path, err)))
+}
+
+fn parse_run(content: &str) -> Result<Vec<RunEntry>, String> {
+    let json = Json::parse(content)?;
+    let items = json.as_array().ok_or("the array of commits expected")?;
+
+    items
+        .iter()
+        .map(|item| {
+            let field = |name| item.get(name).and_then(Json::as_str);
+
+            Ok(RunEntry {
+                id: field("id").ok_or("commit without ID")?.to_string(),
+                subject: field("subject").unwrap_or("").to_string(),
+                grade: field("grade").map(str::to_string),
+                version: item.get("version").and_then(Json::as_u32),
+            })
+        })
+        .collect()
+}
+
+/// Checks whether both runs are made by the same version of the scoring
+/// algorithm, returning the description of the mismatch otherwise: the
+/// grades of such runs may differ even for the same configuration.
+pub fn check_versions(old: &[RunEntry], new: &[RunEntry]) -> Option<String> {
+    let versions = |run: &[RunEntry]| {
+        let mut versions: Vec<_> = run.iter().map(|entry| entry.version).collect();
+        versions.sort_unstable();
+        versions.dedup();
+        versions
+    };
+
+    let (old_versions, new_versions) = (versions(old), versions(new));
+    if old_versions.len() <= 1 && old_versions == new_versions {
+        return None;
+    }
+
+    // Empty runs have nothing to compare with.
+    if old_versions.is_empty() || new_versions.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "the runs are scored by different versions of the algorithm ({} vs {}), \
+         the grades may change regardless of the commits",
+        version_list(&old_versions),
+        version_list(&new_versions)
+    ))
+}
+
+fn version_list(versions: &[Option<u32>]) -> String {
+    versions
+        .iter()
+        .map(|version| match version {
+            Some(version) => version.to_string(),
+            None => "unversioned".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Finds the commits, whose grades differ between the runs. The commits
+/// of the new run go first (in its order), followed by the ones, which
+/// are present only in the old run.
+pub fn diff_runs(old: &[RunEntry], new: &[RunEntry]) -> Vec<GradeChange> {
+    let old_by_id: HashMap<&str, &RunEntry> =
+        old.iter().map(|entry| (entry.id.as_str(), entry)).collect();
+    let new_by_id: HashMap<&str, &RunEntry> =
+        new.iter().map(|entry| (entry.id.as_str(), entry)).collect();
+
+    let changed = new.iter().filter_map(|entry| {
+        let old_grade = RunGrade::of(old_by_id.get(entry.id.as_str()).copied());
+        let new_grade = RunGrade::of(Some(entry));
+
+        if old_grade == new_grade {
+            return None;
+        }
+
+        Some(GradeChange {
+            id: entry.id.clone(),
+            subject: entry.subject.clone(),
+            old: old_grade,
+            new: new_grade,
+        })
+    });
+
+    let removed = old
+        .iter()
+        .filter(|entry| !new_by_id.contains_key(entry.id.as_str()))
+        .map(|entry| GradeChange {
+            id: entry.id.clone(),
+            subject: entry.subject.clone(),
+            old: RunGrade::of(Some(entry)),
+            new: RunGrade::Absent,
+        });
+
+    changed.chain(removed).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_are_parsed() {
+        let run = parse_run(
+            r#"[
+  {"id": "aaa", "author": "Foo", "subject": "Add foo", "score": 87, "grade": "A", "version": 2},
+  {"id": "bbb", "author": "Foo", "subject": "Vendor bar", "score": null, "grade": null}
+]"#,
+        )
+        .unwrap();
+
+        assert_eq!(run.len(), 2);
+        assert_eq!(run[0].grade.as_deref(), Some("A"));
+        assert_eq!(run[1].grade, None);
+        assert_eq!(run[0].version, Some(2));
+        assert_eq!(run[1].version, None);
+        assert!(parse_run(r#"{"id": "aaa"}"#).is_err());
+        assert!(parse_run(r#"[{"subject": "Add foo"}]"#).is_err());
+    }
+
+    #[test]
+    fn grade_changes_are_found() {
+        let entry = |id: &str, grade: Option<&str>| RunEntry {
+            id: id.to_string(),
+            subject: String::new(),
+            grade: grade.map(str::to_string),
+            version: Some(1),
+        };
+
+        let old = vec![
+            entry("aaa", Some("A")),
+            entry("bbb", Some("C")),
+            entry("ccc", None),
+        ];
+        let new = vec![
+            entry("ddd", Some("B")),
+            entry("aaa", Some("A")),
+            entry("bbb", Some("B")),
+        ];
+        let changes = diff_runs(&old, &new);
+
+        let summary: Vec<_> = changes
+            .iter()
+            .map(|change| (change.id.as_str(), change.old.clone(), change.new.clone()))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("ddd", RunGrade::Absent, RunGrade::Graded("B".to_string())),
+                (
+                    "bbb",
+                    RunGrade::Graded("C".to_string()),
+                    RunGrade::Graded("B".to_string())
+                ),
+                ("ccc", RunGrade::Ignored, RunGrade::Absent),
+            ]
+        );
+    }
+
+    #[test]
+    fn version_mismatches_are_reported() {
+        let entry = |version: Option<u32>| RunEntry {
+            id: "aaa".to_string(),
+            subject: String::new(),
+            grade: None,
+            version,
+        };
+
+        let v1 = vec![entry(Some(1)), entry(Some(1))];
+        let v2 = vec![entry(Some(2))];
+        let mixed = vec![entry(None), entry(Some(1))];
+
+        assert_eq!(check_versions(&v1, &v1), None);
+        assert_eq!(check_versions(&v1, &[]), None);
+        assert!(check_versions(&v1, &v2).unwrap().contains("(1 vs 2)"));
+        assert!(check_versions(&mixed, &v1)
+            .unwrap()
+            .contains("(unversioned, 1 vs 1)"));
+    }
+}
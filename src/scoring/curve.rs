@@ -0,0 +1,104 @@
+/// The scoring curve mapping some measure of the commit (e.g. the subject
+/// length) to the score: no score up to `rise_start`, then ascending to
+/// the plateau of the full score from `plateau_start` to `plateau_end`,
+/// then descending to no score again at `fall_end`.
+///
+/// The rules share the curves instead of the ad-hoc formulas, so the
+/// scoring may be tuned by the parameters alone.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Curve {
+    rise_start: f32,
+    plateau_start: f32,
+    plateau_end: f32,
+    fall_end: f32,
+    shape: Shape,
+}
+
+/// The shape of the ascending and the descending parts of the curve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    Linear,
+
+    /// The smoothstep: the score changes slowly near the plateau and
+    /// near zero, so the values slightly off the plateau are barely
+    /// penalized, while the far ones get almost nothing.
+    Smooth,
+}
+
+impl Curve {
+    /// Creates the smooth curve with the specified breakpoints.
+    pub fn new(rise_start: f32, plateau_start: f32, plateau_end: f32, fall_end: f32) -> Self {
+        Self {
+            rise_start,
+            plateau_start,
+            plateau_end,
+            fall_end,
+            shape: Shape::Smooth,
+        }
+    }
+
+    /// Creates the curve ascending to the plateau, which never ends.
+    pub fn rising(rise_start: f32, plateau_start: f32) -> Self {
+        Self::new(rise_start, plateau_start, f32::INFINITY, f32::INFINITY)
+    }
+
+    pub fn with_shape(mut self, shape: Shape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// The end of the plateau: the greatest value getting the full score.
+    pub fn plateau_end(&self) -> f32 {
+        self.plateau_end
+    }
+
+    /// The score from 0.0 to 1.0 for the value.
+    pub fn score(&self, value: f32) -> f32 {
+        if value <= self.rise_start {
+            0.0
+        } else if value < self.plateau_start {
+            self.shape
+                .apply((value - self.rise_start) / (self.plateau_start - self.rise_start))
+        } else if value <= self.plateau_end {
+            1.0
+        } else if value < self.fall_end {
+            self.shape
+                .apply((self.fall_end - value) / (self.fall_end - self.plateau_end))
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Shape {
+    /// Maps the position from 0.0 to 1.0 within the ascending part
+    /// of the curve to the score.
+    fn apply(self, position: f32) -> f32 {
+        match self {
+            Shape::Linear => position,
+            Shape::Smooth => position * position * (3.0 - 2.0 * position),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curve_has_plateau_and_falloff() {
+        let curve = Curve::new(10.0, 20.0, 50.0, 70.0);
+
+        assert_eq!(curve.score(5.0), 0.0);
+        assert_eq!(curve.score(15.0), 0.5);
+        assert!(curve.score(12.0) < 0.2);
+        assert_eq!(curve.score(35.0), 1.0);
+        assert!(curve.score(52.0) > 0.95);
+        assert_eq!(curve.score(60.0), 0.5);
+        assert_eq!(curve.score(80.0), 0.0);
+
+        let linear = Curve::rising(0.0, 1.0).with_shape(Shape::Linear);
+        assert_eq!(linear.score(0.25), 0.25);
+        assert_eq!(linear.score(1000.0), 1.0);
+    }
+}
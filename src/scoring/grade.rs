@@ -9,58 +9,220 @@ pub enum Grade {
     A,
 }
 
-/// A spec for matching grade.
+impl Grade {
+    /// All grades, from the best to the worst.
+    pub const ALL: [Grade; 5] = [Grade::A, Grade::B, Grade::C, Grade::D, Grade::F];
+
+    /// The grade of the absolute score.
+    pub fn from_score(score: u8) -> Grade {
+        match score {
+            0..=19 => Grade::F,
+            20..=39 => Grade::D,
+            40..=59 => Grade::C,
+            60..=79 => Grade::B,
+            _ => Grade::A,
+        }
+    }
+
+    /// The symbol of the grade for the emoji grade style.
+    fn emoji(self) -> &'static str {
+        match self {
+            Grade::A => "\u{1f7e2}", // green circle
+            Grade::B => "\u{1f535}", // blue circle
+            Grade::C => "\u{1f7e1}", // yellow circle
+            Grade::D => "\u{1f7e0}", // orange circle
+            Grade::F => "\u{1f534}", // red circle
+        }
+    }
+
+    /// The range of the absolute scores of the grade (inclusive).
+    fn band(self) -> (u8, u8) {
+        match self {
+            Grade::F => (0, 19),
+            Grade::D => (20, 39),
+            Grade::C => (40, 59),
+            Grade::B => (60, 79),
+            Grade::A => (80, 100),
+        }
+    }
+}
+
+/// The way the grades are displayed.
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct GradeSpec {
-    grade: Grade,
-    rel: Relation,
+pub enum GradeStyle {
+    /// The plain letter grades.
+    Letter,
+
+    /// The letter grades refined by the position of the score within
+    /// the band of the grade: the lower third of the band is marked
+    /// as "B-", while the upper one is marked as "B+". The F grade
+    /// is never refined.
+    PlusMinus,
+
+    /// The colored circles, from green for A to red for F, which are
+    /// easy to scan and survive pasting into the chats without colors.
+    Emoji,
 }
 
-impl FromStr for GradeSpec {
+impl GradeStyle {
+    pub fn render(self, score: u8, grade: Grade) -> String {
+        if self == GradeStyle::Emoji {
+            return grade.emoji().to_string();
+        }
+
+        let (low, high) = grade.band();
+
+        // The relative grades are not related to the bands at all.
+        let refine =
+            self == GradeStyle::PlusMinus && grade != Grade::F && low <= score && score <= high;
+        if !refine {
+            return format!("{:?}", grade);
+        }
+
+        let third = usize::from(score - low) * 3 / usize::from(high - low + 1);
+        let suffix = ["-", "", "+"][third];
+
+        format!("{:?}{}", grade, suffix)
+    }
+}
+
+impl FromStr for GradeStyle {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut chars = s.chars();
-
-        let grade = match chars.next() {
-            Some(chr) => match chr {
-                'A' | 'a' => Grade::A,
-                'B' | 'b' => Grade::B,
-                'C' | 'c' => Grade::C,
-                'D' | 'd' => Grade::D,
-                'F' | 'f' => Grade::F,
-                _ => return Err("grade must be one of: A, B, C, D, F"),
-            },
+        match s.to_ascii_lowercase().as_str() {
+            "letter" => Ok(GradeStyle::Letter),
+            "plus-minus" => Ok(GradeStyle::PlusMinus),
+            "emoji" => Ok(GradeStyle::Emoji),
+            _ => Err("grade style must be one of: letter, plus-minus, emoji"),
+        }
+    }
+}
 
-            None => return Err("grade must be specified"),
-        };
+impl FromStr for Grade {
+    type Err = &'static str;
 
-        let rel = match chars.next() {
-            Some(chr) => match chr {
-                '+' => Relation::Ge,
-                '-' => Relation::Le,
-                _ => return Err("grade relation must be one of: +, -, <empty>"),
-            },
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.trim().chars();
 
-            None => Relation::Eq,
-        };
+        match (chars.next(), chars.next()) {
+            (Some(chr), None) => parse_grade(chr),
+            _ => Err("grade must be one of: A, B, C, D, F"),
+        }
+    }
+}
 
-        if chars.next().is_some() {
-            return Err("grade specification should not contain extra characters");
+/// A spec for matching grade.
+///
+/// The spec is a comma-separated list of terms, each of which is one of:
+///
+/// * `X` matches exactly the grade X;
+/// * `X+` matches the grade X and better ones;
+/// * `X-` matches the grade X and worse ones;
+/// * `X-Y` matches the grades between X and Y (inclusive).
+///
+/// The spec matches the grade if any of its terms does.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GradeSpec {
+    /// A bit mask of the matched grades, indexed by the grade ordinal.
+    grades: u8,
+}
+
+impl FromStr for GradeSpec {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut spec = GradeSpec { grades: 0 };
+
+        for term in s.split(',') {
+            spec = spec.union(parse_term(term.trim())?);
         }
 
-        Ok(GradeSpec { grade, rel })
+        Ok(spec)
     }
 }
 
 impl GradeSpec {
     pub fn matches(self, grade: Grade) -> bool {
-        match self.rel {
-            Relation::Eq => grade == self.grade,
-            Relation::Ge => grade >= self.grade,
-            Relation::Le => grade <= self.grade,
+        self.grades & grade_bit(grade) != 0
+    }
+
+    /// Combines two specs into the one, which matches
+    /// the grade if any of the specs does.
+    pub fn union(self, other: GradeSpec) -> GradeSpec {
+        GradeSpec {
+            grades: self.grades | other.grades,
         }
     }
+
+    fn single(grade: Grade, rel: Relation) -> Self {
+        let (low, high) = match rel {
+            Relation::Eq => (grade, grade),
+            Relation::Ge => (grade, Grade::A),
+            Relation::Le => (Grade::F, grade),
+        };
+
+        Self::range(low, high)
+    }
+
+    fn range(low: Grade, high: Grade) -> Self {
+        let grades = Grade::ALL
+            .iter()
+            .filter(|&&grade| grade >= low && grade <= high)
+            .fold(0, |mask, &grade| mask | grade_bit(grade));
+
+        GradeSpec { grades }
+    }
+}
+
+fn grade_bit(grade: Grade) -> u8 {
+    1 << grade as u8
+}
+
+fn parse_term(term: &str) -> Result<GradeSpec, &'static str> {
+    let mut chars = term.chars();
+
+    let grade = match chars.next() {
+        Some(chr) => parse_grade(chr)?,
+        None => return Err("grade must be specified"),
+    };
+
+    let spec = match chars.next() {
+        Some(chr) => match chr {
+            '+' => GradeSpec::single(grade, Relation::Ge),
+            '-' => match chars.next() {
+                Some(chr) => {
+                    let other = parse_grade(chr)?;
+                    if grade <= other {
+                        GradeSpec::range(grade, other)
+                    } else {
+                        GradeSpec::range(other, grade)
+                    }
+                }
+                None => GradeSpec::single(grade, Relation::Le),
+            },
+            _ => return Err("grade relation must be one of: +, -, <empty>"),
+        },
+
+        None => GradeSpec::single(grade, Relation::Eq),
+    };
+
+    if chars.next().is_some() {
+        return Err("grade specification should not contain extra characters");
+    }
+
+    Ok(spec)
+}
+
+fn parse_grade(chr: char) -> Result<Grade, &'static str> {
+    match chr {
+        'A' | 'a' => Ok(Grade::A),
+        'B' | 'b' => Ok(Grade::B),
+        'C' | 'c' => Ok(Grade::C),
+        'D' | 'd' => Ok(Grade::D),
+        'F' | 'f' => Ok(Grade::F),
+        _ => Err("grade must be one of: A, B, C, D, F"),
+    }
 }
 
 /// A relation specification between different scores/grades.
@@ -87,6 +249,25 @@ mod tests {
         // The rest is guaranteed by PartialOrd's transitivity.
     }
 
+    #[test]
+    fn grades_are_refined_within_bands() {
+        let render = |score| GradeStyle::PlusMinus.render(score, Grade::from_score(score));
+
+        assert_eq!(render(100), "A+");
+        assert_eq!(render(94), "A+");
+        assert_eq!(render(93), "A");
+        assert_eq!(render(80), "A-");
+        assert_eq!(render(79), "B+");
+        assert_eq!(render(66), "B-");
+        assert_eq!(render(38), "D+");
+        assert_eq!(render(5), "F");
+
+        assert_eq!(GradeStyle::PlusMinus.render(95, B), "B");
+        assert_eq!(GradeStyle::Letter.render(100, A), "A");
+        assert_eq!(GradeStyle::Emoji.render(100, A), "\u{1f7e2}");
+        assert_eq!(GradeStyle::Emoji.render(5, F), "\u{1f534}");
+    }
+
     #[test]
     fn invalid_grade_spec_returns_error() {
         assert!(GradeSpec::from_str("").is_err());
@@ -111,7 +292,7 @@ mod tests {
                 let input = format!("{:?}{}", grade, rel_str);
                 let input_lower = input.to_ascii_lowercase();
 
-                let expected = GradeSpec { grade, rel };
+                let expected = GradeSpec::single(grade, rel);
 
                 assert_eq!(GradeSpec::from_str(&input).unwrap(), expected);
                 assert_eq!(GradeSpec::from_str(&input_lower).unwrap(), expected);
@@ -121,7 +302,7 @@ mod tests {
 
     #[test]
     fn grade_spec_matches_eq() {
-        let spec = GradeSpec { grade: C, rel: Eq };
+        let spec = GradeSpec::single(C, Eq);
 
         assert!(!spec.matches(A));
         assert!(!spec.matches(B));
@@ -132,7 +313,7 @@ mod tests {
 
     #[test]
     fn grade_spec_matches_ge() {
-        let spec = GradeSpec { grade: C, rel: Ge };
+        let spec = GradeSpec::single(C, Ge);
 
         assert!(spec.matches(A));
         assert!(spec.matches(B));
@@ -143,7 +324,7 @@ mod tests {
 
     #[test]
     fn grade_spec_matches_le() {
-        let spec = GradeSpec { grade: C, rel: Le };
+        let spec = GradeSpec::single(C, Le);
 
         assert!(!spec.matches(A));
         assert!(!spec.matches(B));
@@ -151,4 +332,58 @@ mod tests {
         assert!(spec.matches(D));
         assert!(spec.matches(F));
     }
+
+    #[test]
+    fn invalid_grade_ranges_and_lists_return_error() {
+        assert!(GradeSpec::from_str("C-").is_ok());
+        assert!(GradeSpec::from_str("C-X").is_err());
+        assert!(GradeSpec::from_str("C-A+").is_err());
+        assert!(GradeSpec::from_str("D,").is_err());
+        assert!(GradeSpec::from_str(",F").is_err());
+    }
+
+    #[test]
+    fn grade_spec_matches_range() {
+        let spec = GradeSpec::from_str("C-A").unwrap();
+
+        assert!(spec.matches(A));
+        assert!(spec.matches(B));
+        assert!(spec.matches(C));
+        assert!(!spec.matches(D));
+        assert!(!spec.matches(F));
+
+        // The order of range bounds does not matter.
+        assert_eq!(GradeSpec::from_str("a-c").unwrap(), spec);
+    }
+
+    #[test]
+    fn grade_spec_matches_list() {
+        let spec = GradeSpec::from_str("D, F").unwrap();
+
+        assert!(!spec.matches(A));
+        assert!(!spec.matches(B));
+        assert!(!spec.matches(C));
+        assert!(spec.matches(D));
+        assert!(spec.matches(F));
+    }
+
+    #[test]
+    fn grade_spec_list_combines_terms() {
+        let spec = GradeSpec::from_str("A,C-D").unwrap();
+        let union = GradeSpec::single(A, Eq).union(GradeSpec::range(D, C));
+
+        assert_eq!(spec, union);
+        assert!(spec.matches(A));
+        assert!(!spec.matches(B));
+        assert!(spec.matches(C));
+        assert!(spec.matches(D));
+        assert!(!spec.matches(F));
+    }
+
+    #[test]
+    fn single_grade_is_parsed() {
+        assert_eq!("b".parse::<Grade>(), Ok(Grade::B));
+        assert!("B+".parse::<Grade>().is_err());
+        assert!("".parse::<Grade>().is_err());
+    }
 }
@@ -1,14 +1,30 @@
+mod curve;
+
 mod grade;
-pub use grade::{Grade, GradeSpec};
+pub use grade::{Grade, GradeSpec, GradeStyle};
+
+mod policy;
+pub use policy::{ClassRef, ScoringPolicy};
+
+mod relative;
+pub use relative::RelativeGrading;
 
 mod rule;
 pub use rule::{
-    BodyLenRule, BodyPresenceRule, BodyWrappingRule, MetadataLinesRule, Rule, SubjectBodyBreakRule,
-    SubjectRule,
+    BodyLenModel, BodyLenRule, BodyPresenceRule, BodyWrappingRule, BranchSubjectRule,
+    BreakingChangeRule, DuplicateMessageRule, IssueSubjectRule, MergeBodyRule, MergeSubjectRule,
+    MessageHygieneRule, MetadataLinesRule, MultiPurposeRule, ParagraphLenRule, ReferenceRule,
+    ScopeRule, StructuredBodyRule, SubjectBodyBreakRule, SubjectLimits, SubjectRule,
+    SubjectStyleRule, TrailerFormatRule, VagueSubjectRule,
 };
 
+mod severity;
+pub use severity::RuleSeverity;
+
 mod score;
-pub use score::Score;
+pub use score::{IgnoreReason, Score, ScoreFormat, ScoreScale};
 
 mod scorer;
-pub use scorer::{ScoredCommit, Scorer, ScorerBuilder};
+pub use scorer::{
+    RuleInfo, RuleScore, ScoredCommit, ScoredMessage, Scorer, ScorerBuilder, SCORING_VERSION,
+};
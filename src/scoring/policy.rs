@@ -0,0 +1,271 @@
+use crate::commit::{Class, Commit, EmojiMode};
+
+use enumset::EnumSet;
+
+/// The class, which the rule overrides of the policy apply to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClassRef {
+    Builtin(Class),
+
+    /// The user-defined class by its index.
+    Custom(usize),
+}
+
+impl ClassRef {
+    fn matches(self, commit: &Commit) -> bool {
+        match self {
+            ClassRef::Builtin(class) => commit.classes().as_set().contains(class),
+            ClassRef::Custom(index) => commit.classes().has_custom(index),
+        }
+    }
+}
+
+/// The override of the rule for commits of the class: either the full
+/// score (exemption) or the weight replacing the configured one.
+#[derive(Clone, Debug)]
+struct RuleOverride {
+    class: ClassRef,
+
+    /// The name of the rule in lowercase.
+    rule: String,
+
+    /// The weight, `None` for exemptions.
+    weight: Option<f32>,
+}
+
+/// Commits of some classes are scored in relaxed fashion, e.g. short
+/// commits are not penalized for the absent body. The scoring policy
+/// defines, which classes are treated this way.
+///
+/// Also, the policy may override specific rules for commits of both
+/// the built-in and the user-defined classes: exempt the commits from
+/// the rule completely, or change the weight of the rule for them.
+#[derive(Clone, Debug)]
+pub struct ScoringPolicy {
+    special_classes: EnumSet<Class>,
+
+    overrides: Vec<RuleOverride>,
+
+    /// Whether the pull request title should be scored instead
+    /// of the subject of squash-merged pull requests.
+    pull_request_titles: bool,
+
+    /// How emoji prefixes of subjects are handled.
+    emoji_mode: EmojiMode,
+}
+
+impl Default for ScoringPolicy {
+    fn default() -> Self {
+        let mut special_classes = EnumSet::new();
+
+        special_classes.insert(Class::Short);
+        special_classes.insert(Class::Refactor);
+        special_classes.insert(Class::Initial);
+
+        Self {
+            special_classes,
+            overrides: Vec::new(),
+            pull_request_titles: false,
+            emoji_mode: EmojiMode::Keep,
+        }
+    }
+}
+
+impl ScoringPolicy {
+    /// The policy without any special classes: every commit
+    /// is scored like the ordinary one.
+    pub fn strict() -> Self {
+        Self {
+            special_classes: EnumSet::new(),
+            ..Self::default()
+        }
+    }
+
+    /// Makes the built-in class scored in the relaxed fashion or,
+    /// if not special, like the ordinary commits.
+    pub fn with_special_class(mut self, class: Class, special: bool) -> Self {
+        if special {
+            self.special_classes.insert(class);
+        } else {
+            self.special_classes.remove(class);
+        }
+
+        self
+    }
+
+    /// Exempts commits of the class from the rules with the specified names.
+    pub fn with_exemptions(mut self, class: ClassRef, rules: &[String]) -> Self {
+        self.overrides.extend(rules.iter().map(|name| RuleOverride {
+            class,
+            rule: name.to_ascii_lowercase(),
+            weight: None,
+        }));
+
+        self
+    }
+
+    /// Replaces the weight of the rule for commits of the class. The weight
+    /// is in the units of the normalized weights (summing to 1.0), and the
+    /// weights are normalized again for these commits.
+    pub fn with_weight(mut self, class: ClassRef, rule: &str, weight: f32) -> Self {
+        self.overrides.push(RuleOverride {
+            class,
+            rule: rule.to_ascii_lowercase(),
+            weight: Some(weight),
+        });
+
+        self
+    }
+
+    /// Checks whether the commit belongs to any of the special classes.
+    pub fn is_special(&self, commit: &Commit) -> bool {
+        let classes = commit.classes().as_set();
+
+        !classes.is_disjoint(self.special_classes)
+    }
+
+    /// Makes the subject scoring use the pull request title extracted
+    /// from the message of squash-merged pull requests, as the subject
+    /// is generated by the hosting platform for such commits.
+    pub fn with_pull_request_titles(mut self, enabled: bool) -> Self {
+        self.pull_request_titles = enabled;
+        self
+    }
+
+    pub fn pull_request_titles(&self) -> bool {
+        self.pull_request_titles
+    }
+
+    pub fn with_emoji_mode(mut self, mode: EmojiMode) -> Self {
+        self.emoji_mode = mode;
+        self
+    }
+
+    pub fn emoji_mode(&self) -> EmojiMode {
+        self.emoji_mode
+    }
+
+    /// Checks whether the commit is exempted from the rule, i.e.
+    /// whether the rule should grant the commit the full score.
+    pub fn is_exempt(&self, commit: &Commit, rule_name: &str) -> bool {
+        self.matching_overrides(commit, rule_name)
+            .any(|item| item.weight.is_none())
+    }
+
+    /// The weight of the rule overridden for the commit by its classes.
+    /// If multiple classes of the commit override the weight, the lowest
+    /// one is used, as the classes are meant to relax the scoring.
+    pub fn weight_override(&self, commit: &Commit, rule_name: &str) -> Option<f32> {
+        self.matching_overrides(commit, rule_name)
+            .filter_map(|item| item.weight)
+            .fold(None, |lowest: Option<f32>, weight| {
+                Some(lowest.map_or(weight, |lowest| lowest.min(weight)))
+            })
+    }
+
+    /// The overrides of the rule for the commit. The names of the rules
+    /// are lowercased once, when the policy is built, so the lookup for
+    /// every commit and rule does not allocate.
+    fn matching_overrides<'a>(
+        &'a self,
+        commit: &'a Commit,
+        rule_name: &'a str,
+    ) -> impl Iterator<Item = &'a RuleOverride> {
+        self.overrides.iter().filter(move |item| {
+            item.rule.eq_ignore_ascii_case(rule_name) && item.class.matches(commit)
+        })
+    }
+
+    /// Checks whether the policy treats commits differently depending on
+    /// their classes, i.e. whether the classes must be known for scoring.
+    pub fn uses_classes(&self) -> bool {
+        !self.special_classes.is_empty() || self.has_overrides() || self.pull_request_titles
+    }
+
+    /// Checks whether some rules are overridden for some classes.
+    pub fn has_overrides(&self) -> bool {
+        !self.overrides.is_empty()
+    }
+
+    /// Checks whether the commit belongs to the specific special class.
+    pub fn is_special_as(&self, commit: &Commit, class: Class) -> bool {
+        self.special_classes.contains(class) && commit.classes().as_set().contains(class)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commit::{CommitId, CustomClass, DiffInfo, MessageInfo, Metadata, ParseOptions};
+    use regex::Regex;
+
+    fn short_commit_with(options: &ParseOptions) -> Commit {
+        let metadata = Metadata::new(
+            CommitId::Placeholder("9335a4dc"),
+            "Leeroy Jenkins".into(),
+            1,
+        );
+        let diff_info = DiffInfo::new(1, 1);
+        let msg_info = MessageInfo::new("Fix typo in README");
+
+        Commit::new(metadata, diff_info, msg_info, options)
+    }
+
+    fn short_commit() -> Commit {
+        short_commit_with(&ParseOptions::default())
+    }
+
+    #[test]
+    fn default_policy_treats_short_commits_as_special() {
+        let policy = ScoringPolicy::default();
+        let commit = short_commit();
+
+        assert!(policy.is_special(&commit));
+        assert!(policy.is_special_as(&commit, Class::Short));
+        assert!(!policy.is_special_as(&commit, Class::Initial));
+    }
+
+    #[test]
+    fn strict_policy_has_no_special_commits() {
+        let policy = ScoringPolicy::strict();
+        let commit = short_commit();
+
+        assert!(!policy.is_special(&commit));
+        assert!(!policy.is_special_as(&commit, Class::Short));
+    }
+
+    #[test]
+    fn custom_classes_are_exempted_from_rules() {
+        let options = ParseOptions::default().with_custom_classes(vec![
+            CustomClass::new("bot").with_author(Regex::new("bot$").unwrap()),
+            CustomClass::new("typo").with_subject(Regex::new("(?i)typo").unwrap()),
+        ]);
+        let commit = short_commit_with(&options);
+
+        let rules = vec!["BodyLenRule".to_string()];
+        let policy = ScoringPolicy::default().with_exemptions(ClassRef::Custom(1), &rules);
+        let policy2 = ScoringPolicy::default().with_exemptions(ClassRef::Custom(0), &rules);
+
+        assert!(policy.is_exempt(&commit, "BodyLenRule"));
+        assert!(policy.is_exempt(&commit, "bodylenrule"));
+        assert!(!policy.is_exempt(&commit, "SubjectRule"));
+        assert!(!policy2.is_exempt(&commit, "BodyLenRule"));
+    }
+
+    #[test]
+    fn builtin_classes_are_configurable() {
+        let commit = short_commit();
+        let short = ClassRef::Builtin(Class::Short);
+
+        let policy = ScoringPolicy::default()
+            .with_special_class(Class::Short, false)
+            .with_weight(short, "BodyLenRule", 0.5)
+            .with_weight(short, "BodyLenRule", 0.0)
+            .with_weight(ClassRef::Builtin(Class::Merge), "SubjectRule", 0.0);
+
+        assert!(!policy.is_special(&commit));
+        assert!(!policy.is_exempt(&commit, "BodyLenRule"));
+        assert_eq!(policy.weight_override(&commit, "bodylenrule"), Some(0.0));
+        assert_eq!(policy.weight_override(&commit, "SubjectRule"), None);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::scoring::{grade::Grade, score::Score};
+
+/// The grading relative to the score distribution of some set of commits
+/// (e.g. the scanned range of the repository): the top 20% of the scores
+/// are graded as A, the next 20% as B, and so on.
+///
+/// The commits with equal scores always get the same grade, which is
+/// determined by the number of commits scored higher.
+///
+/// Only the histogram of the scores is stored, so the grading takes
+/// the constant memory regardless of the number of commits.
+pub struct RelativeGrading {
+    /// The number of commits per each score.
+    counts: [usize; MAX_SCORE + 1],
+    total: usize,
+}
+
+const MAX_SCORE: usize = 100;
+
+impl RelativeGrading {
+    pub fn new<I>(scores: I) -> Self
+    where
+        I: IntoIterator<Item = Score>,
+    {
+        let mut counts = [0; MAX_SCORE + 1];
+        let mut total = 0;
+
+        for score in scores {
+            if let Score::Scored { score, .. } = score {
+                counts[(score as usize).min(MAX_SCORE)] += 1;
+                total += 1;
+            }
+        }
+
+        Self { counts, total }
+    }
+
+    /// Grades the score according to its rank. Ignored scores
+    /// are left as is.
+    pub fn regrade(&self, score: Score) -> Score {
+        match score {
+            Score::Ignored(reason) => Score::Ignored(reason),
+            Score::Scored { score, .. } => Score::Scored {
+                score,
+                grade: self.grade(score),
+            },
+        }
+    }
+
+    fn grade(&self, score: u8) -> Grade {
+        if self.total == 0 {
+            return Grade::A;
+        }
+
+        let higher: usize = self.counts[(score as usize).min(MAX_SCORE) + 1..]
+            .iter()
+            .sum();
+        let rank = higher * Grade::ALL.len() / self.total;
+
+        Grade::ALL[rank.min(Grade::ALL.len() - 1)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::IgnoreReason;
+
+    fn scored(score: u8) -> Score {
+        Score::Scored {
+            score,
+            grade: Grade::F,
+        }
+    }
+
+    fn grades(grading: &RelativeGrading, scores: &[u8]) -> Vec<Grade> {
+        scores
+            .iter()
+            .map(|&score| match grading.regrade(scored(score)) {
+                Score::Scored { grade, .. } => grade,
+                Score::Ignored(_) => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn grades_follow_quintiles() {
+        let scores = [10, 20, 30, 40, 50, 60, 70, 80, 90, 95];
+        let grading = RelativeGrading::new(scores.iter().map(|&score| scored(score)));
+
+        use Grade::*;
+        assert_eq!(
+            grades(&grading, &scores),
+            vec![F, F, D, D, C, C, B, B, A, A]
+        );
+    }
+
+    #[test]
+    fn equal_scores_get_equal_grades() {
+        let scores = [70, 70, 70, 70, 20];
+        let grading = RelativeGrading::new(
+            scores
+                .iter()
+                .map(|&score| scored(score))
+                .chain(Some(Score::Ignored(IgnoreReason::Requested))),
+        );
+
+        assert_eq!(
+            grades(&grading, &scores),
+            vec![Grade::A, Grade::A, Grade::A, Grade::A, Grade::F]
+        );
+        assert!(matches!(
+            grading.regrade(Score::Ignored(IgnoreReason::Merge)),
+            Score::Ignored(IgnoreReason::Merge)
+        ));
+    }
+}
@@ -1,6 +1,24 @@
-use crate::commit::{Class, Commit};
+use std::{
+    cell::RefCell,
+    collections::{HashSet, VecDeque},
+    str::FromStr,
+    sync::Arc,
+};
 
-use enumset::EnumSet;
+use regex::Regex;
+
+use crate::{
+    changelog::{conventional_scope, is_breaking},
+    commit::{
+        is_gitmoji, pull_request_title, split_emoji, words, Class, Commit, EmojiMode,
+        DEFAULT_ISSUE_ID,
+    },
+    patterns,
+    scoring::{
+        curve::{Curve, Shape},
+        policy::ScoringPolicy,
+    },
+};
 
 /// Scoring rule takes care about the specific aspect of the
 /// commit quality and returns result from 0 to 1 depending on
@@ -11,11 +29,55 @@ use enumset::EnumSet;
 /// * what will be its weight in the overall score;
 /// * what is the real scale of the score.
 ///
-/// Both of these items are addressed at the higher levels.
+/// Both of these items are addressed at the higher levels, as well as
+/// the scoring policy, which the rule must respect when scoring commits
+/// of special classes.
 pub trait Rule {
+    /// The name of the rule, which is used for referring
+    /// to the rule in configuration.
+    fn name(&self) -> &'static str;
+
+    /// The one-paragraph description of what the rule measures,
+    /// which is shown to the users (see `commrate rules`).
+    fn describe(&self) -> &'static str;
+
     /// Check the commit against this rule and return the result
     /// between 0 and 1 depending on the commit quality.
-    fn score(&self, commit: &Commit) -> f32;
+    fn score(&self, commit: &Commit, policy: &ScoringPolicy) -> f32;
+
+    /// The short description of the problem for the commits, which
+    /// fail the rule, e.g. "body missing". The rules granting the bonus
+    /// for the optional things have none, as it is not a problem.
+    fn problem(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// The commit data the rule inspects. The diff is the most expensive
+    /// thing to obtain, so it is not calculated at all if none of the
+    /// rules needs it (neither directly, nor via the commit classes).
+    fn input(&self) -> RuleInput {
+        RuleInput::Message
+    }
+
+    /// Forgets the commits seen so far, if the rule remembers them, so
+    /// the next traversal (e.g. of another repository) is scored afresh.
+    fn reset(&self) {}
+}
+
+/// The commit data required by the rule, from the cheapest to the most
+/// expensive one: each kind implies that the previous ones are available.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RuleInput {
+    /// The message (and the metadata) only.
+    Message,
+
+    /// The commit classes, which are checked via the scoring policy.
+    /// Most of the classes are derived from the diff, so the diff is
+    /// required unless the policy ignores the classes.
+    Classes,
+
+    /// The diff itself.
+    Diff,
 }
 
 /// This rule checks the commit subject (the first message line),
@@ -28,61 +90,366 @@ pub trait Rule {
 /// This is pretty crucial, as the subject is inspected much more
 /// frequently than the rest of the body. However, no stylistical
 /// limitations are imposed - only length is scored.
-pub struct SubjectRule;
+///
+/// The length limits may be configured for the project conventions,
+/// e.g. the subjects of 50 characters at most.
+pub struct SubjectRule {
+    curve: Curve,
+}
+
+impl SubjectRule {
+    pub fn new(limits: SubjectLimits) -> Self {
+        Self {
+            curve: limits.curve(),
+        }
+    }
+
+    /// The longest subject getting the full score, if limited.
+    pub fn max_len(&self) -> Option<usize> {
+        let plateau_end = self.curve.plateau_end();
+        if plateau_end.is_finite() {
+            Some(plateau_end as usize)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for SubjectRule {
+    fn default() -> Self {
+        Self::new(SubjectLimits::default())
+    }
+}
+
+/// The breakpoints of the subject length scoring: the subjects up to
+/// `min` characters get no score, the ones from `good_min` to `good_max`
+/// get the full score, while the ones of `max` characters and longer
+/// get no score again. The score changes smoothly between these.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SubjectLimits {
+    min: usize,
+    good_min: usize,
+    good_max: usize,
+    max: usize,
+}
+
+impl SubjectLimits {
+    fn curve(self) -> Curve {
+        Curve::new(
+            self.min as f32,
+            self.good_min as f32,
+            self.good_max as f32,
+            self.max as f32,
+        )
+    }
+}
+
+/// The optimal subjects are long enough to be meaningful and short enough
+/// to fit oneline log or e-mailed patch. Though the long subjects are not
+/// good, they at least carry some useful information, so the score falls
+/// to zero only for 100+ characters.
+impl Default for SubjectLimits {
+    fn default() -> Self {
+        Self {
+            min: 10,
+            good_min: 20,
+            good_max: 70,
+            max: 100,
+        }
+    }
+}
+
+/// The limits are parsed from the comma-separated list of the
+/// breakpoints in the ascending order: "10,20,50,72".
+impl FromStr for SubjectLimits {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const ERROR: &str =
+            "subject limits must be four ascending lengths: MIN,GOOD_MIN,GOOD_MAX,MAX";
+
+        let values = s
+            .split(',')
+            .map(|value| value.trim().parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ERROR)?;
+
+        match *values.as_slice() {
+            [min, good_min, good_max, max]
+                if min < good_min && good_min <= good_max && good_max < max =>
+            {
+                Ok(Self {
+                    min,
+                    good_min,
+                    good_max,
+                    max,
+                })
+            }
+            _ => Err(ERROR),
+        }
+    }
+}
 
 impl Rule for SubjectRule {
-    fn score(&self, commit: &Commit) -> f32 {
-        let classes = commit.classes().as_set();
+    fn name(&self) -> &'static str {
+        "SubjectRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Checks that the subject is present and of reasonable length: long enough to say what is changed (\"fix\" is not a subject), but short enough for `git log --oneline` and the like."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("subject too short or too long")
+    }
+
+    fn input(&self) -> RuleInput {
+        RuleInput::Classes
+    }
 
+    fn score(&self, commit: &Commit, policy: &ScoringPolicy) -> f32 {
         // Typical "Initial commit" gets penalized by ordinary rules,
         // let's forgive this short but traditional message.
-        if classes.contains(Class::Initial) {
+        if policy.is_special_as(commit, Class::Initial) {
             return 1.0;
         }
 
+        self.curve
+            .score(scored_subject(commit, policy).len() as f32)
+    }
+}
+
+/// The subject as it is scored: the title of the squash-merged pull request
+/// instead of the generated subject, if configured, without the emoji.
+fn scored_subject<'a>(commit: &'a Commit, policy: &ScoringPolicy) -> &'a str {
+    let msg_info = commit.msg_info();
+    let mut subject = msg_info.subject().unwrap_or("");
+
+    if policy.pull_request_titles() && commit.classes().as_set().contains(Class::PullRequest) {
+        if let Some(title) = pull_request_title(msg_info) {
+            subject = title;
+        }
+    }
+
+    // The emoji prefix takes few bytes, but carries no more
+    // information than a single word.
+    if policy.emoji_mode() != EmojiMode::Keep {
+        if let Some((_, rest)) = split_emoji(subject) {
+            subject = rest;
+        }
+    }
+
+    subject
+}
+
+/// The subject must say something about the change: "Minor changes" or
+/// "Fix stuff" are long enough to get the points for the subject length,
+/// but consist of the vague words only (in the language of the message).
+pub struct VagueSubjectRule;
+
+impl Rule for VagueSubjectRule {
+    fn name(&self) -> &'static str {
+        "VagueSubjectRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Checks that the subject is not made of the vague words only, like \"Minor changes\" or \"Fix stuff\"."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("vague subject")
+    }
+
+    fn input(&self) -> RuleInput {
+        RuleInput::Classes
+    }
+
+    fn score(&self, commit: &Commit, policy: &ScoringPolicy) -> f32 {
+        // The traditional "Initial commit" is forgiven like by `SubjectRule`.
+        if policy.is_special_as(commit, Class::Initial) {
+            return 1.0;
+        }
+
+        let subject = scored_subject(commit, policy);
+        let vague_words = commit.msg_info().language().keywords().vague;
+        let mut subject_words = words(subject).peekable();
+
+        // The absent subject is not the vague one, but is penalized anyway.
+        if subject_words.peek().is_some()
+            && subject_words.all(|word| vague_words.contains(&word.as_str()))
+        {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
+
+/// This rule checks the subject style, penalizing the "shouting"
+/// ones: written entirely in capital letters or with excessive
+/// punctuation ("!!!", "???"). Such subjects are not better than
+/// the calm ones, which say the same.
+///
+/// For teams using gitmoji, the rule also requires the subject
+/// to start with the emoji from the gitmoji set.
+pub struct SubjectStyleRule;
+
+impl Rule for SubjectStyleRule {
+    fn name(&self) -> &'static str {
+        "SubjectStyleRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Penalizes the \"shouting\" subjects written entirely in capital letters or with excessive punctuation. With `--emoji gitmoji`, the subject must also start with the gitmoji."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("subject style")
+    }
+
+    fn score(&self, commit: &Commit, policy: &ScoringPolicy) -> f32 {
         let subject = commit.msg_info().subject().unwrap_or("");
 
-        // This is a special case for ugly commits, which specify
-        // a ticket/issue ID as commit subject. These are long
-        // enough to get over 10 chars, but should not get even
-        // a single score point.
-        //
-        // Not a bulletproof, but cuts the most obvious crap.
-        if subject.split_ascii_whitespace().count() <= 1 {
+        if policy.emoji_mode() == EmojiMode::Gitmoji
+            && !matches!(split_emoji(subject), Some((emoji, _)) if is_gitmoji(emoji))
+        {
             return 0.0;
         }
 
-        let len = subject.len();
+        if is_all_caps(subject) || EXCESSIVE_PUNCTUATION.is_match(subject) {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Checks whether the text is written in capital letters. Short texts
+/// are not considered, as these are likely to be just acronyms.
+fn is_all_caps(text: &str) -> bool {
+    let letters: Vec<char> = text.chars().filter(|c| c.is_alphabetic()).collect();
 
-        match len {
-            0..=10 => 0.0,
+    letters.len() >= MIN_ALL_CAPS_LETTERS && letters.iter().all(|c| c.is_uppercase())
+}
 
-            // Smoothly ascend to more or less reasonable length (and score).
-            11..=20 => (len as f32 - 10.0) / 10.0,
+/// This rule flags the commits, which are likely to contain multiple
+/// unrelated changes: the subject joins several clauses ("and", "also",
+/// "plus" or semicolons), and the diff is large and touches multiple
+/// top-level directories. Such commits should be split into atomic ones.
+///
+/// Either sign alone is pretty common for good commits, so only
+/// the combination of them is penalized.
+pub struct MultiPurposeRule;
 
-            // The optimal length: long enough to be meaningful and
-            // short enough to fit oneline log or e-mailed patch.
-            21..=70 => 1.0,
+impl Rule for MultiPurposeRule {
+    fn name(&self) -> &'static str {
+        "MultiPurposeRule"
+    }
 
-            // The descending branch of the function goes much more smoothly.
-            // Though long subjects are not good, they at least carry some
-            // useful information. Let's not be so radical here.
-            71..=100 => (100.0 - len as f32) / 100.0,
+    fn describe(&self) -> &'static str {
+        "Flags the commits likely to contain several unrelated changes: the subject joins multiple clauses (\"and\", \"also\", semicolons), while the diff is large and spread over multiple top-level directories."
+    }
 
-            // 100+ chars in subject deserve no mercy, really.
-            _ => 0.0,
+    fn problem(&self) -> Option<&'static str> {
+        Some("multiple unrelated changes")
+    }
+
+    fn input(&self) -> RuleInput {
+        RuleInput::Diff
+    }
+
+    fn score(&self, commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+        let subject = commit.msg_info().subject().unwrap_or("");
+        let spread = match commit.diff_info() {
+            Some(diff_info) => {
+                diff_info.diff_total() >= MULTI_PURPOSE_DIFF
+                    && diff_info.top_dirs() >= MULTI_PURPOSE_DIRS
+            }
+            None => false,
+        };
+
+        let conjunctions = commit.msg_info().language().keywords().conjunctions;
+
+        if spread && has_conjunction(subject, conjunctions) {
+            0.0
+        } else {
+            1.0
         }
     }
 }
 
+/// The substantial changes are usually designed and discussed somewhere:
+/// in the design doc, the issue or the mailing list thread. The large
+/// commits referencing these (by the URL or the issue ID in the body)
+/// get the small bonus, as the reasoning is at hand for the reviewers
+/// and the future readers. The rest of the commits get no bonus, but
+/// these are not penalized either.
+///
+/// Like `MetadataLinesRule`, this rule is expected to have low weight.
+pub struct ReferenceRule;
+
+/// The minimum diff size for considering the change substantial enough
+/// to reference the discussion.
+const REFERENCE_DIFF: usize = 500;
+
+impl Rule for ReferenceRule {
+    fn name(&self) -> &'static str {
+        "ReferenceRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Grants a small bonus for the large commits referencing the design docs or the discussions: the URLs or the issue IDs in the body."
+    }
+
+    fn input(&self) -> RuleInput {
+        RuleInput::Diff
+    }
+
+    fn score(&self, commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+        let large = match commit.diff_info() {
+            Some(diff_info) => diff_info.diff_total() >= REFERENCE_DIFF,
+            None => false,
+        };
+
+        if large && commit.msg_info().reference_lines() > 0 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Checks whether the subject joins multiple clauses.
+fn has_conjunction(subject: &str, conjunctions: &[&str]) -> bool {
+    subject.contains(';') || words(subject).any(|word| conjunctions.contains(&word.as_str()))
+}
+
 /// This rule checks that the commit has at least *any* body.
 ///
 /// Special commits classes are not penalized for body absence.
+/// Note, that the body merely restating the subject is considered
+/// absent during the message parsing, so it does not count here.
 pub struct BodyPresenceRule;
 
 impl Rule for BodyPresenceRule {
-    fn score(&self, commit: &Commit) -> f32 {
-        if commit.msg_info().body_len() > 0 || commit_is_special(commit) {
+    fn name(&self) -> &'static str {
+        "BodyPresenceRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Checks that the commit has the body. The body merely restating the subject counts as absent, while the short, refactoring and initial commits need no body."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("body missing")
+    }
+
+    fn input(&self) -> RuleInput {
+        RuleInput::Classes
+    }
+
+    fn score(&self, commit: &Commit, policy: &ScoringPolicy) -> f32 {
+        if commit.msg_info().body_len() > 0 || policy.is_special(commit) {
             1.0
         } else {
             0.0
@@ -102,7 +469,23 @@ impl Rule for BodyPresenceRule {
 pub struct SubjectBodyBreakRule;
 
 impl Rule for SubjectBodyBreakRule {
-    fn score(&self, commit: &Commit) -> f32 {
+    fn name(&self) -> &'static str {
+        "SubjectBodyBreakRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Checks that the body is separated from the subject by the empty line, so the tools (and the humans) tell them apart."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("no blank line after subject")
+    }
+
+    fn input(&self) -> RuleInput {
+        RuleInput::Classes
+    }
+
+    fn score(&self, commit: &Commit, policy: &ScoringPolicy) -> f32 {
         let msg_info = commit.msg_info();
 
         if msg_info.body_len() > 0 {
@@ -111,7 +494,7 @@ impl Rule for SubjectBodyBreakRule {
             } else {
                 0.0
             }
-        } else if commit_is_special(commit) {
+        } else if policy.is_special(commit) {
             1.0
         } else {
             0.0
@@ -126,32 +509,154 @@ impl Rule for SubjectBodyBreakRule {
 /// should it have. However, the dependency here is clearly
 /// non-linear. Also, there are obvious exceptions for special
 /// cases, which should not be penalized for short/absent body.
-pub struct BodyLenRule;
+///
+/// The model relating the body length to the diff size may be
+/// calibrated for the project (see `BodyLenModel`).
+pub struct BodyLenRule {
+    curve: Curve,
+    model: BodyLenModel,
+}
+
+impl BodyLenRule {
+    /// Creates the rule scoring the ratio of the actual body length
+    /// to the expected one (see `BodyLenModel`) by the curve.
+    pub fn new(curve: Curve) -> Self {
+        Self {
+            curve,
+            model: BodyLenModel::default(),
+        }
+    }
+
+    pub fn with_model(mut self, model: BodyLenModel) -> Self {
+        self.model = model;
+        self
+    }
+}
+
+impl Default for BodyLenRule {
+    fn default() -> Self {
+        Self::new(Curve::rising(0.0, 1.0).with_shape(Shape::Linear))
+    }
+}
+
+/// The model of the body length expected for the diff size, which
+/// yields the ratio of the actual body length to the expected one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BodyLenModel {
+    /// The logarithm of the body length relative to the logarithm of the
+    /// diff size, multiplied by the coefficient: the larger coefficient,
+    /// the shorter bodies are enough.
+    Log(f32),
+
+    /// The line of the body (of `BODY_LINE_LEN` characters) is expected
+    /// per the specified number of the diff lines.
+    Lines(f32),
+}
+
+/// The length of the body line for the `Lines` model, so the bodies
+/// are measured in the same units disregarding the wrapping.
+const BODY_LINE_LEN: f32 = 72.0;
+
+impl Default for BodyLenModel {
+    fn default() -> Self {
+        BodyLenModel::Log(1.0)
+    }
+}
+
+impl BodyLenModel {
+    /// The ratio of the body length (in characters) to the one expected
+    /// for the diff size, 1.0 and more for the long enough bodies.
+    fn ratio(self, body_len: usize, diff_size: usize) -> f32 {
+        match self {
+            // XXX: +1.0 is to pull ln() value for empty body to zero. Tiny diffs
+            // (which are usually exempted as short commits, but not in strict mode)
+            // are treated as 2-line ones to keep the denominator positive.
+            BodyLenModel::Log(coefficient) => {
+                coefficient * (body_len as f32 + 1.0).ln() / (diff_size.max(2) as f32).ln()
+            }
+            BodyLenModel::Lines(diff_lines) => {
+                let expected = (diff_size as f32 / diff_lines).max(1.0);
+                body_len as f32 / BODY_LINE_LEN / expected
+            }
+        }
+    }
+}
+
+/// The models are parsed from their names with the optional parameter:
+/// "log" (the same as "log:1"), "log:1.5" or "lines:50".
+impl FromStr for BodyLenModel {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const ERROR: &str = "body length model must be log[:COEFFICIENT] or lines:DIFF_LINES";
+
+        let mut parts = s.splitn(2, ':');
+        let name = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+        let parameter = match parts.next() {
+            Some(value) => match value.trim().parse::<f32>() {
+                Ok(value) if value.is_finite() && value > 0.0 => Some(value),
+                _ => return Err(ERROR),
+            },
+            None => None,
+        };
+
+        match (name.as_str(), parameter) {
+            ("log", coefficient) => Ok(BodyLenModel::Log(coefficient.unwrap_or(1.0))),
+            ("lines", Some(diff_lines)) => Ok(BodyLenModel::Lines(diff_lines)),
+            _ => Err(ERROR),
+        }
+    }
+}
 
 impl Rule for BodyLenRule {
-    fn score(&self, commit: &Commit) -> f32 {
-        if commit_is_special(commit) {
+    fn name(&self) -> &'static str {
+        "BodyLenRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Relates the length of the body to the size of the diff: the larger the change, the longer explanation it deserves. The dependency is non-linear, and the special commits and the ones changing mostly binary files are not penalized."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("body too short for the diff")
+    }
+
+    fn input(&self) -> RuleInput {
+        RuleInput::Diff
+    }
+
+    fn score(&self, commit: &Commit, policy: &ScoringPolicy) -> f32 {
+        if policy.is_special(commit) {
             return 1.0;
         }
 
         let diff_option = commit.diff_info();
         if diff_option.is_none() {
-            // XXX: this may happen only for merge commits,
-            // which should not reach here.
-            // Maybe panic instead of graceful return?
+            // This happens for merge commits, which should not reach
+            // here, and for the messages scored without the diff.
+            // Nothing to compare the body with, so let's be kind.
             return 1.0;
         }
 
-        let diff_size = diff_option.as_ref().unwrap().diff_total();
-        let body_len = commit.msg_info().body_len();
+        let diff_info = diff_option.as_ref().unwrap();
+        if diff_info.is_mostly_binary() {
+            // The binary assets (e.g. images or jars) have no lines, so
+            // the line count tells nothing about the size of the change.
+            return 1.0;
+        }
 
-        // This formula if VERY rough and thus probably should be adjusted
-        // with coefficients, especially in low diff size or low body len areas.
-        //
-        // XXX: +1.0 is to pull ln() value for empty body to zero.
-        let score = (body_len as f32 + 1.0).ln() / (diff_size as f32).ln();
+        let diff_size = diff_info.diff_total();
+        // The pasted diff adds nothing to the explanation of the change.
+        let msg_info = commit.msg_info();
+        let body_len = msg_info.body_len() - msg_info.pasted_diff_len();
 
-        // To reach this maximum, there should be approximately
+        // The default formula is VERY rough and thus may be calibrated with
+        // the coefficient or replaced, especially in low diff size or low
+        // body len areas.
+        let ratio = self.model.ratio(body_len, diff_size);
+
+        // To reach the plateau of the default curve with the default
+        // model, there should be approximately
         //
         // * one line body for barely long diff (SHORT_COMMIT_SIZE + few lines);
         // * 3-4 lines of body for medium diff (~250 lines);
@@ -159,11 +664,7 @@ impl Rule for BodyLenRule {
         //
         // For larger diffs, the maximum is almost unreachable, unless the author
         // is insane and writes an essay in the log.
-        if score > 1.0 {
-            1.0
-        } else {
-            score
-        }
+        self.curve.score(ratio)
     }
 }
 
@@ -183,12 +684,28 @@ impl Rule for BodyLenRule {
 pub struct BodyWrappingRule;
 
 impl Rule for BodyWrappingRule {
-    fn score(&self, commit: &Commit) -> f32 {
+    fn name(&self) -> &'static str {
+        "BodyWrappingRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Checks that the lines of the body are wrapped to a reasonable width. The score depends on the fraction of the unwrapped lines, so the occasional pasted logs cost little."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("body not wrapped")
+    }
+
+    fn input(&self) -> RuleInput {
+        RuleInput::Classes
+    }
+
+    fn score(&self, commit: &Commit, policy: &ScoringPolicy) -> f32 {
         let msg_info = commit.msg_info();
         let body_lines = msg_info.body_lines();
 
         if msg_info.body_lines() == 0 {
-            if commit_is_special(commit) {
+            if policy.is_special(commit) {
                 return 1.0;
             } else {
                 return 0.0;
@@ -211,7 +728,15 @@ impl Rule for BodyWrappingRule {
 pub struct MetadataLinesRule;
 
 impl Rule for MetadataLinesRule {
-    fn score(&self, commit: &Commit) -> f32 {
+    fn name(&self) -> &'static str {
+        "MetadataLinesRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Grants a small bonus for the well-known metadata lines (trailers) like `Signed-off-by:` or `Fixes:`."
+    }
+
+    fn score(&self, commit: &Commit, _policy: &ScoringPolicy) -> f32 {
         match commit.msg_info().metadata_lines() {
             0 => 0.0,
             1 => 0.6,
@@ -221,23 +746,771 @@ impl Rule for MetadataLinesRule {
     }
 }
 
-fn commit_is_special(commit: &Commit) -> bool {
-    let classes = commit.classes().as_set();
+/// The trailers are parsed by the tools (`git interpret-trailers`, patch
+/// workflows, the forges), so these are useful only when well-formed: the
+/// canonical capitalization of the key, `Key: Value` and the valid person
+/// in the `-by` trailers. Unlike `MetadataLinesRule`, which only counts
+/// the recognized trailers, this rule checks their form, so the trailers
+/// written by hand and mangled are not worth the bonus.
+pub struct TrailerFormatRule;
+
+impl Rule for TrailerFormatRule {
+    fn name(&self) -> &'static str {
+        "TrailerFormatRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Checks that the well-known trailers are well-formed: `Key: Value` with the canonical key capitalization and `Name <email>` in the `-by` trailers."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("malformed trailers")
+    }
+
+    fn score(&self, commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+        let msg_info = commit.msg_info();
+
+        match msg_info.metadata_lines() {
+            0 => 1.0,
+            total => 1.0 - msg_info.malformed_metadata_lines() as f32 / total as f32,
+        }
+    }
+}
+
+/// For the Conventional Commits, the scope must be one of the components
+/// of the project (and must be present, if the team requires it), so the
+/// changelog sections and the history filtered by the scope are complete.
+///
+/// The subjects without the type prefix are not checked.
+pub struct ScopeRule {
+    scopes: Vec<String>,
+    required: bool,
+}
+
+impl ScopeRule {
+    /// Creates the rule accepting the listed scopes (any scope,
+    /// if the list is empty) and, unless required, no scope.
+    pub fn new(scopes: Vec<String>, required: bool) -> Self {
+        Self { scopes, required }
+    }
+
+    fn is_known(&self, scope: &str) -> bool {
+        self.scopes.is_empty() || self.scopes.iter().any(|known| known == scope)
+    }
+}
+
+impl Rule for ScopeRule {
+    fn name(&self) -> &'static str {
+        "ScopeRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Checks that the scope of the Conventional Commit (`fix(parser): ...`) is one of the configured components of the project, and that it is present, if required."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("unknown or missing scope")
+    }
+
+    fn score(&self, commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+        let subject = commit.msg_info().subject().unwrap_or("");
+
+        // The multiple scopes are listed like "fix(api,cli): ...".
+        let known = match conventional_scope(subject) {
+            None => true,
+            Some(None) => !self.required,
+            Some(Some(scopes)) => scopes.split(',').all(|scope| self.is_known(scope.trim())),
+        };
+
+        if known {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// The breaking change must be explained: the Conventional Commit marked
+/// as breaking (`feat!: ...`) must have the `BREAKING CHANGE:` footer
+/// describing what breaks and how to migrate, and the footer itself must
+/// not be empty. Otherwise, the users learn about the change the hard way.
+pub struct BreakingChangeRule;
+
+impl Rule for BreakingChangeRule {
+    fn name(&self) -> &'static str {
+        "BreakingChangeRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Checks that the breaking changes are explained: the commit marked as breaking (`feat!: ...`) has the `BREAKING CHANGE:` footer, and the footer is not empty."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("breaking change unexplained")
+    }
+
+    fn score(&self, commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+        let msg_info = commit.msg_info();
+        let marked = is_breaking(msg_info.subject().unwrap_or(""));
+
+        match (marked, msg_info.breaking_change_len()) {
+            (_, Some(0)) | (true, None) => 0.0,
+            _ => 1.0,
+        }
+    }
+}
+
+/// This rule grants some additional score for the structured body:
+/// multiple paragraphs, bullet lists or sections like "Why:"/"What:".
+///
+/// The well-organized body is much easier to read than the wall of
+/// text of the same length. Like `MetadataLinesRule`, this one is
+/// a little bonus and is expected to have very low weight.
+pub struct StructuredBodyRule;
+
+impl Rule for StructuredBodyRule {
+    fn name(&self) -> &'static str {
+        "StructuredBodyRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Grants a small bonus for the structured body: multiple paragraphs, bullet lists or sections like \"Why:\" and \"What:\"."
+    }
+
+    fn score(&self, commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+        let msg_info = commit.msg_info();
+
+        let features = [
+            msg_info.body_paragraphs() > 1,
+            msg_info.body_bullets() > 0,
+            msg_info.body_sections() > 0,
+        ];
+
+        match features.iter().filter(|present| **present).count() {
+            0 => 0.0,
+            1 => 0.6,
+            2 => 0.8,
+            _ => 1.0,
+        }
+    }
+}
+
+/// The trailing whitespace and the control characters are invisible in
+/// most editors, but break the email-based patch workflows (the patches
+/// fail to apply or get mangled) and are rendered as garbage by the web
+/// interfaces. The tabs in the subject are misaligned in `git log` and
+/// in the subjects of the emails.
+pub struct MessageHygieneRule;
+
+impl Rule for MessageHygieneRule {
+    fn name(&self) -> &'static str {
+        "MessageHygieneRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Penalizes the trailing whitespace, the control characters and the tabs in the subject, which break the email-based and web-based tools."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("stray whitespace or control characters")
+    }
+
+    fn score(&self, commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+        match commit.msg_info().dirty_lines() {
+            0 => 1.0,
+            1 => 0.5,
+            _ => 0.0,
+        }
+    }
+}
+
+/// This rule penalizes the walls of text: the long bodies written as
+/// the single paragraph are hard to read even when properly wrapped,
+/// so the prose longer than `MAX_PARAGRAPH_LINES` should be split into
+/// paragraphs, lists or sections.
+pub struct ParagraphLenRule;
 
-    !classes.intersection(*SPECIAL_CLASSES).is_empty()
+const MAX_PARAGRAPH_LINES: usize = 15;
+
+impl Rule for ParagraphLenRule {
+    fn name(&self) -> &'static str {
+        "ParagraphLenRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Penalizes the walls of text: the paragraphs longer than 15 lines, which should be split into the smaller paragraphs or lists."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("wall of text")
+    }
+
+    fn score(&self, commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+        let longest = commit.msg_info().longest_paragraph_lines();
+
+        if longest <= MAX_PARAGRAPH_LINES {
+            return 1.0;
+        }
+
+        // The score fades out until the paragraph is twice as long.
+        let excess = (longest - MAX_PARAGRAPH_LINES) as f32 / MAX_PARAGRAPH_LINES as f32;
+        (1.0 - excess).max(0.0)
+    }
 }
 
-// Commits of some classes are scored in relaxed fashion.
-// Most rules use the same set of such special classes,
-// so let's predefine this set here.
+/// This rule checks the subject of the merge commit, which should
+/// say what is merged and why, rather than stating the branch name.
+///
+/// Subjects generated by Git or hosting platforms ("Merge branch 'x'",
+/// "Merge pull request #N from ...") get no score, while the custom
+/// ones are scored by length like the subjects of ordinary commits.
+pub struct MergeSubjectRule;
+
+impl Rule for MergeSubjectRule {
+    fn name(&self) -> &'static str {
+        "MergeSubjectRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Checks the subject of the merge commit: the subjects generated by Git or the hosting platforms get no score, while the custom ones are scored by length."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("generated merge subject")
+    }
+
+    fn score(&self, commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+        let subject = commit.msg_info().subject().unwrap_or("");
+
+        if GENERATED_MERGE_SUBJECT.is_match(subject) {
+            0.0
+        } else {
+            SubjectLimits::default().curve().score(subject.len() as f32)
+        }
+    }
+}
+
+/// This rule checks the body of the merge commit for the notes.
+///
+/// Merges are not obliged to have the body, though it is welcome.
+/// However, if the merge had conflicts, the resolution should be
+/// explained, and the bare list of conflicting files generated by
+/// Git is not an explanation.
+pub struct MergeBodyRule;
+
+impl Rule for MergeBodyRule {
+    fn name(&self) -> &'static str {
+        "MergeBodyRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Checks the body of the merge commit: the merges with conflicts should explain the resolution rather than list the conflicting files."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("merge conflicts not explained")
+    }
+
+    fn score(&self, commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+        let msg_info = commit.msg_info();
+
+        let notes_len = msg_info.body_len().saturating_sub(msg_info.conflicts_len());
+
+        if notes_len > 0 {
+            1.0
+        } else if msg_info.conflicts_len() > 0 {
+            0.0
+        } else {
+            0.5
+        }
+    }
+}
+
+/// This rule penalizes the author for copy-pasting the same message
+/// to several commits ("fix", "fix", "fix" streaks), as the identical
+/// messages hardly describe the different changes.
+///
+/// The rule remembers the messages seen during the run, so the most
+/// recent commit with the message is not penalized, while all the
+/// older ones of the same author are.
+///
+/// The memory may be bounded by remembering only the specified number
+/// of the most recent messages, so the duplicates of the messages seen
+/// long ago are not detected anymore.
+pub struct DuplicateMessageRule {
+    seen: RefCell<HashSet<(Arc<str>, u64)>>,
+
+    /// The remembered messages in the order of appearance,
+    /// which is tracked only if the capacity is limited.
+    order: RefCell<VecDeque<(Arc<str>, u64)>>,
+    capacity: Option<usize>,
+}
+
+impl DuplicateMessageRule {
+    pub fn new() -> Self {
+        Self {
+            seen: RefCell::new(HashSet::new()),
+            order: RefCell::new(VecDeque::new()),
+            capacity: None,
+        }
+    }
+
+    /// Creates the rule, which remembers at most
+    /// `capacity` of the most recent messages.
+    pub fn bounded(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..Self::new()
+        }
+    }
+}
+
+impl Rule for DuplicateMessageRule {
+    fn name(&self) -> &'static str {
+        "DuplicateMessageRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Penalizes the same message copied to several commits of the author: only the most recent of them is not penalized."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("duplicate message")
+    }
+
+    fn score(&self, commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+        let key = (
+            commit.metadata().author_shared().clone(),
+            commit.msg_info().digest(),
+        );
+
+        let mut seen = self.seen.borrow_mut();
+        if seen.contains(&key) {
+            return 0.0;
+        }
+
+        if let Some(capacity) = self.capacity {
+            let mut order = self.order.borrow_mut();
+            if order.len() >= capacity {
+                if let Some(oldest) = order.pop_front() {
+                    seen.remove(&oldest);
+                }
+            }
+
+            order.push_back(key.clone());
+        }
+
+        seen.insert(key);
+
+        1.0
+    }
+
+    fn reset(&self) {
+        self.seen.borrow_mut().clear();
+        self.order.borrow_mut().clear();
+    }
+}
+
+/// The subject must say something besides the issue IDs.
+///
+/// Ugly commits specify a ticket/issue ID as the subject ("JIRA-1234"),
+/// maybe with some noise ("JIRA-1234 fix"). These are long enough to get
+/// the points for the subject length, but tell nothing without the
+/// issue tracker at hand. The same applies to single-word subjects.
+///
+/// The issue IDs are recognized by the regex, which may be configured
+/// for the conventions of the team.
+pub struct IssueSubjectRule {
+    issue_pattern: Regex,
+}
+
+impl IssueSubjectRule {
+    pub fn new(issue_pattern: Regex) -> Self {
+        Self { issue_pattern }
+    }
+}
+
+impl Default for IssueSubjectRule {
+    fn default() -> Self {
+        Self::new(DEFAULT_ISSUE_ID.clone())
+    }
+}
+
+impl Rule for IssueSubjectRule {
+    fn name(&self) -> &'static str {
+        "IssueSubjectRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Checks that the subject says something besides the issue IDs: \"JIRA-1234\" or \"JIRA-1234 fix\" is not a subject, as it says nothing without the issue tracker."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("subject is just an issue ID")
+    }
+
+    fn score(&self, commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+        let subject = commit.msg_info().subject().unwrap_or("");
+        let rest = self.issue_pattern.replace_all(subject, " ");
+
+        if words(&rest).nth(1).is_none() {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
+
+/// The subject must not be just the name of the branch.
+///
+/// The web interfaces and the lazy squash merges often leave the branch
+/// name as the subject ("feature/login-page-fix-2"): it is long enough
+/// to get the points for the subject length, but it is the slug for the
+/// machines rather than the description for the humans.
+pub struct BranchSubjectRule;
+
+impl Rule for BranchSubjectRule {
+    fn name(&self) -> &'static str {
+        "BranchSubjectRule"
+    }
+
+    fn describe(&self) -> &'static str {
+        "Checks that the subject is not just the branch name like \"feature/login-page-fix-2\"."
+    }
+
+    fn problem(&self) -> Option<&'static str> {
+        Some("subject is just a branch name")
+    }
+
+    fn score(&self, commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+        let subject = commit.msg_info().subject().unwrap_or("");
+
+        if BRANCH_SLUG.is_match(subject.trim()) {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
+
+/// The minimum number of letters in the subject for
+/// considering it written in capital letters.
+const MIN_ALL_CAPS_LETTERS: usize = 8;
+
+/// The minimum diff size and the number of top-level directories
+/// for considering the commit as spread over the code base.
+const MULTI_PURPOSE_DIFF: usize = 200;
+const MULTI_PURPOSE_DIRS: usize = 3;
+
 lazy_static! {
-    static ref SPECIAL_CLASSES: EnumSet<Class> = {
-        let mut special_set = EnumSet::new();
+    static ref EXCESSIVE_PUNCTUATION: Regex = patterns::builtin(r"[!?]{2,}");
+
+    /// The branch names: the path-like ones ("feature/login") and the
+    /// slugs of three or more words ("login-page-fix"). The dots are not
+    /// allowed in the latter, so the versions like "v1.2.3-rc1" pass.
+    static ref BRANCH_SLUG: Regex = patterns::builtin(r"^[\w.-]+/[\w./-]+$|^\w+(?:[-_]\w+){2,}$");
+    static ref GENERATED_MERGE_SUBJECT: Regex = patterns::builtin(
+        r"^Merge (branch|branches|remote-tracking branch|tag|commit|pull request #\d+|[0-9a-f]{7,40}) "
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commit::MessageInfo;
+
+    #[test]
+    fn all_caps_subjects_are_detected() {
+        assert!(is_all_caps("FIX THE BUILD"));
+        assert!(!is_all_caps("Fix the BUILD"));
+        assert!(!is_all_caps("FIX CI"));
+    }
+
+    #[test]
+    fn subject_conjunctions_are_detected() {
+        let check = |subject: &str| {
+            let conjunctions = MessageInfo::new(subject).language().keywords().conjunctions;
+            has_conjunction(subject, conjunctions)
+        };
+
+        assert!(check("Rework the parser and fix the CLI"));
+        assert!(check("Rework the parser; fix the CLI"));
+        assert!(!check("Expand the command line parser"));
+        assert!(check("Baue den Parser um und repariere die CLI"));
+    }
+
+    #[test]
+    fn issue_id_subjects_are_detected() {
+        use crate::commit::{CommitId, Metadata};
+
+        let score = |subject: &str| {
+            let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+            let commit = Commit::new_from_message(metadata, MessageInfo::new(subject));
+            IssueSubjectRule::default().score(&commit, &ScoringPolicy::default())
+        };
+
+        assert_eq!(score("JIRA-1234"), 0.0);
+        assert_eq!(score("JIRA-1234 fix"), 0.0);
+        assert_eq!(score("[PROJ-42] #17"), 0.0);
+        assert_eq!(score("Refactoring"), 0.0);
+        assert_eq!(score("JIRA-1234: Fix the login form"), 1.0);
+        assert_eq!(score("Fix the parser"), 1.0);
+    }
+
+    #[test]
+    fn unknown_scopes_are_detected() {
+        use crate::commit::{CommitId, Metadata};
 
-        special_set.insert(Class::Short);
-        special_set.insert(Class::Refactor);
-        special_set.insert(Class::Initial);
+        let score = |rule: &ScopeRule, subject: &str| {
+            let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+            let commit = Commit::new_from_message(metadata, MessageInfo::new(subject));
+            rule.score(&commit, &ScoringPolicy::default())
+        };
 
-        special_set
-    };
+        let rule = ScopeRule::new(vec!["api".into(), "cli".into()], false);
+        assert_eq!(score(&rule, "fix(api): reject the empty input"), 1.0);
+        assert_eq!(score(&rule, "fix(api, cli): reject the empty input"), 1.0);
+        assert_eq!(score(&rule, "fix(db): reject the empty input"), 0.0);
+        assert_eq!(score(&rule, "fix: reject the empty input"), 1.0);
+        assert_eq!(score(&rule, "Reject the empty input"), 1.0);
+
+        let required = ScopeRule::new(Vec::new(), true);
+        assert_eq!(score(&required, "fix: reject the empty input"), 0.0);
+        assert_eq!(score(&required, "fix(db): reject the empty input"), 1.0);
+    }
+
+    #[test]
+    fn body_len_score_is_monotonic() {
+        let models = [
+            BodyLenModel::default(),
+            BodyLenModel::Log(1.5),
+            BodyLenModel::Lines(50.0),
+        ];
+
+        for &model in models.iter() {
+            let rule = BodyLenRule::default().with_model(model);
+            let score = |body_len, diff_size| rule.curve.score(model.ratio(body_len, diff_size));
+
+            for diff_size in (0..2000).step_by(37) {
+                for body_len in (0..3000).step_by(41) {
+                    let current = score(body_len, diff_size);
+
+                    assert!((0.0..=1.0).contains(&current));
+                    assert!(score(body_len + 41, diff_size) >= current);
+                    assert!(score(body_len, diff_size + 37) <= current);
+                }
+
+                assert_eq!(score(0, diff_size), 0.0);
+            }
+        }
+    }
+
+    #[test]
+    fn body_len_models_are_calibrated() {
+        let score = |model: BodyLenModel, body_len, diff_size| {
+            BodyLenRule::default()
+                .curve
+                .score(model.ratio(body_len, diff_size))
+        };
+
+        // One paragraph is enough for the medium diff by default.
+        assert_eq!(score(BodyLenModel::default(), 300, 250), 1.0);
+        assert!(score(BodyLenModel::default(), 50, 250) < 1.0);
+        assert_eq!(score(BodyLenModel::Log(1.5), 50, 250), 1.0);
+
+        assert_eq!(score(BodyLenModel::Lines(50.0), 360, 250), 1.0);
+        assert_eq!(score(BodyLenModel::Lines(50.0), 180, 250), 0.5);
+
+        assert_eq!("log".parse(), Ok(BodyLenModel::Log(1.0)));
+        assert_eq!("lines:50".parse(), Ok(BodyLenModel::Lines(50.0)));
+        assert!("lines".parse::<BodyLenModel>().is_err());
+        assert!("log:-1".parse::<BodyLenModel>().is_err());
+        assert!("cubic".parse::<BodyLenModel>().is_err());
+    }
+
+    #[test]
+    fn mostly_binary_changes_need_no_long_body() {
+        use crate::commit::{CommitId, DiffInfo, Metadata, ParseOptions};
+
+        let score = |binary_files| {
+            let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+            let msg_info = MessageInfo::new("Update the icons\n\nThe old ones were blurry.");
+            let diff_info = DiffInfo::new(4000, 1000).with_files(4, binary_files);
+            let commit = Commit::new(metadata, diff_info, msg_info, &ParseOptions::default());
+            BodyLenRule::default().score(&commit, &ScoringPolicy::strict())
+        };
+
+        assert!(score(1) < 0.5);
+        assert_eq!(score(3), 1.0);
+    }
+
+    #[test]
+    fn large_changes_with_references_get_bonus() {
+        use crate::commit::{CommitId, DiffInfo, Metadata, ParseOptions};
+
+        let score = |message: &str, diff: usize| {
+            let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+            let msg_info = MessageInfo::new(message);
+            let diff_info = DiffInfo::new(diff, 0);
+            let commit = Commit::new(metadata, diff_info, msg_info, &ParseOptions::default());
+            ReferenceRule.score(&commit, &ScoringPolicy::default())
+        };
+
+        let linked = "Rework the parser\n\nSee https://example.com/design for details.";
+        let unlinked = "Rework the parser\n\nThe parser is rewritten from scratch.";
+
+        assert_eq!(score(linked, 1000), 1.0);
+        assert_eq!(score(unlinked, 1000), 0.0);
+        assert_eq!(score(linked, 100), 0.0);
+        assert_eq!(ReferenceRule.problem(), None);
+    }
+
+    #[test]
+    fn vague_subjects_are_detected() {
+        use crate::commit::{CommitId, Metadata};
+
+        let score = |subject: &str| {
+            let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+            let commit = Commit::new_from_message(metadata, MessageInfo::new(subject));
+            VagueSubjectRule.score(&commit, &ScoringPolicy::default())
+        };
+
+        assert_eq!(score("Minor changes"), 0.0);
+        assert_eq!(score("Fix stuff"), 0.0);
+        assert_eq!(score("Fix the parser crash on empty input"), 1.0);
+        assert_eq!(score(""), 1.0);
+    }
+
+    #[test]
+    fn subject_limits_are_parsed() {
+        assert_eq!(
+            "10,20,50,72"
+                .parse::<SubjectLimits>()
+                .map(SubjectLimits::curve),
+            Ok(Curve::new(10.0, 20.0, 50.0, 72.0))
+        );
+        assert_eq!(
+            " 10, 20, 20, 72"
+                .parse::<SubjectLimits>()
+                .map(SubjectLimits::curve),
+            Ok(Curve::new(10.0, 20.0, 20.0, 72.0))
+        );
+
+        assert!("10,20,50".parse::<SubjectLimits>().is_err());
+        assert!("20,10,50,72".parse::<SubjectLimits>().is_err());
+        assert!("10,20,72,72".parse::<SubjectLimits>().is_err());
+        assert!("10,20,inf,inf".parse::<SubjectLimits>().is_err());
+        assert!("10,20.5,50,72".parse::<SubjectLimits>().is_err());
+    }
+
+    #[test]
+    fn branch_name_subjects_are_detected() {
+        use crate::commit::{CommitId, Metadata};
+
+        let score = |subject: &str| {
+            let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+            let commit = Commit::new_from_message(metadata, MessageInfo::new(subject));
+            BranchSubjectRule.score(&commit, &ScoringPolicy::default())
+        };
+
+        assert_eq!(score("feature/login-page-fix-2"), 0.0);
+        assert_eq!(score("login_page_fix"), 0.0);
+        assert_eq!(score("Fix the login page"), 1.0);
+        assert_eq!(score("v1.2.3-rc1"), 1.0);
+        assert_eq!(score("fix(login/page): handle the empty password"), 1.0);
+    }
+
+    #[test]
+    fn walls_of_text_are_penalized() {
+        use crate::commit::{CommitId, Metadata};
+
+        let score = |lines: usize, paragraphs: usize| {
+            let paragraph = vec!["Some words of the explanation."; lines].join("\n");
+            let body = vec![paragraph; paragraphs].join("\n\n");
+            let message = format!("Fix the parser\n\n{}", body);
+
+            let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+            let commit = Commit::new_from_message(metadata, MessageInfo::new(&message));
+            ParagraphLenRule.score(&commit, &ScoringPolicy::default())
+        };
+
+        assert_eq!(score(10, 3), 1.0);
+        assert_eq!(score(15, 1), 1.0);
+        assert!(score(20, 1) < 1.0);
+        assert_eq!(score(30, 1), 0.0);
+    }
+
+    #[test]
+    fn merge_conflicts_need_notes() {
+        use crate::commit::{CommitId, Metadata};
+
+        let score = |message: &str| {
+            let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+            let commit = Commit::new_from_message(metadata, MessageInfo::new(message));
+            MergeBodyRule.score(&commit, &ScoringPolicy::default())
+        };
+
+        assert_eq!(score("Merge branch 'main'\n\nConflicts:\n\tmain"), 0.0);
+        assert_eq!(score("Merge the main conflicts\n\nConflicts:\n\tmain"), 0.5);
+        assert_eq!(
+            score("Merge branch 'main'\n\nConflicts:\n\tmain\n\nKept the new API."),
+            1.0
+        );
+    }
+
+    #[test]
+    fn unexplained_breaking_changes_are_detected() {
+        use crate::commit::{CommitId, Metadata};
+
+        let score = |message: &str| {
+            let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+            let commit = Commit::new_from_message(metadata, MessageInfo::new(message));
+            BreakingChangeRule.score(&commit, &ScoringPolicy::default())
+        };
+
+        assert_eq!(
+            score(
+                "feat!: drop v1
+
+BREAKING CHANGE: use v2 instead"
+            ),
+            1.0
+        );
+        assert_eq!(
+            score(
+                "feat!: drop v1
+
+The v1 is unsupported."
+            ),
+            0.0
+        );
+        assert_eq!(
+            score(
+                "feat: drop v1
+
+BREAKING CHANGE:"
+            ),
+            0.0
+        );
+        assert_eq!(score("feat: add v2"), 1.0);
+    }
+
+    #[test]
+    fn bounded_duplicate_rule_forgets_old_messages() {
+        use crate::commit::{CommitId, Metadata};
+
+        let commit = |message: &str| {
+            let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+            Commit::new_from_message(metadata, MessageInfo::new(message))
+        };
+        let policy = ScoringPolicy::default();
+
+        let rule = DuplicateMessageRule::bounded(2);
+        let scores: Vec<f32> = ["Fix", "Fix", "Add foo", "Add bar", "Fix"]
+            .iter()
+            .map(|message| rule.score(&commit(message), &policy))
+            .collect();
+
+        assert_eq!(scores, vec![1.0, 0.0, 1.0, 1.0, 1.0]);
+    }
 }
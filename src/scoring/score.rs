@@ -1,40 +1,137 @@
-use crate::scoring::grade::Grade;
+use crate::scoring::grade::{Grade, GradeStyle};
+
+use std::str::FromStr;
 
 #[derive(Clone, Copy, Debug)]
 pub enum Score {
-    Ignored,
+    Ignored(IgnoreReason),
+
+    Scored { score: u8, grade: Grade },
+}
+
+/// The reason, why the commit was not scored.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IgnoreReason {
+    /// The merge commit, while the merges are not scored.
+    Merge,
+
+    /// The message contains the ignore marker.
+    Requested,
+
+    /// The commit is exempted from all the rules by its classes.
+    Exempt,
+}
+
+impl IgnoreReason {
+    /// The one-letter code of the reason, which is shown
+    /// instead of the grade, e.g. "-M".
+    pub fn code(self) -> char {
+        match self {
+            IgnoreReason::Merge => 'M',
+            IgnoreReason::Requested => 'I',
+            IgnoreReason::Exempt => 'C',
+        }
+    }
 
-    Scored {
-        score: u8,
-        grade: Grade,
-    },
+    /// The name of the reason for the machine-readable output.
+    pub fn name(self) -> &'static str {
+        match self {
+            IgnoreReason::Merge => "merge",
+            IgnoreReason::Requested => "requested",
+            IgnoreReason::Exempt => "exempt",
+        }
+    }
 }
 
 impl Score {
-    pub fn to_string(self, use_score: bool) -> String {
+    pub fn to_string(self, format: ScoreFormat) -> String {
+        match (self, format) {
+            (Self::Ignored(reason), _) => format!("-{}", reason.code()),
+            (Self::Scored { score, .. }, ScoreFormat::Score(scale)) => scale.render(score),
+            (Self::Scored { score, grade }, ScoreFormat::Grade(style)) => {
+                style.render(score, grade)
+            }
+        }
+    }
+}
+
+/// The way the scores are displayed: either as the numeric
+/// scores in the scale, or as the grades in the style.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScoreFormat {
+    Score(ScoreScale),
+    Grade(GradeStyle),
+}
+
+/// The scale of the displayed numeric scores. The scores are always
+/// calculated from 0 to 100, and are converted for displaying only.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScoreScale {
+    Hundred,
+
+    /// From 0.0 to 10.0, with one decimal place.
+    Ten,
+
+    /// From 0 to 5 whole stars.
+    Stars,
+}
+
+impl ScoreScale {
+    /// The score converted to the scale.
+    pub fn value(self, score: u8) -> f32 {
         match self {
-            Self::Ignored => "-".to_string(),
-            Self::Scored { score, grade } => {
-                if use_score {
-                    format!("{}", score)
-                } else {
-                    format!("{:?}", grade)
-                }
+            ScoreScale::Hundred => f32::from(score),
+            ScoreScale::Ten => f32::from(score) / 10.0,
+            ScoreScale::Stars => (f32::from(score) / 20.0).round(),
+        }
+    }
+
+    pub fn render(self, score: u8) -> String {
+        match self {
+            ScoreScale::Hundred => score.to_string(),
+            ScoreScale::Ten => format!("{:.1}", self.value(score)),
+            ScoreScale::Stars => {
+                let stars = self.value(score) as usize;
+                format!("{}{}", "★".repeat(stars), "☆".repeat(5 - stars))
             }
         }
     }
 }
 
+impl FromStr for ScoreScale {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "100" => Ok(ScoreScale::Hundred),
+            "10" => Ok(ScoreScale::Ten),
+            "5" | "stars" => Ok(ScoreScale::Stars),
+            _ => Err("scale must be one of: 100, 10, stars"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn ignored_score_is_rendered_as_dash() {
-        let score = Score::Ignored;
+    fn ignored_score_is_rendered_as_reason() {
+        let score = Score::Ignored(IgnoreReason::Merge);
 
-        assert_eq!(score.to_string(true), "-");
-        assert_eq!(score.to_string(false), "-");
+        assert_eq!(
+            score.to_string(ScoreFormat::Score(ScoreScale::Hundred)),
+            "-M"
+        );
+        assert_eq!(
+            score.to_string(ScoreFormat::Grade(GradeStyle::Letter)),
+            "-M"
+        );
+        assert_eq!(
+            Score::Ignored(IgnoreReason::Requested)
+                .to_string(ScoreFormat::Grade(GradeStyle::Letter)),
+            "-I"
+        );
     }
 
     #[test]
@@ -44,7 +141,7 @@ mod tests {
             grade: Grade::C,
         };
 
-        assert_eq!(score.to_string(false), "C");
+        assert_eq!(score.to_string(ScoreFormat::Grade(GradeStyle::Letter)), "C");
     }
 
     #[test]
@@ -54,6 +151,26 @@ mod tests {
             grade: Grade::C,
         };
 
-        assert_eq!(score.to_string(true), "42");
+        assert_eq!(
+            score.to_string(ScoreFormat::Score(ScoreScale::Hundred)),
+            "42"
+        );
+    }
+
+    #[test]
+    fn score_is_rendered_in_scale() {
+        let score = Score::Scored {
+            score: 73,
+            grade: Grade::B,
+        };
+
+        assert_eq!(score.to_string(ScoreFormat::Score(ScoreScale::Ten)), "7.3");
+        assert_eq!(
+            score.to_string(ScoreFormat::Score(ScoreScale::Stars)),
+            "★★★★☆"
+        );
+        assert_eq!(ScoreScale::Stars.render(100), "★★★★★");
+        assert_eq!(ScoreScale::Stars.render(9), "☆☆☆☆☆");
+        assert_eq!(ScoreScale::Ten.render(100), "10.0");
     }
 }
@@ -1,22 +1,81 @@
-use crate::commit::{Class, Commit};
-use crate::scoring::{grade::Grade, rule::Rule, score::Score};
+use crate::commit::{Class, Commit, CommitId, DiffInfo, MessageInfo, Metadata, ParseOptions};
+use crate::scoring::{
+    grade::Grade,
+    policy::ScoringPolicy,
+    relative::RelativeGrading,
+    rule::{Rule, RuleInput},
+    score::Score,
+    severity::Severity,
+    IgnoreReason,
+};
+
+use std::cmp::Ordering;
+
+/// The version of the scoring algorithm, which is stored along with
+/// the scores (e.g. in the JSON output), so the scores produced by
+/// different versions are not compared silently. It must be bumped
+/// on every change of rules, weights or grade thresholds, which
+/// may affect the scores of the same commits.
+///
+/// Version 2: the rule weights are normalized to sum to 1.0.
+/// Version 3: the issue ID subjects are scored by the dedicated rule.
+/// Version 4: the unexplained breaking changes are penalized.
+/// Version 5: the malformed trailers are penalized.
+/// Version 6: the walls of text are penalized.
+/// Version 7: the stray whitespace and control characters are penalized.
+/// Version 8: the branch name subjects are scored by the dedicated rule.
+/// Version 9: the large commits referencing the discussions get a bonus.
+/// Version 10: the subject length is scored by the smooth curve.
+/// Version 11: the commits changing mostly binary files need no long body.
+/// Version 12: the body length and wrapping weigh as much as before
+/// the duplicate messages were penalized.
+/// Version 13: the rules added after the core ones are weighted lightly.
+/// Version 14: the references grant the bonus instead of the penalty.
+/// Version 15: the commits exempted from every rule are ignored.
+/// Version 16: the vague subjects are scored by the dedicated rule.
+pub const SCORING_VERSION: u32 = 16;
+
+/// The rule score, below which the rule is considered failed,
+/// so its problem is reported.
+const PROBLEM_RULE_SCORE: f32 = 0.5;
 
 pub struct Scorer {
     rules: Vec<ScorerItem>,
+    merge_rules: Vec<ScorerItem>,
+    policy: ScoringPolicy,
 }
 
 pub struct ScorerBuilder {
     rules: Vec<ScorerItem>,
+    merge_rules: Vec<ScorerItem>,
+    policy: ScoringPolicy,
 }
 
 struct ScorerItem {
     rule: Box<dyn Rule>,
+
+    /// The normalized weight.
     weight: f32,
+
+    /// The weight the rule is added with (zero for the warning-only
+    /// rules), for normalizing the weights overridden by the policy.
+    raw_weight: f32,
+
+    severity: Severity,
 }
 
 impl ScorerBuilder {
     pub fn new() -> Self {
-        Self { rules: Vec::new() }
+        Self {
+            rules: Vec::new(),
+            merge_rules: Vec::new(),
+            policy: ScoringPolicy::default(),
+        }
+    }
+
+    pub fn with_policy(mut self, policy: ScoringPolicy) -> Self {
+        self.policy = policy;
+        self
     }
 
     pub fn with_rule<R>(mut self, rule: R, weight: f32) -> Self
@@ -26,55 +85,397 @@ impl ScorerBuilder {
         self.rules.push(ScorerItem {
             rule: Box::new(rule),
             weight,
+            raw_weight: weight,
+            severity: Severity::Score,
+        });
+
+        self
+    }
+
+    /// Adds the rule for scoring merge commits. Merges are not
+    /// scored at all (considered ignored) until some rule is added.
+    pub fn with_merge_rule<R>(mut self, rule: R, weight: f32) -> Self
+    where
+        R: Rule + 'static,
+    {
+        self.merge_rules.push(ScorerItem {
+            rule: Box::new(rule),
+            weight,
+            raw_weight: weight,
+            severity: Severity::Score,
         });
 
         self
     }
 
+    /// Checks whether the rule with the specified name
+    /// (case-insensitive) is added already.
+    pub fn has_rule(&self, name: &str) -> bool {
+        self.rules
+            .iter()
+            .chain(&self.merge_rules)
+            .any(|item| item.rule.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Sets the severity of the rule with the specified name
+    /// (case-insensitive), which is scored by default.
+    pub fn with_severity(mut self, name: &str, severity: Severity) -> Self {
+        for item in self.rules.iter_mut().chain(&mut self.merge_rules) {
+            if item.rule.name().eq_ignore_ascii_case(name) {
+                item.severity = severity;
+            }
+        }
+
+        self
+    }
+
+    /// Builds the scorer with the weights of each rule set (both the
+    /// ordinary and the merge one) scaled to sum to 1.0, so the scores
+    /// always span the whole 0..=100 range.
+    ///
+    /// Panics if the weights are invalid, see `build_checked()`.
     pub fn build(self) -> Scorer {
-        Scorer { rules: self.rules }
+        self.build_checked()
+            .unwrap_or_else(|err| panic!("invalid scorer configuration: {}", err))
+    }
+
+    /// Builds the scorer like `build()` does, but returns an error
+    /// instead of panicking if some weight is negative or not finite,
+    /// or if all the weights of the non-empty rule set are zero.
+    pub fn build_checked(self) -> Result<Scorer, String> {
+        Ok(Scorer {
+            rules: normalize_weights(self.rules)?,
+            merge_rules: normalize_weights(self.merge_rules)?,
+            policy: self.policy,
+        })
     }
 }
 
+/// Drops the disabled rules and scales the weights of the scored ones
+/// to sum to 1.0, while the warning-only rules get no weight at all.
+fn normalize_weights(items: Vec<ScorerItem>) -> Result<Vec<ScorerItem>, String> {
+    let mut items: Vec<_> = items
+        .into_iter()
+        .filter(|item| item.severity != Severity::Off)
+        .collect();
+
+    if let Some(item) = items
+        .iter()
+        .find(|item| !item.weight.is_finite() || item.weight < 0.0)
+    {
+        return Err(format!(
+            "invalid weight {} of {}",
+            item.weight,
+            item.rule.name()
+        ));
+    }
+
+    if items.is_empty() {
+        return Ok(items);
+    }
+
+    for item in &mut items {
+        if item.severity == Severity::Warn {
+            item.weight = 0.0;
+        }
+        item.raw_weight = item.weight;
+    }
+
+    let total: f32 = items.iter().map(|item| item.weight).sum();
+    if total <= 0.0 {
+        return Err("all the rule weights are zero".to_string());
+    }
+
+    for item in &mut items {
+        item.weight /= total;
+    }
+
+    Ok(items)
+}
+
 impl Scorer {
+    pub fn version(&self) -> u32 {
+        SCORING_VERSION
+    }
+
+    /// The descriptions of the rules along with their normalized
+    /// weights: the ordinary rules first, then the merge ones.
+    pub fn rules(&self) -> Vec<RuleInfo> {
+        let info = |merge| {
+            move |item: &ScorerItem| RuleInfo {
+                name: item.rule.name(),
+                description: item.rule.describe(),
+                weight: item.weight,
+                warning: item.severity == Severity::Warn,
+                merge,
+            }
+        };
+
+        self.rules
+            .iter()
+            .map(info(false))
+            .chain(self.merge_rules.iter().map(info(true)))
+            .collect()
+    }
+
+    /// Checks whether scoring of non-merge commits requires the diff. The
+    /// merge rules are not checked, as the diff of merges is never parsed.
+    pub fn needs_diff(&self) -> bool {
+        self.policy.has_overrides()
+            || self.rules.iter().any(|item| match item.rule.input() {
+                RuleInput::Message => false,
+                RuleInput::Classes => self.policy.uses_classes(),
+                RuleInput::Diff => true,
+            })
+    }
+
+    /// Makes the rules forget the commits scored so far, so the commits
+    /// of one traversal are not compared with the ones of another.
+    pub fn reset(&self) {
+        for item in self.rules.iter().chain(&self.merge_rules) {
+            item.rule.reset();
+        }
+    }
+
     pub fn score(&self, commit: Commit) -> ScoredCommit {
-        let score = self.score_internal(&commit);
+        let mut problems = Vec::new();
+        let score = self.score_internal(&commit, &mut problems, None);
 
-        ScoredCommit { commit, score }
+        ScoredCommit {
+            commit,
+            score,
+            problems,
+        }
+    }
+
+    /// Scores the commit like `score()` does, but also returns the scores
+    /// of the individual rules, e.g. for the detailed inspection. The
+    /// ignored commits are not checked against the rules at all.
+    pub fn score_explained(&self, commit: Commit) -> (ScoredCommit, Vec<RuleScore>) {
+        let mut problems = Vec::new();
+        let mut rule_scores = Vec::new();
+        let score = self.score_internal(&commit, &mut problems, Some(&mut rule_scores));
+
+        let scored = ScoredCommit {
+            commit,
+            score,
+            problems,
+        };
+
+        (scored, rule_scores)
+    }
+
+    /// Scores the message alone (e.g. the one being typed in the editor),
+    /// along with the diff it is going to be committed with, if known.
+    /// The scores of the individual rules are returned too, so the
+    /// editors may show what to fix while the message is being written.
+    pub fn score_message(
+        &self,
+        message: &str,
+        diff_info: Option<DiffInfo>,
+        options: &ParseOptions,
+    ) -> ScoredMessage {
+        // The message is scored on its own, not as the part of the history.
+        self.reset();
+
+        let metadata = Metadata::new(MESSAGE_ID, "".into(), 1);
+        let msg_info = MessageInfo::with_options(message, options);
+        let commit = match diff_info {
+            Some(diff_info) => Commit::new(metadata, diff_info, msg_info, options),
+            None => Commit::new_from_message(metadata, msg_info),
+        };
+
+        let (scored, rule_scores) = self.score_explained(commit);
+
+        ScoredMessage {
+            scored,
+            rule_scores,
+        }
     }
 
-    fn score_internal(&self, commit: &Commit) -> Score {
-        if commit.classes().as_set().contains(Class::Merge) {
-            return Score::Ignored;
+    /// Scores the commit, collecting the problems of the failed rules
+    /// (see `Rule::problem()`) and, optionally, the scores of all the rules
+    /// along the way: some rules remember the commits they have seen,
+    /// so the rules cannot be checked again.
+    fn score_internal(
+        &self,
+        commit: &Commit,
+        problems: &mut Vec<&'static str>,
+        mut rule_scores: Option<&mut Vec<RuleScore>>,
+    ) -> Score {
+        if commit.msg_info().ignore_requested() {
+            return Score::Ignored(IgnoreReason::Requested);
         }
 
+        let is_merge = commit.classes().as_set().contains(Class::Merge);
+        let rules = if is_merge {
+            &self.merge_rules
+        } else {
+            &self.rules
+        };
+
+        if is_merge && rules.is_empty() {
+            return Score::Ignored(IgnoreReason::Merge);
+        }
+
+        // The commit exempted from every rule has nothing to be scored for.
+        let exempts: Vec<bool> = if self.policy.has_overrides() {
+            rules
+                .iter()
+                .map(|item| self.policy.is_exempt(commit, item.rule.name()))
+                .collect()
+        } else {
+            vec![false; rules.len()]
+        };
+        if rules.is_empty() || exempts.iter().all(|&exempt| exempt) {
+            return Score::Ignored(IgnoreReason::Exempt);
+        }
+
+        let weights = match self.commit_weights(commit, rules) {
+            Some(weights) => weights,
+            None => return Score::Ignored(IgnoreReason::Exempt),
+        };
+
         let mut score_accum = 0.0;
+        let mut losses = Vec::new();
+
+        for ((item, &weight), &exempt) in rules.iter().zip(&weights).zip(&exempts) {
+            let rule_score = if exempt {
+                1.0
+            } else {
+                item.rule.score(commit, &self.policy)
+            };
+
+            score_accum += 100.0 * rule_score * weight;
+
+            if let Some(rule_scores) = rule_scores.as_deref_mut() {
+                rule_scores.push(RuleScore {
+                    name: item.rule.name(),
+                    weight,
+                    score: rule_score,
+                    exempt,
+                    warning: item.severity == Severity::Warn,
+                    problem: item
+                        .rule
+                        .problem()
+                        .filter(|_| rule_score < PROBLEM_RULE_SCORE),
+                });
+            }
 
-        for item in &self.rules {
-            score_accum += 100.0 * item.rule.score(commit) * item.weight;
+            if rule_score < PROBLEM_RULE_SCORE {
+                if let Some(problem) = item.rule.problem() {
+                    losses.push((problem, weight * (1.0 - rule_score)));
+                }
+            }
         }
 
+        // The most costly problems go first, so the problems of the
+        // warning-only rules, which cost nothing, go last.
+        losses.sort_by(|(_, first), (_, second)| {
+            second.partial_cmp(first).unwrap_or(Ordering::Equal)
+        });
+        problems.extend(losses.into_iter().map(|(problem, _)| problem));
+
         let score = if score_accum > 100.0 {
             100
         } else {
             score_accum.round() as u8
         };
 
-        let grade = match score {
-            0..=19 => Grade::F,
-            20..=39 => Grade::D,
-            40..=59 => Grade::C,
-            60..=79 => Grade::B,
-            _ => Grade::A,
-        };
+        Score::Scored {
+            score,
+            grade: Grade::from_score(score),
+        }
+    }
+}
+
+impl Scorer {
+    /// The normalized weights of the rules for the commit: the weights
+    /// overridden by the policy for the classes of the commit replace
+    /// the configured ones, and all the weights are normalized again.
+    /// Returns `None`, if all the weights are overridden to zero.
+    fn commit_weights(&self, commit: &Commit, rules: &[ScorerItem]) -> Option<Vec<f32>> {
+        let overrides: Vec<_> = rules
+            .iter()
+            .map(|item| match item.severity {
+                Severity::Warn => None,
+                _ => self.policy.weight_override(commit, item.rule.name()),
+            })
+            .collect();
+
+        if overrides.iter().all(Option::is_none) {
+            return Some(rules.iter().map(|item| item.weight).collect());
+        }
+
+        // The overrides are in the units of the normalized weights.
+        let raw_total: f32 = rules.iter().map(|item| item.raw_weight).sum();
+        let raw_weights: Vec<f32> = rules
+            .iter()
+            .zip(overrides)
+            .map(|(item, weight)| weight.map_or(item.raw_weight, |weight| weight * raw_total))
+            .collect();
+
+        let total: f32 = raw_weights.iter().sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        Some(raw_weights.iter().map(|weight| weight / total).collect())
+    }
+}
+
+/// The description of the rule registered in the scorer.
+#[derive(Debug)]
+pub struct RuleInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+
+    /// The normalized weight of the rule.
+    pub weight: f32,
+
+    /// Whether the rule only reports its problem, granting no points.
+    pub warning: bool,
+
+    /// Whether the rule scores the merge commits.
+    pub merge: bool,
+}
+
+/// The score of the individual rule for the commit.
+#[derive(Debug)]
+pub struct RuleScore {
+    pub name: &'static str,
+
+    /// The normalized weight of the rule.
+    pub weight: f32,
+
+    /// The score from 0.0 to 1.0.
+    pub score: f32,
+
+    /// Whether the commit is exempted from the rule by its classes
+    /// (so it gets the full score).
+    pub exempt: bool,
+
+    /// Whether the rule only reports its problem, granting no points.
+    pub warning: bool,
+
+    /// The problem of the commit, if the rule is failed.
+    pub problem: Option<&'static str>,
+}
+
+/// The placeholder ID of the message scored without the commit.
+const MESSAGE_ID: CommitId = CommitId::Placeholder("(message)");
 
-        Score::Scored { score, grade }
+impl RuleScore {
+    /// The points of the total score granted by the rule.
+    pub fn points(&self) -> f32 {
+        100.0 * self.score * self.weight
     }
 }
 
 pub struct ScoredCommit {
     commit: Commit,
     score: Score,
+    problems: Vec<&'static str>,
 }
 
 impl ScoredCommit {
@@ -85,4 +486,355 @@ impl ScoredCommit {
     pub fn score(&self) -> Score {
         self.score
     }
+
+    /// The problems of the commit, which cost it the most points
+    /// first. Only the rules scored below the half are reported.
+    pub fn problems(&self) -> &[&'static str] {
+        &self.problems
+    }
+
+    /// Replaces the absolute grade with the relative one.
+    pub fn regrade(mut self, grading: &RelativeGrading) -> Self {
+        self.score = grading.regrade(self.score);
+        self
+    }
+}
+
+/// The message scored by `Scorer::score_message()` along with the
+/// scores of the individual rules.
+pub struct ScoredMessage {
+    scored: ScoredCommit,
+    rule_scores: Vec<RuleScore>,
+}
+
+impl ScoredMessage {
+    /// The message as the scored commit, which has no ID and no author.
+    pub fn scored(&self) -> &ScoredCommit {
+        &self.scored
+    }
+
+    /// The scores of all the rules, including the passed ones.
+    pub fn rule_scores(&self) -> &[RuleScore] {
+        &self.rule_scores
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        commit::{CommitId, MessageInfo, Metadata},
+        scoring::policy::ClassRef,
+    };
+
+    struct HalfRule;
+
+    impl Rule for HalfRule {
+        fn name(&self) -> &'static str {
+            "HalfRule"
+        }
+
+        fn describe(&self) -> &'static str {
+            "Always grants the half of the score."
+        }
+
+        fn score(&self, _commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+            0.5
+        }
+    }
+
+    fn score_of(scorer: &Scorer) -> Score {
+        let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+        let commit = Commit::new_from_message(metadata, MessageInfo::new("Add foo"));
+
+        scorer.score(commit).score()
+    }
+
+    #[test]
+    fn weights_are_normalized() {
+        let scorer = ScorerBuilder::new()
+            .with_rule(HalfRule, 0.5)
+            .with_rule(HalfRule, 0.6)
+            .build();
+
+        assert!(matches!(
+            score_of(&scorer),
+            Score::Scored {
+                score: 50,
+                grade: Grade::C
+            }
+        ));
+    }
+
+    #[test]
+    fn ignore_reasons_are_reported() {
+        let scorer = ScorerBuilder::new().with_rule(HalfRule, 1.0).build();
+        let reason = |commit: Commit| match scorer.score(commit).score() {
+            Score::Ignored(reason) => Some(reason),
+            Score::Scored { .. } => None,
+        };
+        let metadata = || Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+
+        let merge = Commit::new_from_merge(metadata(), MessageInfo::new("Merge foo"));
+        assert_eq!(reason(merge), Some(IgnoreReason::Merge));
+
+        let message = MessageInfo::new("Add foo\n\nCommrate-Ignore: yes");
+        let requested = Commit::new_from_message(metadata(), message);
+        assert_eq!(reason(requested), Some(IgnoreReason::Requested));
+
+        let empty_scorer = ScorerBuilder::new().build();
+        let commit = Commit::new_from_message(metadata(), MessageInfo::new("Add foo"));
+        assert!(matches!(
+            empty_scorer.score(commit).score(),
+            Score::Ignored(IgnoreReason::Exempt)
+        ));
+    }
+
+    struct FailingRule(&'static str);
+
+    impl Rule for FailingRule {
+        fn name(&self) -> &'static str {
+            "FailingRule"
+        }
+
+        fn describe(&self) -> &'static str {
+            "Always fails."
+        }
+
+        fn problem(&self) -> Option<&'static str> {
+            Some(self.0)
+        }
+
+        fn score(&self, _commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+            0.0
+        }
+    }
+
+    #[test]
+    fn weights_are_overridden_by_classes() {
+        use crate::{
+            commit::{DiffInfo, ParseOptions},
+            scoring::BodyPresenceRule,
+        };
+
+        let policy = ScoringPolicy::strict()
+            .with_weight(ClassRef::Builtin(Class::Short), "BodyPresenceRule", 0.0)
+            .with_weight(ClassRef::Builtin(Class::Refactor), "HalfRule", 0.0);
+        let scorer = ScorerBuilder::new()
+            .with_rule(HalfRule, 1.0)
+            .with_rule(BodyPresenceRule, 1.0)
+            .with_policy(policy)
+            .build();
+
+        let score = |subject: &str, diff: DiffInfo| {
+            let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+            let msg_info = MessageInfo::new(subject);
+            let commit = Commit::new(metadata, diff, msg_info, &ParseOptions::default());
+
+            match scorer.score(commit).score() {
+                Score::Scored { score, .. } => Some(score),
+                Score::Ignored(_) => None,
+            }
+        };
+
+        assert_eq!(score("Add foo", DiffInfo::new(100, 0)), Some(25));
+        assert_eq!(score("Add foo", DiffInfo::new(1, 0)), Some(50));
+        assert_eq!(score("Rename foo to bar", DiffInfo::new(1, 1)), None);
+    }
+
+    #[test]
+    fn severities_are_applied() {
+        struct FailingRule;
+
+        impl Rule for FailingRule {
+            fn name(&self) -> &'static str {
+                "FailingRule"
+            }
+
+            fn describe(&self) -> &'static str {
+                "Always fails."
+            }
+
+            fn problem(&self) -> Option<&'static str> {
+                Some("failed")
+            }
+
+            fn score(&self, _commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+                0.0
+            }
+        }
+
+        let scored = |severity| {
+            let scorer = ScorerBuilder::new()
+                .with_rule(HalfRule, 1.0)
+                .with_rule(FailingRule, 1.0)
+                .with_severity("failingrule", severity)
+                .build();
+            let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+            let commit = Commit::new_from_message(metadata, MessageInfo::new("Add foo"));
+
+            scorer.score(commit)
+        };
+
+        let score = |severity| match scored(severity).score() {
+            Score::Scored { score, .. } => score,
+            Score::Ignored(_) => panic!("commit is ignored"),
+        };
+
+        assert_eq!(score(Severity::Score), 25);
+        assert_eq!(score(Severity::Warn), 50);
+        assert_eq!(score(Severity::Off), 50);
+
+        assert_eq!(scored(Severity::Warn).problems(), ["failed"]);
+        assert!(scored(Severity::Off).problems().is_empty());
+    }
+
+    #[test]
+    fn costly_problems_go_first() {
+        let scorer = ScorerBuilder::new()
+            .with_rule(FailingRule("minor"), 0.1)
+            .with_rule(HalfRule, 0.5)
+            .with_rule(FailingRule("major"), 0.4)
+            .build();
+
+        let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+        let commit = Commit::new_from_message(metadata, MessageInfo::new("Add foo"));
+
+        assert_eq!(scorer.score(commit).problems(), ["major", "minor"]);
+    }
+
+    #[test]
+    fn rule_scores_are_explained() {
+        let scorer = ScorerBuilder::new()
+            .with_rule(FailingRule("broken"), 0.25)
+            .with_rule(HalfRule, 0.75)
+            .build();
+
+        let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+        let commit = Commit::new_from_message(metadata, MessageInfo::new("Add foo"));
+        let (scored, rule_scores) = scorer.score_explained(commit);
+
+        let names: Vec<_> = rule_scores.iter().map(|rule| rule.name).collect();
+        assert_eq!(names, ["FailingRule", "HalfRule"]);
+        assert_eq!(rule_scores[0].problem, Some("broken"));
+        assert_eq!(rule_scores[1].problem, None);
+
+        let points: f32 = rule_scores.iter().map(RuleScore::points).sum();
+        assert!(
+            matches!(scored.score(), Score::Scored { score, .. } if score == points.round() as u8)
+        );
+    }
+
+    #[test]
+    fn messages_are_scored_afresh() {
+        use crate::scoring::DuplicateMessageRule;
+
+        let scorer = ScorerBuilder::new()
+            .with_rule(DuplicateMessageRule::new(), 1.0)
+            .build();
+        let score = || {
+            let metadata = Metadata::new(CommitId::Placeholder("aaa"), "Foo".into(), 1);
+            scorer.score(Commit::new_from_message(
+                metadata,
+                MessageInfo::new("Add foo"),
+            ))
+        };
+
+        assert_eq!(score().problems(), &[] as &[&str]);
+        assert_eq!(score().problems(), &["duplicate message"]);
+
+        scorer.reset();
+        assert_eq!(score().problems(), &[] as &[&str]);
+
+        let options = ParseOptions::default();
+        let scored = scorer.score_message("Add foo", None, &options);
+        assert_eq!(scored.scored().problems(), &[] as &[&str]);
+    }
+
+    struct DiffRule;
+
+    impl Rule for DiffRule {
+        fn name(&self) -> &'static str {
+            "DiffRule"
+        }
+
+        fn describe(&self) -> &'static str {
+            "Requires the diff."
+        }
+
+        fn score(&self, _commit: &Commit, _policy: &ScoringPolicy) -> f32 {
+            1.0
+        }
+
+        fn input(&self) -> RuleInput {
+            RuleInput::Diff
+        }
+    }
+
+    #[test]
+    fn diff_is_needed_only_by_some_rules() {
+        let scorer = |policy: ScoringPolicy| {
+            ScorerBuilder::new()
+                .with_rule(HalfRule, 1.0)
+                .with_rule(crate::scoring::BodyPresenceRule, 1.0)
+                .with_policy(policy)
+                .build()
+        };
+
+        assert!(!scorer(ScoringPolicy::strict()).needs_diff());
+        assert!(scorer(ScoringPolicy::default()).needs_diff());
+        assert!(scorer(ScoringPolicy::strict().with_pull_request_titles(true)).needs_diff());
+
+        let rules = vec!["HalfRule".to_string()];
+        assert!(
+            scorer(ScoringPolicy::strict().with_exemptions(ClassRef::Custom(0), &rules))
+                .needs_diff()
+        );
+
+        let diff_scorer = ScorerBuilder::new()
+            .with_rule(DiffRule, 1.0)
+            .with_policy(ScoringPolicy::strict())
+            .build();
+        assert!(diff_scorer.needs_diff());
+    }
+
+    #[test]
+    fn invalid_weights_are_rejected() {
+        let checked = |weights: &[f32]| {
+            weights
+                .iter()
+                .fold(ScorerBuilder::new(), |builder, &weight| {
+                    builder.with_rule(HalfRule, weight)
+                })
+                .build_checked()
+                .is_ok()
+        };
+
+        assert!(checked(&[]));
+        assert!(checked(&[0.0, 2.0]));
+        assert!(!checked(&[0.0, 0.0]));
+        assert!(!checked(&[-0.5, 1.5]));
+        assert!(!checked(&[f32::NAN]));
+    }
+
+    #[test]
+    fn messages_are_explained() {
+        let scorer = ScorerBuilder::new()
+            .with_rule(HalfRule, 1.0)
+            .with_rule(DiffRule, 1.0)
+            .build();
+        let options = ParseOptions::default();
+
+        let scored = scorer.score_message("Fix the parser", None, &options);
+        assert!(scored.scored().commit().diff_info().is_none());
+
+        let rule_scores = scored.rule_scores();
+        assert_eq!(rule_scores.len(), 2);
+        assert_eq!(rule_scores[0].name, "HalfRule");
+        assert!((rule_scores[0].score - 0.5).abs() < f32::EPSILON);
+
+        let with_diff =
+            scorer.score_message("Fix the parser", Some(DiffInfo::new(10, 2)), &options);
+        assert!(with_diff.scored().commit().diff_info().is_some());
+    }
 }
@@ -0,0 +1,76 @@
+use std::str::FromStr;
+
+/// Defines, how the rule affects the results. The new rules may be
+/// adopted gradually: first reporting their problems as the warnings,
+/// while the scores stay the same, and then scoring the commits.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    /// The rule grants the points and reports its problem.
+    Score,
+
+    /// The rule only reports its problem, granting no points.
+    Warn,
+
+    /// The rule is not checked at all.
+    Off,
+}
+
+impl FromStr for Severity {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "score" => Ok(Self::Score),
+            "warn" => Ok(Self::Warn),
+            "off" => Ok(Self::Off),
+            _ => Err("rule severity must be one of: score, warn, off"),
+        }
+    }
+}
+
+/// The severity configured for the rule, parsed from `NAME=SEVERITY`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleSeverity {
+    pub name: String,
+    pub severity: Severity,
+}
+
+impl FromStr for RuleSeverity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pos = s
+            .find('=')
+            .ok_or_else(|| format!("'{}' must be in the NAME=SEVERITY form", s))?;
+        let name = s[..pos].trim();
+
+        if name.is_empty() {
+            return Err(format!("the rule name is missing in '{}'", s));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            severity: s[pos + 1..].trim().parse()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rule_severities_are_parsed() {
+        assert_eq!(
+            "SubjectStyleRule=Warn".parse(),
+            Ok(RuleSeverity {
+                name: "SubjectStyleRule".to_string(),
+                severity: Severity::Warn,
+            })
+        );
+
+        assert!("SubjectStyleRule".parse::<RuleSeverity>().is_err());
+        assert!("=off".parse::<RuleSeverity>().is_err());
+        assert!("SubjectStyleRule=never".parse::<RuleSeverity>().is_err());
+    }
+}
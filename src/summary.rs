@@ -0,0 +1,503 @@
+use crate::{
+    commit::{AuthorTime, Commit, DiffInfo},
+    scoring::{Grade, Score, ScoredCommit},
+};
+
+use std::{cmp::Ordering, collections::HashMap, path::Path};
+
+/// Aggregated statistics over some set of scored commits.
+///
+/// Summaries of different sets (e.g. different repositories)
+/// may be combined into the single one via `merge()`.
+#[derive(Clone, Debug, Default)]
+pub struct Summary {
+    scored: usize,
+    ignored: usize,
+    score_total: u64,
+    grades: [usize; 5],
+}
+
+impl Summary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, score: Score) {
+        match score {
+            Score::Ignored(_) => self.ignored += 1,
+            Score::Scored { score, grade } => {
+                self.scored += 1;
+                self.score_total += u64::from(score);
+                self.grades[grade as usize] += 1;
+            }
+        }
+    }
+
+    pub fn merge(&mut self, other: &Summary) {
+        self.scored += other.scored;
+        self.ignored += other.ignored;
+        self.score_total += other.score_total;
+
+        for (count, other_count) in self.grades.iter_mut().zip(other.grades.iter()) {
+            *count += other_count;
+        }
+    }
+
+    pub fn scored(&self) -> usize {
+        self.scored
+    }
+
+    pub fn ignored(&self) -> usize {
+        self.ignored
+    }
+
+    /// Average score of the scored commits. Ignored commits
+    /// do not affect the average.
+    pub fn average(&self) -> Option<f32> {
+        if self.scored == 0 {
+            None
+        } else {
+            Some(self.score_total as f32 / self.scored as f32)
+        }
+    }
+
+    pub fn grade_count(&self, grade: Grade) -> usize {
+        self.grades[grade as usize]
+    }
+}
+
+/// Sorts the named summaries from the worst average score to the best
+/// one (and by the names, if equal). The ones without scored commits
+/// go last.
+pub fn sort_worst_first(summaries: &mut [(String, Summary)]) {
+    summaries.sort_by(|(first_name, first), (second_name, second)| {
+        let first_avg = first.average().unwrap_or(f32::INFINITY);
+        let second_avg = second.average().unwrap_or(f32::INFINITY);
+
+        first_avg
+            .partial_cmp(&second_avg)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| first_name.cmp(second_name))
+    });
+}
+
+/// Summaries of the commits by the directories they touch, e.g. by the
+/// components of the monorepo. The commit is added to the summary of each
+/// directory (at the configured depth), which contains the changed files.
+pub struct PathSummaries {
+    depth: usize,
+    summaries: HashMap<String, Summary>,
+}
+
+impl PathSummaries {
+    /// The name of the "directory" for files shallower than the depth
+    /// in the repository root.
+    pub const ROOT: &'static str = "/";
+
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            summaries: HashMap::new(),
+        }
+    }
+
+    pub fn add<P: AsRef<str>>(&mut self, paths: &[P], score: Score) {
+        let mut prefixes: Vec<_> = paths
+            .iter()
+            .map(|path| self.prefix(path.as_ref()))
+            .collect();
+        prefixes.sort_unstable();
+        prefixes.dedup();
+
+        for prefix in prefixes {
+            self.summaries.entry(prefix).or_default().add(score);
+        }
+    }
+
+    /// The summaries from the worst average score to the best one.
+    pub fn into_sorted(self) -> Vec<(String, Summary)> {
+        let mut summaries: Vec<_> = self.summaries.into_iter().collect();
+        sort_worst_first(&mut summaries);
+
+        summaries
+    }
+
+    /// The directory of the path, truncated to the depth.
+    fn prefix(&self, path: &str) -> String {
+        path_prefix(path, self.depth)
+    }
+}
+
+/// The directory of the path, truncated to the depth (`PathSummaries::ROOT`
+/// for the files shallower than the depth).
+fn path_prefix(path: &str, depth: usize) -> String {
+    let dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+    let components: Vec<_> = dir
+        .components()
+        .take(depth)
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+
+    if components.is_empty() {
+        PathSummaries::ROOT.to_string()
+    } else {
+        components.join("/")
+    }
+}
+
+/// The number of commits and the number of the reviewed ones among them.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Coverage {
+    commits: usize,
+    reviewed: usize,
+}
+
+impl Coverage {
+    pub fn add(&mut self, reviewed: bool) {
+        self.commits += 1;
+        if reviewed {
+            self.reviewed += 1;
+        }
+    }
+
+    pub fn commits(&self) -> usize {
+        self.commits
+    }
+
+    pub fn reviewed(&self) -> usize {
+        self.reviewed
+    }
+
+    /// The percentage of the reviewed commits, if there are any commits.
+    pub fn percent(&self) -> Option<f32> {
+        if self.commits == 0 {
+            None
+        } else {
+            Some(100.0 * self.reviewed as f32 / self.commits as f32)
+        }
+    }
+}
+
+/// The review coverage of the commits (all of them, scored or ignored)
+/// by the authors and by the directories they touch: the commit is
+/// considered reviewed, if it has any of the `REVIEW_TRAILERS`.
+pub struct ReviewCoverage {
+    depth: usize,
+    authors: HashMap<String, Coverage>,
+    paths: HashMap<String, Coverage>,
+    total: Coverage,
+}
+
+impl ReviewCoverage {
+    pub const REVIEW_TRAILERS: [&'static str; 2] = ["Reviewed-by", "Acked-by"];
+
+    /// Creates the coverage with the directories summarized
+    /// up to the depth, like `PathSummaries` does.
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            authors: HashMap::new(),
+            paths: HashMap::new(),
+            total: Coverage::default(),
+        }
+    }
+
+    pub fn add(&mut self, commit: &Commit) {
+        let msg_info = commit.msg_info();
+        let reviewed = Self::REVIEW_TRAILERS
+            .iter()
+            .any(|key| msg_info.has_trailer(key));
+
+        self.total.add(reviewed);
+        self.authors
+            .entry(commit.metadata().author().to_string())
+            .or_default()
+            .add(reviewed);
+
+        let paths = commit.diff_info().as_ref().map(DiffInfo::paths);
+        let mut prefixes: Vec<_> = paths
+            .unwrap_or_default()
+            .iter()
+            .map(|path| path_prefix(path, self.depth))
+            .collect();
+        prefixes.sort_unstable();
+        prefixes.dedup();
+
+        for prefix in prefixes {
+            self.paths.entry(prefix).or_default().add(reviewed);
+        }
+    }
+
+    /// The coverage by the authors, from the least covered one.
+    pub fn authors(&self) -> Vec<(String, Coverage)> {
+        sort_least_covered_first(&self.authors)
+    }
+
+    /// The coverage by the directories, from the least covered one.
+    pub fn paths(&self) -> Vec<(String, Coverage)> {
+        sort_least_covered_first(&self.paths)
+    }
+
+    pub fn total(&self) -> Coverage {
+        self.total
+    }
+}
+
+fn sort_least_covered_first(coverages: &HashMap<String, Coverage>) -> Vec<(String, Coverage)> {
+    let mut coverages: Vec<_> = coverages
+        .iter()
+        .map(|(name, coverage)| (name.clone(), *coverage))
+        .collect();
+
+    coverages.sort_by(|(first_name, first), (second_name, second)| {
+        let first_percent = first.percent().unwrap_or(f32::INFINITY);
+        let second_percent = second.percent().unwrap_or(f32::INFINITY);
+
+        first_percent
+            .partial_cmp(&second_percent)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| first_name.cmp(second_name))
+    });
+
+    coverages
+}
+
+/// Summaries of the commits by the local day of the week and the hour
+/// of the day they were authored at, so the rushed late-night (or
+/// Friday evening) commits are easy to spot.
+#[derive(Default)]
+pub struct TimeSummaries {
+    weekdays: [Summary; 7],
+    hours: [Summary; 24],
+}
+
+impl TimeSummaries {
+    pub const WEEKDAYS: [&'static str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+    pub fn add(&mut self, time: AuthorTime, score: Score) {
+        self.weekdays[time.weekday()].add(score);
+        self.hours[time.hour()].add(score);
+    }
+
+    /// The summaries by the days of the week, from Monday.
+    pub fn weekdays(&self) -> Vec<(String, Summary)> {
+        Self::WEEKDAYS
+            .iter()
+            .zip(self.weekdays.iter())
+            .map(|(name, summary)| (name.to_string(), summary.clone()))
+            .collect()
+    }
+
+    /// The summaries by the hours of the day, e.g. "23:00" for the commits
+    /// authored from 23:00 to 23:59.
+    pub fn hours(&self) -> Vec<(String, Summary)> {
+        self.hours
+            .iter()
+            .enumerate()
+            .map(|(hour, summary)| (format!("{:02}:00", hour), summary.clone()))
+            .collect()
+    }
+}
+
+/// The worst of the scored commits (the ignored ones are not considered).
+/// Only the bounded number of commits is kept, so the memory usage does
+/// not depend on the number of the scored commits.
+pub struct WorstCommits {
+    limit: usize,
+    commits: Vec<ScoredCommit>,
+}
+
+impl WorstCommits {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            commits: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, scored: ScoredCommit) {
+        if let Score::Ignored(_) = scored.score() {
+            return;
+        }
+
+        self.commits.push(scored);
+        if self.commits.len() >= 2 * self.limit {
+            self.truncate();
+        }
+    }
+
+    /// The worst commits, from the worst one. The commits with the equal
+    /// scores are kept in the order they were added.
+    pub fn into_sorted(mut self) -> Vec<ScoredCommit> {
+        self.truncate();
+        self.commits
+    }
+
+    fn truncate(&mut self) {
+        self.commits.sort_by_key(|scored| match scored.score() {
+            Score::Scored { score, .. } => score,
+            Score::Ignored(_) => u8::MAX,
+        });
+        self.commits.truncate(self.limit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scoring::IgnoreReason;
+
+    fn scored(score: u8, grade: Grade) -> Score {
+        Score::Scored { score, grade }
+    }
+
+    #[test]
+    fn empty_summary_has_no_average() {
+        let summary = Summary::new();
+
+        assert_eq!(summary.scored(), 0);
+        assert_eq!(summary.ignored(), 0);
+        assert!(summary.average().is_none());
+    }
+
+    #[test]
+    fn ignored_commits_do_not_affect_average() {
+        let mut summary = Summary::new();
+
+        summary.add(scored(90, Grade::A));
+        summary.add(scored(30, Grade::D));
+        summary.add(Score::Ignored(IgnoreReason::Requested));
+
+        assert_eq!(summary.scored(), 2);
+        assert_eq!(summary.ignored(), 1);
+        assert_eq!(summary.average(), Some(60.0));
+    }
+
+    #[test]
+    fn grades_are_counted_separately() {
+        let mut summary = Summary::new();
+
+        summary.add(scored(90, Grade::A));
+        summary.add(scored(95, Grade::A));
+        summary.add(scored(10, Grade::F));
+
+        assert_eq!(summary.grade_count(Grade::A), 2);
+        assert_eq!(summary.grade_count(Grade::B), 0);
+        assert_eq!(summary.grade_count(Grade::F), 1);
+    }
+
+    #[test]
+    fn merged_summary_combines_both_sides() {
+        let mut summary = Summary::new();
+        summary.add(scored(80, Grade::A));
+        summary.add(Score::Ignored(IgnoreReason::Requested));
+
+        let mut other = Summary::new();
+        other.add(scored(40, Grade::C));
+
+        summary.merge(&other);
+
+        assert_eq!(summary.scored(), 2);
+        assert_eq!(summary.ignored(), 1);
+        assert_eq!(summary.grade_count(Grade::A), 1);
+        assert_eq!(summary.grade_count(Grade::C), 1);
+        assert_eq!(summary.average(), Some(60.0));
+    }
+
+    #[test]
+    fn commits_are_summarized_by_directories() {
+        let mut summaries = PathSummaries::new(2);
+
+        summaries.add(
+            &["src/commit/diff.rs", "src/commit/class.rs"],
+            scored(30, Grade::D),
+        );
+        summaries.add(&["src/main.rs", "README.md"], scored(90, Grade::A));
+        summaries.add(
+            &["src/commit/mod.rs"],
+            Score::Ignored(IgnoreReason::Requested),
+        );
+
+        let summaries = summaries.into_sorted();
+        let names: Vec<_> = summaries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["src/commit", "/", "src"]);
+
+        let (_, commit_dir) = &summaries[0];
+        assert_eq!(commit_dir.scored(), 1);
+        assert_eq!(commit_dir.ignored(), 1);
+    }
+
+    #[test]
+    fn review_coverage_is_computed() {
+        use crate::commit::{CommitId, MessageInfo, Metadata, ParseOptions};
+
+        let commit = |author: &str, message: &str, paths: &[&str]| {
+            let metadata = Metadata::new(CommitId::Placeholder("aaa"), author.into(), 1);
+            let paths = paths.iter().map(|path| path.to_string()).collect();
+            let diff_info = DiffInfo::new(10, 0).with_paths(paths);
+            let msg_info = MessageInfo::new(message);
+            Commit::new(metadata, diff_info, msg_info, &ParseOptions::default())
+        };
+
+        let mut coverage = ReviewCoverage::new(1);
+        coverage.add(&commit(
+            "Foo",
+            "Fix the parser\n\nReviewed-by: Bar <bar@example.com>",
+            &["src/parser.rs", "README.md"],
+        ));
+        coverage.add(&commit(
+            "Foo",
+            "Fix the lexer\n\nacked-by: Baz <baz@example.com>",
+            &["src/lexer.rs"],
+        ));
+        coverage.add(&commit("Bar", "Update docs", &["docs/index.md"]));
+
+        assert_eq!(coverage.total().commits(), 3);
+        assert_eq!(coverage.total().reviewed(), 2);
+
+        let authors = coverage.authors();
+        assert_eq!(authors[0].0, "Bar");
+        assert_eq!(authors[0].1.percent(), Some(0.0));
+        assert_eq!(authors[1].1.percent(), Some(100.0));
+
+        let paths: Vec<_> = coverage
+            .paths()
+            .into_iter()
+            .map(|(path, coverage)| (path, coverage.reviewed()))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                ("docs".to_string(), 0),
+                ("/".to_string(), 1),
+                ("src".to_string(), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn commits_are_summarized_by_time() {
+        let mut summaries = TimeSummaries::default();
+
+        // Friday, 23:30 and Saturday, 02:30 (UTC).
+        summaries.add(AuthorTime::new(1_573_860_600, 0), scored(30, Grade::F));
+        summaries.add(AuthorTime::new(1_573_871_400, 0), scored(90, Grade::A));
+        summaries.add(
+            AuthorTime::new(1_573_871_400, 0),
+            Score::Ignored(IgnoreReason::Requested),
+        );
+
+        let weekdays = summaries.weekdays();
+        assert_eq!(weekdays.len(), 7);
+        assert_eq!(weekdays[4].0, "Fri");
+        assert_eq!(weekdays[4].1.average(), Some(30.0));
+        assert_eq!(weekdays[5].1.ignored(), 1);
+        assert_eq!(weekdays[0].1.scored(), 0);
+
+        let hours = summaries.hours();
+        assert_eq!(hours.len(), 24);
+        assert_eq!(hours[23].0, "23:00");
+        assert_eq!(hours[23].1.grade_count(Grade::F), 1);
+        assert_eq!(hours[2].1.grade_count(Grade::A), 1);
+    }
+}
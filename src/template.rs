@@ -0,0 +1,180 @@
+use crate::scoring::{Score, ScoredCommit};
+
+use std::str::FromStr;
+
+/// The fields of the scored commit, which may be used in the template.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Field {
+    Id,
+    ShortId,
+    Author,
+    Subject,
+    Score,
+    Grade,
+    Ignored,
+    Problems,
+    Dirs,
+    Version,
+}
+
+impl Field {
+    const NAMES: &'static [(&'static str, Field)] = &[
+        ("id", Field::Id),
+        ("short_id", Field::ShortId),
+        ("author", Field::Author),
+        ("subject", Field::Subject),
+        ("score", Field::Score),
+        ("grade", Field::Grade),
+        ("ignored", Field::Ignored),
+        ("problems", Field::Problems),
+        ("dirs", Field::Dirs),
+        ("version", Field::Version),
+    ];
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Part {
+    Text(String),
+    Field(Field),
+}
+
+/// The user-provided template, which each commit is rendered through
+/// for the custom reports (`--format template`).
+///
+/// The template is the text with the `{{field}}` placeholders, which
+/// are replaced with the fields of the commit (see `Field::NAMES`);
+/// the fields, which the commit has no value of (e.g. the score of the
+/// ignored commit), are replaced with the empty string. The rest of the
+/// text is printed as is, so the template usually ends with the newline.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn render(&self, scored_commit: &ScoredCommit, scoring_version: u32) -> String {
+        let commit = scored_commit.commit();
+        let metadata = commit.metadata();
+        let score = scored_commit.score();
+
+        let mut rendered = String::new();
+
+        for part in &self.parts {
+            let field = match part {
+                Part::Text(text) => {
+                    rendered.push_str(text);
+                    continue;
+                }
+                Part::Field(field) => field,
+            };
+
+            let value = match (field, score) {
+                (Field::Id, _) => metadata.id().to_string(),
+                (Field::ShortId, _) => format!("{:12.12}", metadata.id()),
+                (Field::Author, _) => metadata.author().to_string(),
+                (Field::Subject, _) => commit.msg_info().subject().unwrap_or("").to_string(),
+                (Field::Score, Score::Scored { score, .. }) => score.to_string(),
+                (Field::Grade, Score::Scored { grade, .. }) => format!("{:?}", grade),
+                (Field::Ignored, Score::Ignored(reason)) => reason.name().to_string(),
+                (Field::Problems, _) => scored_commit.problems().join(", "),
+                (Field::Dirs, _) => commit
+                    .diff_info()
+                    .as_ref()
+                    .map(|diff| diff.top_dir_names().join(","))
+                    .unwrap_or_default(),
+                (Field::Version, _) => scoring_version.to_string(),
+                _ => String::new(),
+            };
+
+            rendered.push_str(&value);
+        }
+
+        rendered
+    }
+}
+
+impl Template {
+    /// Whether the template shows the directories touched by the commits,
+    /// which requires the changed paths to be collected.
+    pub fn needs_dirs(&self) -> bool {
+        self.parts.contains(&Part::Field(Field::Dirs))
+    }
+}
+
+impl FromStr for Template {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut rest = s;
+
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_string()));
+            }
+
+            let after = &rest[start + 2..];
+            let end = after
+                .find("}}")
+                .ok_or_else(|| "unclosed placeholder in the template".to_string())?;
+
+            let name = after[..end].trim();
+            let field = Field::NAMES
+                .iter()
+                .find(|&&(field_name, _)| field_name == name)
+                .map(|&(_, field)| field)
+                .ok_or_else(|| format!("unknown field in the template: {}", name))?;
+
+            parts.push(Part::Field(field));
+            rest = &after[end + 2..];
+        }
+
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+
+        Ok(Template { parts })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        commit::{Commit, CommitId, MessageInfo, Metadata},
+        scoring::ScorerBuilder,
+    };
+
+    #[test]
+    fn fields_are_rendered() {
+        let metadata = Metadata::new(CommitId::Placeholder("(none)"), "John Doe".into(), 1);
+        let commit = Commit::new_from_message(metadata, MessageInfo::new("Fix parser"));
+        // No rules apply to the commit, so it is ignored and has no score.
+        let scored = ScorerBuilder::new().build().score(commit);
+
+        let template: Template = "{{ author }}: {{subject}} [{{score}}{{ignored}}] v{{version}}\n"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            template.render(&scored, 3),
+            "John Doe: Fix parser [exempt] v3\n"
+        );
+    }
+
+    #[test]
+    fn dirs_field_requires_paths() {
+        let template: Template = "{{short_id}} {{dirs}}\n".parse().unwrap();
+        let template2: Template = "{{short_id}} {{subject}}\n".parse().unwrap();
+
+        assert!(template.needs_dirs());
+        assert!(!template2.needs_dirs());
+    }
+
+    #[test]
+    fn invalid_template_returns_error() {
+        assert!("{{author}".parse::<Template>().is_err());
+        assert!("{{sha}}".parse::<Template>().is_err());
+        assert!("no fields".parse::<Template>().is_ok());
+    }
+}
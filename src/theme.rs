@@ -0,0 +1,174 @@
+use crate::scoring::{Grade, Score};
+
+use colored::{Color, Colorize};
+use std::str::FromStr;
+
+/// The color of the text: one of the 16 basic terminal colors, one of
+/// the 256 indexed ones or the true (24-bit) color.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ThemeColor {
+    Basic(Color),
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl ThemeColor {
+    /// Paints the text, unless the colors are disabled (e.g. the output
+    /// is not a terminal or NO_COLOR is set).
+    pub fn paint(self, text: &str) -> String {
+        // The colored crate supports only the basic colors,
+        // so the rest ones are written as the escape codes directly.
+        let code = match self {
+            ThemeColor::Basic(color) => return text.color(color).to_string(),
+            ThemeColor::Indexed(index) => format!("5;{}", index),
+            ThemeColor::Rgb(r, g, b) => format!("2;{};{};{}", r, g, b),
+        };
+
+        if colored::control::SHOULD_COLORIZE.should_colorize() {
+            format!("\x1b[38;{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+impl FromStr for ThemeColor {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const ERROR: &str = "color must be a name (e.g. bright-green), 0-255 or #RRGGBB";
+
+        let s = s.trim();
+
+        let mut chars = s.chars();
+        if chars.next() == Some('#') {
+            let hex = chars.as_str();
+            let component = |i| {
+                hex.get(i..i + 2)
+                    .and_then(|c| u8::from_str_radix(c, 16).ok())
+                    .ok_or(ERROR)
+            };
+
+            if hex.len() != 6 {
+                return Err(ERROR);
+            }
+
+            return Ok(ThemeColor::Rgb(component(0)?, component(2)?, component(4)?));
+        }
+
+        if s.starts_with(|c: char| c.is_ascii_digit()) {
+            return s.parse().map(ThemeColor::Indexed).map_err(|_| ERROR);
+        }
+
+        Color::from_str(&s.replace('-', " "))
+            .map(ThemeColor::Basic)
+            .map_err(|_| ERROR)
+    }
+}
+
+/// The colors of the grades in the table output.
+///
+/// The default ones are chosen for the dark terminals, so the theme
+/// may be overridden via the spec of the form `A=green,C=#af8700,D=208`,
+/// where the grades not mentioned keep their default colors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorTheme {
+    /// The colors of the grades, from F to A.
+    grades: [ThemeColor; 5],
+    ignored: ThemeColor,
+}
+
+impl Default for ColorTheme {
+    fn default() -> Self {
+        Self {
+            grades: [
+                ThemeColor::Basic(Color::Red),
+                ThemeColor::Basic(Color::BrightRed),
+                ThemeColor::Basic(Color::BrightYellow),
+                ThemeColor::Basic(Color::BrightWhite),
+                ThemeColor::Basic(Color::BrightGreen),
+            ],
+            ignored: ThemeColor::Basic(Color::White),
+        }
+    }
+}
+
+impl ColorTheme {
+    pub fn color(&self, score: Score) -> ThemeColor {
+        match score {
+            Score::Scored { grade, .. } => self.grades[grade as usize],
+            Score::Ignored(_) => self.ignored,
+        }
+    }
+}
+
+impl FromStr for ColorTheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut theme = ColorTheme::default();
+
+        for term in s.split(',').filter(|term| !term.trim().is_empty()) {
+            let mut parts = term.splitn(2, '=');
+
+            let (name, color) = match (parts.next(), parts.next()) {
+                (Some(name), Some(color)) => (name.trim(), color),
+                _ => return Err(format!("color theme term must be GRADE=COLOR: {}", term)),
+            };
+
+            let color = color.parse::<ThemeColor>()?;
+
+            if name.eq_ignore_ascii_case("ignored") {
+                theme.ignored = color;
+            } else {
+                let grade = name.parse::<Grade>()?;
+                theme.grades[grade as usize] = color;
+            }
+        }
+
+        Ok(theme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_are_parsed() {
+        assert_eq!(
+            "bright-blue".parse(),
+            Ok(ThemeColor::Basic(Color::BrightBlue))
+        );
+        assert_eq!("Green".parse(), Ok(ThemeColor::Basic(Color::Green)));
+        assert_eq!("208".parse(), Ok(ThemeColor::Indexed(208)));
+        assert_eq!("#af8700".parse(), Ok(ThemeColor::Rgb(0xaf, 0x87, 0x00)));
+
+        assert!("256".parse::<ThemeColor>().is_err());
+        assert!("#af87".parse::<ThemeColor>().is_err());
+        assert!("#zz8700".parse::<ThemeColor>().is_err());
+        assert!("mauve".parse::<ThemeColor>().is_err());
+    }
+
+    #[test]
+    fn theme_overrides_only_mentioned_grades() {
+        let theme: ColorTheme = "B=blue, c=#af8700,ignored=8".parse().unwrap();
+        let scored = |grade| Score::Scored { score: 50, grade };
+
+        assert_eq!(
+            theme.color(scored(Grade::B)),
+            ThemeColor::Basic(Color::Blue)
+        );
+        assert_eq!(
+            theme.color(scored(Grade::C)),
+            ThemeColor::Rgb(0xaf, 0x87, 0x00)
+        );
+        assert_eq!(
+            theme.color(scored(Grade::A)),
+            ColorTheme::default().color(scored(Grade::A))
+        );
+
+        assert!("B".parse::<ColorTheme>().is_err());
+        assert!("X=red".parse::<ColorTheme>().is_err());
+    }
+}
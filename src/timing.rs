@@ -0,0 +1,87 @@
+use std::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// The stages of the run, whose time is measured with `--timing`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stage {
+    /// Walking the history and loading the commit objects.
+    Traversal,
+
+    /// Calculating the diffs (or looking them up in the cache).
+    Diffing,
+
+    /// Parsing and classifying the messages.
+    Parsing,
+
+    Scoring,
+    Printing,
+}
+
+const STAGES: [Stage; 5] = [
+    Stage::Traversal,
+    Stage::Diffing,
+    Stage::Parsing,
+    Stage::Scoring,
+    Stage::Printing,
+];
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The total time of each stage, in nanoseconds.
+static ELAPSED: [AtomicU64; 5] = [
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+    AtomicU64::new(0),
+];
+
+/// Starts measuring the time of the stages. The measurements
+/// are global, as the stages are spread over the whole program,
+/// while the time is not measured at all unless enabled.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Runs the action, adding its time to the stage total. The nested
+/// measurements are not subtracted, so the stages must not overlap.
+pub fn measure<T, F>(stage: Stage, action: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    if !ENABLED.load(Ordering::Relaxed) {
+        return action();
+    }
+
+    let start = Instant::now();
+    let result = action();
+    let elapsed = start.elapsed().as_nanos() as u64;
+
+    ELAPSED[stage as usize].fetch_add(elapsed, Ordering::Relaxed);
+
+    result
+}
+
+/// Prints the time spent in each of the stages to the standard error.
+pub fn report(total: Duration) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    eprintln!("{:12} {:>10}", "STAGE", "TIME");
+
+    for &stage in STAGES.iter() {
+        let name = format!("{:?}", stage).to_ascii_lowercase();
+        let elapsed = Duration::from_nanos(ELAPSED[stage as usize].load(Ordering::Relaxed));
+
+        eprintln!("{:12} {:>10}", name, format_duration(elapsed));
+    }
+
+    eprintln!("{:12} {:>10}", "total", format_duration(total));
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.3}s", duration.as_secs_f64())
+}
@@ -0,0 +1,157 @@
+use crate::{
+    error::warning,
+    http::{self, Auth},
+    json::quote,
+    scoring::{Grade, Score, ScoredCommit},
+    summary::Summary,
+};
+
+/// The maximum number of commits included into the notification,
+/// so the message in the chat stays readable.
+const MAX_COMMITS: usize = 50;
+
+/// The notification about the run, which is posted as JSON to the webhook
+/// (e.g. the incoming webhook of Slack, Teams or Mattermost) when all the
+/// commits are scored.
+///
+/// The notification is posted via the `curl` executable, so HTTPS and
+/// the proxies are supported without bundling the TLS implementation.
+pub struct WebhookNotification {
+    url: String,
+
+    /// The grade, below which the commits are included.
+    threshold: Option<Grade>,
+
+    /// The JSON objects of the included commits.
+    commits: Vec<String>,
+    omitted: usize,
+}
+
+impl WebhookNotification {
+    pub fn new(url: &str, threshold: Option<Grade>) -> Self {
+        Self {
+            url: url.to_string(),
+            threshold,
+            commits: Vec::new(),
+            omitted: 0,
+        }
+    }
+
+    /// Includes the commit into the notification, if it is graded
+    /// below the threshold.
+    pub fn add(&mut self, scored: &ScoredCommit) {
+        let (score, grade) = match (scored.score(), self.threshold) {
+            (Score::Scored { score, grade }, Some(threshold)) if grade < threshold => {
+                (score, grade)
+            }
+            _ => return,
+        };
+
+        if self.commits.len() == MAX_COMMITS {
+            self.omitted += 1;
+            return;
+        }
+
+        let commit = scored.commit();
+        self.commits.push(format!(
+            "{{\"id\": {}, \"author\": {}, \"subject\": {}, \"score\": {}, \"grade\": {}}}",
+            quote(&commit.metadata().id().to_string()),
+            quote(commit.metadata().author()),
+            quote(commit.msg_info().subject().unwrap_or("")),
+            score,
+            quote(&format!("{:?}", grade))
+        ));
+    }
+
+    /// Posts the notification. The failures are reported as warnings,
+    /// as the notification is not the essential part of the run.
+    pub fn send(&self, summary: &Summary, scoring_version: u32) {
+        let payload = self.payload(summary, scoring_version);
+
+        let posted = http::send_json("POST", &self.url, Auth::None, &payload);
+
+        // The webhook URLs often carry the secret tokens in the path.
+        if posted.is_err() {
+            warning(format!(
+                "cannot post the notification to {}",
+                http::url_host(&self.url)
+            ));
+        }
+    }
+
+    /// The JSON payload. The `text` field is the one shown by the chats,
+    /// while the rest ones are for the custom receivers.
+    fn payload(&self, summary: &Summary, scoring_version: u32) -> String {
+        let average = summary
+            .average()
+            .map(|avg| format!("{:.1}", avg))
+            .unwrap_or_else(|| "null".to_string());
+
+        let grades: Vec<_> = Grade::ALL
+            .iter()
+            .map(|&grade| format!("\"{:?}\": {}", grade, summary.grade_count(grade)))
+            .collect();
+
+        format!(
+            "{{\"text\": {}, \"version\": {}, \"scored\": {}, \"ignored\": {}, \"average\": {}, \"grades\": {{{}}}, \"commits\": [{}], \"omitted\": {}}}",
+            quote(&self.text(summary)),
+            scoring_version,
+            summary.scored(),
+            summary.ignored(),
+            average,
+            grades.join(", "),
+            self.commits.join(", "),
+            self.omitted
+        )
+    }
+
+    fn text(&self, summary: &Summary) -> String {
+        let mut text = format!("commrate: {} commits scored", summary.scored());
+
+        if let Some(average) = summary.average() {
+            text.push_str(&format!(", average score {:.1}", average));
+        }
+
+        let grades: Vec<_> = Grade::ALL
+            .iter()
+            .map(|&grade| format!("{:?}: {}", grade, summary.grade_count(grade)))
+            .collect();
+        text.push_str(&format!(" ({})", grades.join(", ")));
+
+        if let Some(threshold) = self.threshold {
+            let below = self.commits.len() + self.omitted;
+            text.push_str(&format!("; {} commits graded below {:?}", below, threshold));
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{json::Json, scoring::IgnoreReason};
+
+    #[test]
+    fn payload_is_valid_json() {
+        let mut summary = Summary::new();
+        summary.add(Score::Scored {
+            score: 90,
+            grade: Grade::A,
+        });
+        summary.add(Score::Ignored(IgnoreReason::Requested));
+
+        let notification = WebhookNotification::new("http://localhost", Some(Grade::C));
+        let payload = Json::parse(&notification.payload(&summary, 2)).unwrap();
+
+        assert_eq!(
+            payload.get("text").and_then(Json::as_str),
+            Some("commrate: 1 commits scored, average score 90.0 (A: 1, B: 0, C: 0, D: 0, F: 0); 0 commits graded below C")
+        );
+        assert_eq!(payload.get("version").and_then(Json::as_u32), Some(2));
+        assert_eq!(
+            payload.get("commits").and_then(Json::as_array),
+            Some(&[][..])
+        );
+    }
+}